@@ -0,0 +1,62 @@
+//! Compare allocation churn of one-shot runs against a reused `PreparedCircuit`
+//!
+//! Run with `cargo bench --bench arena`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use gmw_rs::{Circuit, GmwProtocol, PreparedCircuit};
+
+/// System allocator that counts allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const RUNS: usize = 200;
+
+fn measure(label: &str, mut run: impl FnMut()) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for _ in 0..RUNS {
+        run();
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{label:<10} {:>10.1} allocations/run {:>12?}/run",
+        allocations as f64 / RUNS as f64,
+        elapsed / RUNS as u32
+    );
+}
+
+fn main() {
+    let circuit = Circuit::from_file("circuits/two_bit_equality.json").unwrap();
+    let inputs = [true, false, true, false];
+    let protocol = GmwProtocol::new(3).unwrap();
+    let prepared = PreparedCircuit::new(&circuit);
+
+    println!("two_bit_equality, 3 parties, {RUNS} runs");
+    measure("one-shot", || {
+        protocol.run_with_report(&circuit, &inputs).unwrap();
+    });
+    measure("prepared", || {
+        protocol.run_prepared(&prepared, &inputs).unwrap();
+    });
+}
@@ -0,0 +1,382 @@
+//! Criterion benchmarks of whole protocol runs in local simulation
+//!
+//! Covers a single AND gate, 32- and 64-bit adders, a 64-bit comparison and
+//! AES-128, each with 2, 3 and 5 parties, and compares a layer of 16 AES
+//! S-boxes as a circuit against preprocessed lookup tables. Record a baseline before changing
+//! the OT or engine layers and compare against it afterwards:
+//!
+//! ```text
+//! cargo bench --bench circuits -- --save-baseline main
+//! cargo bench --bench circuits -- --baseline main
+//! ```
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+use gmw_rs::circuit::{Bus, Wire};
+use gmw_rs::lut::{lookup_shared, LookupTable};
+use gmw_rs::{BusEncoding, Circuit, CircuitBuilder, GmwProtocol, LocalEvaluator, PreparedCircuit};
+
+const PARTY_COUNTS: [usize; 3] = [2, 3, 5];
+
+/// FIPS-197 appendix B
+const AES_KEY: [u8; 16] = [
+    0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+];
+const AES_PLAINTEXT: [u8; 16] = [
+    0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37, 0x07, 0x34,
+];
+const AES_CIPHERTEXT: [u8; 16] = [
+    0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11, 0x85, 0x97, 0x19, 0x6a, 0x0b, 0x32,
+];
+
+fn bits(value: u64, width: usize) -> impl Iterator<Item = bool> {
+    (0..width).map(move |i| (value >> i) & 1 == 1)
+}
+
+fn byte_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|&byte| bits(byte.into(), 8))
+        .collect()
+}
+
+fn and_gate() -> (Circuit, Vec<bool>) {
+    (
+        Circuit::from_file("circuits/and.json").unwrap(),
+        vec![true, true],
+    )
+}
+
+fn adder(width: usize) -> (Circuit, Vec<bool>) {
+    let circuit = CircuitBuilder::build(&format!("adder_{width}"), |b| {
+        let x = b.input_bus("a", width);
+        let y = b.input_bus("b", width);
+        let sum = b.add(&x, &y)?;
+        b.output_arithmetic("sum", &sum, BusEncoding::Unsigned);
+        Ok(())
+    })
+    .unwrap();
+    let inputs = bits(0x9e37_79b9_7f4a_7c15, width)
+        .chain(bits(0x6a09_e667_f3bc_c908, width))
+        .collect();
+    (circuit, inputs)
+}
+
+fn comparison(width: usize) -> (Circuit, Vec<bool>) {
+    let circuit = CircuitBuilder::build(&format!("less_than_{width}"), |b| {
+        let x = b.input_bus("a", width);
+        let y = b.input_bus("b", width);
+        let less = b.less_than(&x, &y)?;
+        b.output("less", less);
+        Ok(())
+    })
+    .unwrap();
+    let inputs = bits(0x9e37_79b9_7f4a_7c15, width)
+        .chain(bits(0x6a09_e667_f3bc_c908, width))
+        .collect();
+    (circuit, inputs)
+}
+
+/// Byte as eight wires, least significant bit first
+type Byte<'id> = [Wire<'id>; 8];
+
+fn xor_byte<'id>(b: &mut CircuitBuilder<'id>, x: Byte<'id>, y: Byte<'id>) -> Byte<'id> {
+    std::array::from_fn(|i| b.xor(x[i], y[i]))
+}
+
+/// XOR a public constant into a byte with NOT gates
+fn xor_constant<'id>(b: &mut CircuitBuilder<'id>, x: Byte<'id>, constant: u8) -> Byte<'id> {
+    std::array::from_fn(|i| {
+        if (constant >> i) & 1 == 1 {
+            b.not(x[i])
+        } else {
+            x[i]
+        }
+    })
+}
+
+/// Reduce a polynomial of degree < 15 modulo x^8 + x^4 + x^3 + x + 1
+fn reduce<'id>(b: &mut CircuitBuilder<'id>, mut terms: [Option<Wire<'id>>; 15]) -> Byte<'id> {
+    for degree in (8..15).rev() {
+        if let Some(high) = terms[degree] {
+            for low in [degree - 8, degree - 7, degree - 5, degree - 4] {
+                terms[low] = Some(match terms[low] {
+                    Some(term) => b.xor(term, high),
+                    None => high,
+                });
+            }
+        }
+    }
+    std::array::from_fn(|i| terms[i].expect("every low coefficient receives a term"))
+}
+
+/// GF(2^8) product: 64 AND gates
+fn gf_mul<'id>(b: &mut CircuitBuilder<'id>, x: Byte<'id>, y: Byte<'id>) -> Byte<'id> {
+    let mut terms = [None; 15];
+    for i in 0..8 {
+        for j in 0..8 {
+            let product = b.and(x[i], y[j]);
+            terms[i + j] = Some(match terms[i + j] {
+                Some(term) => b.xor(term, product),
+                None => product,
+            });
+        }
+    }
+    reduce(b, terms)
+}
+
+/// GF(2^8) square, linear and therefore free of AND gates
+fn gf_square<'id>(b: &mut CircuitBuilder<'id>, x: Byte<'id>) -> Byte<'id> {
+    let mut terms = [None; 15];
+    for (i, &bit) in x.iter().enumerate() {
+        terms[2 * i] = Some(bit);
+    }
+    // Odd coefficients are zero; reduction only reads the even ones above 7
+    let zero = b.xor(x[0], x[0]);
+    for term in terms.iter_mut().take(8) {
+        term.get_or_insert(zero);
+    }
+    reduce(b, terms)
+}
+
+fn gf_square_n<'id>(b: &mut CircuitBuilder<'id>, mut x: Byte<'id>, n: usize) -> Byte<'id> {
+    for _ in 0..n {
+        x = gf_square(b, x);
+    }
+    x
+}
+
+/// AES S-box: inversion as x^254 in four multiplications, then the affine map
+fn sbox<'id>(b: &mut CircuitBuilder<'id>, x: Byte<'id>) -> Byte<'id> {
+    let x2 = gf_square(b, x);
+    let x3 = gf_mul(b, x2, x);
+    let x12 = gf_square_n(b, x3, 2);
+    let x15 = gf_mul(b, x12, x3);
+    let x240 = gf_square_n(b, x15, 4);
+    let x252 = gf_mul(b, x240, x12);
+    let inverse = gf_mul(b, x252, x2);
+
+    let affine = std::array::from_fn(|i| {
+        let mut bit = inverse[i];
+        for shift in 4..8 {
+            bit = b.xor(bit, inverse[(i + shift) % 8]);
+        }
+        bit
+    });
+    xor_constant(b, affine, 0x63)
+}
+
+/// Multiplication by x in GF(2^8)
+fn xtime<'id>(b: &mut CircuitBuilder<'id>, x: Byte<'id>) -> Byte<'id> {
+    let high = x[7];
+    std::array::from_fn(|i| match i {
+        0 => high,
+        1 | 3 | 4 => b.xor(x[i - 1], high),
+        _ => x[i - 1],
+    })
+}
+
+fn to_bytes<'id>(bus: &Bus<'id>) -> Vec<Byte<'id>> {
+    bus.bits()
+        .chunks_exact(8)
+        .map(|chunk| std::array::from_fn(|i| chunk[i]))
+        .collect()
+}
+
+/// AES-128 encryption of one block, state bytes in FIPS-197 column order
+fn aes128() -> (Circuit, Vec<bool>) {
+    let circuit = CircuitBuilder::build("aes128", |b| {
+        let key = b.input_bus("k", 128);
+        let plaintext = b.input_bus("p", 128);
+
+        let mut words: Vec<[Byte; 4]> = to_bytes(&key)
+            .chunks_exact(4)
+            .map(|word| [word[0], word[1], word[2], word[3]])
+            .collect();
+        let mut rcon = 1u8;
+        for i in 4..44 {
+            let mut temp = words[i - 1];
+            if i % 4 == 0 {
+                temp.rotate_left(1);
+                for byte in &mut temp {
+                    *byte = sbox(b, *byte);
+                }
+                temp[0] = xor_constant(b, temp[0], rcon);
+                rcon = (rcon << 1) ^ if rcon & 0x80 != 0 { 0x1b } else { 0 };
+            }
+            let previous = words[i - 4];
+            words.push(std::array::from_fn(|j| xor_byte(b, previous[j], temp[j])));
+        }
+        let round_key = |round: usize| -> Vec<Byte> {
+            words[4 * round..4 * round + 4]
+                .iter()
+                .flatten()
+                .copied()
+                .collect()
+        };
+
+        let mut state = to_bytes(&plaintext);
+        for (byte, key_byte) in state.iter_mut().zip(round_key(0)) {
+            *byte = xor_byte(b, *byte, key_byte);
+        }
+        for round in 1..=10 {
+            let substituted: Vec<Byte> = state.iter().map(|&byte| sbox(b, byte)).collect();
+            // ShiftRows: row r of column c comes from column c + r
+            let shifted: Vec<Byte> = (0..16)
+                .map(|i| substituted[i % 4 + 4 * ((i / 4 + i % 4) % 4)])
+                .collect();
+            state = if round == 10 {
+                shifted
+            } else {
+                shifted
+                    .chunks_exact(4)
+                    .flat_map(|column| {
+                        let doubled: Vec<Byte> =
+                            column.iter().map(|&byte| xtime(b, byte)).collect();
+                        (0..4)
+                            .map(|row| {
+                                // 2·a[r] ^ 3·a[r+1] ^ a[r+2] ^ a[r+3]
+                                let next = (row + 1) % 4;
+                                let mut byte = xor_byte(b, doubled[row], doubled[next]);
+                                for other in [next, (row + 2) % 4, (row + 3) % 4] {
+                                    byte = xor_byte(b, byte, column[other]);
+                                }
+                                byte
+                            })
+                            .collect::<Vec<Byte>>()
+                    })
+                    .collect()
+            };
+            for (byte, key_byte) in state.iter_mut().zip(round_key(round)) {
+                *byte = xor_byte(b, *byte, key_byte);
+            }
+        }
+
+        let ciphertext = Bus::new(state.into_iter().flatten().collect());
+        b.output_bus("c", &ciphertext, BusEncoding::Unsigned);
+        Ok(())
+    })
+    .unwrap();
+
+    let mut inputs = byte_bits(&AES_KEY);
+    inputs.extend(byte_bits(&AES_PLAINTEXT));
+    (circuit, inputs)
+}
+
+/// `count` S-boxes side by side, input bus `x` and output bus `s`
+fn sbox_layer(count: usize) -> Circuit {
+    CircuitBuilder::build("sbox_layer", |b| {
+        let input = b.input_bus("x", 8 * count);
+        let output: Vec<Wire> = to_bytes(&input)
+            .into_iter()
+            .flat_map(|byte| sbox(b, byte))
+            .collect();
+        b.output_bus("s", &Bus::new(output), BusEncoding::Unsigned);
+        Ok(())
+    })
+    .unwrap()
+}
+
+/// S-box table read off the circuit, so both sides compute the same function
+fn sbox_table() -> LookupTable {
+    let circuit = sbox_layer(1);
+    LookupTable::from_fn(8, 8, |input| {
+        let values = LocalEvaluator::evaluate(&circuit, &byte_bits(&[input as u8])).unwrap();
+        circuit
+            .metadata
+            .outputs
+            .iter()
+            .enumerate()
+            .fold(0, |byte, (i, output)| {
+                byte | u64::from(values[&output.id]) << i
+            })
+    })
+    .unwrap()
+}
+
+fn bench_circuit(c: &mut Criterion, name: &str, (circuit, inputs): (Circuit, Vec<bool>)) {
+    let prepared = PreparedCircuit::new(&circuit);
+    let mut group = c.benchmark_group(name);
+    for parties in PARTY_COUNTS {
+        let protocol = GmwProtocol::new(parties).unwrap();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(parties),
+            &inputs,
+            |bench, inputs| bench.iter(|| protocol.run_prepared(&prepared, inputs).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn gates(c: &mut Criterion) {
+    bench_circuit(c, "and_gate", and_gate());
+    bench_circuit(c, "adder_32", adder(32));
+    bench_circuit(c, "adder_64", adder(64));
+    bench_circuit(c, "less_than_64", comparison(64));
+}
+
+fn aes(c: &mut Criterion) {
+    let (circuit, inputs) = aes128();
+    // Guard against timing a wrong circuit
+    let report = GmwProtocol::new(2)
+        .unwrap()
+        .run_with_report(&circuit, &inputs)
+        .unwrap();
+    assert_eq!(
+        report.bus_bits("c").unwrap(),
+        byte_bits(&AES_CIPHERTEXT),
+        "AES-128 circuit does not match FIPS-197"
+    );
+    bench_circuit(c, "aes128", (circuit, inputs));
+}
+
+/// One AES round's SubBytes: AND-depth rounds of OTs against a single reveal
+/// per lookup; only the online phase of the tables is timed
+fn sbox_lookup(c: &mut Criterion) {
+    let bytes = AES_PLAINTEXT;
+    bench_circuit(c, "sbox_layer_circuit", (sbox_layer(16), byte_bits(&bytes)));
+
+    let table = sbox_table();
+    assert_eq!(
+        table.lookup(0x53),
+        0xed,
+        "S-box table does not match FIPS-197"
+    );
+    let mut group = c.benchmark_group("sbox_layer_lut");
+    for parties in PARTY_COUNTS {
+        // Party 0 holds the bytes, the others hold zero shares
+        let shares: Vec<u64> = (0..parties).map(|_| 0).collect();
+        group.bench_function(BenchmarkId::from_parameter(parties), |bench| {
+            bench.iter_batched(
+                || {
+                    bytes
+                        .iter()
+                        .map(|_| table.preprocess(parties).unwrap())
+                        .collect::<Vec<_>>()
+                },
+                |tables| {
+                    tables
+                        .into_iter()
+                        .zip(bytes)
+                        .map(|(tables, byte)| {
+                            let mut input = shares.clone();
+                            input[0] = byte.into();
+                            lookup_shared(tables, &input).unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, gates);
+criterion_group! {
+    name = block_cipher;
+    // Tens of thousands of AND gates per run
+    config = Criterion::default().sample_size(10);
+    targets = aes, sbox_lookup
+}
+criterion_main!(benches, block_cipher);
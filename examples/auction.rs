@@ -0,0 +1,119 @@
+//! Sealed-bid second-price (Vickrey) auction
+//!
+//! Each bidder is a party and provides its own bid as a party input. The
+//! circuit scans the bids once, keeping the highest bid, its bidder and the
+//! second-highest bid: a bid above the current best pushes the best down to
+//! second place, any other bid can only raise the second price. Only the
+//! winner and the price they pay are outputs, so the winning bid and every
+//! losing bid stay secret. Ties go to the earlier bidder at the tied price.
+//!
+//! The crate has no networked mode yet: the parties run in-process.
+//!
+//! Run with `cargo run --example auction [BID]...` (default: 5 bidders).
+
+use anyhow::Result;
+use gmw_rs::circuit::Bus;
+use gmw_rs::{
+    BusEncoding, Circuit, CircuitBuilder, GmwProtocol, OpeningRefused, PreparedCircuit, WireId,
+};
+
+/// Bits of each bid
+const BID_BITS: usize = 8;
+
+/// Build the auction over `bidders` bids; also returns the wires of the
+/// highest bid, to show that nobody may open them
+/// Bidder `p`'s bid is its party input bus `bid{p}_`, least significant
+/// bit first
+fn auction_circuit(bidders: usize) -> Result<(Circuit, Vec<WireId>)> {
+    let id_bits = bidders.next_power_of_two().trailing_zeros().max(1) as usize;
+    let mut highest = Vec::new();
+    let circuit = CircuitBuilder::build("auction", |b| {
+        b.description(&format!(
+            "Second-price auction over {bidders} sealed {BID_BITS}-bit bids"
+        ));
+        b.parties(bidders);
+        let bids: Vec<Bus> = (0..bidders)
+            .map(|bidder| b.party_input_bus(bidder, &format!("bid{bidder}_"), BID_BITS))
+            .collect();
+
+        let low = bids[0].bit(0).expect("bids are not empty");
+        let zero = b.xor(low, low);
+        let one = b.not(zero);
+        let constant = |value: usize, width: usize| {
+            Bus::new(
+                (0..width)
+                    .map(|bit| if value >> bit & 1 == 1 { one } else { zero })
+                    .collect(),
+            )
+        };
+
+        let mut best = bids[0].clone();
+        let mut winner = constant(0, id_bits);
+        let mut second = constant(0, BID_BITS);
+        for (bidder, bid) in bids.iter().enumerate().skip(1) {
+            let beats = b.less_than(&best, bid)?;
+            let raised = b.max(&second, bid)?;
+            second = b.mux_bus(beats, &raised, &best)?;
+            best = b.mux_bus(beats, &best, bid)?;
+            winner = b.mux_bus(beats, &winner, &constant(bidder, id_bits))?;
+        }
+
+        highest = best.bits().iter().map(|bit| bit.id()).collect();
+        b.output_bus("winner", &winner, BusEncoding::Unsigned);
+        b.output_bus("price", &second, BusEncoding::Unsigned);
+        Ok(())
+    })?;
+    Ok((circuit, highest))
+}
+
+fn main() -> Result<()> {
+    let mut bids: Vec<u8> = std::env::args()
+        .skip(1)
+        .map(|bid| bid.parse())
+        .collect::<Result<_, _>>()?;
+    if bids.is_empty() {
+        bids = vec![120, 200, 75, 180, 199];
+    }
+    if bids.len() < 2 {
+        return Err(anyhow::anyhow!("An auction needs at least 2 bidders"));
+    }
+
+    let (circuit, highest) = auction_circuit(bids.len())?;
+    let prepared = PreparedCircuit::new(&circuit);
+    let protocol = GmwProtocol::for_circuit(&circuit, None)?;
+    let inputs: Vec<bool> = bids
+        .iter()
+        .flat_map(|&bid| (0..BID_BITS).map(move |bit| bid >> bit & 1 == 1))
+        .collect();
+
+    let report = protocol.run_prepared(&prepared, &inputs)?;
+    let winner = report.decode::<u64>("winner")? as usize;
+    let price = report.decode::<u8>("price")?;
+
+    let top = *bids.iter().max().expect("at least 2 bids");
+    let expected_winner = bids
+        .iter()
+        .position(|&bid| bid == top)
+        .expect("max is a bid");
+    let mut rest = bids.clone();
+    rest.remove(expected_winner);
+    assert_eq!(winner, expected_winner);
+    assert_eq!(Some(&price), rest.iter().max());
+
+    // The winning bid is no output and has no opening policy
+    let refused = protocol
+        .open_wires(&prepared, &inputs, &highest, winner)
+        .unwrap_err();
+    assert!(refused.downcast_ref::<OpeningRefused>().is_some());
+
+    println!(
+        "{}: {} gates, AND-depth {}",
+        circuit.description,
+        circuit.gates.len(),
+        prepared.and_depth
+    );
+    println!("  bidder {winner} wins and pays {price}");
+    println!("  opening the winning bid: {refused}");
+
+    Ok(())
+}
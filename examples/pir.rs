@@ -0,0 +1,66 @@
+//! 1-of-N private information retrieval under 2-party GMW
+//!
+//! The server's database and the client's index are both secret-shared; a
+//! MUX tree selects the indexed record, so neither the index nor the other
+//! records are revealed. Each tree level halves the candidate records using
+//! one index bit: `pick = a ^ (sel & (a ^ b))`, one AND per record bit.
+//!
+//! Run with `cargo run --example pir`.
+
+use anyhow::Result;
+use gmw_rs::{BusEncoding, Circuit, CircuitBuilder, GmwProtocol, PreparedCircuit};
+
+/// Number of index bits; the database holds `1 << INDEX_BITS` records
+const INDEX_BITS: usize = 3;
+/// Bits per record
+const RECORD_BITS: usize = 4;
+
+/// Build the selection circuit
+/// Inputs are the records (least significant bit first, record by record)
+/// followed by the index bits, least significant first
+fn pir_circuit() -> Result<Circuit> {
+    let records = 1 << INDEX_BITS;
+    CircuitBuilder::build("pir", |b| {
+        b.description(&format!(
+            "1-of-{records} PIR over {RECORD_BITS}-bit records"
+        ));
+        let database: Vec<_> = (0..records)
+            .map(|record| b.input_bus(&format!("db{record}_"), RECORD_BITS))
+            .collect();
+        let index = b.input_bus("idx", INDEX_BITS);
+
+        // Each level pairs records 2k and 2k+1 and keeps the one the index bit selects
+        let record = b.oblivious_read(&database, &index)?;
+        b.output_bus("record", &record, BusEncoding::Unsigned);
+        Ok(())
+    })
+}
+
+fn main() -> Result<()> {
+    let database: Vec<u8> = vec![3, 14, 15, 9, 2, 6, 5, 8];
+    let circuit = pir_circuit()?;
+    let prepared = PreparedCircuit::new(&circuit);
+    let protocol = GmwProtocol::new(2)?;
+
+    println!(
+        "{}: {} gates, AND-depth {}",
+        circuit.description,
+        circuit.gates.len(),
+        prepared.and_depth
+    );
+
+    for index in 0..database.len() {
+        let record_bits = database
+            .iter()
+            .flat_map(|&record| (0..RECORD_BITS).map(move |bit| record >> bit & 1 == 1));
+        let index_bits = (0..INDEX_BITS).map(|bit| index >> bit & 1 == 1);
+        let inputs: Vec<bool> = record_bits.chain(index_bits).collect();
+
+        let report = protocol.run_prepared(&prepared, &inputs)?;
+        let record = report.decode::<u8>("record")?;
+        assert_eq!(record, database[index]);
+        println!("  db[{index}] = {record} (rounds {})", report.rounds);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+/*
+ * C API of gmw-rs, built with the `ffi` feature:
+ *
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * Handles are owned by the caller and released with their _free function.
+ * Calls returning int give 0 on success and -1 on failure; calls returning
+ * a handle give NULL on failure. gmw_last_error() then describes the
+ * failure. Runs are local simulations with every party in this process.
+ */
+#ifndef GMW_H
+#define GMW_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct GmwFfiProtocol GmwFfiProtocol;
+typedef struct GmwFfiCircuit GmwFfiCircuit;
+
+/* Latest failure on this thread, valid until the next failing call */
+const char *gmw_last_error(void);
+
+GmwFfiProtocol *gmw_protocol_new(size_t parties);
+void gmw_protocol_free(GmwFfiProtocol *protocol);
+
+/* format is "json", "bristol", "binary" or "text" */
+GmwFfiCircuit *gmw_circuit_load(const uint8_t *data, size_t len, const char *format);
+void gmw_circuit_free(GmwFfiCircuit *circuit);
+
+/* Single-bit inputs and outputs, in metadata order */
+size_t gmw_circuit_input_count(const GmwFfiCircuit *circuit);
+size_t gmw_circuit_output_count(const GmwFfiCircuit *circuit);
+
+/* Inputs start out false and keep their value between runs */
+int gmw_circuit_set_input(GmwFfiCircuit *circuit, size_t index, bool value);
+int gmw_run(GmwFfiProtocol *protocol, GmwFfiCircuit *circuit);
+int gmw_circuit_output(const GmwFfiCircuit *circuit, size_t index, bool *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GMW_H */
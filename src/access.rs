@@ -0,0 +1,118 @@
+use anyhow::Result;
+use std::fmt;
+
+use crate::circuit::{PreparedCircuit, WireId};
+use crate::kernel::algebra::reconstruct;
+use crate::protocol::GmwProtocol;
+
+/// Typed error returned when a circuit's opening policy keeps a wire from a
+/// party; reaches callers inside `anyhow::Error`, recover it with
+/// `downcast_ref`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OpeningRefused {
+    pub wire: WireId,
+    pub party: usize,
+}
+
+impl fmt::Display for OpeningRefused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Party {} may not learn wire {}: the circuit's opening policy does not list it",
+            self.party, self.wire
+        )
+    }
+}
+
+impl std::error::Error for OpeningRefused {}
+
+impl GmwProtocol {
+    /// Evaluate and open `wires` to `party` alone, e.g. to debug a circuit
+    /// or to branch on an intermediate value that may be public
+    /// Each wire must be a declared output, which every party learns anyway,
+    /// or list `party` in the circuit's `openings`. The policy is checked
+    /// before anything is evaluated, and only `party` combines the other
+    /// parties' shares, so the values reach nobody else
+    pub fn open_wires(
+        &self,
+        prepared: &PreparedCircuit,
+        inputs: &[bool],
+        wires: &[WireId],
+        party: usize,
+    ) -> Result<Vec<(WireId, bool)>> {
+        if party >= self.party_count() {
+            return Err(anyhow::anyhow!(
+                "Party {} out of range for {} parties",
+                party,
+                self.party_count()
+            ));
+        }
+        let metadata = &prepared.metadata;
+        for &wire in wires {
+            let output = metadata.outputs.iter().any(|output| output.id == wire);
+            let allowed = metadata
+                .openings
+                .get(&wire)
+                .is_some_and(|parties| parties.contains(&party));
+            if !output && !allowed {
+                return Err(OpeningRefused { wire, party }.into());
+            }
+        }
+
+        // Every other party sends its shares of the wires to `party` only
+        let exports = self.export_wires(prepared, inputs, wires, 0)?;
+        Ok(wires
+            .iter()
+            .enumerate()
+            .map(|(i, &wire)| {
+                let shares: Vec<bool> = exports
+                    .iter()
+                    .map(|export| export.shares[i].share)
+                    .collect();
+                (wire, reconstruct(&shares))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{reference, Circuit, CircuitFormat};
+
+    #[test]
+    fn test_openings_follow_policy() {
+        let mut circuit = reference::full_adder();
+        // Wire 5 is the AND of a and b, wire 4 their XOR
+        circuit.metadata.openings.insert(5, vec![1]);
+        let circuit = Circuit::from_json(&serde_json::to_string(&circuit).unwrap()).unwrap();
+        let prepared = PreparedCircuit::new(&circuit);
+        let protocol = GmwProtocol::new(3).unwrap();
+        let inputs = [true, true, false];
+
+        let opened = protocol.open_wires(&prepared, &inputs, &[5, 8], 1).unwrap();
+        assert_eq!(opened, vec![(5, true), (8, true)]);
+
+        for (wire, party) in [(5, 0), (4, 1)] {
+            let error = protocol
+                .open_wires(&prepared, &inputs, &[wire], party)
+                .unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<OpeningRefused>(),
+                Some(&OpeningRefused { wire, party })
+            );
+        }
+        assert!(protocol.open_wires(&prepared, &inputs, &[5], 3).is_err());
+
+        let binary = circuit.encode(CircuitFormat::Binary).unwrap();
+        let decoded = Circuit::decode(&binary.bytes, CircuitFormat::Binary).unwrap();
+        assert_eq!(decoded.metadata.openings, circuit.metadata.openings);
+        let text = circuit.encode(CircuitFormat::Text).unwrap();
+        assert!(text.warnings[0].contains("opening policies dropped"));
+
+        let mut unknown = reference::full_adder();
+        unknown.metadata.openings.insert(99, vec![0]);
+        assert!(Circuit::from_json(&serde_json::to_string(&unknown).unwrap()).is_err());
+    }
+}
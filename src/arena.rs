@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+/// Flat share storage for all parties during one evaluation
+/// Shares are laid out party-major over dense wire slots; the buffer is
+/// reset rather than reallocated between runs to avoid allocation churn
+#[derive(Debug, Clone, Default)]
+pub struct ShareArena {
+    party_count: usize,
+    wire_count: usize,
+    shares: Vec<Option<bool>>,
+    /// Slots a failed gate left without usable shares, with the failure
+    poisoned: HashMap<usize, String>,
+}
+
+impl ShareArena {
+    /// Allocate storage for `party_count` parties over `wire_count` wire slots
+    pub fn new(party_count: usize, wire_count: usize) -> Self {
+        let mut arena = Self::default();
+        arena.reset(party_count, wire_count);
+        arena
+    }
+
+    /// Clear all shares, reusing the existing allocation where possible
+    pub fn reset(&mut self, party_count: usize, wire_count: usize) {
+        self.party_count = party_count;
+        self.wire_count = wire_count;
+        self.shares.clear();
+        self.shares.resize(party_count * wire_count, None);
+        self.poisoned.clear();
+    }
+
+    /// Number of parties stored in the arena
+    pub fn party_count(&self) -> usize {
+        self.party_count
+    }
+
+    /// Number of wire slots per party
+    pub fn wire_count(&self) -> usize {
+        self.wire_count
+    }
+
+    /// Get a party's share of a wire, if it has been set
+    pub fn get(&self, party_id: usize, slot: usize) -> Option<bool> {
+        self.shares[party_id * self.wire_count + slot]
+    }
+
+    /// Set a party's share of a wire
+    pub fn set(&mut self, party_id: usize, slot: usize, share: bool) {
+        self.shares[party_id * self.wire_count + slot] = Some(share);
+    }
+
+    /// Mark a slot as unusable because the gate writing it failed
+    pub fn poison(&mut self, slot: usize, reason: String) {
+        self.poisoned.insert(slot, reason);
+    }
+
+    /// Failure that poisoned a slot, if any
+    pub fn poisoned(&self, slot: usize) -> Option<&str> {
+        self.poisoned.get(&slot).map(String::as_str)
+    }
+
+    /// Allocated capacity in share slots
+    pub fn capacity(&self) -> usize {
+        self.shares.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_reset_reuses_allocation() {
+        let mut arena = ShareArena::new(3, 10);
+        arena.set(2, 9, true);
+        assert_eq!(arena.get(2, 9), Some(true));
+        assert_eq!(arena.get(0, 9), None);
+
+        let capacity = arena.capacity();
+        arena.reset(2, 10);
+        assert_eq!(arena.get(1, 9), None);
+        assert_eq!(arena.capacity(), capacity);
+    }
+}
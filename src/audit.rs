@@ -0,0 +1,142 @@
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::circuit::{Circuit, InputChecks, LocalEvaluator};
+use crate::protocol::GmwProtocol;
+
+/// Compare GMW execution against plaintext evaluation over random inputs
+#[derive(Debug, Clone)]
+pub struct CircuitAuditor {
+    trials: usize,
+    seed: u64,
+}
+
+/// An output where GMW and plaintext evaluation disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AuditMismatch {
+    pub trial: usize,
+    /// Seed that regenerates this trial's inputs with `trials = 1`
+    pub seed: u64,
+    pub inputs: Vec<bool>,
+    pub output: String,
+    pub expected: bool,
+    pub actual: bool,
+}
+
+/// Outcome of an audit run
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct AuditReport {
+    pub trials: usize,
+    /// Trials whose inputs violated a constraint and were correctly rejected
+    pub rejected: usize,
+    pub mismatches: Vec<AuditMismatch>,
+}
+
+impl AuditReport {
+    /// Whether every trial matched plaintext evaluation
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl CircuitAuditor {
+    /// Create an auditor running `trials` trials, where trial i uses seed `seed + i`
+    pub fn new(trials: usize, seed: u64) -> Self {
+        Self { trials, seed }
+    }
+
+    /// Generate the inputs for a trial seed
+    pub fn trial_inputs(circuit: &Circuit, seed: u64) -> Vec<bool> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..circuit.metadata.inputs.len())
+            .map(|_| rng.gen::<bool>())
+            .collect()
+    }
+
+    /// Run all trials and collect mismatching outputs
+    pub fn run(&self, protocol: &GmwProtocol, circuit: &Circuit) -> Result<AuditReport> {
+        let input_checks = InputChecks::new(circuit)?;
+        let mut mismatches = Vec::new();
+        let mut rejected = 0;
+
+        for trial in 0..self.trials {
+            let seed = self.seed.wrapping_add(trial as u64);
+            let inputs = Self::trial_inputs(circuit, seed);
+
+            // Inputs violating a constraint must abort without revealing outputs
+            if let Some(constraint) = input_checks.first_violation(circuit, &inputs)? {
+                if protocol.run_circuit(circuit, &inputs).is_err() {
+                    rejected += 1;
+                } else {
+                    mismatches.push(AuditMismatch {
+                        trial,
+                        seed,
+                        inputs,
+                        output: constraint.to_string(),
+                        expected: false,
+                        actual: true,
+                    });
+                }
+                continue;
+            }
+
+            let expected_values = LocalEvaluator::evaluate(circuit, &inputs)?;
+            let outputs = protocol.run_circuit(circuit, &inputs)?;
+
+            for (output_info, (name, actual)) in circuit.metadata.outputs.iter().zip(outputs) {
+                let expected = expected_values
+                    .get(&output_info.id)
+                    .copied()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Wire {} not found in circuit", output_info.id)
+                    })?;
+
+                if actual != expected {
+                    mismatches.push(AuditMismatch {
+                        trial,
+                        seed,
+                        inputs: inputs.clone(),
+                        output: name,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        Ok(AuditReport {
+            trials: self.trials,
+            rejected,
+            mismatches,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+
+    #[test]
+    fn test_audit_full_adder() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(4).unwrap();
+
+        let report = CircuitAuditor::new(20, 7).run(&protocol, &circuit).unwrap();
+
+        assert_eq!(report.trials, 20);
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_trial_inputs_reproducible() {
+        let circuit = reference::adder_comparator(2);
+
+        let first = CircuitAuditor::trial_inputs(&circuit, 42);
+        assert_eq!(first.len(), 4);
+        assert_eq!(first, CircuitAuditor::trial_inputs(&circuit, 42));
+    }
+}
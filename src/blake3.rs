@@ -0,0 +1,72 @@
+use crate::receipt::ReceiptHash;
+
+/// BLAKE3 with 32-byte output, in its plain and keyed modes
+/// A thin wrapper over the `blake3` crate, so `HashAlgorithm` and receipt
+/// code name it like the other built-in hashes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake3;
+
+impl Blake3 {
+    pub fn digest(data: &[u8]) -> [u8; 32] {
+        ::blake3::hash(data).into()
+    }
+
+    /// Keyed mode, a MAC and PRF in one pass
+    pub fn keyed_hash(key: &[u8; 32], data: &[u8]) -> [u8; 32] {
+        ::blake3::keyed_hash(key, data).into()
+    }
+}
+
+impl ReceiptHash for Blake3 {
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        Self::digest(data).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Input of the official test vectors: byte i is i mod 251
+    fn input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Official vectors, as a cross-check that the wrapper calls the right
+    /// modes of the crate
+    #[test]
+    fn test_blake3_vectors() {
+        assert_eq!(
+            hex(&Blake3::digest(b"")),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            hex(&Blake3::digest(b"abc")),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!(
+            hex(&Blake3::digest(&input(1))),
+            "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213"
+        );
+        // Two and three chunks: a parent node, then an unbalanced tree
+        assert_eq!(
+            hex(&Blake3::digest(&input(2048))),
+            "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a"
+        );
+        assert_eq!(
+            hex(&Blake3::digest(&input(3072))),
+            "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2"
+        );
+        assert_eq!(
+            hex(&Blake3::keyed_hash(
+                b"whats the Elvish word for friend",
+                b""
+            )),
+            "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! Experimental constant-round backend: multiparty BMR garbling
+//!
+//! Semi-honest BMR with free XOR, in the form of Ben-Efraim, Lindell and
+//! Omri (CCS 2016). Every party holds a secret offset R_p and, for every
+//! wire, a label k_p for masked value 0 (k_p ⊕ R_p for 1) and a share of
+//! the wire's mask bit λ. A wire carrying value v is evaluated on its masked
+//! value Λ = v ⊕ λ and every party's label for Λ. Garbling takes three
+//! rounds whatever the circuit's depth:
+//!
+//! 1. Each party broadcasts its input shares masked with its mask shares,
+//!    and the parties multiply the mask bits of every AND/OR gate's inputs
+//!    with GMW AND gates, all in one layer
+//! 2. Every pair multiplies one party's offset with the other's mask shares
+//!    through OT
+//! 3. Each party broadcasts its share of every garbled row and its labels
+//!    of the masked inputs
+//!
+//! after which the parties evaluate the garbled circuit without talking and
+//! open the outputs as in GMW. XOR and NOT gates are free; an AND or OR gate
+//! costs four rows of one label per party. Rows are encrypted with BLAKE3.
+
+use anyhow::Result;
+use rand::Rng;
+
+use crate::arena::ShareArena;
+use crate::circuit::PreparedCircuit;
+use crate::gates::and_gate_rotated;
+use crate::kernel::{GateType, IndexedGate};
+use crate::ot::OtChannels;
+
+/// Rounds between loading the inputs and opening the outputs
+pub const GARBLING_ROUNDS: usize = 3;
+
+/// Label length in bytes, the computational security parameter
+pub const LABEL_LEN: usize = 16;
+
+type Label = [u8; LABEL_LEN];
+
+/// One party's garbling secrets
+struct PartyKeys {
+    offset: Label,
+    /// Label of masked value 0 of every wire slot
+    zero: Vec<Label>,
+    /// Share of every wire slot's mask bit
+    mask: Vec<bool>,
+}
+
+impl PartyKeys {
+    fn label(&self, slot: usize, masked: bool) -> Label {
+        let mut label = self.zero[slot];
+        if masked {
+            xor_into(&mut label, &self.offset);
+        }
+        label
+    }
+}
+
+/// Public garbled table of one AND/OR gate: for each row Λa·2 + Λb, every
+/// party's output label and the output's masked value, encrypted under the
+/// input labels of that row
+struct GarbledGate {
+    labels: [Vec<Label>; 4],
+    masked: [bool; 4],
+}
+
+/// Garble and evaluate `prepared`, whose input shares are loaded in `arena`,
+/// and write every party's share of each gate output back to it
+/// The shares are mask shares, with party 0's flipped by the masked value,
+/// so outputs and input checks open as after a GMW run
+pub(crate) fn evaluate(
+    prepared: &PreparedCircuit,
+    arena: &mut ShareArena,
+    channels: &mut OtChannels,
+) -> Result<()> {
+    let n = arena.party_count();
+    let slots = prepared.index.len();
+    let mut rng = rand::thread_rng();
+    let inputs = prepared
+        .metadata
+        .inputs
+        .iter()
+        .map(|info| {
+            prepared
+                .index
+                .slot(info.id)
+                .ok_or_else(|| anyhow::anyhow!("Input {} not found", info.name))
+        })
+        .collect::<Result<Vec<usize>>>()?;
+
+    // Labels and mask shares: fresh for inputs and AND/OR outputs, derived
+    // for XOR and NOT outputs
+    let mut parties: Vec<PartyKeys> = (0..n)
+        .map(|_| PartyKeys {
+            offset: rng.gen(),
+            zero: vec![[0; LABEL_LEN]; slots],
+            mask: vec![false; slots],
+        })
+        .collect();
+    let mut assigned = vec![false; slots];
+    for &slot in &inputs {
+        for party in &mut parties {
+            party.zero[slot] = rng.gen();
+            party.mask[slot] = rng.gen();
+        }
+        assigned[slot] = true;
+    }
+    for gate in &prepared.gates {
+        for &input in &gate.inputs {
+            if !assigned.get(input).copied().unwrap_or(false) {
+                return Err(anyhow::anyhow!(
+                    "{} has no value",
+                    slot_name(prepared, input)
+                ));
+            }
+        }
+        let c = gate.output;
+        for (p, party) in parties.iter_mut().enumerate() {
+            match (&gate.gate_type, &gate.inputs[..]) {
+                (GateType::XOR, &[a, b]) => {
+                    let mut zero = party.zero[a];
+                    xor_into(&mut zero, &party.zero[b]);
+                    party.zero[c] = zero;
+                    party.mask[c] = party.mask[a] ^ party.mask[b];
+                }
+                (GateType::NOT, &[a]) => {
+                    party.zero[c] = party.zero[a];
+                    party.mask[c] = party.mask[a] ^ (p == 0);
+                }
+                (GateType::AND | GateType::OR, &[_, _]) => {
+                    party.zero[c] = rng.gen();
+                    party.mask[c] = rng.gen();
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "{} gate at {} has {} inputs",
+                        gate_name(gate),
+                        slot_name(prepared, c),
+                        gate.inputs.len()
+                    ))
+                }
+            }
+        }
+        assigned[c] = true;
+    }
+    let interactive: Vec<&IndexedGate> = prepared
+        .gates
+        .iter()
+        .filter(|gate| gate.gate_type.is_interactive())
+        .collect();
+
+    // Round 1: λa·λb of every AND/OR gate, one GMW AND each, all independent
+    let products = interactive
+        .iter()
+        .enumerate()
+        .map(|(g, gate)| {
+            let shares: Vec<(bool, bool)> = parties
+                .iter()
+                .map(|party| (party.mask[gate.inputs[0]], party.mask[gate.inputs[1]]))
+                .collect();
+            and_gate_rotated(&shares, g, channels)
+        })
+        .collect::<Result<Vec<Vec<bool>>>>()?;
+
+    // The masked output of row (α, β) is χ = base ⊕ α·λb ⊕ β·λa ⊕ public(α, β),
+    // so its products with an offset follow from those of three shared bits
+    let bits: Vec<Vec<[bool; 3]>> = interactive
+        .iter()
+        .zip(&products)
+        .map(|(gate, product)| {
+            let (a, b) = (gate.inputs[0], gate.inputs[1]);
+            parties
+                .iter()
+                .zip(product)
+                .map(|(party, &product)| {
+                    let mut base = product ^ party.mask[gate.output];
+                    if gate.gate_type == GateType::OR {
+                        base ^= party.mask[a] ^ party.mask[b];
+                    }
+                    [base, party.mask[a], party.mask[b]]
+                })
+                .collect()
+        })
+        .collect();
+
+    // Round 2: shares of R_j times each of those bits, as `scaled[j][g][k][p]`.
+    // Party j multiplies its own share locally and sends (r, r ⊕ R_j) by OT
+    // to every other party, which picks by its share and keeps what it gets
+    let mut scaled: Vec<Vec<[Vec<Label>; 3]>> =
+        vec![vec![[(); 3].map(|_| vec![[0; LABEL_LEN]; n]); bits.len()]; n];
+    for (j, party) in parties.iter().enumerate() {
+        for (g, gate_scaled) in scaled[j].iter_mut().enumerate() {
+            for (k, shares) in gate_scaled.iter_mut().enumerate() {
+                shares[j] = scale(&party.offset, bits[g][j][k]);
+            }
+        }
+        for i in (0..n).filter(|&i| i != j) {
+            let pads: Vec<Label> = (0..3 * interactive.len()).map(|_| rng.gen()).collect();
+            let messages = pads
+                .iter()
+                .map(|pad| {
+                    let mut flipped = *pad;
+                    xor_into(&mut flipped, &party.offset);
+                    (pad.to_vec(), flipped.to_vec())
+                })
+                .collect();
+            let choices: Vec<bool> = bits.iter().flat_map(|gate_bits| gate_bits[i]).collect();
+            let received = channels.channel(j, i).transfer_batch(messages, &choices)?;
+            for (t, (pad, got)) in pads.iter().zip(received).enumerate() {
+                let shares = &mut scaled[j][t / 3][t % 3];
+                xor_into(&mut shares[j], pad);
+                shares[i] = got
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("OT returned a malformed offset share"))?;
+            }
+        }
+    }
+
+    // Round 3: every party's share of every row, combined into the public
+    // garbled tables
+    let tables: Vec<GarbledGate> = interactive
+        .iter()
+        .enumerate()
+        .map(|(g, gate)| {
+            let (a, b, c) = (gate.inputs[0], gate.inputs[1], gate.output);
+            let mut table = GarbledGate {
+                labels: [(); 4].map(|_| vec![[0; LABEL_LEN]; n]),
+                masked: [false; 4],
+            };
+            for row in 0..4 {
+                let (alpha, beta) = (row >= 2, row % 2 == 1);
+                let public = match gate.gate_type {
+                    GateType::OR => alpha | beta,
+                    _ => alpha & beta,
+                };
+                for (p, party) in parties.iter().enumerate() {
+                    let (pads, bit) =
+                        row_pad(p, c, row, &party.label(a, alpha), &party.label(b, beta), n);
+                    // Party p's share of χ and of R_j·χ for every j
+                    let [base, mask_a, mask_b] = bits[g][p];
+                    table.masked[row] ^=
+                        bit ^ base ^ (alpha & mask_b) ^ (beta & mask_a) ^ (public && p == 0);
+                    for (j, label) in table.labels[row].iter_mut().enumerate() {
+                        xor_into(label, &pads[j]);
+                        let [base, mask_a, mask_b] = &scaled[j][g];
+                        xor_into(label, &base[p]);
+                        if alpha {
+                            xor_into(label, &mask_b[p]);
+                        }
+                        if beta {
+                            xor_into(label, &mask_a[p]);
+                        }
+                        if p == j {
+                            xor_into(label, &party.zero[c]);
+                            xor_into(label, &scale(&party.offset, public));
+                        }
+                    }
+                }
+            }
+            table
+        })
+        .collect();
+
+    // Masked inputs from the broadcast masked shares, then every party's
+    // label for them
+    let mut masked: Vec<bool> = vec![false; slots];
+    let mut labels: Vec<Vec<Label>> = vec![Vec::new(); slots];
+    for &slot in &inputs {
+        let mut value = false;
+        for (p, party) in parties.iter().enumerate() {
+            let share = arena
+                .get(p, slot)
+                .ok_or_else(|| anyhow::anyhow!("{} has no value", slot_name(prepared, slot)))?;
+            value ^= share ^ party.mask[slot];
+        }
+        masked[slot] = value;
+        labels[slot] = parties
+            .iter()
+            .map(|party| party.label(slot, value))
+            .collect();
+    }
+
+    // Local evaluation; each party checks the label it decrypts is one of
+    // its own, which catches a malformed table
+    let mut tables = tables.iter();
+    for gate in &prepared.gates {
+        let c = gate.output;
+        match gate.inputs[..] {
+            [a, b] if gate.gate_type == GateType::XOR => {
+                masked[c] = masked[a] ^ masked[b];
+                labels[c] = labels[a]
+                    .iter()
+                    .zip(&labels[b])
+                    .map(|(x, y)| {
+                        let mut label = *x;
+                        xor_into(&mut label, y);
+                        label
+                    })
+                    .collect();
+            }
+            [a] => {
+                masked[c] = masked[a];
+                labels[c] = labels[a].clone();
+            }
+            [a, b] => {
+                let table = tables
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Garbled table missing"))?;
+                let row = usize::from(masked[a]) * 2 + usize::from(masked[b]);
+                let mut opened = table.labels[row].clone();
+                let mut value = table.masked[row];
+                for (p, (label_a, label_b)) in labels[a].iter().zip(&labels[b]).enumerate() {
+                    let (pads, bit) = row_pad(p, c, row, label_a, label_b, n);
+                    for (label, pad) in opened.iter_mut().zip(&pads) {
+                        xor_into(label, pad);
+                    }
+                    value ^= bit;
+                }
+                if opened
+                    .iter()
+                    .zip(&parties)
+                    .any(|(label, party)| *label != party.label(c, value))
+                {
+                    return Err(anyhow::anyhow!(
+                        "Garbled {} gate at {} opened to a foreign label",
+                        gate_name(gate),
+                        slot_name(prepared, c)
+                    ));
+                }
+                masked[c] = value;
+                labels[c] = opened;
+            }
+            _ => unreachable!("arity checked while garbling"),
+        }
+        for (p, party) in parties.iter().enumerate() {
+            arena.set(p, c, party.mask[c] ^ (masked[c] && p == 0));
+        }
+    }
+
+    Ok(())
+}
+
+/// Pads of party `party` for row `row` of the gate writing `slot`: one label
+/// per party, then a bit for the masked value
+fn row_pad(
+    party: usize,
+    slot: usize,
+    row: usize,
+    a: &Label,
+    b: &Label,
+    parties: usize,
+) -> (Vec<Label>, bool) {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(b"gmw-rs bmr row");
+    hasher.update(&(party as u64).to_le_bytes());
+    hasher.update(&(slot as u64).to_le_bytes());
+    hasher.update(&[row as u8]);
+    hasher.update(a);
+    hasher.update(b);
+    let mut bytes = vec![0u8; parties * LABEL_LEN + 1];
+    hasher.finalize_xof().fill(&mut bytes);
+    let bit = bytes[parties * LABEL_LEN] & 1 == 1;
+    let pads = bytes
+        .chunks_exact(LABEL_LEN)
+        .map(|chunk| chunk.try_into().expect("chunks are label sized"))
+        .collect();
+    (pads, bit)
+}
+
+/// `offset` if `bit` is set, else the zero label
+fn scale(offset: &Label, bit: bool) -> Label {
+    if bit {
+        *offset
+    } else {
+        [0; LABEL_LEN]
+    }
+}
+
+fn xor_into(label: &mut Label, other: &Label) {
+    for (byte, other) in label.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
+
+fn gate_name(gate: &IndexedGate) -> &'static str {
+    match gate.gate_type {
+        GateType::XOR => "XOR",
+        GateType::NOT => "NOT",
+        GateType::AND => "AND",
+        GateType::OR => "OR",
+    }
+}
+
+fn slot_name(prepared: &PreparedCircuit, slot: usize) -> String {
+    prepared
+        .index
+        .wire(slot)
+        .map_or_else(|| format!("slot {slot}"), |wire| format!("wire {wire}"))
+}
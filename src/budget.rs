@@ -0,0 +1,266 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use crate::hooks::{GateEvent, GateHook};
+use crate::simulation::CommunicationCost;
+
+/// Limits on the work a single evaluation may do
+/// Attach with `GmwProtocol::with_budget`; every evaluation starts from zero
+/// and is aborted with a `BudgetExceeded` error before the step that would
+/// cross a limit, so no output is opened. Unset limits are not checked
+#[derive(Debug, Clone, Default)]
+pub struct ResourceBudget {
+    max_gates: Option<usize>,
+    max_ots: Option<usize>,
+    max_bytes: Option<u64>,
+    max_time: Option<Duration>,
+    cost: CommunicationCost,
+}
+
+impl ResourceBudget {
+    /// A budget with no limits set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gates evaluated, counting every gate of a collapsed linear block
+    pub fn max_gates(mut self, limit: usize) -> Self {
+        self.max_gates = Some(limit);
+        self
+    }
+
+    /// Cross-term OTs, one per pair of parties for each AND/OR gate
+    pub fn max_ots(mut self, limit: usize) -> Self {
+        self.max_ots = Some(limit);
+        self
+    }
+
+    /// OT traffic in bytes, estimated with the budget's `CommunicationCost`
+    pub fn max_bytes(mut self, limit: u64) -> Self {
+        self.max_bytes = Some(limit);
+        self
+    }
+
+    /// Wall-clock time since the evaluation's first step
+    pub fn max_time(mut self, limit: Duration) -> Self {
+        self.max_time = Some(limit);
+        self
+    }
+
+    /// Per-OT cost used to estimate bytes (default: `CommunicationCost::default()`)
+    pub fn with_cost(mut self, cost: CommunicationCost) -> Self {
+        self.cost = cost;
+        self
+    }
+}
+
+/// Resource whose limit an evaluation crossed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BudgetResource {
+    Gates,
+    Ots,
+    Bytes,
+    /// Wall-clock time, in milliseconds
+    Time,
+}
+
+/// Typed error returned when an evaluation exceeds its `ResourceBudget`
+/// `used` is the total the refused step would have brought the resource to,
+/// not the usage so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BudgetExceeded {
+    pub resource: BudgetResource,
+    pub limit: u64,
+    /// Usage the evaluation would have reached, in the resource's unit
+    pub used: u64,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.resource {
+            BudgetResource::Gates => "gates",
+            BudgetResource::Ots => "OTs",
+            BudgetResource::Bytes => "bytes",
+            BudgetResource::Time => "ms",
+        };
+        write!(
+            f,
+            "Budget exceeded: {} {unit} needed, limit is {} {unit}",
+            self.used, self.limit
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Usage of one evaluation so far
+#[derive(Debug, Clone, Copy)]
+struct Usage {
+    start: Instant,
+    gates: usize,
+    ots: usize,
+}
+
+/// Hook enforcing a `ResourceBudget`
+/// Usage is tracked per thread, so the parallel runs of `execute_many` are
+/// each held to the budget on their own
+pub(crate) struct BudgetGuard {
+    budget: ResourceBudget,
+    ots_per_gate: usize,
+    usage: Mutex<HashMap<ThreadId, Usage>>,
+}
+
+impl BudgetGuard {
+    pub(crate) fn new(budget: ResourceBudget, party_count: usize) -> Self {
+        Self {
+            budget,
+            ots_per_gate: party_count * party_count.saturating_sub(1) / 2,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn check_time(&self, usage: &Usage) -> Result<()> {
+        match self.budget.max_time {
+            Some(limit) if usage.start.elapsed() > limit => Err(BudgetExceeded {
+                resource: BudgetResource::Time,
+                limit: limit.as_millis() as u64,
+                used: usage.start.elapsed().as_millis() as u64,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn check(limit: Option<u64>, used: u64, resource: BudgetResource) -> Result<()> {
+    match limit {
+        Some(limit) if used > limit => Err(BudgetExceeded {
+            resource,
+            limit,
+            used,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+impl GateHook for BudgetGuard {
+    fn before_gate(&self, event: &GateEvent<'_>) -> Result<()> {
+        let (step, gates, ots) = match *event {
+            GateEvent::Gate {
+                step, gate_type, ..
+            } => {
+                let ots = if gate_type.is_interactive() {
+                    self.ots_per_gate
+                } else {
+                    0
+                };
+                (step, 1, ots)
+            }
+            GateEvent::Linear {
+                step, gate_count, ..
+            } => (step, gate_count, 0),
+        };
+
+        let mut usage = self.usage.lock().unwrap_or_else(PoisonError::into_inner);
+        let fresh = Usage {
+            start: Instant::now(),
+            gates: 0,
+            ots: 0,
+        };
+        let usage = usage.entry(thread::current().id()).or_insert(fresh);
+        if step == 0 {
+            *usage = fresh;
+        }
+
+        let (gates, ots) = (usage.gates + gates, usage.ots + ots);
+        check(
+            self.budget.max_gates.map(|limit| limit as u64),
+            gates as u64,
+            BudgetResource::Gates,
+        )?;
+        check(
+            self.budget.max_ots.map(|limit| limit as u64),
+            ots as u64,
+            BudgetResource::Ots,
+        )?;
+        check(
+            self.budget.max_bytes,
+            ots as u64 * self.budget.cost.bytes_per_ot,
+            BudgetResource::Bytes,
+        )?;
+        self.check_time(usage)?;
+
+        usage.gates = gates;
+        usage.ots = ots;
+        Ok(())
+    }
+
+    fn after_gate(&self, _event: &GateEvent<'_>, _elapsed: Duration) -> Result<()> {
+        let usage = self.usage.lock().unwrap_or_else(PoisonError::into_inner);
+        match usage.get(&thread::current().id()) {
+            Some(usage) => self.check_time(usage),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::Circuit;
+    use crate::protocol::GmwProtocol;
+
+    fn exceeded(protocol: &GmwProtocol, circuit: &Circuit) -> Option<BudgetExceeded> {
+        let error = protocol.run_with_report(circuit, &[true; 3]).err()?;
+        error.downcast_ref::<BudgetExceeded>().cloned()
+    }
+
+    #[test]
+    fn test_budget_limits() {
+        // The full adder has 3 interactive gates: 3 cross-term OTs for 2 parties
+        let circuit = reference::full_adder();
+        let run = |budget: ResourceBudget| {
+            let protocol = GmwProtocol::new(2).unwrap().with_budget(budget);
+            exceeded(&protocol, &circuit)
+        };
+
+        assert_eq!(run(ResourceBudget::new().max_ots(3)), None);
+        let error = run(ResourceBudget::new().max_ots(2)).unwrap();
+        assert_eq!(error.resource, BudgetResource::Ots);
+        assert_eq!((error.limit, error.used), (2, 3));
+
+        let error = run(ResourceBudget::new().max_bytes(1024)).unwrap();
+        assert_eq!(error.resource, BudgetResource::Bytes);
+        assert_eq!(
+            run(ResourceBudget::new().max_gates(1)).unwrap().resource,
+            BudgetResource::Gates
+        );
+        assert_eq!(
+            run(ResourceBudget::new().max_time(Duration::ZERO))
+                .unwrap()
+                .resource,
+            BudgetResource::Time
+        );
+    }
+
+    #[test]
+    fn test_budget_resets_per_evaluation() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(3)
+            .unwrap()
+            .with_budget(ResourceBudget::new().max_ots(9));
+
+        // 3 gates × 3 pairs fit once; the budget does not accumulate across runs
+        for _ in 0..3 {
+            assert_eq!(exceeded(&protocol, &circuit), None);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use crate::circuit::{Circuit, CircuitFormat, CircuitRegistry, PreparedCircuit};
+
+/// Counters of a `CircuitCache`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CacheStats {
+    /// Lookups answered without reading the file
+    pub hits: u64,
+    /// Lookups that parsed a reference for the first time
+    pub misses: u64,
+    /// Lookups that re-parsed a file changed since it was cached
+    pub reloads: u64,
+    /// Prepared circuits dropped to stay within capacity
+    pub evictions: u64,
+}
+
+/// LRU cache of prepared circuits for long-running processes
+/// Circuits are keyed by a hash of their canonical (expanded) JSON, so
+/// references to identical circuits share one `PreparedCircuit` and skip
+/// parsing, linear collapsing and layering. A file is re-read when its
+/// modification time or size changes. The hash only deduplicates and is
+/// not collision-resistant against crafted circuits
+pub struct CircuitCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Reference -> file version and circuit key
+    references: HashMap<String, (FileVersion, u64)>,
+    /// Circuit key -> prepared circuit and last use
+    prepared: HashMap<u64, (Arc<PreparedCircuit>, u64)>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileVersion {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl CircuitCache {
+    /// Keep at most `capacity` prepared circuits (at least one)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Prepared circuit for `reference` (`path` or `path#name`), loading it if
+    /// it is new, evicted or changed on disk
+    pub fn get(&self, reference: &str) -> Result<Arc<PreparedCircuit>> {
+        let (path, name) = CircuitRegistry::split_reference(reference);
+        let metadata = fs::metadata(path)?;
+        let version = FileVersion {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        };
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.clock += 1;
+        let now = state.clock;
+
+        let cached = state.references.get(reference).copied();
+        if let Some((cached_version, key)) = cached {
+            if cached_version == version {
+                if let Some((prepared, last_used)) = state.prepared.get_mut(&key) {
+                    *last_used = now;
+                    let prepared = Arc::clone(prepared);
+                    state.stats.hits += 1;
+                    return Ok(prepared);
+                }
+            }
+        }
+
+        // New, evicted or changed: parse and key by content; binary files
+        // are kept so their stored evaluation plan can be used
+        let binary = match CircuitFormat::from_path(path) {
+            Some(CircuitFormat::Binary) if name.is_none() => Some(fs::read(path)?),
+            _ => None,
+        };
+        let circuit = match &binary {
+            Some(bytes) => Circuit::decode(bytes, CircuitFormat::Binary)?,
+            None => Circuit::load(reference)?,
+        };
+        let key = canonical_hash(&circuit)?;
+        match cached {
+            Some((cached_version, _)) if cached_version != version => state.stats.reloads += 1,
+            _ => state.stats.misses += 1,
+        }
+        state
+            .references
+            .insert(reference.to_string(), (version, key));
+
+        let prepared = match state.prepared.get_mut(&key) {
+            Some((prepared, last_used)) => {
+                *last_used = now;
+                Arc::clone(prepared)
+            }
+            None => {
+                let prepared = Arc::new(match &binary {
+                    Some(bytes) => PreparedCircuit::from_binary(bytes)?,
+                    None => PreparedCircuit::new(&circuit),
+                });
+                state.prepared.insert(key, (Arc::clone(&prepared), now));
+                self.evict(&mut state);
+                prepared
+            }
+        };
+        Ok(prepared)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stats
+    }
+
+    /// Prepared circuits currently held
+    pub fn len(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .prepared
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop least recently used circuits beyond capacity
+    fn evict(&self, state: &mut CacheState) {
+        while state.prepared.len() > self.capacity {
+            let Some(oldest) = state
+                .prepared
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(&key, _)| key)
+            else {
+                return;
+            };
+            state.prepared.remove(&oldest);
+            state.references.retain(|_, (_, key)| *key != oldest);
+            state.stats.evictions += 1;
+        }
+    }
+}
+
+/// Hash of the circuit's JSON encoding after macro expansion
+fn canonical_hash(circuit: &Circuit) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_vec(circuit)?.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_cache_hits_reloads_and_evicts() {
+        let dir = env::temp_dir().join(format!("gmw-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let copy = |source: &str, name: &str| {
+            let path = dir.join(name);
+            fs::copy(source, &path).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        let and = copy("circuits/and.json", "a.json");
+        let and_again = copy("circuits/and.json", "b.json");
+
+        let cache = CircuitCache::new(1);
+        let first = cache.get(&and).unwrap();
+        assert!(Arc::ptr_eq(&first, &cache.get(&and).unwrap()));
+        // Same circuit under another path shares the prepared circuit
+        assert!(Arc::ptr_eq(&first, &cache.get(&and_again).unwrap()));
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 2);
+
+        // A changed file is reloaded
+        fs::copy("circuits/full_adder.json", dir.join("a.json")).unwrap();
+        assert_eq!(cache.get(&and).unwrap().name, "full_adder");
+        assert_eq!(cache.stats().reloads, 1);
+
+        // Capacity 1: the AND circuit was evicted for the full adder
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.get(&and_again).unwrap().name, "AND_gate");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1006 @@
+use anyhow::Result;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+
+use crate::circuit::{
+    BusEncoding, BusExtension, BusInfo, Circuit, CircuitMetadata, Gate, GateType, InputConstraint,
+    InputInfo, OutputInfo, PartySpec, WireId,
+};
+
+/// Invariant lifetime tying handles to the builder that created them
+type Brand<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+/// Single-bit wire of the circuit under construction
+/// Handles are branded with their builder's lifetime, so a wire from one
+/// circuit cannot be passed to another builder
+/// (`tests/compile_fail/foreign_wire.rs`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wire<'id> {
+    id: WireId,
+    _brand: Brand<'id>,
+}
+
+impl Wire<'_> {
+    /// Wire ID in the built circuit
+    pub fn id(self) -> WireId {
+        self.id
+    }
+}
+
+/// Multi-bit value, least significant bit first
+/// A distinct type from `Wire`, so buses and single bits cannot be confused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bus<'id> {
+    bits: Vec<Wire<'id>>,
+}
+
+impl<'id> Bus<'id> {
+    pub fn new(bits: Vec<Wire<'id>>) -> Self {
+        Self { bits }
+    }
+
+    pub fn width(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Bit `i`, counting from the least significant
+    pub fn bit(&self, i: usize) -> Option<Wire<'id>> {
+        self.bits.get(i).copied()
+    }
+
+    pub fn bits(&self) -> &[Wire<'id>] {
+        &self.bits
+    }
+}
+
+/// Result of `CircuitBuilder::add` or `sub` with its wraparound flags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arithmetic<'id> {
+    /// Sum or difference, as wide as the operands
+    pub value: Bus<'id>,
+    /// Carry-out of an addition or borrow of a subtraction: unsigned wraparound
+    pub carry: Wire<'id>,
+    /// Two's-complement overflow: signed wraparound
+    pub overflow: Wire<'id>,
+}
+
+/// Builds a circuit gate by gate through typed wire handles
+///
+/// ```
+/// use gmw_rs::circuit::CircuitBuilder;
+///
+/// let circuit = CircuitBuilder::build("half_adder", |b| {
+///     let (x, y) = (b.input("x"), b.input("y"));
+///     let sum = b.xor(x, y);
+///     let carry = b.and(x, y);
+///     b.output("sum", sum);
+///     b.output("carry", carry);
+///     Ok(())
+/// })
+/// .unwrap();
+/// assert_eq!(circuit.gates.len(), 2);
+/// ```
+pub struct CircuitBuilder<'id> {
+    circuit: Circuit,
+    next_id: WireId,
+    input_names: HashSet<String>,
+    output_names: HashSet<String>,
+    error: Option<anyhow::Error>,
+    _brand: Brand<'id>,
+}
+
+impl CircuitBuilder<'_> {
+    /// Run `define` on a fresh builder and return the finished circuit
+    /// Fails if `define` does, or if an input or output name was declared twice
+    pub fn build<F>(name: &str, define: F) -> Result<Circuit>
+    where
+        F: for<'id> FnOnce(&mut CircuitBuilder<'id>) -> Result<()>,
+    {
+        let mut builder = CircuitBuilder {
+            circuit: Circuit {
+                name: name.to_string(),
+                description: String::new(),
+                gates: Vec::new(),
+                metadata: CircuitMetadata::default(),
+            },
+            next_id: 0,
+            input_names: HashSet::new(),
+            output_names: HashSet::new(),
+            error: None,
+            _brand: PhantomData,
+        };
+        define(&mut builder)?;
+
+        if let (None, Some(spec)) = (&builder.error, &builder.circuit.metadata.parties) {
+            spec.validate(&builder.circuit.metadata)?;
+        }
+        match builder.error {
+            Some(error) => Err(error),
+            None => Ok(builder.circuit),
+        }
+    }
+}
+
+impl<'id> CircuitBuilder<'id> {
+    pub fn description(&mut self, description: &str) {
+        self.circuit.description = description.to_string();
+    }
+
+    /// Declare the number of parties the circuit is written for
+    pub fn parties(&mut self, count: usize) {
+        self.circuit
+            .metadata
+            .parties
+            .get_or_insert_with(PartySpec::default)
+            .count = count;
+    }
+
+    /// Declare the next circuit input, provided by `party`; the circuit must
+    /// also declare its party count with `parties`
+    pub fn party_input(&mut self, party: usize, name: &str) -> Wire<'id> {
+        let wire = self.input(name);
+        self.circuit
+            .metadata
+            .parties
+            .get_or_insert_with(PartySpec::default)
+            .owners
+            .insert(name.to_string(), party);
+        wire
+    }
+
+    /// Declare the next circuit input
+    pub fn input(&mut self, name: &str) -> Wire<'id> {
+        let wire = self.wire();
+        if !self.input_names.insert(name.to_string()) {
+            self.fail(format!("Duplicate input name {name}"));
+        }
+        self.circuit.metadata.inputs.push(InputInfo {
+            name: name.to_string(),
+            id: wire.id,
+        });
+        wire
+    }
+
+    /// Declare `width` inputs named `{prefix}0`, `{prefix}1`, ...
+    pub fn input_bus(&mut self, prefix: &str, width: usize) -> Bus<'id> {
+        Bus::new(
+            (0..width)
+                .map(|i| self.input(&format!("{prefix}{i}")))
+                .collect(),
+        )
+    }
+
+    /// `input_bus` with every input provided by `party`
+    pub fn party_input_bus(&mut self, party: usize, prefix: &str, width: usize) -> Bus<'id> {
+        Bus::new(
+            (0..width)
+                .map(|i| self.party_input(party, &format!("{prefix}{i}")))
+                .collect(),
+        )
+    }
+
+    /// Declare inputs `{name}0`, `{name}1`, ... as input bus `name` whose value
+    /// is given in `encoding`, so `InputEncoder` can set it; a one-hot bus is
+    /// also constrained to exactly one set bit
+    pub fn input_encoded(&mut self, name: &str, width: usize, encoding: BusEncoding) -> Bus<'id> {
+        let bus = self.input_bus(name, width);
+        let bits: Vec<String> = (0..width).map(|i| format!("{name}{i}")).collect();
+        if encoding == BusEncoding::OneHot {
+            self.circuit
+                .metadata
+                .constraints
+                .push(InputConstraint::OneHot { bits: bits.clone() });
+        }
+        self.circuit.metadata.input_buses.push(BusInfo {
+            name: name.to_string(),
+            bits,
+            encoding,
+            carry: None,
+            overflow: None,
+            extend: None,
+        });
+        bus
+    }
+
+    pub fn xor(&mut self, a: Wire<'id>, b: Wire<'id>) -> Wire<'id> {
+        self.gate(GateType::XOR, vec![a.id, b.id])
+    }
+
+    pub fn and(&mut self, a: Wire<'id>, b: Wire<'id>) -> Wire<'id> {
+        self.gate(GateType::AND, vec![a.id, b.id])
+    }
+
+    pub fn or(&mut self, a: Wire<'id>, b: Wire<'id>) -> Wire<'id> {
+        self.gate(GateType::OR, vec![a.id, b.id])
+    }
+
+    pub fn not(&mut self, a: Wire<'id>) -> Wire<'id> {
+        self.gate(GateType::NOT, vec![a.id])
+    }
+
+    /// `when_true` if `select` is set, else `when_false`: one AND
+    pub fn mux(
+        &mut self,
+        select: Wire<'id>,
+        when_false: Wire<'id>,
+        when_true: Wire<'id>,
+    ) -> Wire<'id> {
+        let differ = self.xor(when_false, when_true);
+        let flip = self.and(select, differ);
+        self.xor(when_false, flip)
+    }
+
+    /// Bitwise `mux` over two buses of equal width
+    pub fn mux_bus(
+        &mut self,
+        select: Wire<'id>,
+        when_false: &Bus<'id>,
+        when_true: &Bus<'id>,
+    ) -> Result<Bus<'id>> {
+        check_widths("mux", when_false, when_true)?;
+        Ok(Bus::new(
+            when_false
+                .bits
+                .iter()
+                .zip(&when_true.bits)
+                .map(|(&a, &b)| self.mux(select, a, b))
+                .collect(),
+        ))
+    }
+
+    /// `a + b` with a ripple-carry adder: one AND per bit
+    /// Each carry is `majority(a_i, b_i, c) = c ^ ((a_i ^ c) & (b_i ^ c))`
+    pub fn add(&mut self, a: &Bus<'id>, b: &Bus<'id>) -> Result<Arithmetic<'id>> {
+        self.ripple(a, b, false)
+    }
+
+    /// `a - b` with a ripple-borrow subtractor: one AND per bit
+    /// Each borrow is `majority(!a_i, b_i, w)`
+    pub fn sub(&mut self, a: &Bus<'id>, b: &Bus<'id>) -> Result<Arithmetic<'id>> {
+        self.ripple(a, b, true)
+    }
+
+    fn ripple(&mut self, a: &Bus<'id>, b: &Bus<'id>, subtract: bool) -> Result<Arithmetic<'id>> {
+        check_widths(if subtract { "subtract" } else { "add" }, a, b)?;
+        if a.width() == 0 {
+            return Err(anyhow::anyhow!("Cannot do arithmetic on empty buses"));
+        }
+
+        let mut value = Vec::with_capacity(a.width());
+        let (mut carry, mut carry_in) = (None, None);
+        for (&a_i, &b_i) in a.bits.iter().zip(&b.bits) {
+            // Subtraction borrows where addition carries with `a_i` inverted
+            let a_i = if subtract { self.not(a_i) } else { a_i };
+            let (bit, next) = match carry {
+                None => {
+                    let bit = self.xor(a_i, b_i);
+                    (bit, self.and(a_i, b_i))
+                }
+                Some(c) => {
+                    let half = self.xor(a_i, b_i);
+                    let a_c = self.xor(a_i, c);
+                    let b_c = self.xor(b_i, c);
+                    let flip = self.and(a_c, b_c);
+                    (self.xor(half, c), self.xor(c, flip))
+                }
+            };
+            // Inverting `a_i` inverted the sum bit too
+            value.push(if subtract { self.not(bit) } else { bit });
+            carry_in = carry;
+            carry = Some(next);
+        }
+
+        let carry = carry.expect("bus is not empty");
+        let overflow = match carry_in {
+            Some(carry_in) => self.xor(carry_in, carry),
+            // A 1-bit value overflows whenever it carries
+            None => carry,
+        };
+        Ok(Arithmetic {
+            value: Bus::new(value),
+            carry,
+            overflow,
+        })
+    }
+
+    /// Swap `a` and `b` if `select` is set: one AND per bit, all in one layer
+    /// Returns `(a, b)` or `(b, a)`; the compare-exchange of sorting networks
+    pub fn cond_swap(
+        &mut self,
+        select: Wire<'id>,
+        a: &Bus<'id>,
+        b: &Bus<'id>,
+    ) -> Result<(Bus<'id>, Bus<'id>)> {
+        check_widths("swap", a, b)?;
+        let (mut swapped_a, mut swapped_b) = (Vec::new(), Vec::new());
+        for (&a_i, &b_i) in a.bits.iter().zip(&b.bits) {
+            let differ = self.xor(a_i, b_i);
+            let flip = self.and(select, differ);
+            swapped_a.push(self.xor(a_i, flip));
+            swapped_b.push(self.xor(b_i, flip));
+        }
+        Ok((Bus::new(swapped_a), Bus::new(swapped_b)))
+    }
+
+    /// Whether `a < b` as unsigned integers: one AND per bit
+    /// Scanning upward, `lt_i = lt_(i-1) ^ ((a_i ^ b_i) & (b_i ^ lt_(i-1)))`,
+    /// i.e. the highest differing bit decides, so the AND-depth is the width
+    pub fn less_than(&mut self, a: &Bus<'id>, b: &Bus<'id>) -> Result<Wire<'id>> {
+        check_widths("compare", a, b)?;
+        let (Some(&a_0), Some(&b_0)) = (a.bits.first(), b.bits.first()) else {
+            return Err(anyhow::anyhow!("Cannot compare empty buses"));
+        };
+        let not_a = self.not(a_0);
+        let mut less = self.and(not_a, b_0);
+        for (&a_i, &b_i) in a.bits.iter().zip(&b.bits).skip(1) {
+            let differ = self.xor(a_i, b_i);
+            let towards_b = self.xor(b_i, less);
+            let flip = self.and(differ, towards_b);
+            less = self.xor(less, flip);
+        }
+        Ok(less)
+    }
+
+    /// Unsigned minimum: a comparator plus one layer of muxes, 2 ANDs per bit
+    pub fn min(&mut self, a: &Bus<'id>, b: &Bus<'id>) -> Result<Bus<'id>> {
+        let b_less = self.less_than(b, a)?;
+        self.mux_bus(b_less, a, b)
+    }
+
+    /// Unsigned maximum: a comparator plus one layer of muxes, 2 ANDs per bit
+    pub fn max(&mut self, a: &Bus<'id>, b: &Bus<'id>) -> Result<Bus<'id>> {
+        let b_less = self.less_than(b, a)?;
+        self.mux_bus(b_less, b, a)
+    }
+
+    /// `(min, max)` of two unsigned values sharing one comparator
+    /// Same cost as `min` alone, since the swap needs one AND per bit
+    pub fn min_max(&mut self, a: &Bus<'id>, b: &Bus<'id>) -> Result<(Bus<'id>, Bus<'id>)> {
+        let b_less = self.less_than(b, a)?;
+        self.cond_swap(b_less, a, b)
+    }
+
+    /// Read `array[index]` without revealing the index
+    /// Builds a MUX tree that consumes one index bit (least significant first)
+    /// per level: (n - 1) * width ANDs at AND-depth `index.width()`. Indices
+    /// past the end of the array read zero
+    pub fn oblivious_read(&mut self, array: &[Bus<'id>], index: &Bus<'id>) -> Result<Bus<'id>> {
+        let width = self.check_array(array, index)?;
+
+        let mut level = array.to_vec();
+        for &select in &index.bits {
+            if !level.len().is_multiple_of(2) {
+                // Odd tail: pair the last record with zeros for out-of-range indices
+                let zeros = self.zeros(level[0].bits[0], width);
+                level.push(zeros);
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| self.mux_bus(select, &pair[0], &pair[1]))
+                .collect::<Result<_>>()?;
+        }
+        Ok(level.swap_remove(0))
+    }
+
+    /// Return a copy of `array` with `array[index]` replaced by `value`
+    /// Every record is rewritten, so the index stays hidden: a one-hot
+    /// decoder of the index selects, per record, the old value or `value`.
+    /// Writes past the end of the array change nothing
+    pub fn oblivious_write(
+        &mut self,
+        array: &[Bus<'id>],
+        index: &Bus<'id>,
+        value: &Bus<'id>,
+    ) -> Result<Vec<Bus<'id>>> {
+        let width = self.check_array(array, index)?;
+        if value.width() != width {
+            return Err(anyhow::anyhow!(
+                "Cannot write a {}-bit value into {}-bit records",
+                value.width(),
+                width
+            ));
+        }
+
+        let hits = self.decode(index, array.len());
+        array
+            .iter()
+            .zip(hits)
+            .map(|(record, hit)| self.mux_bus(hit, record, value))
+            .collect()
+    }
+
+    /// Validate an array and index; returns the record width
+    fn check_array(&self, array: &[Bus<'id>], index: &Bus<'id>) -> Result<usize> {
+        let width = array
+            .first()
+            .map(Bus::width)
+            .filter(|&width| width > 0)
+            .ok_or_else(|| anyhow::anyhow!("Oblivious access needs a non-empty array"))?;
+        if array.iter().any(|record| record.width() != width) {
+            return Err(anyhow::anyhow!("Array records differ in width"));
+        }
+        let addressable = u32::try_from(index.width())
+            .ok()
+            .and_then(|bits| 1usize.checked_shl(bits))
+            .unwrap_or(usize::MAX);
+        if index.width() == 0 || array.len() > addressable {
+            return Err(anyhow::anyhow!(
+                "A {}-bit index cannot address {} records",
+                index.width(),
+                array.len()
+            ));
+        }
+        Ok(width)
+    }
+
+    /// One-hot wires for index values 0..count, least significant bit first
+    fn decode(&mut self, index: &Bus<'id>, count: usize) -> Vec<Wire<'id>> {
+        let first = index.bits[0];
+        let mut hits = vec![self.not(first), first];
+        for &bit in &index.bits[1..] {
+            hits.truncate(count);
+            let clear = self.not(bit);
+            let mut next: Vec<_> = hits.iter().map(|&hit| self.and(hit, clear)).collect();
+            // Once every record has a hit wire, higher bits only need to be clear
+            if next.len() < count {
+                next.extend(hits.iter().map(|&hit| self.and(hit, bit)));
+            }
+            hits = next;
+        }
+        hits.truncate(count);
+        hits
+    }
+
+    /// `width` wires that are always zero, computed as `wire ^ wire`
+    fn zeros(&mut self, wire: Wire<'id>, width: usize) -> Bus<'id> {
+        let zero = self.xor(wire, wire);
+        Bus::new(vec![zero; width])
+    }
+
+    /// `bus` widened to `width` bits; narrowing is an error since it loses
+    /// bits
+    pub fn extend(
+        &mut self,
+        bus: &Bus<'id>,
+        width: usize,
+        extension: BusExtension,
+    ) -> Result<Bus<'id>> {
+        let Some(&top) = bus.bits.last() else {
+            return Err(anyhow::anyhow!("Cannot extend an empty bus"));
+        };
+        if width < bus.width() {
+            return Err(anyhow::anyhow!(
+                "Cannot extend a {}-bit bus to {} bits",
+                bus.width(),
+                width
+            ));
+        }
+        let padding = width - bus.width();
+        let high = match extension {
+            BusExtension::Zero => self.zeros(top, padding),
+            BusExtension::Sign => Bus::new(vec![top; padding]),
+        };
+        Ok(Bus::new([bus.bits(), high.bits()].concat()))
+    }
+
+    /// Insert `circuit` as a sub-circuit and return its outputs
+    /// Each input bus of `circuit`, and each input outside its input buses
+    /// as a 1-bit bus of the same name, is wired to the bus given for it in
+    /// `inputs`. The widths must match unless the input bus declares
+    /// `extend`, which widens narrower buses. The result holds the output
+    /// buses of `circuit` and its remaining outputs as 1-bit buses, so their
+    /// widths come from its metadata. Constraints and party declarations of
+    /// `circuit` are not carried over
+    pub fn call(
+        &mut self,
+        circuit: &Circuit,
+        inputs: &[(&str, &Bus<'id>)],
+    ) -> Result<HashMap<String, Bus<'id>>> {
+        let metadata = &circuit.metadata;
+        let input_ids: HashMap<&str, WireId> = metadata
+            .inputs
+            .iter()
+            .map(|input| (input.name.as_str(), input.id))
+            .collect();
+        let in_buses: HashSet<&str> = metadata
+            .input_buses
+            .iter()
+            .flat_map(|bus| bus.bits.iter().map(String::as_str))
+            .collect();
+        let ports: Vec<(&str, Vec<&str>, Option<BusExtension>)> = metadata
+            .input_buses
+            .iter()
+            .map(|bus| {
+                let bits = bus.bits.iter().map(String::as_str).collect();
+                (bus.name.as_str(), bits, bus.extend)
+            })
+            .chain(
+                metadata
+                    .inputs
+                    .iter()
+                    .filter(|input| !in_buses.contains(input.name.as_str()))
+                    .map(|input| (input.name.as_str(), vec![input.name.as_str()], None)),
+            )
+            .collect();
+
+        let mut wires: HashMap<WireId, Wire<'id>> = HashMap::new();
+        let mut connected = HashSet::new();
+        for &(name, bus) in inputs {
+            let (_, bits, extend) = ports
+                .iter()
+                .find(|(port, _, _)| *port == name)
+                .ok_or_else(|| anyhow::anyhow!("Circuit {} has no input {}", circuit.name, name))?;
+            if !connected.insert(name) {
+                return Err(anyhow::anyhow!("Input {} is wired twice", name));
+            }
+            let bus = match (bus.width().cmp(&bits.len()), extend) {
+                (Ordering::Equal, _) => bus.clone(),
+                (Ordering::Less, Some(extension)) => self.extend(bus, bits.len(), *extension)?,
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Cannot wire a {}-bit bus to the {}-bit input {} of {}{}",
+                        bus.width(),
+                        bits.len(),
+                        name,
+                        circuit.name,
+                        if bus.width() < bits.len() {
+                            "; declare `extend` on the input bus to widen it"
+                        } else {
+                            ""
+                        }
+                    ))
+                }
+            };
+            for (bit, &wire) in bits.iter().zip(bus.bits()) {
+                let id = input_ids
+                    .get(bit)
+                    .ok_or_else(|| anyhow::anyhow!("Input bus bit {} is not an input", bit))?;
+                wires.insert(*id, wire);
+            }
+        }
+        if let Some((name, _, _)) = ports.iter().find(|(port, _, _)| !connected.contains(port)) {
+            return Err(anyhow::anyhow!(
+                "Input {} of {} is not wired",
+                name,
+                circuit.name
+            ));
+        }
+
+        for gate in &circuit.gates {
+            let operands = gate
+                .inputs
+                .iter()
+                .map(|id| {
+                    wires.get(id).map(|wire| wire.id).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Gate {} of {} reads undefined wire {}",
+                            gate.id,
+                            circuit.name,
+                            id
+                        )
+                    })
+                })
+                .collect::<Result<_>>()?;
+            let wire = self.gate(gate.gate_type.clone(), operands);
+            wires.insert(gate.id, wire);
+        }
+
+        let output = |name: &str| {
+            metadata
+                .outputs
+                .iter()
+                .find(|output| output.name == name)
+                .and_then(|output| wires.get(&output.id).copied())
+                .ok_or_else(|| anyhow::anyhow!("Output {} of {} is undefined", name, circuit.name))
+        };
+        let mut outputs = HashMap::new();
+        for bus in &metadata.buses {
+            let bits = bus
+                .bits
+                .iter()
+                .map(|bit| output(bit))
+                .collect::<Result<_>>()?;
+            outputs.insert(bus.name.clone(), Bus::new(bits));
+        }
+        let out_buses: HashSet<&str> = metadata
+            .buses
+            .iter()
+            .flat_map(|bus| bus.bits.iter().map(String::as_str))
+            .collect();
+        for info in &metadata.outputs {
+            if !out_buses.contains(info.name.as_str()) {
+                outputs.insert(info.name.clone(), Bus::new(vec![output(&info.name)?]));
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Reveal a wire as a named output
+    pub fn output(&mut self, name: &str, wire: Wire<'id>) {
+        if !self.output_names.insert(name.to_string()) {
+            self.fail(format!("Duplicate output name {name}"));
+        }
+        self.circuit.metadata.outputs.push(OutputInfo {
+            name: name.to_string(),
+            id: wire.id,
+        });
+    }
+
+    /// Reveal a bus as outputs `{name}0`, `{name}1`, ... grouped into bus `name`
+    pub fn output_bus(&mut self, name: &str, bus: &Bus<'id>, encoding: BusEncoding) {
+        let bits = bus
+            .bits
+            .iter()
+            .enumerate()
+            .map(|(i, &wire)| {
+                let bit_name = format!("{name}{i}");
+                self.output(&bit_name, wire);
+                bit_name
+            })
+            .collect();
+        self.circuit.metadata.buses.push(BusInfo {
+            name: name.to_string(),
+            bits,
+            encoding,
+            carry: None,
+            overflow: None,
+            extend: None,
+        });
+    }
+
+    /// Reveal an `add` or `sub` result as bus `name` with its flags as
+    /// outputs `{name}_carry` and `{name}_overflow`, declared in the bus
+    /// metadata so `ExecutionReport::decode_checked` can detect wraparound
+    pub fn output_arithmetic(
+        &mut self,
+        name: &str,
+        result: &Arithmetic<'id>,
+        encoding: BusEncoding,
+    ) {
+        let carry = format!("{name}_carry");
+        let overflow = format!("{name}_overflow");
+        self.output(&carry, result.carry);
+        self.output(&overflow, result.overflow);
+        self.output_bus(name, &result.value, encoding);
+        if let Some(bus) = self.circuit.metadata.buses.last_mut() {
+            bus.carry = Some(carry);
+            bus.overflow = Some(overflow);
+        }
+    }
+
+    fn wire(&mut self) -> Wire<'id> {
+        self.next_id += 1;
+        Wire {
+            id: self.next_id,
+            _brand: PhantomData,
+        }
+    }
+
+    fn gate(&mut self, gate_type: GateType, inputs: Vec<WireId>) -> Wire<'id> {
+        let wire = self.wire();
+        self.circuit.gates.push(Gate {
+            id: wire.id,
+            gate_type,
+            inputs,
+        });
+        wire
+    }
+
+    /// Remember the first misuse as the build error
+    fn fail(&mut self, message: String) {
+        self.error.get_or_insert_with(|| anyhow::anyhow!(message));
+    }
+}
+
+/// Reject binary operations on buses of different widths
+fn check_widths(operation: &str, a: &Bus<'_>, b: &Bus<'_>) -> Result<()> {
+    if a.width() != b.width() {
+        return Err(anyhow::anyhow!(
+            "Cannot {} buses of widths {} and {}",
+            operation,
+            a.width(),
+            b.width()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::{
+        circuit::{CircuitFormat, PreparedCircuit},
+        protocol::GmwProtocol,
+    };
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_builder_adder() {
+        // 3-bit ripple-carry adder
+        let circuit = CircuitBuilder::build("adder", |b| {
+            let (x, y) = (b.input_bus("x", 3), b.input_bus("y", 3));
+            let mut carry = None;
+            let mut sum = Vec::new();
+            for (&xi, &yi) in x.bits().iter().zip(y.bits()) {
+                let half = b.xor(xi, yi);
+                let generate = b.and(xi, yi);
+                match carry {
+                    None => {
+                        sum.push(half);
+                        carry = Some(generate);
+                    }
+                    Some(c) => {
+                        sum.push(b.xor(half, c));
+                        let propagate = b.and(half, c);
+                        carry = Some(b.or(generate, propagate));
+                    }
+                }
+            }
+            sum.extend(carry);
+            b.output_bus("sum", &Bus::new(sum), BusEncoding::Unsigned);
+            Ok(())
+        })
+        .unwrap();
+
+        let protocol = GmwProtocol::new(2).unwrap();
+        for (x, y) in [(0u64, 0u64), (3, 5), (7, 7), (6, 1)] {
+            let inputs: Vec<bool> = (0..3)
+                .map(|i| x >> i & 1 == 1)
+                .chain((0..3).map(|i| y >> i & 1 == 1))
+                .collect();
+            let report = protocol.run_with_report(&circuit, &inputs).unwrap();
+            assert_eq!(report.decode::<u64>("sum").unwrap(), x + y);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_oblivious_read_and_write() {
+        let database: [u64; 5] = [3, 14, 15, 9, 2];
+        let circuit = CircuitBuilder::build("ram", |b| {
+            let array: Vec<Bus> = (0..database.len())
+                .map(|i| b.input_bus(&format!("r{i}_"), 4))
+                .collect();
+            let (index, value) = (b.input_bus("i", 3), b.input_bus("v", 4));
+            let read = b.oblivious_read(&array, &index)?;
+            let written = b.oblivious_write(&array, &index, &value)?;
+            b.output_bus("read", &read, BusEncoding::Unsigned);
+            for (i, record) in written.iter().enumerate() {
+                b.output_bus(&format!("w{i}_"), record, BusEncoding::Unsigned);
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let protocol = GmwProtocol::new(2).unwrap();
+        let bits = |value: u64, width: usize| (0..width).map(move |i| value >> i & 1 == 1);
+        for index in 0..8u64 {
+            let inputs: Vec<bool> = database
+                .iter()
+                .flat_map(|&record| bits(record, 4))
+                .chain(bits(index, 3))
+                .chain(bits(7, 4))
+                .collect();
+            let report = protocol.run_with_report(&circuit, &inputs).unwrap();
+
+            let expected = database.get(index as usize).copied().unwrap_or(0);
+            assert_eq!(report.decode::<u64>("read").unwrap(), expected);
+            for (i, &record) in database.iter().enumerate() {
+                let expected = if i as u64 == index { 7 } else { record };
+                assert_eq!(report.decode::<u64>(&format!("w{i}_")).unwrap(), expected);
+            }
+        }
+
+        // 5 records cannot be addressed by a 2-bit index
+        assert!(CircuitBuilder::build("small", |b| {
+            let array: Vec<Bus> = (0..5).map(|i| b.input_bus(&format!("r{i}_"), 1)).collect();
+            let index = b.input_bus("i", 2);
+            b.oblivious_read(&array, &index)?;
+            Ok(())
+        })
+        .is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_arithmetic_flags() {
+        let circuit = CircuitBuilder::build("alu", |b| {
+            let (x, y) = (b.input_bus("x", 3), b.input_bus("y", 3));
+            let sum = b.add(&x, &y)?;
+            let difference = b.sub(&x, &y)?;
+            let signed = b.add(&x, &y)?;
+            b.output_arithmetic("sum", &sum, BusEncoding::Unsigned);
+            b.output_arithmetic("diff", &difference, BusEncoding::Unsigned);
+            b.output_arithmetic("signed", &signed, BusEncoding::TwosComplement);
+            Ok(())
+        })
+        .unwrap();
+        // Flags survive the binary format
+        let encoded = circuit.encode(CircuitFormat::Binary).unwrap();
+        let circuit = Circuit::decode(&encoded.bytes, CircuitFormat::Binary).unwrap();
+
+        let protocol = GmwProtocol::new(2).unwrap();
+        let bits = |value: i64| (0..3).map(move |i| value >> i & 1 == 1);
+        for (x, y) in [(0, 0), (3, 4), (5, 3), (2, 6), (7, 7), (1, 7)] {
+            let inputs: Vec<bool> = bits(x).chain(bits(y)).collect();
+            let report = protocol.run_with_report(&circuit, &inputs).unwrap();
+
+            assert_eq!(report.carry("sum").unwrap(), x + y > 7);
+            assert_eq!(report.carry("diff").unwrap(), x < y);
+            assert_eq!(
+                report.decode_checked::<i64>("sum").ok(),
+                (x + y <= 7).then_some(x + y)
+            );
+            assert_eq!(
+                report.decode_checked::<i64>("diff").ok(),
+                (x >= y).then_some(x - y)
+            );
+            assert_eq!(report.decode::<i64>("diff").unwrap(), (x - y) & 7);
+
+            let signed = |value: i64| if value > 3 { value - 8 } else { value };
+            let sum = signed(x) + signed(y);
+            assert_eq!(report.overflow("signed").unwrap(), !(-4..=3).contains(&sum));
+            assert_eq!(
+                report.wrapped("signed").unwrap(),
+                Some(!(-4..=3).contains(&sum))
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_swap_and_min_max() {
+        let circuit = CircuitBuilder::build("min_max", |b| {
+            let (x, y, swap) = (b.input_bus("x", 3), b.input_bus("y", 3), b.input("s"));
+            let less = b.less_than(&x, &y)?;
+            let (first, second) = b.cond_swap(swap, &x, &y)?;
+            let (low, high) = b.min_max(&x, &y)?;
+            let (min, max) = (b.min(&x, &y)?, b.max(&x, &y)?);
+            b.output("lt", less);
+            for (name, bus) in [("a", first), ("b", second), ("lo", low), ("hi", high)] {
+                b.output_bus(name, &bus, BusEncoding::Unsigned);
+            }
+            b.output_bus("min", &min, BusEncoding::Unsigned);
+            b.output_bus("max", &max, BusEncoding::Unsigned);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(PreparedCircuit::new(&circuit).and_depth, 4);
+
+        let protocol = GmwProtocol::new(2).unwrap();
+        let bits = |value: u64| (0..3).map(move |i| value >> i & 1 == 1);
+        for (x, y, swap) in [
+            (0u64, 0u64, false),
+            (5, 3, true),
+            (3, 5, false),
+            (7, 6, true),
+        ] {
+            let inputs: Vec<bool> = bits(x).chain(bits(y)).chain([swap]).collect();
+            let report = protocol.run_with_report(&circuit, &inputs).unwrap();
+            let decode = |name: &str| report.decode::<u64>(name).unwrap();
+
+            assert_eq!(report.output("lt"), Some(x < y));
+            let swapped = if swap { (y, x) } else { (x, y) };
+            assert_eq!((decode("a"), decode("b")), swapped);
+            assert_eq!((decode("lo"), decode("hi")), (x.min(y), x.max(y)));
+            assert_eq!((decode("min"), decode("max")), (x.min(y), x.max(y)));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_call_checks_and_extends_bus_widths() {
+        let adder = |encoding| {
+            CircuitBuilder::build("adder4", |b| {
+                let x = b.input_encoded("x", 4, encoding);
+                let y = b.input_encoded("y", 4, encoding);
+                let sum = b.add(&x, &y)?;
+                b.output_bus("sum", &sum.value, encoding);
+                Ok(())
+            })
+            .unwrap()
+        };
+        let caller = |callee: &Circuit| {
+            CircuitBuilder::build("caller", |b| {
+                let (a, c) = (b.input_bus("a", 2), b.input_bus("c", 4));
+                let outputs = b.call(callee, &[("x", &a), ("y", &c)])?;
+                assert_eq!(outputs["sum"].width(), 4);
+                b.output_bus("total", &outputs["sum"], callee.metadata.buses[0].encoding);
+                Ok(())
+            })
+        };
+        let run = |circuit: &Circuit, a: u64, c: u64| {
+            let inputs: Vec<bool> = (0..2)
+                .map(|i| a >> i & 1 == 1)
+                .chain((0..4).map(|i| c >> i & 1 == 1))
+                .collect();
+            GmwProtocol::new(2)
+                .unwrap()
+                .run_with_report(circuit, &inputs)
+                .unwrap()
+        };
+
+        let mut unsigned = adder(BusEncoding::Unsigned);
+        let error = caller(&unsigned).unwrap_err().to_string();
+        assert!(error.contains("2-bit bus to the 4-bit input x"), "{error}");
+
+        unsigned.metadata.input_buses[0].extend = Some(BusExtension::Zero);
+        let circuit = caller(&unsigned).unwrap();
+        assert_eq!(run(&circuit, 3, 5).decode::<u64>("total").unwrap(), 8);
+
+        // 0b11 is -1 once sign-extended
+        let mut signed = adder(BusEncoding::TwosComplement);
+        signed.metadata.input_buses[0].extend = Some(BusExtension::Sign);
+        let circuit = caller(&signed).unwrap();
+        assert_eq!(run(&circuit, 3, 5).decode::<i64>("total").unwrap(), 4);
+
+        // Wider buses and unwired inputs are rejected
+        let error = CircuitBuilder::build("caller", |b| {
+            let wide = b.input_bus("w", 8);
+            b.call(&unsigned, &[("x", &wide), ("y", &wide)])?;
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("8-bit bus to the 4-bit input"));
+        let error = CircuitBuilder::build("caller", |b| {
+            let a = b.input_bus("a", 4);
+            b.call(&unsigned, &[("x", &a)])?;
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("Input y of adder4 is not wired"));
+
+        // The extension survives the binary format
+        let encoded = unsigned.encode(CircuitFormat::Binary).unwrap();
+        let decoded = Circuit::decode(&encoded.bytes, CircuitFormat::Binary).unwrap();
+        assert_eq!(
+            decoded.metadata.input_buses[0].extend,
+            Some(BusExtension::Zero)
+        );
+    }
+
+    #[test]
+    fn test_builder_rejects_duplicate_names() {
+        let result = CircuitBuilder::build("dup", |b| {
+            let a = b.input("a");
+            let a2 = b.input("a");
+            let both = b.and(a, a2);
+            b.output("a", both);
+            Ok(())
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_builder_parties() {
+        let circuit = CircuitBuilder::build("owned", |b| {
+            b.parties(3);
+            let x = b.party_input(0, "x");
+            let y = b.party_input(2, "y");
+            let both = b.and(x, y);
+            b.output("both", both);
+            Ok(())
+        })
+        .unwrap();
+        let spec = circuit.metadata.parties.unwrap();
+        assert_eq!(spec.count, 3);
+        assert_eq!(spec.inputs_of(2), ["y"]);
+
+        // Owners beyond the count, or without a count, are rejected
+        let result = CircuitBuilder::build("bad_owner", |b| {
+            b.parties(2);
+            let x = b.party_input(2, "x");
+            b.output("x_out", x);
+            Ok(())
+        });
+        assert!(result.is_err());
+        let result = CircuitBuilder::build("no_count", |b| {
+            let x = b.party_input(0, "x");
+            b.output("x_out", x);
+            Ok(())
+        });
+        assert!(result.is_err());
+    }
+}
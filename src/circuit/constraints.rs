@@ -0,0 +1,250 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::circuit::{Circuit, Gate, GateType, InputConstraint, LocalEvaluator, WireId};
+
+/// Check subcircuits generated from a circuit's input constraints
+/// The gates are appended after the circuit's own gates; each check wire is
+/// true when its constraint holds
+#[derive(Debug, Clone, Default)]
+pub struct InputChecks {
+    pub gates: Vec<Gate>,
+    /// Constraint description and the wire carrying its result
+    pub checks: Vec<(String, WireId)>,
+}
+
+/// Bit of a check subcircuit, folded while it is still a constant
+#[derive(Debug, Clone, Copy)]
+enum Bit {
+    Const(bool),
+    Wire(WireId),
+}
+
+impl InputChecks {
+    /// Generate check gates for every constraint in the circuit metadata
+    pub fn new(circuit: &Circuit) -> Result<Self> {
+        let constraints = &circuit.metadata.constraints;
+        if constraints.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let inputs: HashMap<&str, WireId> = circuit
+            .metadata
+            .inputs
+            .iter()
+            .map(|input| (input.name.as_str(), input.id))
+            .collect();
+        let next_id = circuit
+            .gates
+            .iter()
+            .map(|gate| gate.id)
+            .chain(inputs.values().copied())
+            .max()
+            .unwrap_or(0);
+
+        let mut builder = CheckBuilder {
+            next_id,
+            gates: Vec::new(),
+        };
+        let mut checks = Vec::with_capacity(constraints.len());
+
+        for constraint in constraints {
+            let (InputConstraint::LessThan { bits, .. } | InputConstraint::OneHot { bits }) =
+                constraint;
+            let wires = bits
+                .iter()
+                .map(|name| {
+                    inputs.get(name.as_str()).copied().ok_or_else(|| {
+                        anyhow::anyhow!("Constraint {} uses unknown input {}", constraint, name)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let anchor = *wires
+                .first()
+                .ok_or_else(|| anyhow::anyhow!("Constraint {} has no inputs", constraint))?;
+
+            let valid = match constraint {
+                InputConstraint::LessThan { bound, .. } => builder.less_than(&wires, *bound)?,
+                InputConstraint::OneHot { .. } => builder.one_hot(&wires)?,
+            };
+            let wire = builder.materialize(valid, anchor)?;
+            checks.push((constraint.to_string(), wire));
+        }
+
+        Ok(Self {
+            gates: builder.gates,
+            checks,
+        })
+    }
+
+    /// Evaluate the checks in plaintext and return the first violated constraint
+    pub fn first_violation(&self, circuit: &Circuit, inputs: &[bool]) -> Result<Option<&str>> {
+        if self.checks.is_empty() {
+            return Ok(None);
+        }
+
+        let mut checked = circuit.clone();
+        checked.gates.extend(self.gates.iter().cloned());
+        let values = LocalEvaluator::evaluate(&checked, inputs)?;
+
+        Ok(self
+            .checks
+            .iter()
+            .find(|(_, wire)| values.get(wire) != Some(&true))
+            .map(|(constraint, _)| constraint.as_str()))
+    }
+}
+
+/// Appends gates with fresh wire IDs above those used by the circuit
+struct CheckBuilder {
+    next_id: WireId,
+    gates: Vec<Gate>,
+}
+
+impl CheckBuilder {
+    fn gate(&mut self, gate_type: GateType, inputs: Vec<WireId>) -> Result<WireId> {
+        self.next_id = self
+            .next_id
+            .checked_add(1)
+            .ok_or_else(|| anyhow::anyhow!("No wire IDs left for input checks"))?;
+        self.gates.push(Gate {
+            id: self.next_id,
+            gate_type,
+            inputs,
+        });
+        Ok(self.next_id)
+    }
+
+    fn not(&mut self, x: Bit) -> Result<Bit> {
+        Ok(match x {
+            Bit::Const(value) => Bit::Const(!value),
+            Bit::Wire(wire) => Bit::Wire(self.gate(GateType::NOT, vec![wire])?),
+        })
+    }
+
+    fn and(&mut self, x: Bit, y: Bit) -> Result<Bit> {
+        Ok(match (x, y) {
+            (Bit::Const(false), _) | (_, Bit::Const(false)) => Bit::Const(false),
+            (Bit::Const(true), other) | (other, Bit::Const(true)) => other,
+            (Bit::Wire(x), Bit::Wire(y)) => Bit::Wire(self.gate(GateType::AND, vec![x, y])?),
+        })
+    }
+
+    fn or(&mut self, x: Bit, y: Bit) -> Result<Bit> {
+        Ok(match (x, y) {
+            (Bit::Const(true), _) | (_, Bit::Const(true)) => Bit::Const(true),
+            (Bit::Const(false), other) | (other, Bit::Const(false)) => other,
+            (Bit::Wire(x), Bit::Wire(y)) => Bit::Wire(self.gate(GateType::OR, vec![x, y])?),
+        })
+    }
+
+    /// Turn a bit into a wire; constants are derived from `anchor` as
+    /// `anchor ^ anchor` since circuits have no constant wires
+    fn materialize(&mut self, bit: Bit, anchor: WireId) -> Result<WireId> {
+        match bit {
+            Bit::Wire(wire) => Ok(wire),
+            Bit::Const(value) => {
+                let zero = self.gate(GateType::XOR, vec![anchor, anchor])?;
+                if value {
+                    self.gate(GateType::NOT, vec![zero])
+                } else {
+                    Ok(zero)
+                }
+            }
+        }
+    }
+
+    /// x < bound for x given least significant bit first
+    /// Scanning upward, lt_i = ~x_i | lt_(i-1) where the bound bit is 1 and
+    /// ~x_i & lt_(i-1) where it is 0, starting from "equal so far" (false)
+    fn less_than(&mut self, bits: &[WireId], bound: u64) -> Result<Bit> {
+        if bits.len() < 64 && bound >= 1 << bits.len() {
+            return Ok(Bit::Const(true));
+        }
+
+        let mut less = Bit::Const(false);
+        for (i, &wire) in bits.iter().enumerate() {
+            let bound_bit = i < 64 && bound >> i & 1 == 1;
+            let not_x = self.not(Bit::Wire(wire))?;
+            less = if bound_bit {
+                self.or(not_x, less)?
+            } else {
+                self.and(not_x, less)?
+            };
+        }
+
+        Ok(less)
+    }
+
+    /// Exactly one bit set: at least one, and no bit set after another
+    fn one_hot(&mut self, bits: &[WireId]) -> Result<Bit> {
+        let mut seen = Bit::Const(false);
+        let mut conflict = Bit::Const(false);
+
+        for &wire in bits {
+            let both = self.and(seen, Bit::Wire(wire))?;
+            conflict = self.or(conflict, both)?;
+            seen = self.or(seen, Bit::Wire(wire))?;
+        }
+
+        let no_conflict = self.not(conflict)?;
+        self.and(seen, no_conflict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constrained_circuit(constraint: &str) -> Circuit {
+        Circuit::from_json(&format!(
+            r#"{{
+                "name": "constrained",
+                "description": "Three inputs with a constraint",
+                "metadata": {{
+                    "inputs": [
+                        {{"name": "x0", "id": 1}},
+                        {{"name": "x1", "id": 2}},
+                        {{"name": "x2", "id": 3}}
+                    ],
+                    "outputs": [{{"name": "out", "id": 10}}],
+                    "constraints": [{constraint}]
+                }},
+                "gates": [{{"id": 10, "type": "XOR", "in": [1, 2]}}]
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    fn check(circuit: &Circuit, value: u8) -> bool {
+        let checks = InputChecks::new(circuit).unwrap();
+        let inputs: Vec<bool> = (0..3).map(|bit| value >> bit & 1 == 1).collect();
+        checks.first_violation(circuit, &inputs).unwrap().is_none()
+    }
+
+    #[test]
+    fn test_less_than_check() {
+        for bound in 0..=9u8 {
+            let circuit = constrained_circuit(&format!(
+                r#"{{"kind": "less_than", "bits": ["x0", "x1", "x2"], "bound": {bound}}}"#
+            ));
+            for value in 0..8u8 {
+                assert_eq!(check(&circuit, value), value < bound, "{value} < {bound}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_hot_check() {
+        let circuit = constrained_circuit(r#"{"kind": "one_hot", "bits": ["x0", "x1", "x2"]}"#);
+        for value in 0..8u8 {
+            assert_eq!(check(&circuit, value), value.count_ones() == 1, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_input_rejected() {
+        let circuit = constrained_circuit(r#"{"kind": "one_hot", "bits": ["x0", "y"]}"#);
+        assert!(InputChecks::new(&circuit).is_err());
+    }
+}
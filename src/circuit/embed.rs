@@ -0,0 +1,140 @@
+use anyhow::Result;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::circuit::{Circuit, CircuitFormat, CircuitRegistry, LocalEvaluator};
+
+/// Directory under `OUT_DIR` holding embedded circuits; `include_circuit!`
+/// spells it out since `concat!` only takes literals
+pub const EMBED_DIR: &str = "gmw-circuits";
+
+/// Build-script half of `include_circuit!`
+/// Call from `build.rs` for every circuit the crate embeds, with the same
+/// reference (`path` or `path#name`, relative to the package root) later
+/// passed to the macro. The circuit is parsed and validated now, so a broken
+/// circuit fails the build, and its binary encoding is written to `OUT_DIR`
+pub fn embed_circuit(reference: &str) -> Result<PathBuf> {
+    let out_dir = env::var("OUT_DIR")
+        .map_err(|_| anyhow::anyhow!("embed_circuit must be called from a build script"))?;
+    let (path, _) = CircuitRegistry::split_reference(reference);
+    println!("cargo:rerun-if-changed={path}");
+    embed_circuit_into(reference, Path::new(&out_dir))
+}
+
+/// Validate `reference` and write its binary encoding below `out_dir`
+pub fn embed_circuit_into(reference: &str, out_dir: &Path) -> Result<PathBuf> {
+    let (path, _) = CircuitRegistry::split_reference(reference);
+    let relative = Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !relative {
+        return Err(anyhow::anyhow!(
+            "Embedded circuit {} must be a relative path without ..",
+            reference
+        ));
+    }
+
+    let circuit = Circuit::load(reference)
+        .map_err(|error| anyhow::anyhow!("Cannot embed {}: {}", reference, error))?;
+    let bytes = validate(&circuit)
+        .map_err(|error| anyhow::anyhow!("Cannot embed {}: {}", reference, error))?;
+
+    let target = out_dir.join(EMBED_DIR).join(format!("{reference}.gmwc"));
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&target, bytes)?;
+    Ok(target)
+}
+
+/// Check what a run would otherwise only find out at runtime (gate arity,
+/// wires used before they are defined, outputs and buses naming missing
+/// wires) and return the binary encoding
+fn validate(circuit: &Circuit) -> Result<Vec<u8>> {
+    // Binary encoding rejects gates of the wrong arity
+    let bytes = circuit.encode(CircuitFormat::Binary)?.bytes;
+    let values = LocalEvaluator::evaluate(circuit, &vec![false; circuit.metadata.inputs.len()])?;
+    for output in &circuit.metadata.outputs {
+        if !values.contains_key(&output.id) {
+            return Err(anyhow::anyhow!(
+                "Output {} uses undefined wire {}",
+                output.name,
+                output.id
+            ));
+        }
+    }
+    let outputs = |name: &String| circuit.metadata.outputs.iter().any(|o| &o.name == name);
+    let inputs = |name: &String| circuit.metadata.inputs.iter().any(|i| &i.name == name);
+    for bus in &circuit.metadata.buses {
+        let flags = bus.carry.iter().chain(&bus.overflow);
+        if let Some(bit) = bus.bits.iter().chain(flags).find(|bit| !outputs(bit)) {
+            return Err(anyhow::anyhow!(
+                "Bus {} references missing output {}",
+                bus.name,
+                bit
+            ));
+        }
+    }
+    for bus in &circuit.metadata.input_buses {
+        if let Some(bit) = bus.bits.iter().find(|bit| !inputs(bit)) {
+            return Err(anyhow::anyhow!(
+                "Input bus {} references missing input {}",
+                bus.name,
+                bit
+            ));
+        }
+    }
+    Ok(bytes)
+}
+
+/// Circuit embedded in the binary by `embed::embed_circuit` in `build.rs`
+/// No file is read and nothing can fail to parse at runtime; the reference
+/// must match the one given to the build script
+#[macro_export]
+macro_rules! include_circuit {
+    ($reference:literal) => {
+        $crate::Circuit::decode(
+            include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/gmw-circuits/",
+                $reference,
+                ".gmwc"
+            )),
+            $crate::CircuitFormat::Binary,
+        )
+        .expect("embedded circuits are validated by the build script")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_circuit() {
+        let dir = env::temp_dir().join(format!("gmw-embed-{}", std::process::id()));
+        let target = embed_circuit_into("circuits/full_adder.json", &dir).unwrap();
+        assert_eq!(
+            target,
+            dir.join("gmw-circuits/circuits/full_adder.json.gmwc")
+        );
+
+        let embedded = Circuit::decode(&fs::read(&target).unwrap(), CircuitFormat::Binary).unwrap();
+        let original = Circuit::from_file("circuits/full_adder.json").unwrap();
+        assert_eq!(
+            serde_json::to_value(&embedded).unwrap(),
+            serde_json::to_value(&original).unwrap()
+        );
+
+        assert!(embed_circuit_into("../circuits/and.json", &dir).is_err());
+        assert!(embed_circuit_into("circuits/missing.json", &dir).is_err());
+
+        // A gate reading a wire defined later fails validation
+        let mut broken = original;
+        broken.gates.reverse();
+        assert!(validate(&broken).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
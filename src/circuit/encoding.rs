@@ -0,0 +1,232 @@
+use anyhow::Result;
+
+use crate::circuit::{BusEncoding, BusInfo, Circuit};
+
+/// Bits of `value` in `encoding`, least significant bit first
+/// For `OneHot` the value is the index of the set bit; for `FixedPoint` it
+/// is the raw integer, i.e. the number scaled by 2^`frac_bits`
+pub fn encode_bits(value: i128, width: usize, encoding: BusEncoding) -> Result<Vec<bool>> {
+    if width == 0 {
+        return Err(anyhow::anyhow!("Cannot encode into an empty bus"));
+    }
+    if width > 127 {
+        return Err(anyhow::anyhow!(
+            "Bus width {} exceeds the 127-bit encoding limit",
+            width
+        ));
+    }
+
+    let fits = match encoding {
+        BusEncoding::Unsigned => value >= 0 && value >> width == 0,
+        BusEncoding::SignMagnitude => value.unsigned_abs() >> (width - 1) == 0,
+        BusEncoding::TwosComplement | BusEncoding::FixedPoint { .. } => {
+            matches!(value >> (width - 1), 0 | -1)
+        }
+        BusEncoding::OneHot => (0..width as i128).contains(&value),
+    };
+    if !fits {
+        return Err(anyhow::anyhow!(
+            "Value {} does not fit in {} bits as {:?}",
+            value,
+            width,
+            encoding
+        ));
+    }
+
+    let bits = match encoding {
+        BusEncoding::SignMagnitude => {
+            let magnitude = value.unsigned_abs();
+            let mut bits: Vec<bool> = (0..width - 1).map(|i| (magnitude >> i) & 1 == 1).collect();
+            bits.push(value < 0);
+            bits
+        }
+        BusEncoding::OneHot => (0..width).map(|i| i as i128 == value).collect(),
+        // Arithmetic shifts yield the two's complement bits of negative values
+        _ => (0..width).map(|i| (value >> i) & 1 == 1).collect(),
+    };
+    Ok(bits)
+}
+
+/// Raw Q-format integer nearest to `value`
+pub fn fixed_to_raw(value: f64, frac_bits: u8) -> Result<i128> {
+    if !value.is_finite() {
+        return Err(anyhow::anyhow!("Cannot encode {} as fixed point", value));
+    }
+    // Out-of-range values saturate and are then rejected by `encode_bits`
+    Ok((value * 2f64.powi(frac_bits.into())).round() as i128)
+}
+
+/// Number represented by a raw Q-format integer
+pub fn raw_to_fixed(raw: i128, frac_bits: u8) -> f64 {
+    raw as f64 / 2f64.powi(frac_bits.into())
+}
+
+/// Builds a circuit's input vector from application-level values
+/// Multi-bit inputs declared in `metadata.input_buses` are set by bus name
+/// in their declared encoding; any other input is set bit by bit
+pub struct InputEncoder<'a> {
+    circuit: &'a Circuit,
+    values: Vec<Option<bool>>,
+}
+
+impl<'a> InputEncoder<'a> {
+    pub fn new(circuit: &'a Circuit) -> Self {
+        Self {
+            circuit,
+            values: vec![None; circuit.metadata.inputs.len()],
+        }
+    }
+
+    /// Set a single input
+    pub fn bit(&mut self, name: &str, value: bool) -> Result<&mut Self> {
+        let position = self
+            .circuit
+            .metadata
+            .inputs
+            .iter()
+            .position(|info| info.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Input {} not found", name))?;
+        self.values[position] = Some(value);
+        Ok(self)
+    }
+
+    /// Set an integer input bus; a fixed-point bus takes the integer as a
+    /// number and a one-hot bus as the index of the set bit
+    pub fn int(&mut self, bus: &str, value: i64) -> Result<&mut Self> {
+        let info = self.bus(bus)?;
+        let raw = match info.encoding {
+            BusEncoding::FixedPoint { frac_bits } => i128::from(value)
+                .checked_mul(1 << frac_bits)
+                .ok_or_else(|| anyhow::anyhow!("Value {} overflows bus {}", value, bus))?,
+            _ => value.into(),
+        };
+        self.set_bus(info, raw)
+    }
+
+    /// Set a one-hot bus to variant `index`, e.g. an enum discriminant
+    pub fn variant(&mut self, bus: &str, index: usize) -> Result<&mut Self> {
+        let info = self.bus(bus)?;
+        if info.encoding != BusEncoding::OneHot {
+            return Err(anyhow::anyhow!("Input bus {} is not one-hot", bus));
+        }
+        self.set_bus(info, i128::try_from(index)?)
+    }
+
+    /// Set a fixed-point bus, rounding to the nearest representable value
+    pub fn fixed(&mut self, bus: &str, value: f64) -> Result<&mut Self> {
+        let info = self.bus(bus)?;
+        let BusEncoding::FixedPoint { frac_bits } = info.encoding else {
+            return Err(anyhow::anyhow!("Input bus {} is not fixed point", bus));
+        };
+        self.set_bus(info, fixed_to_raw(value, frac_bits)?)
+    }
+
+    /// Input vector in metadata order; fails if any input was not set
+    pub fn finish(&self) -> Result<Vec<bool>> {
+        self.values
+            .iter()
+            .zip(&self.circuit.metadata.inputs)
+            .map(|(value, info)| {
+                value.ok_or_else(|| anyhow::anyhow!("Input {} was not set", info.name))
+            })
+            .collect()
+    }
+
+    fn bus(&self, name: &str) -> Result<&'a BusInfo> {
+        self.circuit
+            .metadata
+            .input_buses
+            .iter()
+            .find(|bus| bus.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Input bus {} not found", name))
+    }
+
+    fn set_bus(&mut self, info: &BusInfo, raw: i128) -> Result<&mut Self> {
+        let bits = encode_bits(raw, info.bits.len(), info.encoding)?;
+        for (name, bit) in info.bits.iter().zip(bits) {
+            self.bit(name, bit)?;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{CircuitBuilder, LocalEvaluator};
+
+    #[test]
+    fn test_encode_bits() {
+        let bits = |value, width, encoding| encode_bits(value, width, encoding).unwrap();
+        assert_eq!(
+            bits(6, 4, BusEncoding::Unsigned),
+            [false, true, true, false]
+        );
+        assert_eq!(
+            bits(-3, 4, BusEncoding::TwosComplement),
+            [true, false, true, true]
+        );
+        assert_eq!(
+            bits(-3, 4, BusEncoding::SignMagnitude),
+            [true, true, false, true]
+        );
+        assert_eq!(bits(2, 4, BusEncoding::OneHot), [false, false, true, false]);
+
+        assert!(encode_bits(16, 4, BusEncoding::Unsigned).is_err());
+        assert!(encode_bits(-1, 4, BusEncoding::Unsigned).is_err());
+        assert!(encode_bits(8, 4, BusEncoding::TwosComplement).is_err());
+        assert!(encode_bits(-8, 4, BusEncoding::SignMagnitude).is_err());
+        assert!(encode_bits(4, 4, BusEncoding::OneHot).is_err());
+
+        // Q4.4: 2.75 is 44/16
+        assert_eq!(fixed_to_raw(2.75, 4).unwrap(), 44);
+        assert_eq!(raw_to_fixed(-44, 4), -2.75);
+        assert!(fixed_to_raw(f64::NAN, 4).is_err());
+    }
+
+    #[test]
+    fn test_input_encoder() {
+        let circuit = CircuitBuilder::build("encoded", |b| {
+            let color = b.input_encoded("color", 3, BusEncoding::OneHot);
+            let price = b.input_encoded("price", 8, BusEncoding::FixedPoint { frac_bits: 4 });
+            let flag = b.input("flag");
+            let red = color.bit(0).expect("color has three variants");
+            let selected = b.and(red, flag);
+            b.output("red_and_flag", selected);
+            b.output_bus(
+                "price_out",
+                &price,
+                BusEncoding::FixedPoint { frac_bits: 4 },
+            );
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(circuit.metadata.input_buses.len(), 2);
+        // One-hot inputs are constrained to a single set bit
+        assert_eq!(circuit.metadata.constraints.len(), 1);
+
+        let mut encoder = InputEncoder::new(&circuit);
+        encoder.variant("color", 0).unwrap();
+        encoder.fixed("price", -1.5).unwrap();
+        assert!(encoder.finish().is_err());
+        encoder.bit("flag", true).unwrap();
+        let inputs = encoder.finish().unwrap();
+        assert_eq!(&inputs[..3], [true, false, false]);
+        // -1.5 in Q4.4 is -24
+        assert_eq!(
+            &inputs[3..11],
+            encode_bits(-24, 8, BusEncoding::TwosComplement).unwrap()
+        );
+
+        let values = LocalEvaluator::evaluate(&circuit, &inputs).unwrap();
+        let output = &circuit.metadata.outputs[0];
+        assert!(values[&output.id]);
+
+        assert!(encoder.int("price", 8).is_err());
+        assert!(encoder.variant("price", 0).is_err());
+        assert!(encoder.fixed("color", 1.0).is_err());
+        assert!(encoder.variant("color", 3).is_err());
+        encoder.int("price", -8).unwrap();
+        assert!(encoder.bit("missing", true).is_err());
+    }
+}
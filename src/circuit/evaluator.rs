@@ -1,4 +1,4 @@
-use crate::circuit::{Circuit, GateType, WireId};
+use crate::circuit::{Circuit, GateType, PreparedCircuit, WireId};
 use anyhow::Result;
 use std::collections::HashMap;
 
@@ -55,6 +55,121 @@ impl LocalEvaluator {
             .ok_or_else(|| anyhow::anyhow!("Wire {} not found in circuit", wire_id))
     }
 
+    /// Plaintext value of every slot of a prepared circuit, input checks
+    /// included, indexed like its share arena; unwritten slots, and gates
+    /// reading one, are `None`
+    pub fn evaluate_slots(
+        prepared: &PreparedCircuit,
+        inputs: &[bool],
+    ) -> Result<Vec<Option<bool>>> {
+        let mut values = vec![None; prepared.index.len()];
+        for (info, &input) in prepared.metadata.inputs.iter().zip(inputs) {
+            let slot = prepared
+                .index
+                .slot(info.id)
+                .ok_or_else(|| anyhow::anyhow!("Input {} not found", info.name))?;
+            values[slot] = Some(input);
+        }
+
+        for gate in &prepared.gates {
+            let Some(operands) = gate
+                .inputs
+                .iter()
+                .map(|&slot| values.get(slot).copied().flatten())
+                .collect::<Option<Vec<bool>>>()
+            else {
+                continue;
+            };
+            values[gate.output] = Some(match (&gate.gate_type, operands.as_slice()) {
+                (GateType::AND, &[a, b]) => a & b,
+                (GateType::OR, &[a, b]) => a | b,
+                (GateType::XOR, &[a, b]) => a ^ b,
+                (GateType::NOT, &[a]) => !a,
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Gate at slot {} has {} inputs",
+                        gate.output,
+                        operands.len()
+                    ))
+                }
+            });
+        }
+
+        Ok(values)
+    }
+
+    /// Evaluate with some inputs unknown (`None`), in three-valued logic
+    /// A wire is `Some` when the known inputs already decide it whatever the
+    /// unknown ones are: `x & 0` is 0, `x | 1` is 1. Unknown wires are kept
+    /// as literals of an unknown wire, possibly negated, so `x ^ x`,
+    /// `x & !x` and `x | !x` are decided too; other identities are not
+    /// found, so `None` means undecided by this analysis, not necessarily
+    /// dependent on the unknown inputs
+    pub fn evaluate_partial(
+        circuit: &Circuit,
+        inputs: &[Option<bool>],
+    ) -> Result<HashMap<WireId, Option<bool>>> {
+        if inputs.len() != circuit.metadata.inputs.len() {
+            return Err(anyhow::anyhow!(
+                "Circuit expects {} inputs, got {}",
+                circuit.metadata.inputs.len(),
+                inputs.len()
+            ));
+        }
+        let mut wire_values = HashMap::new();
+        for (info, &input) in circuit.metadata.inputs.iter().zip(inputs) {
+            let value = match input {
+                Some(bit) => Partial::Known(bit),
+                None => Partial::unknown(info.id),
+            };
+            wire_values.insert(info.id, value);
+        }
+
+        for gate in &circuit.gates {
+            let operand = |position: usize| {
+                let wire = gate.inputs.get(position).copied().ok_or_else(|| {
+                    anyhow::anyhow!("Gate {} has {} inputs", gate.id, gate.inputs.len())
+                })?;
+                wire_values
+                    .get(&wire)
+                    .copied()
+                    .ok_or_else(|| anyhow::anyhow!("Wire {} not found", wire))
+            };
+            let result = match gate.gate_type {
+                GateType::AND => Partial::and(operand(0)?, operand(1)?, gate.id),
+                GateType::OR => !Partial::and(!operand(0)?, !operand(1)?, gate.id),
+                GateType::XOR => Partial::xor(operand(0)?, operand(1)?, gate.id),
+                GateType::NOT => !operand(0)?,
+            };
+            wire_values.insert(gate.id, result);
+        }
+
+        Ok(wire_values
+            .into_iter()
+            .map(|(wire, value)| (wire, value.known()))
+            .collect())
+    }
+
+    /// Every output by name with its value if the known inputs decide it,
+    /// in metadata order
+    pub fn determined_outputs(
+        circuit: &Circuit,
+        inputs: &[Option<bool>],
+    ) -> Result<Vec<(String, Option<bool>)>> {
+        let wire_values = Self::evaluate_partial(circuit, inputs)?;
+        circuit
+            .metadata
+            .outputs
+            .iter()
+            .map(|output| {
+                let value = wire_values
+                    .get(&output.id)
+                    .ok_or_else(|| anyhow::anyhow!("Wire {} not found", output.id))?;
+                Ok((output.name.clone(), *value))
+            })
+            .collect()
+    }
+
     /// Helper to get wire value with error handling
     fn get_wire_value(wire_values: &HashMap<WireId, bool>, wire_id: WireId) -> Result<bool> {
         wire_values
@@ -64,6 +179,94 @@ impl LocalEvaluator {
     }
 }
 
+/// Wire value in partial evaluation: known, or the value of an unknown
+/// wire, possibly negated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Partial {
+    Known(bool),
+    Unknown { wire: WireId, negated: bool },
+}
+
+impl Partial {
+    fn unknown(wire: WireId) -> Self {
+        Partial::Unknown {
+            wire,
+            negated: false,
+        }
+    }
+
+    fn known(self) -> Option<bool> {
+        match self {
+            Partial::Known(bit) => Some(bit),
+            Partial::Unknown { .. } => None,
+        }
+    }
+
+    /// `a & b`, as a new unknown `output` unless decided or equal to an operand
+    fn and(a: Self, b: Self, output: WireId) -> Self {
+        use Partial::{Known, Unknown};
+        match (a, b) {
+            (Known(false), _) | (_, Known(false)) => Known(false),
+            (Known(true), other) | (other, Known(true)) => other,
+            (
+                Unknown {
+                    wire: x,
+                    negated: p,
+                },
+                Unknown {
+                    wire: y,
+                    negated: q,
+                },
+            ) if x == y => {
+                if p == q {
+                    a
+                } else {
+                    Known(false)
+                }
+            }
+            _ => Partial::unknown(output),
+        }
+    }
+
+    fn xor(a: Self, b: Self, output: WireId) -> Self {
+        use Partial::{Known, Unknown};
+        match (a, b) {
+            (Known(x), Known(y)) => Known(x ^ y),
+            (Known(flip), Unknown { wire, negated }) | (Unknown { wire, negated }, Known(flip)) => {
+                Unknown {
+                    wire,
+                    negated: negated ^ flip,
+                }
+            }
+            (
+                Unknown {
+                    wire: x,
+                    negated: p,
+                },
+                Unknown {
+                    wire: y,
+                    negated: q,
+                },
+            ) if x == y => Known(p ^ q),
+            _ => Partial::unknown(output),
+        }
+    }
+}
+
+impl std::ops::Not for Partial {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Partial::Known(bit) => Partial::Known(!bit),
+            Partial::Unknown { wire, negated } => Partial::Unknown {
+                wire,
+                negated: !negated,
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +297,7 @@ mod tests {
                     name: "result".to_string(),
                     id: 3,
                 }],
+                ..Default::default()
             },
         };
 
@@ -115,4 +319,49 @@ mod tests {
             true
         );
     }
+
+    #[test]
+    fn test_partial_evaluation() {
+        let circuit = Circuit::from_file("circuits/full_adder.json").unwrap();
+        // a = 0, b = 0: no carry whatever cin is, sum follows cin
+        let outputs =
+            LocalEvaluator::determined_outputs(&circuit, &[Some(false), Some(false), None]);
+        assert_eq!(
+            outputs.unwrap(),
+            [
+                ("sum".to_string(), None),
+                ("carry".to_string(), Some(false))
+            ]
+        );
+
+        // Every decided wire agrees with each completion of the unknown inputs
+        let trits = [None, Some(false), Some(true)];
+        for code in 0..27 {
+            let partial: Vec<Option<bool>> =
+                (0..3).map(|i| trits[code / 3usize.pow(i) % 3]).collect();
+            let values = LocalEvaluator::evaluate_partial(&circuit, &partial).unwrap();
+            for completion in 0..8 {
+                let inputs: Vec<bool> = (partial.iter().enumerate())
+                    .map(|(i, input)| input.unwrap_or(completion >> i & 1 == 1))
+                    .collect();
+                for (wire, value) in LocalEvaluator::evaluate(&circuit, &inputs).unwrap() {
+                    assert!(values[&wire].is_none_or(|decided| decided == value));
+                }
+            }
+        }
+
+        // Identities on one unknown wire are decided
+        let circuit = Circuit::from_json(
+            r#"{"name": "identities", "description": "",
+                "gates": [{"id": 1, "type": "NOT", "in": [0]}, {"id": 2, "type": "XOR", "in": [0, 0]},
+                          {"id": 3, "type": "AND", "in": [0, 1]}, {"id": 4, "type": "OR", "in": [1, 0]}],
+                "metadata": {"inputs": [{"name": "x", "id": 0}], "outputs": [
+                    {"name": "zero", "id": 2}, {"name": "never", "id": 3}, {"name": "always", "id": 4}]}}"#,
+        )
+        .unwrap();
+        let outputs = LocalEvaluator::determined_outputs(&circuit, &[None]).unwrap();
+        let values: Vec<Option<bool>> = outputs.into_iter().map(|(_, value)| value).collect();
+        assert_eq!(values, [Some(false), Some(false), Some(true)]);
+        assert!(LocalEvaluator::evaluate_partial(&circuit, &[]).is_err());
+    }
 }
@@ -0,0 +1,1441 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::circuit::macros::constant_operand;
+use crate::circuit::sieve::{from_sieve, to_sieve};
+use crate::circuit::text::{from_text, to_text};
+use crate::circuit::{
+    BusEncoding, BusExtension, BusInfo, Circuit, CircuitMetadata, CircuitRegistry, CircuitTemplate,
+    Gate, GateType, InputConstraint, InputInfo, OutputInfo, PartySpec, PreparedCircuit,
+    ScheduleHint, WireId,
+};
+
+/// Magic bytes at the start of a compact binary circuit
+const BINARY_MAGIC: &[u8; 4] = b"GMWC";
+const BINARY_VERSION: u8 = 2;
+/// Version 1 had fixed gate tags instead of an opcode table
+const BINARY_VERSION_FIXED_OPCODES: u8 = 1;
+/// Bits of a bus encoding tag marking a following carry or overflow output name
+const BUS_CARRY: u8 = 0x10;
+const BUS_OVERFLOW: u8 = 0x20;
+/// Bit of a bus encoding tag marking an input bus declaration
+const BUS_INPUT: u8 = 0x40;
+/// Bit of a bus encoding tag marking a following extension byte
+const BUS_EXTEND: u8 = 0x80;
+
+/// Gate types this reader understands: name in the opcode table and arity
+/// Version 1 files use the position in this list as their fixed gate tag
+pub(crate) const GATE_OPCODES: [(&str, GateType, usize); 4] = [
+    ("XOR", GateType::XOR, 2),
+    ("NOT", GateType::NOT, 1),
+    ("AND", GateType::AND, 2),
+    ("OR", GateType::OR, 2),
+];
+
+/// Supported on-disk circuit encodings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CircuitFormat {
+    /// The crate's own JSON format, the only one that carries a description
+    Json,
+    /// Bristol Fashion text format (XOR, AND, INV, EQ, EQW gates)
+    Bristol,
+    /// Compact binary encoding of the JSON model, lossless
+    Binary,
+    /// Line-based text meant to be written by hand, e.g. `g5 = AND g1 g2`
+    Text,
+    /// SIEVE IR 2.0 relation over the field of two elements; outputs are
+    /// asserted equal to public inputs
+    Sieve,
+}
+
+/// A circuit encoded in some format, with notes on information that was lost
+#[derive(Debug, Clone)]
+pub struct EncodedCircuit {
+    pub bytes: Vec<u8>,
+    pub warnings: Vec<String>,
+}
+
+impl CircuitFormat {
+    /// Guess the format from a file extension
+    /// `.json` is JSON, `.txt` and `.bristol` are Bristol, `.gmwc` is binary
+    /// `.gmwt` is text and `.sieve` and `.rel` are SIEVE IR
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "txt" | "bristol" => Some(Self::Bristol),
+            "gmwc" => Some(Self::Binary),
+            "gmwt" => Some(Self::Text),
+            "sieve" | "rel" => Some(Self::Sieve),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CircuitFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "json" => Ok(Self::Json),
+            "bristol" => Ok(Self::Bristol),
+            "binary" => Ok(Self::Binary),
+            "text" => Ok(Self::Text),
+            "sieve" => Ok(Self::Sieve),
+            _ => Err(anyhow::anyhow!("Unknown circuit format: {}", name)),
+        }
+    }
+}
+
+impl fmt::Display for CircuitFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Bristol => write!(f, "bristol"),
+            Self::Binary => write!(f, "binary"),
+            Self::Text => write!(f, "text"),
+            Self::Sieve => write!(f, "sieve"),
+        }
+    }
+}
+
+impl Circuit {
+    /// Load a circuit in the format implied by its extension, defaulting to JSON
+    /// JSON files may hold several circuits, selected with `file.json#name`
+    pub fn load(reference: &str) -> Result<Self> {
+        let (path, name) = CircuitRegistry::split_reference(reference);
+
+        match CircuitFormat::from_path(path).unwrap_or(CircuitFormat::Json) {
+            CircuitFormat::Json if name.is_none() && Path::new(&meta_path(path)).exists() => {
+                Self::from_files(path, &meta_path(path))
+            }
+            CircuitFormat::Json => Ok(CircuitRegistry::from_file(path)?.select(name)?.clone()),
+            _ if name.is_some() => Err(anyhow::anyhow!(
+                "Only JSON files hold multiple circuits: {}",
+                reference
+            )),
+            format => Self::decode(&fs::read(path)?, format),
+        }
+    }
+
+    /// Load a JSON circuit whose gate list and metadata live in separate
+    /// files, e.g. a generated `circuit.json` next to a hand-maintained
+    /// `circuit.meta.json`. The gate file holds a bare array of gates or an
+    /// object with `gates`; the metadata file holds `metadata` and usually
+    /// `name` and `description`. A field given in both files is an error
+    pub fn from_files(gates: &str, metadata: &str) -> Result<Self> {
+        let mut fields = match serde_json::from_str(&fs::read_to_string(gates)?)? {
+            serde_json::Value::Array(gates) => {
+                serde_json::Map::from_iter([("gates".to_string(), gates.into())])
+            }
+            serde_json::Value::Object(fields) => fields,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "{} holds neither gates nor a circuit",
+                    gates
+                ))
+            }
+        };
+        let serde_json::Value::Object(meta) = serde_json::from_str(&fs::read_to_string(metadata)?)?
+        else {
+            return Err(anyhow::anyhow!("{} is not a JSON object", metadata));
+        };
+        for (key, value) in meta {
+            if fields.contains_key(&key) {
+                return Err(anyhow::anyhow!(
+                    "{} is given in both {} and {}",
+                    key,
+                    gates,
+                    metadata
+                ));
+            }
+            fields.insert(key, value);
+        }
+        Ok(serde_json::from_value(fields.into())?)
+    }
+
+    /// Load a circuit template and instantiate it with `params`
+    /// Without parameters this is `load`, so plain circuits work too
+    pub fn load_with_params(reference: &str, params: &HashMap<String, i64>) -> Result<Self> {
+        if params.is_empty() {
+            return Self::load(reference);
+        }
+        CircuitTemplate::from_file(reference)?.instantiate(params)
+    }
+
+    /// Parse a circuit from bytes in the given format
+    pub fn decode(bytes: &[u8], format: CircuitFormat) -> Result<Self> {
+        match format {
+            CircuitFormat::Json => {
+                let value: serde_json::Value = serde_json::from_slice(bytes)?;
+                if CircuitTemplate::is_template(&value) {
+                    CircuitTemplate::from_value(value)?.instantiate(&HashMap::new())
+                } else {
+                    Ok(serde_json::from_value(value)?)
+                }
+            }
+            CircuitFormat::Bristol => from_bristol(std::str::from_utf8(bytes)?),
+            CircuitFormat::Binary => from_binary(bytes),
+            CircuitFormat::Text => from_text(std::str::from_utf8(bytes)?),
+            CircuitFormat::Sieve => from_sieve(std::str::from_utf8(bytes)?),
+        }
+    }
+
+    /// Gate type names a binary circuit declares in its opcode table, read
+    /// without decoding the rest, so support can be checked up front
+    pub fn binary_gate_types(bytes: &[u8]) -> Result<Vec<String>> {
+        let mut reader = BinaryReader::new(bytes)?;
+        Ok(reader
+            .opcode_table()?
+            .into_iter()
+            .map(|opcode| opcode.name)
+            .collect())
+    }
+
+    /// Gate type names this build can read from binary circuits
+    pub fn supported_gate_types() -> Vec<&'static str> {
+        GATE_OPCODES.iter().map(|&(name, _, _)| name).collect()
+    }
+
+    /// Encode the circuit in the given format
+    pub fn encode(&self, format: CircuitFormat) -> Result<EncodedCircuit> {
+        match format {
+            CircuitFormat::Json => Ok(EncodedCircuit {
+                bytes: serde_json::to_vec_pretty(self)?,
+                warnings: Vec::new(),
+            }),
+            CircuitFormat::Bristol => to_bristol(self),
+            CircuitFormat::Binary => Ok(EncodedCircuit {
+                bytes: to_binary(self)?,
+                warnings: Vec::new(),
+            }),
+            CircuitFormat::Text => to_text(self),
+            CircuitFormat::Sieve => to_sieve(self),
+        }
+    }
+
+    /// Graphviz DOT of the gate graph, for drawing only
+    /// Inputs and outputs are boxes named after their metadata; interactive
+    /// gates are shaded. There is no reader: DOT is not a circuit format
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph \"{}\" {{\n  rankdir=LR;\n", dot_escape(&self.name));
+        for input in &self.metadata.inputs {
+            let _ = writeln!(
+                dot,
+                "  w{} [shape=box, label=\"{}\"];",
+                input.id,
+                dot_escape(&input.name)
+            );
+        }
+        for gate in &self.gates {
+            let style = if gate.gate_type.is_interactive() {
+                ", style=filled, fillcolor=lightgrey"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                dot,
+                "  w{} [label=\"{:?}\"{style}];",
+                gate.id, gate.gate_type
+            );
+            for input in &gate.inputs {
+                let _ = writeln!(dot, "  w{input} -> w{};", gate.id);
+            }
+        }
+        for (i, output) in self.metadata.outputs.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "  out{i} [shape=box, label=\"{}\"];\n  w{} -> out{i};",
+                dot_escape(&output.name),
+                output.id
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Wire of a Bristol export before final numbering
+#[derive(Debug, Clone, Copy)]
+enum BristolWire {
+    Input(usize),
+    Internal(usize),
+    Output(usize),
+}
+
+/// Write Bristol Fashion with one single-bit value per input and output
+/// Bristol requires inputs to occupy the first wires and outputs the last,
+/// so wires are renumbered; OR gates are expanded with De Morgan's law
+fn to_bristol(circuit: &Circuit) -> Result<EncodedCircuit> {
+    let metadata = &circuit.metadata;
+    let mut wires: HashMap<WireId, BristolWire> = metadata
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| (input.id, BristolWire::Input(i)))
+        .collect();
+
+    // Outputs driven directly by a gate are written straight to their final wire
+    let mut output_wires = HashMap::new();
+    for (j, output) in metadata.outputs.iter().enumerate() {
+        let is_gate = circuit.gates.iter().any(|gate| gate.id == output.id);
+        if is_gate && !output_wires.contains_key(&output.id) {
+            output_wires.insert(output.id, j);
+        }
+    }
+
+    let mut lines: Vec<(Vec<BristolWire>, BristolWire, &str)> = Vec::new();
+    let mut internal = 0;
+    let mut fresh = || {
+        internal += 1;
+        BristolWire::Internal(internal - 1)
+    };
+    let mut or_gates = 0;
+
+    for gate in &circuit.gates {
+        let inputs = gate
+            .inputs
+            .iter()
+            .map(|wire| {
+                wires.get(wire).copied().ok_or_else(|| {
+                    anyhow::anyhow!("Gate {} uses wire {} before it is defined", gate.id, wire)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let target = match output_wires.get(&gate.id) {
+            Some(&j) => BristolWire::Output(j),
+            None => fresh(),
+        };
+
+        match gate.gate_type {
+            GateType::XOR => lines.push((inputs, target, "XOR")),
+            GateType::AND => lines.push((inputs, target, "AND")),
+            GateType::NOT => lines.push((inputs, target, "INV")),
+            GateType::OR => {
+                // x | y = ~(~x & ~y)
+                let (not_x, not_y, and) = (fresh(), fresh(), fresh());
+                lines.push((vec![inputs[0]], not_x, "INV"));
+                lines.push((vec![inputs[1]], not_y, "INV"));
+                lines.push((vec![not_x, not_y], and, "AND"));
+                lines.push((vec![and], target, "INV"));
+                or_gates += 1;
+            }
+        }
+        wires.insert(gate.id, target);
+    }
+
+    // Outputs that are inputs or repeated need an explicit copy
+    for (j, output) in metadata.outputs.iter().enumerate() {
+        if output_wires.get(&output.id) != Some(&j) {
+            let source = wires.get(&output.id).copied().ok_or_else(|| {
+                anyhow::anyhow!("Output {} uses undefined wire {}", output.name, output.id)
+            })?;
+            lines.push((vec![source], BristolWire::Output(j), "EQW"));
+        }
+    }
+
+    let input_count = metadata.inputs.len();
+    let number = |wire: BristolWire| match wire {
+        BristolWire::Input(i) => i,
+        BristolWire::Internal(k) => input_count + k,
+        BristolWire::Output(j) => input_count + internal + j,
+    };
+
+    let mut text = format!(
+        "{} {}\n{}\n{}\n\n",
+        lines.len(),
+        input_count + internal + metadata.outputs.len(),
+        bristol_widths(input_count),
+        bristol_widths(metadata.outputs.len())
+    );
+    for (inputs, output, gate_type) in &lines {
+        let inputs: Vec<String> = inputs.iter().map(|&w| number(w).to_string()).collect();
+        text.push_str(&format!(
+            "{} 1 {} {} {}\n",
+            inputs.len(),
+            inputs.join(" "),
+            number(*output),
+            gate_type
+        ));
+    }
+
+    let mut warnings = vec![
+        "Bristol format has no circuit name, description or wire names; wires are renumbered"
+            .to_string(),
+    ];
+    if !metadata.buses.is_empty() {
+        warnings.push(format!("{} bus declarations dropped", metadata.buses.len()));
+    }
+    if !metadata.input_buses.is_empty() {
+        warnings.push(format!(
+            "{} input bus declarations dropped",
+            metadata.input_buses.len()
+        ));
+    }
+    if metadata.parties.is_some() {
+        warnings.push("party declaration dropped".to_string());
+    }
+    if !metadata.schedule.is_empty() {
+        warnings.push(format!(
+            "{} scheduling hints dropped",
+            metadata.schedule.len()
+        ));
+    }
+    if !metadata.openings.is_empty() {
+        warnings.push(format!(
+            "{} opening policies dropped; those wires can no longer be opened",
+            metadata.openings.len()
+        ));
+    }
+    if !metadata.public_inputs.is_empty() {
+        warnings.push(format!(
+            "{} public input declarations dropped",
+            metadata.public_inputs.len()
+        ));
+    }
+    if !metadata.constraints.is_empty() {
+        warnings.push(format!(
+            "{} input constraints dropped",
+            metadata.constraints.len()
+        ));
+    }
+    if or_gates > 0 {
+        warnings.push(format!("{or_gates} OR gates expanded into INV/AND gates"));
+    }
+
+    Ok(EncodedCircuit {
+        bytes: text.into_bytes(),
+        warnings,
+    })
+}
+
+/// Bristol value list where every value is a single bit
+fn bristol_widths(count: usize) -> String {
+    std::iter::once(count.to_string())
+        .chain(std::iter::repeat_n("1".to_string(), count))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Read Bristol Fashion
+/// Multi-bit values become one input or output per bit, first wire as the
+/// least significant bit; multi-bit outputs are also declared as buses
+fn from_bristol(text: &str) -> Result<Circuit> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let mut numbers = |what: &str| -> Result<Vec<usize>> {
+        lines
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Bristol circuit is missing its {} line", what))?
+            .split_whitespace()
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid number in {} line: {}", what, token))
+            })
+            .collect()
+    };
+
+    let header = numbers("header")?;
+    let input_widths = numbers("input")?;
+    let output_widths = numbers("output")?;
+    let (gate_count, wire_count) = match header[..] {
+        [gates, wires] => (gates, wires),
+        _ => return Err(anyhow::anyhow!("Bristol header must be `<gates> <wires>`")),
+    };
+    let input_widths = bristol_value_widths(&input_widths, "input")?;
+    let output_widths = bristol_value_widths(&output_widths, "output")?;
+
+    let mut metadata = CircuitMetadata::default();
+    let mut wire = 0;
+    for (value, &width) in input_widths.iter().enumerate() {
+        for bit in 0..width {
+            metadata.inputs.push(InputInfo {
+                name: bristol_bit_name("in", value, bit, width),
+                id: wire,
+            });
+            wire += 1;
+        }
+    }
+
+    // EQW gates copy a wire, so they become aliases rather than gates, and
+    // EQ gates fix a wire to a constant, which is folded into the gates
+    // reading it so that no OT is spent on it
+    let mut aliases: HashMap<WireId, WireId> = HashMap::new();
+    let resolve = |aliases: &HashMap<WireId, WireId>, wire: WireId| {
+        aliases.get(&wire).copied().unwrap_or(wire)
+    };
+    let mut constants: HashMap<WireId, bool> = HashMap::new();
+    let mut next_id = wire_count as WireId;
+    let mut fresh = || {
+        next_id += 1;
+        Ok(next_id - 1)
+    };
+
+    let mut gates = Vec::with_capacity(gate_count);
+    for line in lines {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (gate_type, counts) = tokens
+            .split_last()
+            .ok_or_else(|| anyhow::anyhow!("Empty Bristol gate line"))?;
+        let counts = counts
+            .iter()
+            .map(|token| {
+                token
+                    .parse::<WireId>()
+                    .map_err(|_| anyhow::anyhow!("Invalid wire in gate line: {}", line))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (inputs, output) = match counts[..] {
+            [2, 1, a, b, out] => (vec![a, b], out),
+            [1, 1, a, out] => (vec![a], out),
+            _ => return Err(anyhow::anyhow!("Unsupported Bristol gate line: {}", line)),
+        };
+        if *gate_type == "EQ" {
+            match inputs[..] {
+                [value @ (0 | 1)] => constants.insert(output, value == 1),
+                _ => return Err(anyhow::anyhow!("Unsupported Bristol gate: {}", line)),
+            };
+            continue;
+        }
+        let inputs: Vec<WireId> = inputs.iter().map(|&w| resolve(&aliases, w)).collect();
+
+        let gate_type = match (*gate_type, inputs.len()) {
+            ("XOR", 2) => GateType::XOR,
+            ("AND", 2) => GateType::AND,
+            ("INV", 1) => GateType::NOT,
+            ("EQW", 1) => {
+                match constants.get(&inputs[0]) {
+                    Some(&value) => {
+                        constants.insert(output, value);
+                    }
+                    None => {
+                        aliases.insert(output, inputs[0]);
+                    }
+                }
+                continue;
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported Bristol gate: {}", line)),
+        };
+        let known: Vec<Option<bool>> = inputs
+            .iter()
+            .map(|wire| constants.get(wire).copied())
+            .collect();
+        match (&gate_type, &known[..]) {
+            (GateType::NOT, &[Some(x)]) => {
+                constants.insert(output, !x);
+            }
+            (_, &[Some(x), Some(y)]) => {
+                let value = if gate_type == GateType::XOR {
+                    x ^ y
+                } else {
+                    x & y
+                };
+                constants.insert(output, value);
+            }
+            (_, &[Some(constant), None]) => {
+                gates.extend(constant_operand(
+                    &gate_type, inputs[1], constant, output, &mut fresh,
+                )?);
+            }
+            (_, &[None, Some(constant)]) => {
+                gates.extend(constant_operand(
+                    &gate_type, inputs[0], constant, output, &mut fresh,
+                )?);
+            }
+            _ => gates.push(Gate {
+                id: output,
+                gate_type,
+                inputs,
+            }),
+        }
+    }
+
+    // Outputs fixed by EQ gates read a constant derived from the first
+    // input: x ^ x is 0, and its NOT is 1
+    let first_input = metadata.inputs.first().map(|input| input.id);
+    let (mut zero, mut one): (Option<WireId>, Option<WireId>) = (None, None);
+    let mut constant_wire = |value: bool, gates: &mut Vec<Gate>| -> Result<WireId> {
+        let zero = match zero {
+            Some(zero) => zero,
+            None => {
+                let input = first_input.ok_or_else(|| {
+                    anyhow::anyhow!("Bristol circuit without inputs has constant outputs")
+                })?;
+                let wire = fresh()?;
+                gates.push(Gate {
+                    id: wire,
+                    gate_type: GateType::XOR,
+                    inputs: vec![input, input],
+                });
+                *zero.insert(wire)
+            }
+        };
+        if !value {
+            return Ok(zero);
+        }
+        match one {
+            Some(one) => Ok(one),
+            None => {
+                let wire = fresh()?;
+                gates.push(Gate {
+                    id: wire,
+                    gate_type: GateType::NOT,
+                    inputs: vec![zero],
+                });
+                Ok(*one.insert(wire))
+            }
+        }
+    };
+
+    let output_total: usize = output_widths.iter().sum();
+    let mut wire = wire_count
+        .checked_sub(output_total)
+        .ok_or_else(|| anyhow::anyhow!("Bristol circuit has more output bits than wires"))?
+        as WireId;
+    for (value, &width) in output_widths.iter().enumerate() {
+        let mut bits = Vec::with_capacity(width);
+        for bit in 0..width {
+            let name = bristol_bit_name("out", value, bit, width);
+            let id = match constants.get(&wire) {
+                Some(&constant) => constant_wire(constant, &mut gates)?,
+                None => resolve(&aliases, wire),
+            };
+            metadata.outputs.push(OutputInfo {
+                name: name.clone(),
+                id,
+            });
+            bits.push(name);
+            wire += 1;
+        }
+        if width > 1 {
+            metadata.buses.push(BusInfo {
+                name: format!("out{value}"),
+                bits,
+                encoding: BusEncoding::Unsigned,
+                carry: None,
+                overflow: None,
+                extend: None,
+            });
+        }
+    }
+
+    Ok(Circuit {
+        name: "bristol".to_string(),
+        description: "Imported from Bristol Fashion".to_string(),
+        gates,
+        metadata,
+    })
+}
+
+/// Check a Bristol `<count> <width>...` line and return the widths
+fn bristol_value_widths(line: &[usize], what: &str) -> Result<Vec<usize>> {
+    match line.split_first() {
+        Some((&count, widths)) if widths.len() == count => Ok(widths.to_vec()),
+        _ => Err(anyhow::anyhow!(
+            "Bristol {} line must be `<count> <width>...`",
+            what
+        )),
+    }
+}
+
+fn bristol_bit_name(prefix: &str, value: usize, bit: usize, width: usize) -> String {
+    if width == 1 {
+        format!("{prefix}{value}")
+    } else {
+        format!("{prefix}{value}_{bit}")
+    }
+}
+
+/// Write the compact binary format
+/// Layout: magic, version, then LEB128 integers and length-prefixed UTF-8
+/// strings for the opcode table, name, description, inputs, outputs, buses,
+/// input constraints, party declaration and gates, then scheduling hints,
+/// public input names, opening policies and the prepared evaluation plan,
+/// which older files may stop short of. The opcode table names each gate type the circuit uses,
+/// with its arity, and gates refer to it by position, so a reader can still
+/// walk a file with gate types it does not know and report them by name
+fn to_binary(circuit: &Circuit) -> Result<Vec<u8>> {
+    let mut bytes = BINARY_MAGIC.to_vec();
+    bytes.push(BINARY_VERSION);
+
+    // Opcodes are assigned in order of first use
+    let mut used: Vec<usize> = Vec::new();
+    for gate in &circuit.gates {
+        let known = GATE_OPCODES
+            .iter()
+            .position(|(_, gate_type, _)| *gate_type == gate.gate_type)
+            .ok_or_else(|| anyhow::anyhow!("Gate {} has no binary opcode", gate.id))?;
+        if !used.contains(&known) {
+            used.push(known);
+        }
+    }
+    write_varint(&mut bytes, used.len() as u64);
+    for &known in &used {
+        let (name, _, arity) = GATE_OPCODES[known];
+        write_string(&mut bytes, name);
+        write_varint(&mut bytes, arity as u64);
+    }
+
+    write_string(&mut bytes, &circuit.name);
+    write_string(&mut bytes, &circuit.description);
+
+    let metadata = &circuit.metadata;
+    write_varint(&mut bytes, metadata.inputs.len() as u64);
+    for input in &metadata.inputs {
+        write_varint(&mut bytes, input.id);
+        write_string(&mut bytes, &input.name);
+    }
+    write_varint(&mut bytes, metadata.outputs.len() as u64);
+    for output in &metadata.outputs {
+        write_varint(&mut bytes, output.id);
+        write_string(&mut bytes, &output.name);
+    }
+    let buses = metadata.buses.iter().map(|bus| (bus, 0));
+    let input_buses = metadata.input_buses.iter().map(|bus| (bus, BUS_INPUT));
+    write_varint(
+        &mut bytes,
+        (metadata.buses.len() + metadata.input_buses.len()) as u64,
+    );
+    for (bus, input) in buses.chain(input_buses) {
+        write_string(&mut bytes, &bus.name);
+        let encoding = match bus.encoding {
+            BusEncoding::Unsigned => 0,
+            BusEncoding::SignMagnitude => 1,
+            BusEncoding::TwosComplement => 2,
+            BusEncoding::OneHot => 3,
+            BusEncoding::FixedPoint { .. } => 4,
+        };
+        let carry = if bus.carry.is_some() { BUS_CARRY } else { 0 };
+        let overflow = if bus.overflow.is_some() {
+            BUS_OVERFLOW
+        } else {
+            0
+        };
+        let extend = if bus.extend.is_some() { BUS_EXTEND } else { 0 };
+        bytes.push(encoding | carry | overflow | input | extend);
+        if let BusEncoding::FixedPoint { frac_bits } = bus.encoding {
+            bytes.push(frac_bits);
+        }
+        write_strings(&mut bytes, &bus.bits);
+        for flag in bus.carry.iter().chain(&bus.overflow) {
+            write_string(&mut bytes, flag);
+        }
+        match bus.extend {
+            Some(BusExtension::Zero) => bytes.push(0),
+            Some(BusExtension::Sign) => bytes.push(1),
+            None => {}
+        }
+    }
+    write_varint(&mut bytes, metadata.constraints.len() as u64);
+    for constraint in &metadata.constraints {
+        match constraint {
+            InputConstraint::LessThan { bits, bound } => {
+                bytes.push(0);
+                write_strings(&mut bytes, bits);
+                write_varint(&mut bytes, *bound);
+            }
+            InputConstraint::OneHot { bits } => {
+                bytes.push(1);
+                write_strings(&mut bytes, bits);
+            }
+        }
+    }
+
+    // Party count, 0 when undeclared, then input owners
+    let parties = metadata.parties.clone().unwrap_or_default();
+    write_varint(&mut bytes, parties.count as u64);
+    write_varint(&mut bytes, parties.owners.len() as u64);
+    for (input, &party) in &parties.owners {
+        write_string(&mut bytes, input);
+        write_varint(&mut bytes, party as u64);
+    }
+
+    write_varint(&mut bytes, circuit.gates.len() as u64);
+    for gate in &circuit.gates {
+        let opcode = used
+            .iter()
+            .position(|&known| GATE_OPCODES[known].1 == gate.gate_type)
+            .expect("every gate type is in the opcode table");
+        let arity = GATE_OPCODES[used[opcode]].2;
+        if gate.inputs.len() != arity {
+            return Err(anyhow::anyhow!(
+                "Gate {} has {} inputs, expected {}",
+                gate.id,
+                gate.inputs.len(),
+                arity
+            ));
+        }
+
+        write_varint(&mut bytes, opcode as u64);
+        write_varint(&mut bytes, gate.id);
+        for &input in &gate.inputs {
+            write_varint(&mut bytes, input);
+        }
+    }
+
+    // Hints trail the gates: wire, layer + 1 (0 for none), group flag and
+    // name, priority. Public input names and then opening policies (wire,
+    // parties) follow the hints, and the prepared evaluation plan closes the
+    // file, so every earlier section is written, possibly empty
+    write_varint(&mut bytes, metadata.schedule.len() as u64);
+    for (&wire, hint) in &metadata.schedule {
+        write_varint(&mut bytes, wire);
+        write_varint(&mut bytes, hint.layer.map_or(0, |layer| layer as u64 + 1));
+        bytes.push(u8::from(hint.group.is_some()));
+        if let Some(group) = &hint.group {
+            write_string(&mut bytes, group);
+        }
+        write_varint(&mut bytes, u64::from(hint.priority));
+    }
+    write_strings(&mut bytes, &metadata.public_inputs);
+    write_varint(&mut bytes, metadata.openings.len() as u64);
+    for (&wire, parties) in &metadata.openings {
+        write_varint(&mut bytes, wire);
+        write_varint(&mut bytes, parties.len() as u64);
+        for &party in parties {
+            write_varint(&mut bytes, party as u64);
+        }
+    }
+    PreparedCircuit::new(circuit).write_plan(&mut bytes);
+
+    Ok(bytes)
+}
+
+fn from_binary(bytes: &[u8]) -> Result<Circuit> {
+    Ok(read_binary(bytes)?.0)
+}
+
+/// Read a binary circuit, returning the reader positioned after the
+/// circuit's sections, at the stored evaluation plan if there is one
+pub(crate) fn read_binary(bytes: &[u8]) -> Result<(Circuit, BinaryReader<'_>)> {
+    let mut reader = BinaryReader::new(bytes)?;
+    let opcodes = reader.opcode_table()?;
+    let unknown: Vec<&str> = opcodes
+        .iter()
+        .filter(|opcode| opcode.gate_type.is_none())
+        .map(|opcode| opcode.name.as_str())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Circuit uses gate types this reader does not support: {}",
+            unknown.join(", ")
+        ));
+    }
+
+    let name = reader.string()?;
+    let description = reader.string()?;
+
+    let mut metadata = CircuitMetadata::default();
+    for _ in 0..reader.varint()? {
+        let id = reader.varint()?;
+        metadata.inputs.push(InputInfo {
+            name: reader.string()?,
+            id,
+        });
+    }
+    for _ in 0..reader.varint()? {
+        let id = reader.varint()?;
+        metadata.outputs.push(OutputInfo {
+            name: reader.string()?,
+            id,
+        });
+    }
+    for _ in 0..reader.varint()? {
+        let name = reader.string()?;
+        let tag = reader.byte()?;
+        let encoding = match tag & !(BUS_CARRY | BUS_OVERFLOW | BUS_INPUT | BUS_EXTEND) {
+            0 => BusEncoding::Unsigned,
+            1 => BusEncoding::SignMagnitude,
+            2 => BusEncoding::TwosComplement,
+            3 => BusEncoding::OneHot,
+            4 => BusEncoding::FixedPoint {
+                frac_bits: reader.byte()?,
+            },
+            _ => return Err(anyhow::anyhow!("Unknown bus encoding tag {}", tag)),
+        };
+        let bits = reader.strings()?;
+        let carry = (tag & BUS_CARRY != 0)
+            .then(|| reader.string())
+            .transpose()?;
+        let overflow = (tag & BUS_OVERFLOW != 0)
+            .then(|| reader.string())
+            .transpose()?;
+        let extend = if tag & BUS_EXTEND != 0 {
+            match reader.byte()? {
+                0 => Some(BusExtension::Zero),
+                1 => Some(BusExtension::Sign),
+                tag => return Err(anyhow::anyhow!("Unknown bus extension tag {}", tag)),
+            }
+        } else {
+            None
+        };
+        let bus = BusInfo {
+            name,
+            bits,
+            encoding,
+            carry,
+            overflow,
+            extend,
+        };
+        if tag & BUS_INPUT != 0 {
+            metadata.input_buses.push(bus);
+        } else {
+            metadata.buses.push(bus);
+        }
+    }
+    for _ in 0..reader.varint()? {
+        let constraint = match reader.byte()? {
+            0 => InputConstraint::LessThan {
+                bits: reader.strings()?,
+                bound: reader.varint()?,
+            },
+            1 => InputConstraint::OneHot {
+                bits: reader.strings()?,
+            },
+            tag => return Err(anyhow::anyhow!("Unknown constraint tag {}", tag)),
+        };
+        metadata.constraints.push(constraint);
+    }
+    if reader.version != BINARY_VERSION_FIXED_OPCODES {
+        let count = reader.varint()? as usize;
+        let owners = (0..reader.varint()?)
+            .map(|_| Ok((reader.string()?, reader.varint()? as usize)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        if count > 0 || !owners.is_empty() {
+            metadata.parties = Some(PartySpec { count, owners });
+        }
+    }
+
+    let mut gates = Vec::new();
+    for _ in 0..reader.varint()? {
+        let opcode = reader.varint()?;
+        let entry = usize::try_from(opcode)
+            .ok()
+            .and_then(|opcode| opcodes.get(opcode))
+            .ok_or_else(|| anyhow::anyhow!("Unknown gate opcode {}", opcode))?;
+        let gate_type = entry
+            .gate_type
+            .clone()
+            .expect("unknown types were rejected");
+        let arity = entry.arity;
+        let id = reader.varint()?;
+        let inputs = (0..arity)
+            .map(|_| reader.varint())
+            .collect::<Result<Vec<_>>>()?;
+        gates.push(Gate {
+            id,
+            gate_type,
+            inputs,
+        });
+    }
+
+    if !reader.is_at_end() {
+        for _ in 0..reader.varint()? {
+            let wire = reader.varint()?;
+            let layer = reader.varint()?.checked_sub(1).map(|layer| layer as usize);
+            let group = match reader.byte()? {
+                0 => None,
+                _ => Some(reader.string()?),
+            };
+            let priority = u32::try_from(reader.varint()?)
+                .map_err(|_| anyhow::anyhow!("Schedule priority of wire {} is too large", wire))?;
+            metadata.schedule.insert(
+                wire,
+                ScheduleHint {
+                    layer,
+                    group,
+                    priority,
+                },
+            );
+        }
+    }
+    if !reader.is_at_end() {
+        metadata.public_inputs = reader.strings()?;
+    }
+    if !reader.is_at_end() {
+        for _ in 0..reader.varint()? {
+            let wire = reader.varint()?;
+            let parties = (0..reader.varint()?)
+                .map(|_| reader.varint().map(|party| party as usize))
+                .collect::<Result<Vec<_>>>()?;
+            metadata.openings.insert(wire, parties);
+        }
+    }
+
+    let circuit = Circuit {
+        name,
+        description,
+        gates,
+        metadata,
+    };
+    Ok((circuit, reader))
+}
+
+pub(crate) fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+pub(crate) fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    write_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn write_strings(bytes: &mut Vec<u8>, values: &[String]) {
+    write_varint(bytes, values.len() as u64);
+    for value in values {
+        write_string(bytes, value);
+    }
+}
+
+/// Entry of a binary circuit's opcode table
+struct Opcode {
+    name: String,
+    arity: usize,
+    /// `None` for gate types this reader does not know
+    gate_type: Option<GateType>,
+}
+
+/// Cursor over a binary circuit
+pub(crate) struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    version: u8,
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Check the magic and version, leaving the cursor at the opcode table
+    fn new(bytes: &'a [u8]) -> Result<Self> {
+        let mut reader = BinaryReader {
+            bytes,
+            position: 0,
+            version: 0,
+        };
+        if reader.take(BINARY_MAGIC.len())? != BINARY_MAGIC {
+            return Err(anyhow::anyhow!("Not a binary circuit file"));
+        }
+        reader.version = reader.byte()?;
+        if !matches!(
+            reader.version,
+            BINARY_VERSION | BINARY_VERSION_FIXED_OPCODES
+        ) {
+            return Err(anyhow::anyhow!(
+                "Unsupported binary circuit version {}",
+                reader.version
+            ));
+        }
+        Ok(reader)
+    }
+
+    /// Read the opcode table; version 1 files imply the fixed one
+    fn opcode_table(&mut self) -> Result<Vec<Opcode>> {
+        if self.version == BINARY_VERSION_FIXED_OPCODES {
+            return Ok(GATE_OPCODES
+                .iter()
+                .map(|(name, gate_type, arity)| Opcode {
+                    name: name.to_string(),
+                    arity: *arity,
+                    gate_type: Some(gate_type.clone()),
+                })
+                .collect());
+        }
+
+        (0..self.varint()?)
+            .map(|_| {
+                let name = self.string()?;
+                let arity = self.varint()? as usize;
+                let gate_type = GATE_OPCODES
+                    .iter()
+                    .find(|(known, _, _)| *known == name)
+                    .map(|(_, gate_type, known_arity)| {
+                        if *known_arity == arity {
+                            Ok(gate_type.clone())
+                        } else {
+                            Err(anyhow::anyhow!(
+                                "Gate type {} declared with {} inputs, expected {}",
+                                name,
+                                arity,
+                                known_arity
+                            ))
+                        }
+                    })
+                    .transpose()?;
+                Ok(Opcode {
+                    name,
+                    arity,
+                    gate_type,
+                })
+            })
+            .collect()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow::anyhow!("Binary circuit is truncated"))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow::anyhow!("Varint longer than 64 bits"))
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String> {
+        let len = self.varint()? as usize;
+        Ok(std::str::from_utf8(self.take(len)?)?.to_string())
+    }
+
+    fn strings(&mut self) -> Result<Vec<String>> {
+        (0..self.varint()?).map(|_| self.string()).collect()
+    }
+}
+
+/// Metadata file belonging to a JSON gate file: `adder.json` pairs with
+/// `adder.meta.json`
+fn meta_path(path: &str) -> String {
+    let stem = path.strip_suffix(".json").unwrap_or(path);
+    format!("{stem}.meta.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::LocalEvaluator;
+
+    fn outputs(circuit: &Circuit, inputs: &[bool]) -> Vec<bool> {
+        circuit
+            .metadata
+            .outputs
+            .iter()
+            .map(|info| LocalEvaluator::get_output(circuit, inputs, info.id).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_gates_and_metadata_in_separate_files() {
+        let adder: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string("circuits/full_adder.json").unwrap()).unwrap();
+        let dir = std::env::temp_dir().join(format!("gmw-split-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let gates = dir.join("adder.json").to_str().unwrap().to_string();
+        let metadata = meta_path(&gates);
+        fs::write(&gates, adder["gates"].to_string()).unwrap();
+        let mut meta = adder.clone();
+        meta.as_object_mut().unwrap().remove("gates");
+        fs::write(&metadata, meta.to_string()).unwrap();
+
+        let circuit = Circuit::from_files(&gates, &metadata).unwrap();
+        assert_eq!(
+            serde_json::to_value(&circuit).unwrap(),
+            serde_json::to_value(Circuit::from_file("circuits/full_adder.json").unwrap()).unwrap()
+        );
+        // `load` finds the metadata file next to the gates
+        assert_eq!(
+            outputs(&Circuit::load(&gates).unwrap(), &[true, false, true]),
+            vec![false, true]
+        );
+
+        // Fields may not be given twice
+        fs::write(&gates, adder.to_string()).unwrap();
+        let error = Circuit::from_files(&gates, &metadata).unwrap_err();
+        assert!(error.to_string().contains("given in both"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let circuit = Circuit::from_file("circuits/full_adder.json").unwrap();
+        let encoded = circuit.encode(CircuitFormat::Binary).unwrap();
+        assert!(encoded.warnings.is_empty());
+
+        let decoded = Circuit::decode(&encoded.bytes, CircuitFormat::Binary).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&circuit).unwrap()
+        );
+
+        assert!(Circuit::decode(&encoded.bytes[..10], CircuitFormat::Binary).is_err());
+
+        // Input bus declarations, their encodings and input owners survive
+        let circuit = crate::circuit::CircuitBuilder::build("encoded", |b| {
+            b.parties(2);
+            b.party_input(1, "owned");
+            let kind = b.input_encoded("kind", 3, BusEncoding::OneHot);
+            let price = b.input_encoded("price", 6, BusEncoding::FixedPoint { frac_bits: 3 });
+            b.output_bus("kind_out", &kind, BusEncoding::OneHot);
+            b.output_bus(
+                "price_out",
+                &price,
+                BusEncoding::FixedPoint { frac_bits: 3 },
+            );
+            Ok(())
+        })
+        .unwrap();
+        let encoded = circuit.encode(CircuitFormat::Binary).unwrap();
+        let decoded = Circuit::decode(&encoded.bytes, CircuitFormat::Binary).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&circuit).unwrap()
+        );
+
+        // Scheduling hints travel in the trailing section, Bristol drops them
+        let mut circuit = Circuit::from_file("circuits/full_adder.json").unwrap();
+        let and_gate = circuit
+            .gates
+            .iter()
+            .find(|gate| gate.gate_type == GateType::AND);
+        let hint = ScheduleHint {
+            layer: Some(2),
+            group: Some("carry".to_string()),
+            priority: 3,
+        };
+        circuit.metadata.schedule.insert(and_gate.unwrap().id, hint);
+        let encoded = circuit.encode(CircuitFormat::Binary).unwrap();
+        let decoded = Circuit::decode(&encoded.bytes, CircuitFormat::Binary).unwrap();
+        assert_eq!(decoded.metadata.schedule, circuit.metadata.schedule);
+        let bristol = circuit.encode(CircuitFormat::Bristol).unwrap();
+        assert!(bristol
+            .warnings
+            .iter()
+            .any(|w| w.contains("scheduling hints")));
+
+        // Public inputs follow the hints, with or without any hints
+        circuit.metadata.public_inputs = vec!["cin".to_string()];
+        for schedule in [circuit.metadata.schedule.clone(), BTreeMap::new()] {
+            circuit.metadata.schedule = schedule;
+            let encoded = circuit.encode(CircuitFormat::Binary).unwrap();
+            let decoded = Circuit::decode(&encoded.bytes, CircuitFormat::Binary).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(&circuit).unwrap()
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_binary_stores_prepared_plan() {
+        let mut circuit = crate::circuit::reference::adder_comparator(4);
+        circuit
+            .metadata
+            .constraints
+            .push(InputConstraint::LessThan {
+                bits: (0..3).map(|bit| format!("a{bit}")).collect(),
+                bound: 5,
+            });
+        let bytes = circuit.encode(CircuitFormat::Binary).unwrap().bytes;
+        let fresh = PreparedCircuit::new(&circuit);
+        let stored = PreparedCircuit::from_binary(&bytes).unwrap();
+        assert_eq!(stored.gates, fresh.gates);
+        assert_eq!(stored.plan.steps, fresh.plan.steps);
+        assert_eq!(stored.plan.round_starts, fresh.plan.round_starts);
+        assert_eq!(stored.step_layers(), fresh.step_layers());
+        assert_eq!(
+            (stored.and_depth, stored.rounds),
+            (fresh.and_depth, fresh.rounds)
+        );
+        assert_eq!(
+            stored.input_checks().unwrap(),
+            fresh.input_checks().unwrap()
+        );
+        let protocol = crate::protocol::GmwProtocol::new(2).unwrap();
+        let inputs: Vec<bool> = (0..circuit.metadata.inputs.len())
+            .map(|bit| bit % 3 == 0)
+            .collect();
+        assert_eq!(
+            protocol.run_prepared(&stored, &inputs).unwrap().outputs,
+            protocol.run_prepared(&fresh, &inputs).unwrap().outputs
+        );
+
+        // Files without a plan are prepared from scratch; a cut plan is caught
+        let decoded = Circuit::decode(&bytes, CircuitFormat::Binary).unwrap();
+        let plan_start = bytes.len() - {
+            let mut plan = Vec::new();
+            fresh.write_plan(&mut plan);
+            plan.len()
+        };
+        let legacy = PreparedCircuit::from_binary(&bytes[..plan_start]).unwrap();
+        assert_eq!(legacy.plan.steps, PreparedCircuit::new(&decoded).plan.steps);
+        assert!(PreparedCircuit::from_binary(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_bristol_round_trip_preserves_function() {
+        for file in ["circuits/full_adder.json", "circuits/mux_2to1.json"] {
+            let circuit = Circuit::from_file(file).unwrap();
+            let encoded = circuit.encode(CircuitFormat::Bristol).unwrap();
+            assert!(!encoded.warnings.is_empty());
+
+            let decoded = Circuit::decode(&encoded.bytes, CircuitFormat::Bristol).unwrap();
+            assert_eq!(decoded.metadata.inputs.len(), circuit.metadata.inputs.len());
+            assert!(decoded
+                .gates
+                .iter()
+                .all(|gate| gate.gate_type != GateType::OR));
+
+            for input in 0..8u8 {
+                let inputs: Vec<bool> = (0..3).map(|bit| input >> bit & 1 == 1).collect();
+                assert_eq!(outputs(&decoded, &inputs), outputs(&circuit, &inputs));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bristol_multi_bit_values() {
+        // 2-bit value XORed with a 2-bit value, copied to a 2-bit output
+        let text = "4 8\n2 2 2\n1 2\n\n2 1 0 2 4 XOR\n2 1 1 3 5 XOR\n1 1 4 6 EQW\n1 1 5 7 EQW\n";
+        let circuit = Circuit::decode(text.as_bytes(), CircuitFormat::Bristol).unwrap();
+
+        assert_eq!(circuit.gates.len(), 2);
+        assert_eq!(circuit.metadata.inputs[1].name, "in0_1");
+        assert_eq!(circuit.metadata.outputs[0].id, 4);
+        assert_eq!(circuit.metadata.buses[0].bits, vec!["out0_0", "out0_1"]);
+        assert_eq!(
+            outputs(&circuit, &[true, true, false, true]),
+            vec![true, false]
+        );
+    }
+
+    #[test]
+    fn test_bristol_constants_cost_no_ot() {
+        // Wires 2 and 4 are fixed to 1 and 0; only in0 & in1 needs an OT
+        let text = "6 8\n2 1 1\n4 1 1 1 1\n\n1 1 1 2 EQ\n2 1 0 2 3 AND\n1 1 0 4 EQ\n\
+                    2 1 1 4 5 XOR\n2 1 2 4 6 AND\n2 1 3 1 7 AND\n";
+        let circuit = Circuit::decode(text.as_bytes(), CircuitFormat::Bristol).unwrap();
+        let interactive = circuit
+            .gates
+            .iter()
+            .filter(|gate| gate.gate_type.is_interactive())
+            .count();
+        assert_eq!(interactive, 1);
+
+        for (a, b) in [(false, true), (true, false), (true, true)] {
+            assert_eq!(outputs(&circuit, &[a, b]), vec![false, b, false, a & b]);
+        }
+        let bad = "1 3\n1 1\n1 1\n\n1 1 2 2 EQ\n";
+        assert!(Circuit::decode(bad.as_bytes(), CircuitFormat::Bristol).is_err());
+    }
+
+    #[test]
+    fn test_binary_opcode_table() {
+        let circuit = Circuit::from_file("circuits/full_adder.json").unwrap();
+        let bytes = circuit.encode(CircuitFormat::Binary).unwrap().bytes;
+        let mut used: Vec<String> = Vec::new();
+        for gate in &circuit.gates {
+            let name = format!("{:?}", gate.gate_type);
+            if !used.contains(&name) {
+                used.push(name);
+            }
+        }
+        assert_eq!(Circuit::binary_gate_types(&bytes).unwrap(), used);
+
+        // Metadata-free file with one gate each way; `table` is None for version 1
+        let file = |version: u8, table: Option<(&str, u64)>, gate: &[u64]| {
+            let mut bytes = BINARY_MAGIC.to_vec();
+            bytes.push(version);
+            if let Some((name, arity)) = table {
+                write_varint(&mut bytes, 1);
+                write_string(&mut bytes, name);
+                write_varint(&mut bytes, arity);
+            }
+            write_string(&mut bytes, "future");
+            write_string(&mut bytes, "");
+            // No inputs, outputs, buses or constraints; version 2 adds no parties
+            let empty_sections = if table.is_some() { 6 } else { 4 };
+            for _ in 0..empty_sections {
+                write_varint(&mut bytes, 0);
+            }
+            write_varint(&mut bytes, 1);
+            for &value in gate {
+                write_varint(&mut bytes, value);
+            }
+            bytes
+        };
+
+        // A gate type added later is named rather than misread
+        let future = file(BINARY_VERSION, Some(("MAJ", 3)), &[0, 4, 1, 2, 3]);
+        assert_eq!(Circuit::binary_gate_types(&future).unwrap(), ["MAJ"]);
+        let err = Circuit::decode(&future, CircuitFormat::Binary).unwrap_err();
+        assert!(err.to_string().contains("MAJ"));
+
+        let wrong_arity = file(BINARY_VERSION, Some(("AND", 3)), &[0, 4, 1, 2, 3]);
+        assert!(Circuit::decode(&wrong_arity, CircuitFormat::Binary).is_err());
+        let bad_opcode = file(BINARY_VERSION, Some(("AND", 2)), &[1, 3, 1, 2]);
+        assert!(Circuit::decode(&bad_opcode, CircuitFormat::Binary).is_err());
+
+        // Version 1 files with fixed tags still load; tag 2 is AND
+        let legacy = file(BINARY_VERSION_FIXED_OPCODES, None, &[2, 3, 1, 2]);
+        let decoded = Circuit::decode(&legacy, CircuitFormat::Binary).unwrap();
+        assert_eq!(decoded.gates[0].gate_type, GateType::AND);
+        assert_eq!(decoded.gates[0].inputs, [1, 2]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let circuit = Circuit::from_file("circuits/full_adder.json").unwrap();
+        let dot = circuit.to_dot();
+        assert!(dot.starts_with("digraph \"full_adder\" {"));
+        let edges = circuit
+            .gates
+            .iter()
+            .map(|gate| gate.inputs.len())
+            .sum::<usize>()
+            + circuit.metadata.outputs.len();
+        assert_eq!(dot.matches(" -> ").count(), edges);
+        assert!(dot.contains("[label=\"AND\", style=filled"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            CircuitFormat::from_path("adder64.txt"),
+            Some(CircuitFormat::Bristol)
+        );
+        assert_eq!(
+            CircuitFormat::from_path("a.gmwc"),
+            Some(CircuitFormat::Binary)
+        );
+        assert_eq!(
+            CircuitFormat::from_path("adder.gmwt"),
+            Some(CircuitFormat::Text)
+        );
+        assert_eq!(
+            CircuitFormat::from_path("relation.rel"),
+            Some(CircuitFormat::Sieve)
+        );
+        assert_eq!(CircuitFormat::from_path("a"), None);
+        assert!("yaml".parse::<CircuitFormat>().is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+
+use crate::circuit::{Circuit, WireId};
+use crate::kernel::IndexedGate;
+use std::collections::HashMap;
+
+/// Dense wire indexing for a circuit
+/// Maps sparse external wire IDs to contiguous slots so per-party shares
+/// can live in a `Vec` instead of a `HashMap` during evaluation
+#[derive(Debug, Clone, Default)]
+pub struct WireIndex {
+    slots: HashMap<WireId, usize>,
+    wires: Vec<WireId>,
+}
+
+impl WireIndex {
+    /// Build an index covering every wire referenced by the circuit
+    /// Slots are assigned in order of first appearance: metadata inputs first,
+    /// then gate inputs and outputs in gate order
+    pub fn new(circuit: &Circuit) -> Self {
+        let mut index = Self::default();
+
+        for input in &circuit.metadata.inputs {
+            index.insert(input.id);
+        }
+
+        for gate in &circuit.gates {
+            for &wire in &gate.inputs {
+                index.insert(wire);
+            }
+            index.insert(gate.id);
+        }
+
+        index
+    }
+
+    /// Rebuild an index from its wires in slot order, as stored with a
+    /// prepared binary circuit
+    pub(crate) fn from_wires(wires: Vec<WireId>) -> Result<Self> {
+        let mut slots = HashMap::with_capacity(wires.len());
+        for (slot, &wire) in wires.iter().enumerate() {
+            if slots.insert(wire, slot).is_some() {
+                return Err(anyhow::anyhow!("Wire {} is indexed twice", wire));
+            }
+        }
+        Ok(Self { slots, wires })
+    }
+
+    /// Number of distinct wires in the index
+    pub fn len(&self) -> usize {
+        self.wires.len()
+    }
+
+    /// Whether the index contains no wires
+    pub fn is_empty(&self) -> bool {
+        self.wires.is_empty()
+    }
+
+    /// Look up the dense slot for an external wire ID
+    pub fn slot(&self, wire: WireId) -> Option<usize> {
+        self.slots.get(&wire).copied()
+    }
+
+    /// Look up the external wire ID stored in a dense slot
+    pub fn wire(&self, slot: usize) -> Option<WireId> {
+        self.wires.get(slot).copied()
+    }
+
+    /// Resolve all gates of the circuit to dense slots
+    pub fn resolve_gates(&self, circuit: &Circuit) -> Vec<IndexedGate> {
+        circuit
+            .gates
+            .iter()
+            .map(|gate| IndexedGate {
+                gate_type: gate.gate_type.clone(),
+                inputs: gate.inputs.iter().map(|wire| self.slots[wire]).collect(),
+                output: self.slots[&gate.id],
+            })
+            .collect()
+    }
+
+    /// Insert a wire if it is not already indexed and return its slot
+    fn insert(&mut self, wire: WireId) -> usize {
+        if let Some(&slot) = self.slots.get(&wire) {
+            return slot;
+        }
+
+        let slot = self.wires.len();
+        self.slots.insert(wire, slot);
+        self.wires.push(wire);
+        slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{CircuitMetadata, Gate, GateType, InputInfo, OutputInfo};
+
+    #[test]
+    fn test_wire_index_compacts_large_ids() {
+        let a = u64::from(u32::MAX) + 10;
+        let b = 1 << 40;
+        let out = u64::MAX;
+
+        let circuit = Circuit {
+            name: "test_index".to_string(),
+            description: "Sparse wire IDs beyond 32 bits".to_string(),
+            gates: vec![Gate {
+                id: out,
+                gate_type: GateType::AND,
+                inputs: vec![a, b],
+            }],
+            metadata: CircuitMetadata {
+                inputs: vec![
+                    InputInfo {
+                        name: "a".to_string(),
+                        id: a,
+                    },
+                    InputInfo {
+                        name: "b".to_string(),
+                        id: b,
+                    },
+                ],
+                outputs: vec![OutputInfo {
+                    name: "result".to_string(),
+                    id: out,
+                }],
+                ..Default::default()
+            },
+        };
+
+        let index = WireIndex::new(&circuit);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.slot(a), Some(0));
+        assert_eq!(index.slot(b), Some(1));
+        assert_eq!(index.slot(out), Some(2));
+        assert_eq!(index.wire(2), Some(out));
+        assert_eq!(index.slot(7), None);
+
+        let gates = index.resolve_gates(&circuit);
+        assert_eq!(gates[0].inputs, vec![0, 1]);
+        assert_eq!(gates[0].output, 2);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::circuit::{Circuit, IndexedGate, ScheduleHint, WireId};
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Interactive (AND) depth layering of a circuit
+/// Local gates inherit the depth of their deepest input, interactive gates
+/// add one, so all interactive gates of the same depth can share a round
+#[derive(Debug, Clone, Default)]
+pub struct Layering {
+    /// Depth of each gate in circuit order
+    pub gate_depths: Vec<usize>,
+    /// Number of interactive layers (the circuit's AND-depth)
+    pub depth: usize,
+}
+
+impl Layering {
+    /// Compute the layering, requiring gates to be in topological order
+    pub fn new(circuit: &Circuit) -> Result<Self> {
+        let mut wire_depths: HashMap<WireId, usize> = circuit
+            .metadata
+            .inputs
+            .iter()
+            .map(|input| (input.id, 0))
+            .collect();
+        let mut gate_depths = Vec::with_capacity(circuit.gates.len());
+        let mut depth = 0;
+
+        for gate in &circuit.gates {
+            let input_depth = gate
+                .inputs
+                .iter()
+                .map(|wire| {
+                    wire_depths.get(wire).copied().ok_or_else(|| {
+                        anyhow::anyhow!("Gate {} uses wire {} before it is defined", gate.id, wire)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .max()
+                .unwrap_or(0);
+
+            let gate_depth = if gate.gate_type.is_interactive() {
+                input_depth + 1
+            } else {
+                input_depth
+            };
+
+            wire_depths.insert(gate.id, gate_depth);
+            gate_depths.push(gate_depth);
+            depth = depth.max(gate_depth);
+        }
+
+        Ok(Self { gate_depths, depth })
+    }
+
+    /// Compute the layering of resolved gates over `wire_count` slots
+    /// Slots not produced by an earlier gate are treated as inputs (depth 0)
+    pub fn from_gates(gates: &[IndexedGate], wire_count: usize) -> Self {
+        Self::with_minimums(gates, wire_count, &[])
+    }
+
+    /// `from_gates` honouring one optional hint per gate
+    /// Gates are placed no earlier than their `layer` hint, then every group
+    /// is moved to the deepest layer any of its gates reached
+    pub fn with_hints(
+        gates: &[IndexedGate],
+        wire_count: usize,
+        hints: &[Option<&ScheduleHint>],
+    ) -> Self {
+        let hint = |i: usize| hints.get(i).copied().flatten();
+        let mut minimums: Vec<usize> = (0..gates.len())
+            .map(|i| hint(i).and_then(|hint| hint.layer).unwrap_or(0))
+            .collect();
+        let hinted = Self::with_minimums(gates, wire_count, &minimums);
+
+        let mut group_depths: HashMap<&str, usize> = HashMap::new();
+        for (i, &depth) in hinted.gate_depths.iter().enumerate() {
+            if let Some(group) = hint(i).and_then(|hint| hint.group.as_deref()) {
+                let group_depth = group_depths.entry(group).or_default();
+                *group_depth = (*group_depth).max(depth);
+            }
+        }
+        if group_depths.is_empty() {
+            return hinted;
+        }
+        for (i, minimum) in minimums.iter_mut().enumerate() {
+            if let Some(group) = hint(i).and_then(|hint| hint.group.as_deref()) {
+                *minimum = (*minimum).max(group_depths[group]);
+            }
+        }
+        Self::with_minimums(gates, wire_count, &minimums)
+    }
+
+    /// Layering where gate `i` has depth at least `minimums[i]`
+    fn with_minimums(gates: &[IndexedGate], wire_count: usize, minimums: &[usize]) -> Self {
+        let mut slot_depths = vec![0; wire_count];
+        let mut gate_depths = Vec::with_capacity(gates.len());
+        let mut depth = 0;
+
+        for gate in gates {
+            let input_depth = gate
+                .inputs
+                .iter()
+                .map(|&slot| slot_depths[slot])
+                .max()
+                .unwrap_or(0);
+            let gate_depth = (input_depth + usize::from(gate.gate_type.is_interactive()))
+                .max(minimums.get(gate_depths.len()).copied().unwrap_or(0));
+
+            slot_depths[gate.output] = gate_depth;
+            gate_depths.push(gate_depth);
+            depth = depth.max(gate_depth);
+        }
+
+        Self { gate_depths, depth }
+    }
+
+    /// Reorder gates so each interactive layer is contiguous: for every depth,
+    /// its interactive gates come first, then the local gates that follow them
+    /// The order stays topological and otherwise keeps the original order
+    pub fn schedule(&self, gates: &[IndexedGate]) -> Vec<IndexedGate> {
+        self.schedule_with_priorities(gates, &[])
+    }
+
+    /// `schedule` with the gates of each depth and kind ordered by descending
+    /// `priorities[i]` (0 when missing)
+    pub fn schedule_with_priorities(
+        &self,
+        gates: &[IndexedGate],
+        priorities: &[u32],
+    ) -> Vec<IndexedGate> {
+        let mut order: Vec<usize> = (0..gates.len()).collect();
+        order.sort_by_key(|&i| {
+            (
+                self.gate_depths[i],
+                !gates[i].gate_type.is_interactive(),
+                Reverse(priorities.get(i).copied().unwrap_or(0)),
+            )
+        });
+        order.into_iter().map(|i| gates[i].clone()).collect()
+    }
+
+    /// Number of interactive gates in each layer, indexed from layer 1
+    pub fn interactive_gates_per_layer(&self, circuit: &Circuit) -> Vec<usize> {
+        let mut counts = vec![0; self.depth];
+
+        for (gate, &depth) in circuit.gates.iter().zip(&self.gate_depths) {
+            if gate.gate_type.is_interactive() {
+                counts[depth - 1] += 1;
+            }
+        }
+
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::GateType;
+    #[cfg(feature = "std")]
+    use crate::{
+        circuit::{LocalEvaluator, PreparedCircuit},
+        protocol::GmwProtocol,
+    };
+
+    #[test]
+    fn test_layering_full_adder() {
+        let circuit = reference::full_adder();
+        let layering = Layering::new(&circuit).unwrap();
+
+        // carry = (a & b) | ((a ^ b) & cin) needs two rounds: the ANDs, then the OR
+        assert_eq!(layering.depth, 2);
+        assert_eq!(layering.interactive_gates_per_layer(&circuit), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_schedule_groups_layers() {
+        let gate = |gate_type, inputs: Vec<usize>, output| IndexedGate {
+            gate_type,
+            inputs,
+            output,
+        };
+        // x = a & b; y = x ^ c; z = c & d; out = y & z
+        let gates = vec![
+            gate(GateType::AND, vec![0, 1], 4),
+            gate(GateType::XOR, vec![4, 2], 5),
+            gate(GateType::AND, vec![2, 3], 6),
+            gate(GateType::AND, vec![5, 6], 7),
+        ];
+
+        let layering = Layering::from_gates(&gates, 8);
+        assert_eq!(layering.gate_depths, vec![1, 1, 1, 2]);
+
+        let outputs: Vec<usize> = layering
+            .schedule(&gates)
+            .iter()
+            .map(|gate| gate.output)
+            .collect();
+        assert_eq!(outputs, vec![4, 6, 5, 7]);
+    }
+
+    #[test]
+    fn test_hints_delay_group_and_order() {
+        let gate = |inputs: Vec<usize>, output| IndexedGate {
+            gate_type: GateType::AND,
+            inputs,
+            output,
+        };
+        // x = a & b; y = x & c; z = c & d
+        let gates = vec![
+            gate(vec![0, 1], 4),
+            gate(vec![4, 2], 5),
+            gate(vec![2, 3], 6),
+        ];
+        let grouped = ScheduleHint {
+            group: Some("late".to_string()),
+            ..ScheduleHint::default()
+        };
+
+        // z joins y's round through the group, x can never move below layer 1
+        let early = ScheduleHint {
+            layer: Some(0),
+            ..ScheduleHint::default()
+        };
+        let layering =
+            Layering::with_hints(&gates, 7, &[Some(&early), Some(&grouped), Some(&grouped)]);
+        assert_eq!(layering.gate_depths, vec![1, 2, 2]);
+
+        // Priority reorders gates within a layer only
+        let layering = Layering::from_gates(&gates, 7);
+        let outputs: Vec<usize> = layering
+            .schedule_with_priorities(&gates, &[0, 9, 5])
+            .iter()
+            .map(|gate| gate.output)
+            .collect();
+        assert_eq!(outputs, vec![6, 4, 5]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_layer_hint_adds_round() {
+        let json = |schedule: &str| {
+            format!(
+                r#"{{"name": "hinted", "description": "", "metadata": {{
+                    "inputs": [{{"name": "a", "id": 1}}, {{"name": "b", "id": 2}},
+                               {{"name": "c", "id": 3}}, {{"name": "d", "id": 4}}],
+                    "outputs": [{{"name": "out", "id": 12}}]{schedule}}},
+                  "gates": [{{"id": 10, "type": "AND", "in": [1, 2]}},
+                            {{"id": 11, "type": "AND", "in": [3, 4]}},
+                            {{"id": 12, "type": "XOR", "in": [10, 11]}}]}}"#
+            )
+        };
+        let plain = Circuit::from_json(&json("")).unwrap();
+        let hinted = Circuit::from_json(&json(r#", "schedule": {"11": {"layer": 2}}"#)).unwrap();
+        assert_eq!(PreparedCircuit::new(&plain).rounds, 1);
+        let prepared = PreparedCircuit::new(&hinted);
+        assert_eq!(prepared.rounds, 2);
+        assert_eq!(prepared.and_depth, 1);
+
+        let protocol = GmwProtocol::new(3).unwrap();
+        for inputs in [[true, true, false, true], [true, true, true, true]] {
+            let report = protocol.run_prepared(&prepared, &inputs).unwrap();
+            let expected = LocalEvaluator::get_output(&hinted, &inputs, 12).unwrap();
+            assert_eq!(report.output("out"), Some(expected));
+        }
+
+        assert!(Circuit::from_json(&json(r#", "schedule": {"99": {"layer": 2}}"#)).is_err());
+    }
+}
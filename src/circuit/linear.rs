@@ -0,0 +1,371 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::arena::ShareArena;
+use crate::circuit::{GateType, IndexedGate};
+
+/// One output of a linear block: the XOR of a subset of the block inputs,
+/// optionally negated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearRow {
+    /// Slot the row's value is written to
+    pub output: usize,
+    /// Bitset over the block inputs selecting the XOR terms
+    pub terms: Vec<u64>,
+    /// Whether the XOR is negated (an odd number of NOT gates on the path)
+    pub negate: bool,
+}
+
+/// A maximal XOR/NOT subcircuit collapsed into a matrix over GF(2)
+/// Each party multiplies the block's input shares by the matrix in one
+/// pass instead of interpreting every linear gate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinearBlock {
+    /// Slots read by the block but produced outside it
+    pub inputs: Vec<usize>,
+    /// One row per gate output in the block, in gate order
+    pub rows: Vec<LinearRow>,
+}
+
+/// One step of a linear-collapsed evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EvaluationStep {
+    /// Several linear gates applied together
+    Linear(LinearBlock),
+    /// A single interactive gate
+    Gate(IndexedGate),
+}
+
+/// Gate order rewritten into linear blocks and interactive gates
+#[derive(Debug, Clone, Default)]
+pub struct LinearPlan {
+    pub steps: Vec<EvaluationStep>,
+    /// Steps that open a new round even if nothing in the open round is
+    /// read, so gates hinted to a later layer do not join an earlier round
+    pub round_starts: HashSet<usize>,
+}
+
+impl LinearBlock {
+    /// Number of linear gates collapsed into the block
+    pub fn gate_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Apply the block to every party's shares
+    /// Negation is applied by party 0 only, as for a single NOT gate
+    pub fn apply(&self, arena: &mut ShareArena) -> Result<()> {
+        let words = self.inputs.len().div_ceil(64);
+        let mut bits = vec![0u64; words];
+
+        for party_id in 0..arena.party_count() {
+            bits.iter_mut().for_each(|word| *word = 0);
+
+            for (position, &slot) in self.inputs.iter().enumerate() {
+                let share = arena
+                    .get(party_id, slot)
+                    .ok_or_else(|| anyhow::anyhow!("Missing Party {} input", party_id))?;
+                bits[position / 64] |= u64::from(share) << (position % 64);
+            }
+
+            for row in &self.rows {
+                let parity = row
+                    .terms
+                    .iter()
+                    .zip(&bits)
+                    .fold(0, |acc, (terms, bits)| acc ^ (terms & bits).count_ones())
+                    & 1;
+                let share = (parity == 1) ^ (row.negate && party_id == 0);
+                arena.set(party_id, row.output, share);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Row for an input slot: an existing row if the block produced it,
+    /// otherwise a new block input
+    fn row_for(&mut self, slot: usize, rows: &HashMap<usize, usize>) -> (Vec<u64>, bool) {
+        if let Some(&row) = rows.get(&slot) {
+            let row = &self.rows[row];
+            return (row.terms.clone(), row.negate);
+        }
+
+        let position = match self.inputs.iter().position(|&input| input == slot) {
+            Some(position) => position,
+            None => {
+                self.inputs.push(slot);
+                self.inputs.len() - 1
+            }
+        };
+
+        let mut terms = vec![0u64; position / 64 + 1];
+        terms[position / 64] |= 1 << (position % 64);
+        (terms, false)
+    }
+
+    fn push(&mut self, gate: &IndexedGate, rows: &mut HashMap<usize, usize>) {
+        let (mut terms, mut negate) = self.row_for(gate.inputs[0], rows);
+
+        match gate.gate_type {
+            GateType::XOR => {
+                let (other, other_negate) = self.row_for(gate.inputs[1], rows);
+                if other.len() > terms.len() {
+                    terms.resize(other.len(), 0);
+                }
+                terms.iter_mut().zip(&other).for_each(|(a, b)| *a ^= b);
+                negate ^= other_negate;
+            }
+            GateType::NOT => negate = !negate,
+            GateType::AND | GateType::OR => unreachable!("interactive gate in linear block"),
+        }
+
+        rows.insert(gate.output, self.rows.len());
+        self.rows.push(LinearRow {
+            output: gate.output,
+            terms,
+            negate,
+        });
+    }
+}
+
+impl LinearPlan {
+    /// Group linear gates into maximal blocks
+    /// A block stays open across interactive gates that do not read it and
+    /// is closed when an interactive gate needs one of its outputs, or when a
+    /// linear gate needs an interactive output produced after it opened
+    pub fn new(gates: &[IndexedGate]) -> Self {
+        let mut steps = Vec::new();
+        let mut block = LinearBlock::default();
+        let mut rows = HashMap::new();
+        let mut deferred_outputs = HashSet::new();
+
+        for gate in gates {
+            let reads_block = gate.inputs.iter().any(|slot| rows.contains_key(slot));
+            let reads_deferred = gate
+                .inputs
+                .iter()
+                .any(|slot| deferred_outputs.contains(slot));
+
+            if gate.gate_type.is_interactive() {
+                if reads_block {
+                    Self::flush(&mut steps, &mut block, &mut rows, &mut deferred_outputs);
+                }
+                if !block.rows.is_empty() {
+                    deferred_outputs.insert(gate.output);
+                }
+                steps.push(EvaluationStep::Gate(gate.clone()));
+            } else {
+                if reads_deferred {
+                    Self::flush(&mut steps, &mut block, &mut rows, &mut deferred_outputs);
+                }
+                block.push(gate, &mut rows);
+            }
+        }
+
+        Self::flush(&mut steps, &mut block, &mut rows, &mut deferred_outputs);
+        Self {
+            steps,
+            round_starts: HashSet::new(),
+        }
+    }
+
+    /// `new` keeping interactive gates of different layers in different
+    /// rounds; `layers[i]` is the layer of gate `i`
+    pub fn with_layers(gates: &[IndexedGate], layers: &[usize]) -> Self {
+        let mut plan = Self::new(gates);
+        let gate_layers: HashMap<usize, usize> = gates
+            .iter()
+            .zip(layers)
+            .map(|(gate, &layer)| (gate.output, layer))
+            .collect();
+
+        let mut open_layer = None;
+        for (position, step) in plan.steps.iter().enumerate() {
+            if let EvaluationStep::Gate(gate) = step {
+                let layer = gate_layers.get(&gate.output).copied();
+                if open_layer.is_some() && layer != open_layer {
+                    plan.round_starts.insert(position);
+                }
+                open_layer = layer;
+            }
+        }
+        plan
+    }
+
+    /// Number of linear blocks in the plan
+    pub fn block_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step, EvaluationStep::Linear(_)))
+            .count()
+    }
+
+    /// Communication rounds needed by the interactive gates when executed in
+    /// step order: a round closes when a step reads an output of the open round
+    pub fn interactive_rounds(&self) -> usize {
+        self.step_rounds().last().copied().unwrap_or(0)
+    }
+
+    /// Step writing each slot; slots missing from the map are inputs, final
+    /// before the first step runs
+    pub fn slot_steps(&self) -> HashMap<usize, usize> {
+        let mut steps = HashMap::new();
+        for (position, step) in self.steps.iter().enumerate() {
+            match step {
+                EvaluationStep::Linear(block) => {
+                    steps.extend(block.rows.iter().map(|row| (row.output, position)))
+                }
+                EvaluationStep::Gate(gate) => {
+                    steps.insert(gate.output, position);
+                }
+            }
+        }
+        steps
+    }
+
+    /// Number of rounds opened once each step has run, in step order
+    pub fn step_rounds(&self) -> Vec<usize> {
+        let mut rounds = 0;
+        let mut open_round = HashSet::new();
+        let mut step_rounds = Vec::with_capacity(self.steps.len());
+
+        for (position, step) in self.steps.iter().enumerate() {
+            let reads_open_round = match step {
+                EvaluationStep::Linear(block) => &block.inputs,
+                EvaluationStep::Gate(gate) => &gate.inputs,
+            }
+            .iter()
+            .any(|slot| open_round.contains(slot));
+            if reads_open_round || self.round_starts.contains(&position) {
+                open_round.clear();
+            }
+
+            if let EvaluationStep::Gate(gate) = step {
+                if open_round.is_empty() {
+                    rounds += 1;
+                }
+                open_round.insert(gate.output);
+            }
+            step_rounds.push(rounds);
+        }
+
+        step_rounds
+    }
+
+    fn flush(
+        steps: &mut Vec<EvaluationStep>,
+        block: &mut LinearBlock,
+        rows: &mut HashMap<usize, usize>,
+        deferred_outputs: &mut HashSet<usize>,
+    ) {
+        if !block.rows.is_empty() {
+            steps.push(EvaluationStep::Linear(std::mem::take(block)));
+        }
+        rows.clear();
+        deferred_outputs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::PreparedCircuit;
+    #[cfg(feature = "std")]
+    use crate::{
+        circuit::{Circuit, LocalEvaluator},
+        protocol::GmwProtocol,
+    };
+
+    #[test]
+    fn test_full_adder_blocks() {
+        let circuit = reference::full_adder();
+        let prepared = PreparedCircuit::new(&circuit);
+        let plan = LinearPlan::new(&prepared.gates);
+
+        // sum = a ^ b ^ cin collapses into one block, deferred past a & b
+        assert_eq!(plan.steps.len(), 4);
+        assert_eq!(plan.block_count(), 1);
+        assert!(matches!(plan.steps[0], EvaluationStep::Gate(_)));
+
+        let EvaluationStep::Linear(block) = &plan.steps[1] else {
+            panic!("expected a linear block");
+        };
+        assert_eq!(block.gate_count(), 2);
+        assert_eq!(block.inputs.len(), 3);
+    }
+
+    #[test]
+    fn test_block_matches_gates() {
+        // out = ~(a ^ b) ^ a = ~b
+        let gates = vec![
+            IndexedGate {
+                gate_type: GateType::XOR,
+                inputs: vec![0, 1],
+                output: 2,
+            },
+            IndexedGate {
+                gate_type: GateType::NOT,
+                inputs: vec![2],
+                output: 3,
+            },
+            IndexedGate {
+                gate_type: GateType::XOR,
+                inputs: vec![3, 0],
+                output: 4,
+            },
+        ];
+        let plan = LinearPlan::new(&gates);
+        assert_eq!(plan.steps.len(), 1);
+
+        let EvaluationStep::Linear(block) = &plan.steps[0] else {
+            panic!("expected a linear block");
+        };
+        assert_eq!(block.inputs, vec![0, 1]);
+        assert_eq!(block.rows[2].terms, vec![0b10]);
+        assert!(block.rows[2].negate);
+
+        let mut arena = ShareArena::new(2, 5);
+        for (party_id, (a, b)) in [(true, false), (true, true)].into_iter().enumerate() {
+            arena.set(party_id, 0, a);
+            arena.set(party_id, 1, b);
+        }
+        block.apply(&mut arena).unwrap();
+
+        // b = false ^ true = true, so ~b = false
+        assert!(!(arena.get(0, 4).unwrap() ^ arena.get(1, 4).unwrap()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_collapsed_runs_match_local_evaluation() {
+        let protocol = GmwProtocol::new(3).unwrap();
+
+        for file in [
+            "circuits/complex_circuit.json",
+            "circuits/full_adder.json",
+            "circuits/mux_2to1.json",
+            "circuits/two_bit_equality.json",
+        ] {
+            let circuit = Circuit::from_file(file).unwrap();
+            let prepared = PreparedCircuit::new(&circuit);
+            let input_count = circuit.metadata.inputs.len();
+
+            for input in 0..1u32 << input_count {
+                let inputs: Vec<bool> = (0..input_count).map(|bit| input >> bit & 1 == 1).collect();
+                let report = protocol.run_prepared(&prepared, &inputs).unwrap();
+
+                for info in &circuit.metadata.outputs {
+                    let expected = LocalEvaluator::get_output(&circuit, &inputs, info.id).unwrap();
+                    assert_eq!(
+                        report.output(&info.name),
+                        Some(expected),
+                        "{file} {inputs:?}"
+                    );
+                }
+            }
+        }
+    }
+}
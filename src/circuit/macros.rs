@@ -0,0 +1,507 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use crate::circuit::{Circuit, CircuitMetadata, Gate, GateType, WireId};
+
+/// Arithmetic building blocks that expand to primitive gates at load time
+/// XOR and AND gates with more than two inputs are expanded the same way,
+/// into balanced trees of two-input gates
+/// A macro writes its first output to the gate `id` and its second output,
+/// if any, to `id + 1`; internal wires are allocated above every wire ID
+/// declared in the file, in gate order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MacroGate {
+    /// in: [a, b]; out: sum, carry
+    HalfAdder,
+    /// in: [a, b, carry_in]; out: sum, carry
+    FullAdder,
+    /// in: [a, b]; out: difference, borrow of a - b
+    HalfSubtractor,
+    /// in: [a, b, borrow_in]; out: difference, borrow of a - b - borrow_in
+    FullSubtractor,
+    /// in: [a0..an-1, b0..bn-1], least significant bit first; out: a < b
+    ComparatorLt,
+}
+
+/// Gate type as written in circuit JSON
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum SourceGateType {
+    Primitive(GateType),
+    Macro(MacroGate),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SourceGate {
+    id: WireId,
+    #[serde(rename = "type")]
+    gate_type: SourceGateType,
+    #[serde(rename = "in")]
+    inputs: Vec<WireId>,
+    /// Public second operand of an XOR, AND or OR gate with one input wire
+    #[serde(default, rename = "const")]
+    constant: Option<bool>,
+}
+
+/// Circuit as written in JSON, before macro expansion
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct CircuitSource {
+    name: String,
+    description: String,
+    gates: Vec<SourceGate>,
+    metadata: CircuitMetadata,
+}
+
+impl MacroGate {
+    /// Number of outputs written from the gate `id` upward
+    pub fn output_count(&self) -> usize {
+        match self {
+            MacroGate::ComparatorLt => 1,
+            _ => 2,
+        }
+    }
+
+    fn check_inputs(&self, gate: WireId, inputs: &[WireId]) -> Result<()> {
+        let valid = match self {
+            MacroGate::HalfAdder | MacroGate::HalfSubtractor => inputs.len() == 2,
+            MacroGate::FullAdder | MacroGate::FullSubtractor => inputs.len() == 3,
+            MacroGate::ComparatorLt => !inputs.is_empty() && inputs.len().is_multiple_of(2),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Macro gate {} ({:?}) has {} inputs",
+                gate,
+                self,
+                inputs.len()
+            ))
+        }
+    }
+}
+
+impl TryFrom<CircuitSource> for Circuit {
+    type Error = anyhow::Error;
+
+    fn try_from(source: CircuitSource) -> Result<Self> {
+        let mut declared = HashSet::new();
+        for input in &source.metadata.inputs {
+            declared.insert(input.id);
+        }
+        for gate in &source.gates {
+            let outputs = match gate.gate_type {
+                SourceGateType::Primitive(_) => 1,
+                SourceGateType::Macro(macro_gate) => macro_gate.output_count(),
+            };
+            for offset in 0..outputs as WireId {
+                let wire = gate
+                    .id
+                    .checked_add(offset)
+                    .ok_or_else(|| anyhow::anyhow!("Macro gate {} overflows wire IDs", gate.id))?;
+                if !declared.insert(wire) {
+                    return Err(anyhow::anyhow!("Wire {} is defined more than once", wire));
+                }
+            }
+        }
+
+        let mut expander = Expander {
+            next_id: declared.iter().max().copied().unwrap_or(0),
+            gates: Vec::with_capacity(source.gates.len()),
+        };
+        for gate in source.gates {
+            match gate.gate_type {
+                SourceGateType::Primitive(gate_type) => match gate.constant {
+                    None if matches!(gate_type, GateType::XOR | GateType::AND)
+                        && gate.inputs.len() > 2 =>
+                    {
+                        expander.reduce(gate_type, &gate.inputs, gate.id)?;
+                    }
+                    None => expander.gates.push(Gate {
+                        id: gate.id,
+                        gate_type,
+                        inputs: gate.inputs,
+                    }),
+                    Some(constant) => {
+                        let [wire] = gate.inputs[..] else {
+                            return Err(anyhow::anyhow!(
+                                "Gate {} with a constant operand must have one input wire",
+                                gate.id
+                            ));
+                        };
+                        let lowered =
+                            constant_operand(&gate_type, wire, constant, gate.id, &mut || {
+                                expander.fresh()
+                            })?;
+                        expander.gates.extend(lowered);
+                    }
+                },
+                SourceGateType::Macro(_) if gate.constant.is_some() => {
+                    return Err(anyhow::anyhow!(
+                        "Macro gate {} cannot take a constant operand",
+                        gate.id
+                    ));
+                }
+                SourceGateType::Macro(macro_gate) => {
+                    macro_gate.check_inputs(gate.id, &gate.inputs)?;
+                    expander.expand(macro_gate, gate.id, &gate.inputs)?;
+                }
+            }
+        }
+
+        if let Some(name) = source.metadata.public_inputs.iter().find(|name| {
+            !source
+                .metadata
+                .inputs
+                .iter()
+                .any(|input| &input.name == *name)
+        }) {
+            return Err(anyhow::anyhow!("Public input {} not found", name));
+        }
+
+        let produced: HashSet<WireId> = expander.gates.iter().map(|gate| gate.id).collect();
+        if let Some(wire) = source
+            .metadata
+            .schedule
+            .keys()
+            .find(|wire| !produced.contains(wire))
+        {
+            return Err(anyhow::anyhow!(
+                "Schedule hint for wire {} which no gate produces",
+                wire
+            ));
+        }
+        for (wire, parties) in &source.metadata.openings {
+            let input = source.metadata.inputs.iter().any(|input| input.id == *wire);
+            if !produced.contains(wire) && !input {
+                return Err(anyhow::anyhow!("Opening policy for unknown wire {}", wire));
+            }
+            if let Some(spec) = &source.metadata.parties {
+                if let Some(party) = parties.iter().find(|&&party| party >= spec.count) {
+                    return Err(anyhow::anyhow!(
+                        "Opening policy of wire {} names party {} of only {}",
+                        wire,
+                        party,
+                        spec.count
+                    ));
+                }
+            }
+        }
+
+        Ok(Circuit {
+            name: source.name,
+            description: source.description,
+            gates: expander.gates,
+            metadata: source.metadata,
+        })
+    }
+}
+
+/// Gates computing `wire <gate_type> constant` into `output` from XOR and
+/// NOT gates only, so a public operand never costs an OT: x ^ 1 = !x,
+/// x & 0 = x ^ x, x | 1 = !(x ^ x), and x ^ 0 = x & 1 = x | 0 = !!x.
+/// `fresh` allocates the intermediate wire some forms need
+pub(crate) fn constant_operand(
+    gate_type: &GateType,
+    wire: WireId,
+    constant: bool,
+    output: WireId,
+    fresh: &mut dyn FnMut() -> Result<WireId>,
+) -> Result<Vec<Gate>> {
+    let gate = |gate_type, inputs, id| Gate {
+        id,
+        gate_type,
+        inputs,
+    };
+    Ok(match (gate_type, constant) {
+        (GateType::NOT, _) => {
+            return Err(anyhow::anyhow!(
+                "NOT gate {} cannot take a constant operand",
+                output
+            ))
+        }
+        (GateType::XOR, true) => vec![gate(GateType::NOT, vec![wire], output)],
+        (GateType::AND, false) => vec![gate(GateType::XOR, vec![wire, wire], output)],
+        (GateType::OR, true) => {
+            let zero = fresh()?;
+            vec![
+                gate(GateType::XOR, vec![wire, wire], zero),
+                gate(GateType::NOT, vec![zero], output),
+            ]
+        }
+        (GateType::XOR | GateType::AND | GateType::OR, _) => {
+            let inverted = fresh()?;
+            vec![
+                gate(GateType::NOT, vec![wire], inverted),
+                gate(GateType::NOT, vec![inverted], output),
+            ]
+        }
+    })
+}
+
+/// Emits primitive gates, allocating internal wires sequentially
+struct Expander {
+    next_id: WireId,
+    gates: Vec<Gate>,
+}
+
+impl Expander {
+    /// Allocate the next internal wire
+    fn fresh(&mut self) -> Result<WireId> {
+        self.next_id = self
+            .next_id
+            .checked_add(1)
+            .ok_or_else(|| anyhow::anyhow!("No wire IDs left for macro expansion"))?;
+        Ok(self.next_id)
+    }
+
+    /// Emit a gate to `output`, or to a fresh internal wire if `None`
+    fn gate(
+        &mut self,
+        gate_type: GateType,
+        inputs: Vec<WireId>,
+        output: Option<WireId>,
+    ) -> Result<WireId> {
+        let id = match output {
+            Some(id) => id,
+            None => self.fresh()?,
+        };
+        self.gates.push(Gate {
+            id,
+            gate_type,
+            inputs,
+        });
+        Ok(id)
+    }
+
+    /// Reduce a wide XOR or AND into `output` with a balanced tree of
+    /// two-input gates, so an n-input AND costs ceil(log2 n) rounds
+    fn reduce(&mut self, gate_type: GateType, inputs: &[WireId], output: WireId) -> Result<()> {
+        let mut level = inputs.to_vec();
+        while level.len() > 2 {
+            level = level
+                .chunks(2)
+                .map(|pair| match *pair {
+                    [a, b] => self.gate(gate_type.clone(), vec![a, b], None),
+                    _ => Ok(pair[0]),
+                })
+                .collect::<Result<_>>()?;
+        }
+        self.gate(gate_type, level, Some(output))?;
+        Ok(())
+    }
+
+    fn expand(&mut self, macro_gate: MacroGate, id: WireId, inputs: &[WireId]) -> Result<()> {
+        match macro_gate {
+            MacroGate::HalfAdder => {
+                let (a, b) = (inputs[0], inputs[1]);
+                self.gate(GateType::XOR, vec![a, b], Some(id))?;
+                self.gate(GateType::AND, vec![a, b], Some(id + 1))?;
+            }
+            MacroGate::FullAdder => {
+                // carry = (a & b) ^ ((a ^ b) & cin); the two terms never both hold
+                let (a, b, carry_in) = (inputs[0], inputs[1], inputs[2]);
+                let a_xor_b = self.gate(GateType::XOR, vec![a, b], None)?;
+                self.gate(GateType::XOR, vec![a_xor_b, carry_in], Some(id))?;
+                let generate = self.gate(GateType::AND, vec![a, b], None)?;
+                let propagate = self.gate(GateType::AND, vec![a_xor_b, carry_in], None)?;
+                self.gate(GateType::XOR, vec![generate, propagate], Some(id + 1))?;
+            }
+            MacroGate::HalfSubtractor => {
+                let (a, b) = (inputs[0], inputs[1]);
+                self.gate(GateType::XOR, vec![a, b], Some(id))?;
+                let not_a = self.gate(GateType::NOT, vec![a], None)?;
+                self.gate(GateType::AND, vec![not_a, b], Some(id + 1))?;
+            }
+            MacroGate::FullSubtractor => {
+                // borrow = (~a & b) ^ (~(a ^ b) & bin); the two terms never both hold
+                let (a, b, borrow_in) = (inputs[0], inputs[1], inputs[2]);
+                let a_xor_b = self.gate(GateType::XOR, vec![a, b], None)?;
+                self.gate(GateType::XOR, vec![a_xor_b, borrow_in], Some(id))?;
+                let not_a = self.gate(GateType::NOT, vec![a], None)?;
+                let borrow = self.gate(GateType::AND, vec![not_a, b], None)?;
+                let equal = self.gate(GateType::NOT, vec![a_xor_b], None)?;
+                let pass = self.gate(GateType::AND, vec![equal, borrow_in], None)?;
+                self.gate(GateType::XOR, vec![borrow, pass], Some(id + 1))?;
+            }
+            MacroGate::ComparatorLt => {
+                // Scanning upward, lt_i = (~a_i & b_i) ^ (~(a_i ^ b_i) & lt_(i-1))
+                let (a, b) = inputs.split_at(inputs.len() / 2);
+                let mut less = None;
+
+                for (i, (&a_i, &b_i)) in a.iter().zip(b).enumerate() {
+                    let output = (i + 1 == a.len()).then_some(id);
+                    let not_a = self.gate(GateType::NOT, vec![a_i], None)?;
+
+                    less = Some(match less {
+                        None => self.gate(GateType::AND, vec![not_a, b_i], output)?,
+                        Some(previous) => {
+                            let below = self.gate(GateType::AND, vec![not_a, b_i], None)?;
+                            let differ = self.gate(GateType::XOR, vec![a_i, b_i], None)?;
+                            let equal = self.gate(GateType::NOT, vec![differ], None)?;
+                            let carried = self.gate(GateType::AND, vec![equal, previous], None)?;
+                            self.gate(GateType::XOR, vec![below, carried], output)?
+                        }
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{LocalEvaluator, PreparedCircuit};
+
+    fn macro_circuit(gate_type: &str, input_count: usize) -> Circuit {
+        let inputs: Vec<String> = (0..input_count)
+            .map(|i| format!(r#"{{"name": "x{i}", "id": {}}}"#, i + 1))
+            .collect();
+        let wires: Vec<String> = (1..=input_count).map(|i| i.to_string()).collect();
+
+        Circuit::from_json(&format!(
+            r#"{{
+                "name": "macro",
+                "description": "Single macro gate",
+                "metadata": {{
+                    "inputs": [{}],
+                    "outputs": [{{"name": "first", "id": 100}}]
+                }},
+                "gates": [{{"id": 100, "type": "{gate_type}", "in": [{}]}}]
+            }}"#,
+            inputs.join(", "),
+            wires.join(", ")
+        ))
+        .unwrap()
+    }
+
+    fn outputs(circuit: &Circuit, value: u32, input_count: usize) -> (bool, bool) {
+        let inputs: Vec<bool> = (0..input_count).map(|bit| value >> bit & 1 == 1).collect();
+        let values = LocalEvaluator::evaluate(circuit, &inputs).unwrap();
+        (values[&100], values.get(&101).copied().unwrap_or(false))
+    }
+
+    #[test]
+    fn test_adders_and_subtractors() {
+        for (gate_type, input_count) in [
+            ("HALF_ADDER", 2),
+            ("FULL_ADDER", 3),
+            ("HALF_SUBTRACTOR", 2),
+            ("FULL_SUBTRACTOR", 3),
+        ] {
+            let circuit = macro_circuit(gate_type, input_count);
+
+            for value in 0..1u32 << input_count {
+                let bits: Vec<i32> = (0..input_count).map(|i| (value >> i & 1) as i32).collect();
+                let result = match gate_type {
+                    "HALF_ADDER" | "FULL_ADDER" => bits.iter().sum::<i32>(),
+                    _ => bits[0] - bits[1..].iter().sum::<i32>(),
+                };
+                // Low bit, then carry (sum >= 2) or borrow (difference < 0)
+                let expected = (result & 1 == 1, !(0..=1).contains(&result));
+                assert_eq!(
+                    outputs(&circuit, value, input_count),
+                    expected,
+                    "{gate_type} {bits:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparator() {
+        for width in 1..=3 {
+            let circuit = macro_circuit("COMPARATOR_LT", 2 * width);
+            for value in 0..1u32 << (2 * width) {
+                let (a, b) = (value & ((1 << width) - 1), value >> width);
+                assert_eq!(outputs(&circuit, value, 2 * width).0, a < b, "{a} < {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_operands_are_linear() {
+        for gate_type in ["XOR", "AND", "OR"] {
+            for constant in [false, true] {
+                let circuit = Circuit::from_json(&format!(
+                    r#"{{
+                    "name": "constant",
+                    "description": "Gate with a public operand",
+                    "metadata": {{
+                        "inputs": [{{"name": "x", "id": 1}}],
+                        "outputs": [{{"name": "first", "id": 100}}]
+                    }},
+                    "gates": [{{"id": 100, "type": "{gate_type}", "in": [1], "const": {constant}}}]
+                }}"#
+                ))
+                .unwrap();
+                assert!(circuit
+                    .gates
+                    .iter()
+                    .all(|gate| !gate.gate_type.is_interactive()));
+
+                for x in [false, true] {
+                    let expected = match gate_type {
+                        "XOR" => x ^ constant,
+                        "AND" => x & constant,
+                        _ => x | constant,
+                    };
+                    assert_eq!(outputs(&circuit, u32::from(x), 1).0, expected);
+                }
+            }
+        }
+
+        let not = r#"{
+            "name": "constant",
+            "description": "NOT has no second operand",
+            "metadata": {"inputs": [{"name": "x", "id": 1}], "outputs": []},
+            "gates": [{"id": 100, "type": "NOT", "in": [1], "const": true}]
+        }"#;
+        assert!(Circuit::from_json(not).is_err());
+    }
+
+    #[test]
+    fn test_wide_xor_and_and() {
+        for (gate_type, input_count) in [("XOR", 3), ("AND", 5), ("AND", 8)] {
+            let circuit = macro_circuit(gate_type, input_count);
+            assert!(circuit.gates.iter().all(|gate| gate.inputs.len() == 2));
+            assert_eq!(circuit.gates.len(), input_count - 1);
+            let expected_depth = if gate_type == "AND" {
+                input_count.next_power_of_two().trailing_zeros() as usize
+            } else {
+                0
+            };
+            assert_eq!(PreparedCircuit::new(&circuit).and_depth, expected_depth);
+
+            for value in 0..1u32 << input_count {
+                let expected = match gate_type {
+                    "XOR" => value.count_ones() % 2 == 1,
+                    _ => value.count_ones() as usize == input_count,
+                };
+                assert_eq!(outputs(&circuit, value, input_count).0, expected);
+            }
+        }
+
+        // Wide ORs are not expanded and stay a malformed gate
+        assert_eq!(macro_circuit("OR", 3).gates[0].inputs.len(), 3);
+    }
+
+    #[test]
+    fn test_overlapping_outputs_rejected() {
+        let json = r#"{
+            "name": "overlap",
+            "description": "Second adder output collides with a gate",
+            "metadata": {"inputs": [{"name": "a", "id": 1}, {"name": "b", "id": 2}], "outputs": []},
+            "gates": [
+                {"id": 10, "type": "HALF_ADDER", "in": [1, 2]},
+                {"id": 11, "type": "XOR", "in": [1, 2]}
+            ]
+        }"#;
+        assert!(Circuit::from_json(json).is_err());
+    }
+}
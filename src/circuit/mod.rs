@@ -1,5 +1,34 @@
+pub mod builder;
+pub mod constraints;
+pub mod embed;
+pub mod encoding;
 pub mod evaluator;
+pub mod format;
+pub mod index;
+pub mod layers;
+pub mod linear;
+pub mod macros;
+pub mod prepared;
+pub mod public;
+#[cfg(test)]
+pub(crate) mod reference;
+pub mod registry;
+mod sieve;
+pub mod template;
+mod text;
 pub mod types;
 
+pub use crate::kernel::IndexedGate;
+pub use builder::{Arithmetic, Bus, CircuitBuilder, Wire};
+pub use constraints::InputChecks;
+pub use encoding::InputEncoder;
 pub use evaluator::LocalEvaluator;
+pub use format::{CircuitFormat, EncodedCircuit};
+pub use index::WireIndex;
+pub use layers::Layering;
+pub use linear::{EvaluationStep, LinearBlock, LinearPlan, LinearRow};
+pub use macros::MacroGate;
+pub use prepared::PreparedCircuit;
+pub use registry::{CircuitFile, CircuitRegistry};
+pub use template::CircuitTemplate;
 pub use types::*;
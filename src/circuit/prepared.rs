@@ -0,0 +1,356 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+#[cfg(feature = "std")]
+use crate::arena::ShareArena;
+use crate::circuit::format::{read_binary, write_string, write_varint, BinaryReader, GATE_OPCODES};
+use crate::circuit::{
+    Circuit, CircuitMetadata, EvaluationStep, IndexedGate, InputChecks, Layering, LinearBlock,
+    LinearPlan, LinearRow, WireIndex,
+};
+
+/// Version of the evaluation plan stored in binary circuits; bump it when
+/// scheduling or the linear collapse changes, so older plans are rebuilt
+/// instead of trusted
+const PLAN_VERSION: u64 = 1;
+
+/// Circuit preprocessed for repeated evaluation
+/// Wires are resolved to dense slots once, and the share arena is kept
+/// between runs so batch workloads do not reallocate per evaluation.
+/// Gates are scheduled layer by layer so each AND-depth level is one round.
+/// Linear subcircuits are collapsed into GF(2) blocks for evaluation, and
+/// input constraints are compiled into check gates appended to the circuit
+#[derive(Debug)]
+pub struct PreparedCircuit {
+    pub name: String,
+    pub metadata: CircuitMetadata,
+    pub index: WireIndex,
+    pub gates: Vec<IndexedGate>,
+    pub plan: LinearPlan,
+    /// AND-depth of the circuit, including input checks
+    pub and_depth: usize,
+    /// Interactive rounds of the evaluation schedule; equals `and_depth`
+    /// unless the scheduler serializes layers
+    pub rounds: usize,
+    /// Interactive layer of each plan step
+    step_layers: Vec<usize>,
+    /// Constraint descriptions and check slots, or why they could not be built
+    checks: std::result::Result<Vec<(String, usize)>, String>,
+    /// Reused by protocol runs
+    #[cfg(feature = "std")]
+    arena: Mutex<ShareArena>,
+}
+
+impl PreparedCircuit {
+    /// Index the circuit's wires and resolve its gates
+    pub fn new(circuit: &Circuit) -> Self {
+        let input_checks = InputChecks::new(circuit);
+        let checked;
+        let circuit = match &input_checks {
+            Ok(input_checks) if !input_checks.gates.is_empty() => {
+                checked = Circuit {
+                    gates: [&circuit.gates[..], &input_checks.gates[..]].concat(),
+                    ..circuit.clone()
+                };
+                &checked
+            }
+            _ => circuit,
+        };
+
+        let index = WireIndex::new(circuit);
+        let resolved = index.resolve_gates(circuit);
+        let layering = Layering::from_gates(&resolved, index.len());
+        let schedule = &circuit.metadata.schedule;
+        let (gates, plan) = if schedule.is_empty() {
+            let gates = layering.schedule(&resolved);
+            let plan = LinearPlan::new(&gates);
+            (gates, plan)
+        } else {
+            let hints: Vec<_> = circuit
+                .gates
+                .iter()
+                .map(|gate| schedule.get(&gate.id))
+                .collect();
+            let priorities: Vec<u32> = hints
+                .iter()
+                .map(|hint| hint.map_or(0, |hint| hint.priority))
+                .collect();
+            let hinted = Layering::with_hints(&resolved, index.len(), &hints);
+            let gates = hinted.schedule_with_priorities(&resolved, &priorities);
+            let depths: HashMap<usize, usize> = resolved
+                .iter()
+                .zip(&hinted.gate_depths)
+                .map(|(gate, &depth)| (gate.output, depth))
+                .collect();
+            let layers: Vec<usize> = gates.iter().map(|gate| depths[&gate.output]).collect();
+            let plan = LinearPlan::with_layers(&gates, &layers);
+            (gates, plan)
+        };
+        let checks = input_checks
+            .map(|input_checks| {
+                input_checks
+                    .checks
+                    .into_iter()
+                    .map(|(name, wire)| (name, index.slot(wire).expect("check wire is indexed")))
+                    .collect()
+            })
+            .map_err(|err| err.to_string());
+
+        Self {
+            name: circuit.name.clone(),
+            metadata: circuit.metadata.clone(),
+            index,
+            gates,
+            and_depth: layering.depth,
+            rounds: plan.interactive_rounds(),
+            step_layers: plan.step_rounds(),
+            plan,
+            checks,
+            #[cfg(feature = "std")]
+            arena: Mutex::new(ShareArena::default()),
+        }
+    }
+
+    /// Prepare `circuit` with its public inputs fixed to `public`, so only
+    /// the secret part is evaluated; see `Circuit::specialize`
+    pub fn with_public(circuit: &Circuit, public: &[(&str, bool)]) -> Result<Self> {
+        Ok(Self::new(&circuit.specialize(public)?))
+    }
+
+    /// Prepare a compact binary circuit from the evaluation plan stored in
+    /// it, so loading a large circuit only reads the plan instead of
+    /// re-running the layering and linear analyses
+    /// Files written before plans were stored, or with a plan of another
+    /// version, are prepared from scratch; a damaged plan is an error
+    pub fn from_binary(bytes: &[u8]) -> Result<Self> {
+        let (circuit, mut reader) = read_binary(bytes)?;
+        if reader.is_at_end() || reader.varint()? != PLAN_VERSION {
+            return Ok(Self::new(&circuit));
+        }
+        Self::read_plan(&circuit, &mut reader)
+            .map_err(|err| anyhow::anyhow!("Stored evaluation plan is damaged: {}", err))
+    }
+
+    /// Input constraint checks as (description, slot) pairs
+    /// Fails if the circuit declares a constraint that cannot be compiled
+    pub fn input_checks(&self) -> Result<&[(String, usize)]> {
+        self.checks
+            .as_deref()
+            .map_err(|err| anyhow::anyhow!("{}", err))
+    }
+
+    /// Interactive layer of each plan step, as `LinearPlan::step_rounds`
+    pub fn step_layers(&self) -> &[usize] {
+        &self.step_layers
+    }
+
+    /// Lock the share arena for an evaluation
+    #[cfg(feature = "std")]
+    pub(crate) fn arena(&self) -> MutexGuard<'_, ShareArena> {
+        self.arena.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl PreparedCircuit {
+    /// Append the evaluation plan to a binary circuit: plan version, wires
+    /// in slot order, scheduled gates, plan steps, round starts, depth and
+    /// rounds, step layers and input checks
+    pub(crate) fn write_plan(&self, bytes: &mut Vec<u8>) {
+        write_varint(bytes, PLAN_VERSION);
+        write_varint(bytes, self.index.len() as u64);
+        for slot in 0..self.index.len() {
+            write_varint(bytes, self.index.wire(slot).expect("slot is indexed"));
+        }
+        write_varint(bytes, self.gates.len() as u64);
+        for gate in &self.gates {
+            write_gate(bytes, gate);
+        }
+
+        write_varint(bytes, self.plan.steps.len() as u64);
+        for step in &self.plan.steps {
+            match step {
+                EvaluationStep::Gate(gate) => {
+                    bytes.push(0);
+                    write_gate(bytes, gate);
+                }
+                EvaluationStep::Linear(block) => {
+                    bytes.push(1);
+                    write_slots(bytes, &block.inputs);
+                    write_varint(bytes, block.rows.len() as u64);
+                    for row in &block.rows {
+                        write_varint(bytes, row.output as u64);
+                        write_varint(bytes, row.terms.len() as u64);
+                        for &word in &row.terms {
+                            write_varint(bytes, word);
+                        }
+                        bytes.push(u8::from(row.negate));
+                    }
+                }
+            }
+        }
+        let mut round_starts: Vec<usize> = self.plan.round_starts.iter().copied().collect();
+        round_starts.sort_unstable();
+        write_slots(bytes, &round_starts);
+
+        write_varint(bytes, self.and_depth as u64);
+        write_varint(bytes, self.rounds as u64);
+        write_slots(bytes, &self.step_layers);
+        match &self.checks {
+            Ok(checks) => {
+                bytes.push(0);
+                write_varint(bytes, checks.len() as u64);
+                for (description, slot) in checks {
+                    write_string(bytes, description);
+                    write_varint(bytes, *slot as u64);
+                }
+            }
+            Err(err) => {
+                bytes.push(1);
+                write_string(bytes, err);
+            }
+        }
+    }
+
+    /// Read a plan written by `write_plan` for `circuit`, checking every slot
+    /// and step it refers to
+    fn read_plan(circuit: &Circuit, reader: &mut BinaryReader) -> Result<Self> {
+        let wires = (0..reader.varint()?)
+            .map(|_| reader.varint())
+            .collect::<Result<Vec<_>>>()?;
+        let index = WireIndex::from_wires(wires)?;
+        if let Some(gate) = circuit
+            .gates
+            .iter()
+            .find(|gate| index.slot(gate.id).is_none())
+        {
+            return Err(anyhow::anyhow!("Gate {} has no slot", gate.id));
+        }
+        let slots = index.len();
+
+        let gates = (0..reader.varint()?)
+            .map(|_| read_gate(reader, slots))
+            .collect::<Result<Vec<_>>>()?;
+        let steps = (0..reader.varint()?)
+            .map(|_| match reader.byte()? {
+                0 => Ok(EvaluationStep::Gate(read_gate(reader, slots)?)),
+                1 => {
+                    let inputs = read_slots(reader, slots)?;
+                    let rows = (0..reader.varint()?)
+                        .map(|_| {
+                            let output = read_slot(reader, slots)?;
+                            let terms = (0..reader.varint()?)
+                                .map(|_| reader.varint())
+                                .collect::<Result<Vec<_>>>()?;
+                            let negate = reader.byte()? != 0;
+                            Ok(LinearRow {
+                                output,
+                                terms,
+                                negate,
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok(EvaluationStep::Linear(LinearBlock { inputs, rows }))
+                }
+                tag => Err(anyhow::anyhow!("Unknown plan step tag {}", tag)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let round_starts: HashSet<usize> = read_slots(reader, steps.len())?.into_iter().collect();
+
+        let and_depth = reader.varint()? as usize;
+        let rounds = reader.varint()? as usize;
+        let step_layers = (0..reader.varint()?)
+            .map(|_| reader.varint().map(|layer| layer as usize))
+            .collect::<Result<Vec<_>>>()?;
+        if step_layers.len() != steps.len() {
+            return Err(anyhow::anyhow!(
+                "{} step layers for {} steps",
+                step_layers.len(),
+                steps.len()
+            ));
+        }
+        let checks = match reader.byte()? {
+            0 => Ok((0..reader.varint()?)
+                .map(|_| Ok((reader.string()?, read_slot(reader, slots)?)))
+                .collect::<Result<Vec<_>>>()?),
+            1 => Err(reader.string()?),
+            tag => return Err(anyhow::anyhow!("Unknown input check tag {}", tag)),
+        };
+
+        Ok(Self {
+            name: circuit.name.clone(),
+            metadata: circuit.metadata.clone(),
+            index,
+            gates,
+            plan: LinearPlan {
+                steps,
+                round_starts,
+            },
+            and_depth,
+            rounds,
+            step_layers,
+            checks,
+            #[cfg(feature = "std")]
+            arena: Mutex::new(ShareArena::default()),
+        })
+    }
+}
+
+/// Gate as its opcode in the fixed gate table, output slot and input slots
+fn write_gate(bytes: &mut Vec<u8>, gate: &IndexedGate) {
+    let opcode = GATE_OPCODES
+        .iter()
+        .position(|(_, gate_type, _)| *gate_type == gate.gate_type)
+        .expect("every gate type has an opcode");
+    write_varint(bytes, opcode as u64);
+    write_varint(bytes, gate.output as u64);
+    for &input in &gate.inputs {
+        write_varint(bytes, input as u64);
+    }
+}
+
+fn read_gate(reader: &mut BinaryReader, slots: usize) -> Result<IndexedGate> {
+    let opcode = reader.varint()?;
+    let (_, gate_type, arity) = usize::try_from(opcode)
+        .ok()
+        .and_then(|opcode| GATE_OPCODES.get(opcode))
+        .ok_or_else(|| anyhow::anyhow!("Unknown gate opcode {}", opcode))?;
+    let output = read_slot(reader, slots)?;
+    let inputs = (0..*arity)
+        .map(|_| read_slot(reader, slots))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(IndexedGate {
+        gate_type: gate_type.clone(),
+        inputs,
+        output,
+    })
+}
+
+fn write_slots(bytes: &mut Vec<u8>, slots: &[usize]) {
+    write_varint(bytes, slots.len() as u64);
+    for &slot in slots {
+        write_varint(bytes, slot as u64);
+    }
+}
+
+fn read_slots(reader: &mut BinaryReader, slots: usize) -> Result<Vec<usize>> {
+    (0..reader.varint()?)
+        .map(|_| read_slot(reader, slots))
+        .collect()
+}
+
+/// Read an index below `slots`
+fn read_slot(reader: &mut BinaryReader, slots: usize) -> Result<usize> {
+    let slot = reader.varint()?;
+    usize::try_from(slot)
+        .ok()
+        .filter(|&slot| slot < slots)
+        .ok_or_else(|| anyhow::anyhow!("Index {} out of range for {}", slot, slots))
+}
+
+impl From<&Circuit> for PreparedCircuit {
+    fn from(circuit: &Circuit) -> Self {
+        Self::new(circuit)
+    }
+}
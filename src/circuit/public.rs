@@ -0,0 +1,254 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use crate::circuit::{Circuit, Gate, GateType, InputConstraint, OutputInfo, WireId};
+
+/// Value of a wire once public inputs are fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Known {
+    Public(bool),
+    /// Secret, carried by this wire of the specialized circuit
+    Secret(WireId),
+}
+
+impl Circuit {
+    /// Circuit computing the same outputs once the public inputs are fixed
+    /// to `public`, which must give a value for every input listed in
+    /// `metadata.public_inputs`. Public values are propagated through the
+    /// gates: gates with a public result disappear, gates with one public
+    /// operand become a wire or a NOT, and gates no output needs are dropped.
+    /// The remaining secret inputs keep their metadata order
+    pub fn specialize(&self, public: &[(&str, bool)]) -> Result<Circuit> {
+        let metadata = &self.metadata;
+        let mut known: HashMap<WireId, Known> = HashMap::new();
+        for &(name, value) in public {
+            if !metadata.public_inputs.iter().any(|input| input == name) {
+                return Err(anyhow::anyhow!("Input {} is not declared public", name));
+            }
+            let input = metadata
+                .inputs
+                .iter()
+                .find(|input| input.name == name)
+                .ok_or_else(|| anyhow::anyhow!("Input {} not found", name))?;
+            if known.insert(input.id, Known::Public(value)).is_some() {
+                return Err(anyhow::anyhow!("Public input {} is given twice", name));
+            }
+        }
+        if let Some(missing) = metadata
+            .public_inputs
+            .iter()
+            .find(|name| !public.iter().any(|&(given, _)| given == name.as_str()))
+        {
+            return Err(anyhow::anyhow!("No value for public input {}", missing));
+        }
+        if let Some(constraint) = metadata.constraints.iter().find(|constraint| {
+            let (InputConstraint::LessThan { bits, .. } | InputConstraint::OneHot { bits }) =
+                constraint;
+            bits.iter().any(|bit| metadata.public_inputs.contains(bit))
+        }) {
+            return Err(anyhow::anyhow!(
+                "Constraint {} covers public inputs; check it in the clear instead",
+                constraint
+            ));
+        }
+
+        let value = |known: &HashMap<WireId, Known>, wire: WireId| {
+            known.get(&wire).copied().unwrap_or(Known::Secret(wire))
+        };
+        let mut gates = Vec::new();
+        for gate in &self.gates {
+            let inputs: Vec<Known> = gate
+                .inputs
+                .iter()
+                .map(|&wire| value(&known, wire))
+                .collect();
+            let mut emit = |gate_type: GateType, inputs: Vec<WireId>| {
+                gates.push(Gate {
+                    id: gate.id,
+                    gate_type,
+                    inputs,
+                });
+                Known::Secret(gate.id)
+            };
+            use Known::{Public, Secret};
+            let result = match (&gate.gate_type, inputs.as_slice()) {
+                (GateType::NOT, &[Public(x)]) => Public(!x),
+                (GateType::NOT, &[Secret(x)]) => emit(GateType::NOT, vec![x]),
+                (GateType::XOR, &[Public(x), Public(y)]) => Public(x ^ y),
+                (GateType::AND, &[Public(x), Public(y)]) => Public(x & y),
+                (GateType::OR, &[Public(x), Public(y)]) => Public(x | y),
+                (GateType::XOR, &[Public(false), wire] | &[wire, Public(false)])
+                | (GateType::AND, &[Public(true), wire] | &[wire, Public(true)])
+                | (GateType::OR, &[Public(false), wire] | &[wire, Public(false)]) => wire,
+                (GateType::XOR, &[Public(true), Secret(x)] | &[Secret(x), Public(true)]) => {
+                    emit(GateType::NOT, vec![x])
+                }
+                (GateType::AND, &[Public(false), _] | &[_, Public(false)]) => Public(false),
+                (GateType::OR, &[Public(true), _] | &[_, Public(true)]) => Public(true),
+                (gate_type, inputs) => {
+                    let wires = inputs
+                        .iter()
+                        .map(|input| match input {
+                            Secret(wire) => Ok(*wire),
+                            Public(_) => Err(anyhow::anyhow!(
+                                "Gate {} has an unexpected number of inputs",
+                                gate.id
+                            )),
+                        })
+                        .collect::<Result<_>>()?;
+                    emit(gate_type.clone(), wires)
+                }
+            };
+            known.insert(gate.id, result);
+        }
+
+        // Outputs that became public read a constant derived from a secret
+        // input: s ^ s is a valid sharing of 0, and its NOT one of 1
+        let secret_inputs: Vec<_> = metadata
+            .inputs
+            .iter()
+            .filter(|input| !metadata.public_inputs.contains(&input.name))
+            .cloned()
+            .collect();
+        let mut next_id = self
+            .gates
+            .iter()
+            .map(|gate| gate.id)
+            .chain(metadata.inputs.iter().map(|input| input.id))
+            .max()
+            .unwrap_or(0);
+        let mut constants: HashMap<bool, WireId> = HashMap::new();
+        let mut outputs = Vec::with_capacity(metadata.outputs.len());
+        for output in &metadata.outputs {
+            let id = match value(&known, output.id) {
+                Known::Secret(wire) => wire,
+                Known::Public(bit) => {
+                    let source = secret_inputs.first().ok_or_else(|| {
+                        anyhow::anyhow!("Every input is public; evaluate the circuit in the clear")
+                    })?;
+                    let zero = *constants.entry(false).or_insert_with(|| {
+                        next_id += 1;
+                        gates.push(Gate {
+                            id: next_id,
+                            gate_type: GateType::XOR,
+                            inputs: vec![source.id, source.id],
+                        });
+                        next_id
+                    });
+                    if bit {
+                        *constants.entry(true).or_insert_with(|| {
+                            next_id += 1;
+                            gates.push(Gate {
+                                id: next_id,
+                                gate_type: GateType::NOT,
+                                inputs: vec![zero],
+                            });
+                            next_id
+                        })
+                    } else {
+                        zero
+                    }
+                }
+            };
+            outputs.push(OutputInfo {
+                name: output.name.clone(),
+                id,
+            });
+        }
+
+        // Drop gates outside the cone of the outputs
+        let mut live: HashSet<WireId> = outputs.iter().map(|output| output.id).collect();
+        let mut kept: Vec<Gate> = Vec::with_capacity(gates.len());
+        for gate in gates.into_iter().rev() {
+            if live.contains(&gate.id) {
+                live.extend(&gate.inputs);
+                kept.push(gate);
+            }
+        }
+        kept.reverse();
+
+        let produced: HashSet<WireId> = kept.iter().map(|gate| gate.id).collect();
+        let mut specialized = self.clone();
+        let metadata = &mut specialized.metadata;
+        metadata.inputs = secret_inputs;
+        metadata.outputs = outputs;
+        metadata.input_buses.retain(|bus| {
+            bus.bits
+                .iter()
+                .all(|bit| !self.metadata.public_inputs.contains(bit))
+        });
+        if let Some(parties) = &mut metadata.parties {
+            parties
+                .owners
+                .retain(|input, _| !self.metadata.public_inputs.contains(input));
+        }
+        metadata.schedule.retain(|wire, _| produced.contains(wire));
+        let inputs: HashSet<WireId> = metadata.inputs.iter().map(|input| input.id).collect();
+        metadata
+            .openings
+            .retain(|wire, _| produced.contains(wire) || inputs.contains(wire));
+        metadata.public_inputs.clear();
+        specialized.gates = kept;
+        Ok(specialized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::{LocalEvaluator, PreparedCircuit};
+
+    fn outputs(circuit: &Circuit, inputs: &[bool]) -> Vec<bool> {
+        circuit
+            .metadata
+            .outputs
+            .iter()
+            .map(|info| LocalEvaluator::get_output(circuit, inputs, info.id).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_public_select_removes_and_gates() {
+        let mut mux = Circuit::from_file("circuits/mux_2to1.json").unwrap();
+        mux.metadata.public_inputs = vec!["sel".to_string()];
+
+        for sel in [false, true] {
+            let specialized = mux.specialize(&[("sel", sel)]).unwrap();
+            let prepared = PreparedCircuit::with_public(&mux, &[("sel", sel)]).unwrap();
+            assert_eq!(prepared.and_depth, 0);
+            assert_eq!(specialized.metadata.inputs.len(), 2);
+            for a in [false, true] {
+                for b in [false, true] {
+                    assert_eq!(outputs(&specialized, &[a, b]), outputs(&mux, &[a, b, sel]));
+                }
+            }
+        }
+
+        assert!(mux.specialize(&[]).is_err());
+        assert!(mux.specialize(&[("a", true), ("sel", true)]).is_err());
+    }
+
+    #[test]
+    fn test_public_outputs_become_constants() {
+        let mut adder = reference::full_adder();
+        adder.metadata.public_inputs = vec!["a".to_string(), "b".to_string()];
+
+        // a = b = 1 fixes the carry to 1 whatever cin is
+        let specialized = adder.specialize(&[("a", true), ("b", true)]).unwrap();
+        assert!(specialized
+            .gates
+            .iter()
+            .all(|gate| !gate.gate_type.is_interactive()));
+        for cin in [false, true] {
+            assert_eq!(
+                outputs(&specialized, &[cin]),
+                outputs(&adder, &[true, true, cin])
+            );
+        }
+
+        adder.metadata.public_inputs.push("cin".to_string());
+        let all = [("a", true), ("b", true), ("cin", false)];
+        assert!(adder.specialize(&all).is_err());
+    }
+}
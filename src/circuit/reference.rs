@@ -0,0 +1,137 @@
+//! Reference circuits generated with `CircuitBuilder`, so tests need no
+//! checked-in circuit files and exercise the builder on the way
+// The circuit tier's own tests use only some of them
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+
+use crate::circuit::{Bus, BusEncoding, BusInfo, Circuit, CircuitBuilder, Wire};
+
+/// Half adder over inputs `a` and `b`, with outputs `sum` and `carry`
+/// grouped into the unsigned bus `total`
+pub(crate) fn half_adder() -> Circuit {
+    let mut circuit = CircuitBuilder::build("half_adder", |b| {
+        b.description("Half-adder: adds two bits");
+        let (x, y) = (b.input("a"), b.input("b"));
+        let sum = b.xor(x, y);
+        let carry = b.and(x, y);
+        b.output("sum", sum);
+        b.output("carry", carry);
+        Ok(())
+    })
+    .expect("half adder builds");
+    circuit.metadata.buses.push(total());
+    circuit
+}
+
+/// Full adder over inputs `a`, `b` and `cin`, with outputs `sum` and
+/// `carry` grouped into the unsigned bus `total`
+/// Gates come in the order of `circuits/full_adder.json`: two half adders
+/// whose carries are ORed, so wires 4 to 8 are the gates and the AND of `a`
+/// and `b` is wire 5
+pub(crate) fn full_adder() -> Circuit {
+    let mut circuit = CircuitBuilder::build("full_adder", |b| {
+        b.description("Full-adder: adds three bits with carry");
+        let (x, y, cin) = (b.input("a"), b.input("b"), b.input("cin"));
+        let half = b.xor(x, y);
+        let first_carry = b.and(x, y);
+        let sum = b.xor(half, cin);
+        let second_carry = b.and(half, cin);
+        let carry = b.or(first_carry, second_carry);
+        b.output("sum", sum);
+        b.output("carry", carry);
+        Ok(())
+    })
+    .expect("full adder builds");
+    circuit.metadata.buses.push(total());
+    circuit
+}
+
+/// Adder and comparator of two `width`-bit inputs `a0..` and `b0..`: the
+/// unsigned bus `sum` with flag outputs `sum_carry` and `sum_overflow`, and
+/// outputs `lt` (`a < b`) and `eq` (`a == b`)
+pub(crate) fn adder_comparator(width: usize) -> Circuit {
+    CircuitBuilder::build(&format!("adder_comparator{width}"), |b| {
+        let (x, y) = (b.input_bus("a", width), b.input_bus("b", width));
+        let sum = b.add(&x, &y)?;
+        b.output_arithmetic("sum", &sum, BusEncoding::Unsigned);
+        let less = b.less_than(&x, &y)?;
+        b.output("lt", less);
+        let equal = equal(b, &x, &y);
+        b.output("eq", equal);
+        Ok(())
+    })
+    .expect("adder and comparator build")
+}
+
+/// `a == b`: the AND of every bit's XNOR
+fn equal<'id>(b: &mut CircuitBuilder<'id>, x: &Bus<'id>, y: &Bus<'id>) -> Wire<'id> {
+    let same: Vec<_> = x
+        .bits()
+        .iter()
+        .zip(y.bits())
+        .map(|(&x_i, &y_i)| {
+            let differ = b.xor(x_i, y_i);
+            b.not(differ)
+        })
+        .collect();
+    same.into_iter()
+        .reduce(|all, bit| b.and(all, bit))
+        .expect("buses are not empty")
+}
+
+/// The `total` bus of the adders
+fn total() -> BusInfo {
+    BusInfo {
+        name: "total".to_string(),
+        bits: vec!["sum".to_string(), "carry".to_string()],
+        encoding: BusEncoding::Unsigned,
+        carry: None,
+        overflow: None,
+        extend: None,
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::protocol::GmwProtocol;
+
+    /// Bits of `value`, least significant first
+    fn bits(value: u64, width: usize) -> Vec<bool> {
+        (0..width).map(|bit| value >> bit & 1 == 1).collect()
+    }
+
+    #[test]
+    fn test_reference_truth_tables() {
+        let (half, full) = (half_adder(), full_adder());
+        let width = 3;
+        let arithmetic = adder_comparator(width);
+        let range = 1u64 << width;
+
+        for parties in 2..=5 {
+            let protocol = GmwProtocol::new(parties).unwrap();
+            for inputs in 0..4 {
+                let report = protocol.run_with_report(&half, &bits(inputs, 2)).unwrap();
+                let expected = inputs.count_ones() as u64;
+                assert_eq!(report.decode::<u64>("total").unwrap(), expected);
+            }
+            for inputs in 0..8 {
+                let report = protocol.run_with_report(&full, &bits(inputs, 3)).unwrap();
+                let expected = inputs.count_ones() as u64;
+                assert_eq!(report.decode::<u64>("total").unwrap(), expected);
+            }
+            for (x, y) in (0..range).flat_map(|x| (0..range).map(move |y| (x, y))) {
+                let inputs = [bits(x, width), bits(y, width)].concat();
+                let report = protocol.run_with_report(&arithmetic, &inputs).unwrap();
+                let case = format!("{parties} parties, {x} and {y}");
+                assert_eq!(
+                    report.decode::<u64>("sum").unwrap(),
+                    (x + y) % range,
+                    "{case}"
+                );
+                assert_eq!(report.carry("sum").unwrap(), x + y >= range, "{case}");
+                assert_eq!(report.output("lt"), Some(x < y), "{case}");
+                assert_eq!(report.output("eq"), Some(x == y), "{case}");
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::circuit::{Circuit, CircuitTemplate};
+
+/// JSON file holding several named circuits
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CircuitFile {
+    pub circuits: Vec<Circuit>,
+}
+
+/// Named circuits available for selection
+#[derive(Debug, Clone, Default)]
+pub struct CircuitRegistry {
+    circuits: Vec<Circuit>,
+}
+
+impl CircuitRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a multi-circuit file, or a single-circuit file as a registry of one
+    pub fn from_file(path: &str) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Parse a multi-circuit file, or a single circuit as a registry of one
+    /// A single-circuit template is instantiated with its default parameters
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let circuits = if value.get("circuits").is_some() {
+            serde_json::from_value::<CircuitFile>(value)?.circuits
+        } else if CircuitTemplate::is_template(&value) {
+            vec![CircuitTemplate::from_value(value)?.instantiate(&HashMap::new())?]
+        } else {
+            vec![serde_json::from_value::<Circuit>(value)?]
+        };
+
+        let mut registry = Self::new();
+        for circuit in circuits {
+            registry.insert(circuit)?;
+        }
+        Ok(registry)
+    }
+
+    /// Add a circuit, rejecting duplicate names
+    pub fn insert(&mut self, circuit: Circuit) -> Result<()> {
+        if self.get(&circuit.name).is_some() {
+            return Err(anyhow::anyhow!("Duplicate circuit name: {}", circuit.name));
+        }
+        self.circuits.push(circuit);
+        Ok(())
+    }
+
+    /// Look up a circuit by name
+    pub fn get(&self, name: &str) -> Option<&Circuit> {
+        self.circuits.iter().find(|circuit| circuit.name == name)
+    }
+
+    /// Names of all circuits in file order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.circuits.iter().map(|circuit| circuit.name.as_str())
+    }
+
+    /// All circuits in file order
+    pub fn iter(&self) -> impl Iterator<Item = &Circuit> {
+        self.circuits.iter()
+    }
+
+    /// Number of circuits in the registry
+    pub fn len(&self) -> usize {
+        self.circuits.len()
+    }
+
+    /// Whether the registry holds no circuits
+    pub fn is_empty(&self) -> bool {
+        self.circuits.is_empty()
+    }
+
+    /// Select a circuit, by name if given, otherwise the only one present
+    pub fn select(&self, name: Option<&str>) -> Result<&Circuit> {
+        match name {
+            Some(name) => self.get(name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Circuit {} not found; available: {}",
+                    name,
+                    self.names().collect::<Vec<_>>().join(", ")
+                )
+            }),
+            None if self.circuits.len() == 1 => Ok(&self.circuits[0]),
+            None => Err(anyhow::anyhow!(
+                "File holds {} circuits; select one with <file>#<name> ({})",
+                self.circuits.len(),
+                self.names().collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    /// Split a `file#name` reference; paths that exist as files are not split
+    pub fn split_reference(reference: &str) -> (&str, Option<&str>) {
+        match reference.rsplit_once('#') {
+            Some((path, name)) if !Path::new(reference).exists() => (path, Some(name)),
+            _ => (reference, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch_json() -> String {
+        let half_adder = fs::read_to_string("circuits/half_adder.json").unwrap();
+        let full_adder = fs::read_to_string("circuits/full_adder.json").unwrap();
+        format!(r#"{{"circuits": [{half_adder}, {full_adder}]}}"#)
+    }
+
+    #[test]
+    fn test_registry_select() {
+        let registry = CircuitRegistry::from_json(&batch_json()).unwrap();
+        assert_eq!(registry.len(), 2);
+
+        let names: Vec<&str> = registry.names().collect();
+        let full_adder = registry.select(Some(names[1])).unwrap();
+        assert_eq!(full_adder.metadata.inputs.len(), 3);
+
+        assert!(registry.select(None).is_err());
+        assert!(registry.select(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_single_circuit_file_and_duplicates() {
+        let registry = CircuitRegistry::from_file("circuits/and.json").unwrap();
+        assert!(registry.select(None).is_ok());
+
+        let mut registry = registry;
+        let duplicate = registry.iter().next().unwrap().clone();
+        assert!(registry.insert(duplicate).is_err());
+    }
+
+    #[test]
+    fn test_split_reference() {
+        assert_eq!(
+            CircuitRegistry::split_reference("lib.json#half_adder"),
+            ("lib.json", Some("half_adder"))
+        );
+        assert_eq!(
+            CircuitRegistry::split_reference("circuits/and.json"),
+            ("circuits/and.json", None)
+        );
+    }
+}
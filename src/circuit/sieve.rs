@@ -0,0 +1,609 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::circuit::macros::constant_operand;
+use crate::circuit::{
+    Circuit, CircuitMetadata, EncodedCircuit, Gate, GateType, InputInfo, OutputInfo, WireId,
+};
+
+/// Print a circuit as a SIEVE IR 2.0 circuit over the field of two elements
+///
+/// ```text
+/// version 2.0.0;
+/// circuit;
+/// @type field 2;
+/// @begin
+///   $0 <- @private(0);
+///   $1 <- @private(0);
+///   $2 <- @mul(0: $0, $1);
+///   $3 <- @public(0);
+///   $4 <- @add(0: $2, $3);
+///   @assert_zero(0: $4);
+/// @end
+/// ```
+///
+/// XOR is `@add`, AND is `@mul`, NOT adds the constant 1 and OR becomes
+/// `a + b + a·b`. Secret inputs are private inputs in metadata order, and
+/// declared public inputs are the first public inputs. IR circuits are
+/// relations without outputs, so each output is asserted equal to the next
+/// public input: the relation holds when the outputs take those values
+pub(crate) fn to_sieve(circuit: &Circuit) -> Result<EncodedCircuit> {
+    let metadata = &circuit.metadata;
+    let mut text = String::from("version 2.0.0;\ncircuit;\n@type field 2;\n@begin\n");
+    let mut wires: HashMap<WireId, u64> = HashMap::new();
+    let mut next = 0u64;
+    let mut assign = |text: &mut String, body: &str| -> Result<u64> {
+        writeln!(text, "  ${next} <- {body};")?;
+        next += 1;
+        Ok(next - 1)
+    };
+
+    let (public, private): (Vec<&InputInfo>, Vec<&InputInfo>) = metadata
+        .inputs
+        .iter()
+        .partition(|input| metadata.public_inputs.contains(&input.name));
+    for input in private {
+        wires.insert(input.id, assign(&mut text, "@private(0)")?);
+    }
+    for input in public {
+        wires.insert(input.id, assign(&mut text, "@public(0)")?);
+    }
+
+    let wire = |wires: &HashMap<WireId, u64>, id: WireId| {
+        wires
+            .get(&id)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Wire {} is used before it is set", id))
+    };
+    for gate in &circuit.gates {
+        let operands = gate
+            .inputs
+            .iter()
+            .map(|&id| wire(&wires, id))
+            .collect::<Result<Vec<u64>>>()?;
+        let output = match (&gate.gate_type, &operands[..]) {
+            (GateType::XOR, &[a, b]) => assign(&mut text, &format!("@add(0: ${a}, ${b})"))?,
+            (GateType::AND, &[a, b]) => assign(&mut text, &format!("@mul(0: ${a}, ${b})"))?,
+            (GateType::NOT, &[a]) => assign(&mut text, &format!("@addc(0: ${a}, <1>)"))?,
+            (GateType::OR, &[a, b]) => {
+                let sum = assign(&mut text, &format!("@add(0: ${a}, ${b})"))?;
+                let product = assign(&mut text, &format!("@mul(0: ${a}, ${b})"))?;
+                assign(&mut text, &format!("@add(0: ${sum}, ${product})"))?
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Gate {} has {} inputs",
+                    gate.id,
+                    gate.inputs.len()
+                ))
+            }
+        };
+        wires.insert(gate.id, output);
+    }
+
+    for output in &metadata.outputs {
+        let value = wire(&wires, output.id)?;
+        let expected = assign(&mut text, "@public(0)")?;
+        let difference = assign(&mut text, &format!("@add(0: ${value}, ${expected})"))?;
+        writeln!(text, "  @assert_zero(0: ${difference});")?;
+    }
+    text.push_str("@end\n");
+
+    let mut warnings = vec![
+        "SIEVE IR has no circuit name, description or wire names; wires are renumbered".to_string(),
+        format!(
+            "Outputs are asserted equal to public inputs {}..{}, after the declared public inputs",
+            metadata.public_inputs.len(),
+            metadata.public_inputs.len() + metadata.outputs.len()
+        ),
+    ];
+    if !metadata.buses.is_empty() || !metadata.input_buses.is_empty() {
+        warnings.push("Buses are dropped".to_string());
+    }
+    if !metadata.constraints.is_empty() || metadata.parties.is_some() {
+        warnings.push("Input constraints and party declarations are dropped".to_string());
+    }
+    if !metadata.schedule.is_empty() || !metadata.openings.is_empty() {
+        warnings.push("Scheduling hints and opening policies are dropped".to_string());
+    }
+    Ok(EncodedCircuit {
+        bytes: text.into_bytes(),
+        warnings,
+    })
+}
+
+/// Operand of an IR gate: a wire or a field constant
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    Wire(WireId),
+    Constant(bool),
+}
+
+/// IR statement of the supported subset
+#[derive(Debug)]
+enum Statement {
+    Input {
+        output: WireId,
+        public: bool,
+    },
+    Gate {
+        output: WireId,
+        gate_type: GateType,
+        inputs: Vec<Operand>,
+    },
+    AssertZero(WireId),
+}
+
+/// Read a SIEVE IR 2.0 circuit over the field of two elements
+/// Supported are the input, `@add`, `@mul`, `@addc`, `@mulc`, copy,
+/// constant and `@assert_zero` statements; `@new` and `@delete` are
+/// ignored. Functions, plugins, conversions and other fields are rejected.
+/// Private inputs become inputs `private0`, `private1`, ..., public inputs
+/// become declared public inputs `public0`, ..., and each assertion becomes
+/// an output `assert0`, ..., which is 0 exactly when the assertion holds.
+/// Constants are folded into the gates reading them, as for Bristol
+pub(crate) fn from_sieve(text: &str) -> Result<Circuit> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+    };
+    parser.header()?;
+
+    let mut statements = Vec::new();
+    while parser.peek() != Some("@end") {
+        if let Some(statement) = parser.statement()? {
+            statements.push(statement);
+        }
+    }
+
+    let mut next_id = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::Input { output, .. } | Statement::Gate { output, .. } => Some(*output),
+            Statement::AssertZero(_) => None,
+        })
+        .max()
+        .map_or(0, |max| max + 1);
+    let mut fresh = || {
+        next_id += 1;
+        Ok(next_id - 1)
+    };
+
+    let mut metadata = CircuitMetadata::default();
+    let mut gates = Vec::new();
+    let mut aliases: HashMap<WireId, WireId> = HashMap::new();
+    let mut constants: HashMap<WireId, bool> = HashMap::new();
+    let mut assertions = Vec::new();
+    let (mut private_count, mut public_count) = (0, 0);
+    let operand = |aliases: &HashMap<WireId, WireId>,
+                   constants: &HashMap<WireId, bool>,
+                   operand: Operand| match operand {
+        Operand::Wire(wire) => match constants.get(&wire) {
+            Some(&value) => Operand::Constant(value),
+            None => Operand::Wire(aliases.get(&wire).copied().unwrap_or(wire)),
+        },
+        constant => constant,
+    };
+
+    for statement in statements {
+        let (output, gate_type, inputs) = match statement {
+            Statement::Input { output, public } => {
+                let name = if public {
+                    public_count += 1;
+                    format!("public{}", public_count - 1)
+                } else {
+                    private_count += 1;
+                    format!("private{}", private_count - 1)
+                };
+                if public {
+                    metadata.public_inputs.push(name.clone());
+                }
+                metadata.inputs.push(InputInfo { name, id: output });
+                continue;
+            }
+            Statement::AssertZero(wire) => {
+                assertions.push(operand(&aliases, &constants, Operand::Wire(wire)));
+                continue;
+            }
+            Statement::Gate {
+                output,
+                gate_type,
+                inputs,
+            } => (output, gate_type, inputs),
+        };
+        let inputs: Vec<Operand> = inputs
+            .into_iter()
+            .map(|input| operand(&aliases, &constants, input))
+            .collect();
+        match (&gate_type, &inputs[..]) {
+            // Copies and constant assignments are read as XOR with 0
+            (_, &[Operand::Constant(value)]) => {
+                constants.insert(output, value);
+            }
+            (_, &[Operand::Wire(wire)]) => {
+                aliases.insert(output, wire);
+            }
+            (_, &[Operand::Constant(x), Operand::Constant(y)]) => {
+                let value = if gate_type == GateType::XOR {
+                    x ^ y
+                } else {
+                    x & y
+                };
+                constants.insert(output, value);
+            }
+            (GateType::XOR, &[Operand::Wire(wire), Operand::Constant(false)])
+            | (GateType::AND, &[Operand::Wire(wire), Operand::Constant(true)]) => {
+                aliases.insert(output, wire);
+            }
+            (_, &[Operand::Wire(wire), Operand::Constant(constant)])
+            | (_, &[Operand::Constant(constant), Operand::Wire(wire)]) => {
+                gates.extend(constant_operand(
+                    &gate_type, wire, constant, output, &mut fresh,
+                )?);
+            }
+            (_, &[Operand::Wire(a), Operand::Wire(b)]) => gates.push(Gate {
+                id: output,
+                gate_type,
+                inputs: vec![a, b],
+            }),
+            _ => return Err(anyhow::anyhow!("Malformed gate for ${}", output)),
+        }
+    }
+
+    // Constant assertions read a constant derived from the first input:
+    // x ^ x is 0, and its NOT is 1
+    let first_input = metadata.inputs.first().map(|input| input.id);
+    let mut constant_wires: HashMap<bool, WireId> = HashMap::new();
+    for (index, assertion) in assertions.into_iter().enumerate() {
+        let id = match assertion {
+            Operand::Wire(wire) => wire,
+            Operand::Constant(value) => {
+                if let Some(&wire) = constant_wires.get(&value) {
+                    wire
+                } else {
+                    let input = first_input.ok_or_else(|| {
+                        anyhow::anyhow!("SIEVE IR circuit without inputs asserts a constant")
+                    })?;
+                    let zero = match constant_wires.get(&false) {
+                        Some(&zero) => zero,
+                        None => {
+                            let zero = fresh()?;
+                            gates.push(Gate {
+                                id: zero,
+                                gate_type: GateType::XOR,
+                                inputs: vec![input, input],
+                            });
+                            constant_wires.insert(false, zero);
+                            zero
+                        }
+                    };
+                    if value {
+                        let one = fresh()?;
+                        gates.push(Gate {
+                            id: one,
+                            gate_type: GateType::NOT,
+                            inputs: vec![zero],
+                        });
+                        constant_wires.insert(true, one);
+                        one
+                    } else {
+                        zero
+                    }
+                }
+            }
+        };
+        metadata.outputs.push(OutputInfo {
+            name: format!("assert{index}"),
+            id,
+        });
+    }
+
+    Ok(Circuit {
+        name: "sieve".to_string(),
+        description: "Imported from SIEVE IR".to_string(),
+        gates,
+        metadata,
+    })
+}
+
+/// Split IR text into words and punctuation, dropping `//` and `/* */`
+/// comments
+fn tokenize(text: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => previous = c,
+                        None => return Err(anyhow::anyhow!("Unterminated SIEVE IR comment")),
+                    }
+                }
+            }
+            '<' if chars.peek() == Some(&'-') => {
+                chars.next();
+                tokens.push("<-".to_string());
+            }
+            '(' | ')' | ',' | ';' | ':' | '<' | '>' => tokens.push(c.to_string()),
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"(),;:<>/".contains(*c))
+                {
+                    word.push(c);
+                }
+                tokens.push(word);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<&str> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or_else(|| anyhow::anyhow!("SIEVE IR circuit ends early; missing @end"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<()> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(anyhow::anyhow!(
+                "Expected `{}` in SIEVE IR, found `{}`",
+                expected,
+                token
+            )),
+        }
+    }
+
+    /// `version 2.x.y; circuit; @type field 2; @begin`
+    fn header(&mut self) -> Result<()> {
+        self.expect("version")?;
+        let version = self.next()?.to_string();
+        if !version.starts_with("2.") {
+            return Err(anyhow::anyhow!(
+                "Unsupported SIEVE IR version {}; only 2.x is read",
+                version
+            ));
+        }
+        self.expect(";")?;
+        self.expect("circuit")?;
+        self.expect(";")?;
+        self.expect("@type")?;
+        self.expect("field")?;
+        let modulus = self.next()?.to_string();
+        if modulus != "2" {
+            return Err(anyhow::anyhow!(
+                "Unsupported SIEVE IR field {}; only boolean circuits (field 2) are read",
+                modulus
+            ));
+        }
+        self.expect(";")?;
+        match self.next()? {
+            "@begin" => Ok(()),
+            token => Err(anyhow::anyhow!(
+                "Unsupported SIEVE IR declaration {}; only one field 2 type is read",
+                token
+            )),
+        }
+    }
+
+    /// Skip an optional `<type> :` prefix; every wire has type 0
+    fn type_index(&mut self) -> Result<()> {
+        if self.tokens.get(self.position + 1).map(String::as_str) == Some(":") {
+            let index = self.next()?.to_string();
+            if index != "0" {
+                return Err(anyhow::anyhow!("Unknown SIEVE IR type {}", index));
+            }
+            self.next()?;
+        }
+        Ok(())
+    }
+
+    fn wire(&mut self) -> Result<WireId> {
+        let token = self.next()?;
+        token
+            .strip_prefix('$')
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("Expected a SIEVE IR wire, found `{}`", token))
+    }
+
+    /// `< c >`, reduced modulo 2
+    fn constant(&mut self) -> Result<bool> {
+        self.expect("<")?;
+        let token = self.next()?;
+        let value = field_element(token)?;
+        self.expect(">")?;
+        Ok(value)
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        self.type_index()?;
+        if self.peek() == Some("<") {
+            Ok(Operand::Constant(self.constant()?))
+        } else {
+            Ok(Operand::Wire(self.wire()?))
+        }
+    }
+
+    /// One statement; `None` for the ignored memory directives
+    fn statement(&mut self) -> Result<Option<Statement>> {
+        let first = self.next()?.to_string();
+        match first.as_str() {
+            "@new" | "@delete" => {
+                while self.next()? != ";" {}
+                return Ok(None);
+            }
+            "@assert_zero" => {
+                self.expect("(")?;
+                self.type_index()?;
+                let wire = self.wire()?;
+                self.expect(")")?;
+                self.expect(";")?;
+                return Ok(Some(Statement::AssertZero(wire)));
+            }
+            _ => {}
+        }
+
+        self.position -= 1;
+        let output = self.wire()?;
+        self.expect("<-")?;
+        let statement = match self.peek() {
+            Some(name @ ("@private" | "@public")) => {
+                let public = name == "@public";
+                self.next()?;
+                self.expect("(")?;
+                if self.peek() != Some(")") {
+                    let index = self.next()?.to_string();
+                    if index != "0" {
+                        return Err(anyhow::anyhow!("Unknown SIEVE IR type {}", index));
+                    }
+                }
+                self.expect(")")?;
+                Statement::Input { output, public }
+            }
+            Some(name @ ("@add" | "@mul" | "@addc" | "@mulc")) => {
+                let gate_type = if name.starts_with("@add") {
+                    GateType::XOR
+                } else {
+                    GateType::AND
+                };
+                self.next()?;
+                self.expect("(")?;
+                let a = self.operand()?;
+                self.expect(",")?;
+                let b = self.operand()?;
+                self.expect(")")?;
+                Statement::Gate {
+                    output,
+                    gate_type,
+                    inputs: vec![a, b],
+                }
+            }
+            Some(name) if name.starts_with('@') => {
+                return Err(anyhow::anyhow!("Unsupported SIEVE IR gate {}", name))
+            }
+            _ => Statement::Gate {
+                output,
+                gate_type: GateType::XOR,
+                inputs: vec![self.operand()?],
+            },
+        };
+        self.expect(";")?;
+        Ok(Some(statement))
+    }
+}
+
+/// Field element of a constant, in decimal or `0x` hexadecimal, modulo 2
+fn field_element(token: &str) -> Result<bool> {
+    let digits = token.strip_prefix("0x").unwrap_or(token);
+    let radix = if digits.len() < token.len() { 16 } else { 10 };
+    let last = digits
+        .chars()
+        .last()
+        .and_then(|digit| digit.to_digit(radix))
+        .filter(|_| digits.chars().all(|digit| digit.is_digit(radix)))
+        .ok_or_else(|| anyhow::anyhow!("Invalid SIEVE IR constant {}", token))?;
+    // Both radixes are even, so the parity is that of the last digit
+    Ok(last % 2 == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::circuit::{Circuit, CircuitFormat, LocalEvaluator};
+
+    fn outputs(circuit: &Circuit, inputs: &[bool]) -> Vec<bool> {
+        circuit
+            .metadata
+            .outputs
+            .iter()
+            .map(|info| LocalEvaluator::get_output(circuit, inputs, info.id).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_sieve_round_trip_asserts_outputs() {
+        for file in ["circuits/full_adder.json", "circuits/mux_2to1.json"] {
+            let circuit = Circuit::from_file(file).unwrap();
+            let encoded = circuit.encode(CircuitFormat::Sieve).unwrap();
+            assert!(!encoded.warnings.is_empty());
+            let decoded = Circuit::decode(&encoded.bytes, CircuitFormat::Sieve).unwrap();
+
+            // Inputs, then one public input per output holding its claimed value
+            let output_count = circuit.metadata.outputs.len();
+            assert_eq!(
+                decoded.metadata.public_inputs,
+                ["public0", "public1"][..output_count]
+            );
+            for input in 0..8u8 {
+                let inputs: Vec<bool> = (0..3).map(|bit| input >> bit & 1 == 1).collect();
+                let expected = outputs(&circuit, &inputs);
+                for claim in 0..1u8 << output_count {
+                    let claimed: Vec<bool> =
+                        (0..output_count).map(|bit| claim >> bit & 1 == 1).collect();
+                    let all: Vec<bool> = inputs.iter().chain(&claimed).copied().collect();
+                    let differences: Vec<bool> =
+                        expected.iter().zip(&claimed).map(|(x, y)| x ^ y).collect();
+                    assert_eq!(outputs(&decoded, &all), differences);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sieve_import() {
+        let text = "version 2.0.0;\ncircuit;\n@type field 2;\n@begin\n\
+                    // inputs\n  $0 <- @private();\n  $1 <- @public(0);\n\
+                    @new(0: $2 ... $8);\n  $2 <- <1>;\n  $3 <- @mul(0: $0, $2); /* x & 1 */\n\
+                    $4 <- @mulc($1, <0x2>);\n  $5 <- @addc(0: $3, <1>);\n  $6 <- $5;\n\
+                    $7 <- @add($6, $1);\n  $8 <- @mul($0, $1);\n\
+                    @assert_zero(0: $7);\n  @assert_zero($4);\n  @assert_zero($8);\n\
+                    @delete(0: $0, $8);\n@end\n";
+        let circuit = Circuit::decode(text.as_bytes(), CircuitFormat::Sieve).unwrap();
+        assert_eq!(circuit.metadata.public_inputs, ["public0"]);
+        // Constants cost no OT: only $0 · $1 is interactive
+        let interactive = circuit
+            .gates
+            .iter()
+            .filter(|gate| gate.gate_type.is_interactive())
+            .count();
+        assert_eq!(interactive, 1);
+        for (x, y) in [(false, false), (false, true), (true, false), (true, true)] {
+            assert_eq!(outputs(&circuit, &[x, y]), [!x ^ y, false, x & y]);
+        }
+
+        for bad in [
+            "version 2.0.0; circuit; @type field 7; @begin @end",
+            "version 2.0.0; circuit; @type field 2; @type field 3; @begin @end",
+            "version 1.0.0; circuit; @type field 2; @begin @end",
+            "version 2.0.0; circuit; @type field 2; @begin $0 <- @private(0); $1 <- @call(f, $0);",
+            "version 2.0.0; circuit; @type field 2; @begin $0 <- @private(0);",
+        ] {
+            assert!(
+                Circuit::decode(bad.as_bytes(), CircuitFormat::Sieve).is_err(),
+                "{bad}"
+            );
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use crate::circuit::Circuit;
+
+/// Largest number of items a single `for` loop may expand to
+const MAX_LOOP_ITEMS: i64 = 1 << 20;
+
+/// Parameterized circuit JSON describing a family of circuits
+///
+/// A template is circuit JSON with a top-level `params` object mapping each
+/// parameter to its default (or `null` if it must be given). Inside:
+/// - a string containing `$` but no `${` is an integer expression, e.g.
+///   `"$N"` or `"100 + 2 * $i"`, with `+ - * / %` and parentheses
+/// - `${expr}` inside any other string is replaced by the expression's value,
+///   e.g. `"a${i}"`; the `$` before variable names is optional inside braces
+/// - an array element `{"for": "i", "from": 0, "to": "$N", "each": ...}`
+///   expands `each` once per `i` in `from..to`; arrays produced by `each`
+///   are spliced into the enclosing array
+#[derive(Debug, Clone)]
+pub struct CircuitTemplate {
+    params: BTreeMap<String, Option<i64>>,
+    body: Value,
+}
+
+impl CircuitTemplate {
+    /// Whether parsed JSON is a template rather than a plain circuit
+    pub fn is_template(value: &Value) -> bool {
+        value.get("params").is_some()
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    pub fn from_value(mut value: Value) -> Result<Self> {
+        let params = value
+            .as_object_mut()
+            .and_then(|object| object.remove("params"))
+            .ok_or_else(|| anyhow::anyhow!("Template has no params object"))?;
+        let params = params
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("Template params must be an object"))?
+            .iter()
+            .map(|(name, default)| match default {
+                Value::Null => Ok((name.clone(), None)),
+                _ => default
+                    .as_i64()
+                    .map(|default| (name.clone(), Some(default)))
+                    .ok_or_else(|| anyhow::anyhow!("Default of {} is not an integer", name)),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            params,
+            body: value,
+        })
+    }
+
+    /// Declared parameters and their defaults
+    pub fn params(&self) -> &BTreeMap<String, Option<i64>> {
+        &self.params
+    }
+
+    /// Expand the template with the given parameters, falling back to defaults
+    pub fn instantiate(&self, params: &HashMap<String, i64>) -> Result<Circuit> {
+        if let Some(unknown) = params.keys().find(|name| !self.params.contains_key(*name)) {
+            return Err(anyhow::anyhow!("Unknown template parameter {}", unknown));
+        }
+
+        let env = self
+            .params
+            .iter()
+            .map(|(name, default)| {
+                params
+                    .get(name)
+                    .copied()
+                    .or(*default)
+                    .map(|value| (name.clone(), value))
+                    .ok_or_else(|| anyhow::anyhow!("Missing template parameter {}", name))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(serde_json::from_value(expand(&self.body, &env)?)?)
+    }
+}
+
+fn expand(value: &Value, env: &HashMap<String, i64>) -> Result<Value> {
+    Ok(match value {
+        Value::String(text) if text.contains("${") => Value::String(interpolate(text, env)?),
+        Value::String(text) if text.contains('$') => Value::from(evaluate(text, env)?),
+        Value::Array(items) => {
+            let mut expanded = Vec::with_capacity(items.len());
+            for item in items {
+                match item.get("for") {
+                    Some(_) => expand_loop(item, env, &mut expanded)?,
+                    None => expanded.push(expand(item, env)?),
+                }
+            }
+            Value::Array(expanded)
+        }
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), expand(value, env)?)))
+                .collect::<Result<Map<_, _>>>()?,
+        ),
+        _ => value.clone(),
+    })
+}
+
+fn expand_loop(item: &Value, env: &HashMap<String, i64>, out: &mut Vec<Value>) -> Result<()> {
+    let var = item["for"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Loop variable must be a string"))?;
+    let bound = |key: &str, default: i64| -> Result<i64> {
+        match item.get(key) {
+            None => Ok(default),
+            Some(Value::String(text)) => evaluate(text, env),
+            Some(value) => value
+                .as_i64()
+                .ok_or_else(|| anyhow::anyhow!("Loop bound {} is not an integer", key)),
+        }
+    };
+    let (from, to) = (bound("from", 0)?, bound("to", 0)?);
+    if to.saturating_sub(from) > MAX_LOOP_ITEMS {
+        return Err(anyhow::anyhow!(
+            "Loop over {} expands to more than {} items",
+            var,
+            MAX_LOOP_ITEMS
+        ));
+    }
+    let each = item
+        .get("each")
+        .ok_or_else(|| anyhow::anyhow!("Loop over {} has no each", var))?;
+
+    let mut env = env.clone();
+    for i in from..to {
+        env.insert(var.to_string(), i);
+        match expand(each, &env)? {
+            Value::Array(items) => out.extend(items),
+            value => out.push(value),
+        }
+    }
+    Ok(())
+}
+
+/// Replace every `${expr}` in a string with its value
+fn interpolate(text: &str, env: &HashMap<String, i64>) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow::anyhow!("Unclosed ${{ in {:?}", text))?;
+        out.push_str(&rest[..start]);
+        out.push_str(&evaluate(&rest[start + 2..start + end], env)?.to_string());
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Evaluate an integer expression over `$name` variables
+fn evaluate(text: &str, env: &HashMap<String, i64>) -> Result<i64> {
+    let mut parser = Parser {
+        chars: text.chars().filter(|c| !c.is_whitespace()).collect(),
+        pos: 0,
+        env,
+    };
+    let value = parser.sum()?;
+    if parser.pos != parser.chars.len() {
+        return Err(anyhow::anyhow!("Unexpected input in expression {:?}", text));
+    }
+    Ok(value)
+}
+
+/// Recursive-descent parser: sum := product (('+'|'-') product)*,
+/// product := atom (('*'|'/'|'%') atom)*, atom := number | [$]name | -atom | (sum)
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    env: &'a HashMap<String, i64>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn sum(&mut self) -> Result<i64> {
+        let mut value = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.pos += 1;
+            let rhs = self.product()?;
+            value = if op == '+' {
+                value.checked_add(rhs)
+            } else {
+                value.checked_sub(rhs)
+            }
+            .ok_or_else(|| anyhow::anyhow!("Expression overflows"))?;
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<i64> {
+        let mut value = self.atom()?;
+        while let Some(op @ ('*' | '/' | '%')) = self.peek() {
+            self.pos += 1;
+            let rhs = self.atom()?;
+            value = match op {
+                '*' => value.checked_mul(rhs),
+                '/' => value.checked_div(rhs),
+                _ => value.checked_rem(rhs),
+            }
+            .ok_or_else(|| anyhow::anyhow!("Expression overflows or divides by zero"))?;
+        }
+        Ok(value)
+    }
+
+    fn atom(&mut self) -> Result<i64> {
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                self.atom()?
+                    .checked_neg()
+                    .ok_or_else(|| anyhow::anyhow!("Expression overflows"))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let value = self.sum()?;
+                if self.peek() != Some(')') {
+                    return Err(anyhow::anyhow!("Missing ) in expression"));
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(c) if c == '$' || c.is_ascii_alphabetic() || c == '_' => {
+                self.pos += usize::from(c == '$');
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                self.env
+                    .get(&name)
+                    .copied()
+                    .ok_or_else(|| anyhow::anyhow!("Unknown variable ${}", name))
+            }
+            Some(c) if c.is_ascii_digit() => Ok(self.take_while(|c| c.is_ascii_digit()).parse()?),
+            other => Err(anyhow::anyhow!("Unexpected {:?} in expression", other)),
+        }
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(&accept) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::{circuit::LocalEvaluator, protocol::GmwProtocol};
+
+    #[test]
+    fn test_expressions() {
+        let env = HashMap::from([("N".to_string(), 4), ("i".to_string(), 2)]);
+        assert_eq!(evaluate("$N", &env).unwrap(), 4);
+        assert_eq!(evaluate("100 + 2 * $i - 1", &env).unwrap(), 103);
+        assert_eq!(evaluate("($N - 1) % 3", &env).unwrap(), 0);
+        assert_eq!(interpolate("s${i + 1}_${$N}", &env).unwrap(), "s3_4");
+        assert!(evaluate("$M", &env).is_err());
+        assert!(evaluate("1 / 0", &env).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ripple_adder_family() {
+        let template = CircuitTemplate::from_file("circuits/ripple_adder.json").unwrap();
+        let protocol = GmwProtocol::new(2).unwrap();
+
+        for width in 1..=4i64 {
+            let params = HashMap::from([("N".to_string(), width)]);
+            let circuit = template.instantiate(&params).unwrap();
+            assert_eq!(circuit.metadata.inputs.len(), 2 * width as usize);
+
+            for (a, b) in [(0u64, 0u64), (1, 1), ((1 << width) - 1, 1), (2, 3)] {
+                let (a, b) = (a % (1 << width), b % (1 << width));
+                let inputs: Vec<bool> = (0..width)
+                    .map(|i| a >> i & 1 == 1)
+                    .chain((0..width).map(|i| b >> i & 1 == 1))
+                    .collect();
+                let report = protocol.run_with_report(&circuit, &inputs).unwrap();
+                assert_eq!(report.decode::<u64>("sum").unwrap(), a + b, "{a} + {b}");
+                assert!(LocalEvaluator::evaluate(&circuit, &inputs).is_ok());
+            }
+        }
+
+        assert!(template
+            .instantiate(&HashMap::from([("M".to_string(), 1)]))
+            .is_err());
+    }
+}
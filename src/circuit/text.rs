@@ -0,0 +1,322 @@
+use anyhow::Result;
+use std::fmt::Write;
+
+use crate::circuit::{
+    BusEncoding, BusExtension, BusInfo, Circuit, CircuitMetadata, EncodedCircuit, Gate, GateType,
+    InputInfo, OutputInfo, WireId,
+};
+
+/// Print a circuit in the line-based text format
+/// One declaration per line, `#` starts a comment:
+///
+/// ```text
+/// circuit half_adder "Adds two bits"
+/// input a g1
+/// input b g2
+/// g3 = XOR g1 g2
+/// g4 = AND g1 g2
+/// output sum g3
+/// output carry g4
+/// bus total unsigned sum carry
+/// ```
+///
+/// Buses take `carry=`, `overflow=` and `extend=` options, and input buses
+/// are declared with `input_bus` in the same form. Party declarations,
+/// public inputs, constraints, scheduling hints and opening policies have no
+/// syntax and are dropped with a warning
+pub(crate) fn to_text(circuit: &Circuit) -> Result<EncodedCircuit> {
+    let metadata = &circuit.metadata;
+    let mut text = format!(
+        "circuit {} {}\n",
+        token(&circuit.name)?,
+        serde_json::to_string(&circuit.description)?
+    );
+    for input in &metadata.inputs {
+        writeln!(text, "input {} g{}", token(&input.name)?, input.id)?;
+    }
+    for gate in &circuit.gates {
+        let operands: Vec<String> = gate.inputs.iter().map(|wire| format!("g{wire}")).collect();
+        writeln!(
+            text,
+            "g{} = {:?} {}",
+            gate.id,
+            gate.gate_type,
+            operands.join(" ")
+        )?;
+    }
+    for output in &metadata.outputs {
+        writeln!(text, "output {} g{}", token(&output.name)?, output.id)?;
+    }
+    for (keyword, bus) in (metadata.buses.iter().map(|bus| ("bus", bus)))
+        .chain(metadata.input_buses.iter().map(|bus| ("input_bus", bus)))
+    {
+        write!(
+            text,
+            "{keyword} {} {}",
+            token(&bus.name)?,
+            encoding(bus.encoding)
+        )?;
+        for bit in &bus.bits {
+            write!(text, " {}", token(bit)?)?;
+        }
+        if let Some(carry) = &bus.carry {
+            write!(text, " carry={}", token(carry)?)?;
+        }
+        if let Some(overflow) = &bus.overflow {
+            write!(text, " overflow={}", token(overflow)?)?;
+        }
+        match bus.extend {
+            Some(BusExtension::Zero) => text.push_str(" extend=zero"),
+            Some(BusExtension::Sign) => text.push_str(" extend=sign"),
+            None => {}
+        }
+        text.push('\n');
+    }
+
+    let mut warnings = Vec::new();
+    if metadata.parties.is_some() {
+        warnings.push("party declaration dropped".to_string());
+    }
+    if !metadata.public_inputs.is_empty() {
+        warnings.push(format!(
+            "{} public input declarations dropped",
+            metadata.public_inputs.len()
+        ));
+    }
+    if !metadata.constraints.is_empty() {
+        warnings.push(format!(
+            "{} input constraints dropped",
+            metadata.constraints.len()
+        ));
+    }
+    if !metadata.schedule.is_empty() {
+        warnings.push(format!(
+            "{} scheduling hints dropped",
+            metadata.schedule.len()
+        ));
+    }
+    if !metadata.openings.is_empty() {
+        warnings.push(format!(
+            "{} opening policies dropped; those wires can no longer be opened",
+            metadata.openings.len()
+        ));
+    }
+
+    Ok(EncodedCircuit {
+        bytes: text.into_bytes(),
+        warnings,
+    })
+}
+
+/// Parse the text format written by `to_text`
+/// Declarations may come in any order, except that gates keep theirs; a
+/// missing `circuit` line leaves the name `text` and no description
+pub(crate) fn from_text(text: &str) -> Result<Circuit> {
+    let mut circuit = Circuit {
+        name: "text".to_string(),
+        description: String::new(),
+        gates: Vec::new(),
+        metadata: CircuitMetadata::default(),
+    };
+    let mut named = false;
+    for (number, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        parse_line(&mut circuit, &mut named, line)
+            .map_err(|error| anyhow::anyhow!("Line {}: {}", number + 1, error))?;
+    }
+    Ok(circuit)
+}
+
+fn parse_line(circuit: &mut Circuit, named: &mut bool, line: &str) -> Result<()> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let metadata = &mut circuit.metadata;
+    match tokens.as_slice() {
+        ["circuit", name, ..] => {
+            if std::mem::replace(named, true) {
+                return Err(anyhow::anyhow!("Circuit is named twice"));
+            }
+            circuit.name = name.to_string();
+            let description = line["circuit".len()..].trim_start()[name.len()..].trim();
+            if !description.is_empty() {
+                circuit.description = serde_json::from_str(description)
+                    .map_err(|_| anyhow::anyhow!("Description must be a quoted string"))?;
+            }
+        }
+        ["input", name, wire] => metadata.inputs.push(InputInfo {
+            name: name.to_string(),
+            id: wire_ref(wire)?,
+        }),
+        ["output", name, wire] => metadata.outputs.push(OutputInfo {
+            name: name.to_string(),
+            id: wire_ref(wire)?,
+        }),
+        [keyword @ ("bus" | "input_bus"), name, encoding_name, rest @ ..] => {
+            let mut bus = BusInfo {
+                name: name.to_string(),
+                bits: Vec::new(),
+                encoding: parse_encoding(encoding_name)?,
+                carry: None,
+                overflow: None,
+                extend: None,
+            };
+            for item in rest {
+                match item.split_once('=') {
+                    None => bus.bits.push(item.to_string()),
+                    Some(("carry", carry)) => bus.carry = Some(carry.to_string()),
+                    Some(("overflow", overflow)) => bus.overflow = Some(overflow.to_string()),
+                    Some(("extend", "zero")) => bus.extend = Some(BusExtension::Zero),
+                    Some(("extend", "sign")) => bus.extend = Some(BusExtension::Sign),
+                    Some(_) => return Err(anyhow::anyhow!("Unknown bus option {}", item)),
+                }
+            }
+            if *keyword == "bus" {
+                metadata.buses.push(bus);
+            } else {
+                metadata.input_buses.push(bus);
+            }
+        }
+        [output, "=", gate_type, operands @ ..] => {
+            let (gate_type, arity) = match *gate_type {
+                "XOR" => (GateType::XOR, 2),
+                "AND" => (GateType::AND, 2),
+                "OR" => (GateType::OR, 2),
+                "NOT" => (GateType::NOT, 1),
+                _ => return Err(anyhow::anyhow!("Unknown gate type {}", gate_type)),
+            };
+            if operands.len() != arity {
+                return Err(anyhow::anyhow!(
+                    "{:?} takes {} inputs, got {}",
+                    gate_type,
+                    arity,
+                    operands.len()
+                ));
+            }
+            circuit.gates.push(Gate {
+                id: wire_ref(output)?,
+                gate_type,
+                inputs: operands
+                    .iter()
+                    .map(|operand| wire_ref(operand))
+                    .collect::<Result<_>>()?,
+            });
+        }
+        _ => return Err(anyhow::anyhow!("Cannot parse `{}`", line)),
+    }
+    Ok(())
+}
+
+/// `line` up to a `#` outside the quoted description
+fn strip_comment(line: &str) -> &str {
+    let (mut quoted, mut escaped) = (false, false);
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Wire ID from a `g<id>` reference
+fn wire_ref(token: &str) -> Result<WireId> {
+    token
+        .strip_prefix('g')
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("Expected a wire like g5, got {}", token))
+}
+
+/// A name as a single token, rejecting names the parser would split
+fn token(name: &str) -> Result<&str> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '#' || c == '=') {
+        return Err(anyhow::anyhow!(
+            "Name {:?} cannot be written in the text format",
+            name
+        ));
+    }
+    Ok(name)
+}
+
+fn encoding(encoding: BusEncoding) -> String {
+    match encoding {
+        BusEncoding::Unsigned => "unsigned".to_string(),
+        BusEncoding::SignMagnitude => "sign_magnitude".to_string(),
+        BusEncoding::TwosComplement => "twos_complement".to_string(),
+        BusEncoding::OneHot => "one_hot".to_string(),
+        BusEncoding::FixedPoint { frac_bits } => format!("fixed_point:{frac_bits}"),
+    }
+}
+
+fn parse_encoding(name: &str) -> Result<BusEncoding> {
+    match name {
+        "unsigned" => Ok(BusEncoding::Unsigned),
+        "sign_magnitude" => Ok(BusEncoding::SignMagnitude),
+        "twos_complement" => Ok(BusEncoding::TwosComplement),
+        "one_hot" => Ok(BusEncoding::OneHot),
+        _ => name
+            .strip_prefix("fixed_point:")
+            .and_then(|bits| bits.parse().ok())
+            .map(|frac_bits| BusEncoding::FixedPoint { frac_bits })
+            .ok_or_else(|| anyhow::anyhow!("Unknown bus encoding {}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::CircuitFormat;
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut circuit = reference::full_adder();
+        circuit.description = "Adds \"#3\" bits # not a comment".to_string();
+        let encoded = circuit.encode(CircuitFormat::Text).unwrap();
+        assert!(encoded.warnings.is_empty());
+        let text = String::from_utf8(encoded.bytes).unwrap();
+        assert!(text.contains("g5 = AND g1 g2\n"));
+        assert!(text.contains("bus total unsigned sum carry\n"));
+
+        let decoded = Circuit::decode(text.as_bytes(), CircuitFormat::Text).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&circuit).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_text_by_hand() {
+        let text = "\
+            # Half adder, written by hand\n\
+            input x g1\n\
+            input y g2\n\
+            g3 = XOR g1 g2   # sum\n\
+            g4 = AND g1 g2\n\
+            output s g3\n\
+            output c g4\n\
+            bus v twos_complement s c overflow=c\n";
+        let circuit = Circuit::decode(text.as_bytes(), CircuitFormat::Text).unwrap();
+        assert_eq!(circuit.name, "text");
+        assert_eq!(circuit.gates.len(), 2);
+        assert_eq!(circuit.metadata.buses[0].overflow.as_deref(), Some("c"));
+
+        for (bad, message) in [
+            ("g3 = AND g1", "takes 2 inputs"),
+            ("g3 = NAND g1 g2", "Unknown gate type"),
+            ("input x 1", "Expected a wire"),
+            ("bus v decimal s", "Unknown bus encoding"),
+            (
+                "circuit a \"x\"\ncircuit b",
+                "Line 2: Circuit is named twice",
+            ),
+        ] {
+            let error = Circuit::decode(bad.as_bytes(), CircuitFormat::Text).unwrap_err();
+            assert!(error.to_string().contains(message), "{bad}: {error}");
+        }
+    }
+}
@@ -1,10 +1,18 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 
-pub type WireId = u32;
+pub use crate::kernel::GateType;
 
+use crate::circuit::macros::CircuitSource;
+
+pub type WireId = u64;
+
+/// Boolean circuit; macro gates in JSON are expanded to primitives on load
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "CircuitSource")]
 pub struct Circuit {
     pub name: String,
     pub description: String,
@@ -34,18 +42,99 @@ pub struct Gate {
     pub inputs: Vec<WireId>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum GateType {
-    XOR,
-    NOT,
-    AND,
-    OR,
-}
-
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CircuitMetadata {
     pub inputs: Vec<InputInfo>,
     pub outputs: Vec<OutputInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buses: Vec<BusInfo>,
+    /// Multi-bit inputs with the encoding their values are given in;
+    /// `bits` name inputs rather than outputs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_buses: Vec<BusInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<InputConstraint>,
+    /// Party structure the circuit was written for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parties: Option<PartySpec>,
+    /// Inputs known to every party, fixed before evaluation with
+    /// `Circuit::specialize`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub public_inputs: Vec<String>,
+    /// Scheduling hints keyed by the output wire of the gate they tune
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schedule: BTreeMap<WireId, ScheduleHint>,
+    /// Parties allowed to learn each intermediate wire opened with
+    /// `GmwProtocol::open_wires`; wires not listed cannot be opened
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub openings: BTreeMap<WireId, Vec<usize>>,
+}
+
+/// Hand-tuning of where one gate is evaluated, e.g. from an external compiler
+/// A gate is never placed before its inputs are available, so hints can
+/// only delay or reorder it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleHint {
+    /// Interactive layer (round) to evaluate the gate in at the earliest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<usize>,
+    /// Gates of a group are moved to the layer of the group's deepest gate,
+    /// so their OTs share one round
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Gates of the same layer run in descending priority
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub priority: u32,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Number of parties a circuit is meant to run with and which party
+/// provides each input
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartySpec {
+    pub count: usize,
+    /// Input name to owning party; inputs not listed may come from any party
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub owners: BTreeMap<String, usize>,
+}
+
+impl PartySpec {
+    /// Check the count and that every owner names an input of `metadata`
+    /// and a party below the count
+    pub fn validate(&self, metadata: &CircuitMetadata) -> Result<()> {
+        if self.count < 2 {
+            return Err(anyhow::anyhow!(
+                "Circuit declares {} parties; at least 2 are needed",
+                self.count
+            ));
+        }
+        for (input, &party) in &self.owners {
+            if !metadata.inputs.iter().any(|info| &info.name == input) {
+                return Err(anyhow::anyhow!("Owned input {} not found", input));
+            }
+            if party >= self.count {
+                return Err(anyhow::anyhow!(
+                    "Input {} is owned by party {} of only {}",
+                    input,
+                    party,
+                    self.count
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Inputs provided by `party`, in name order
+    pub fn inputs_of(&self, party: usize) -> Vec<&str> {
+        self.owners
+            .iter()
+            .filter(|&(_, &owner)| owner == party)
+            .map(|(input, _)| input.as_str())
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,3 +148,75 @@ pub struct OutputInfo {
     pub name: String,
     pub id: WireId,
 }
+
+/// Multi-bit output formed from named single-bit outputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusInfo {
+    pub name: String,
+    /// Output names forming the bus, least significant bit first
+    pub bits: Vec<String>,
+    #[serde(default)]
+    pub encoding: BusEncoding,
+    /// Output holding the carry-out of an addition or the borrow of a
+    /// subtraction, i.e. unsigned wraparound
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub carry: Option<String>,
+    /// Output holding the two's-complement overflow flag, i.e. signed wraparound
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overflow: Option<String>,
+    /// How `CircuitBuilder::call` widens a narrower bus wired to this input
+    /// bus; without it a width mismatch is an error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extend: Option<BusExtension>,
+}
+
+/// Widening of a bus to more bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BusExtension {
+    /// New high bits are 0, keeping unsigned values
+    Zero,
+    /// New high bits repeat the top bit, keeping two's-complement values
+    Sign,
+}
+
+/// How the bits of a bus are interpreted as a value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BusEncoding {
+    #[default]
+    Unsigned,
+    /// Most significant bit is the sign, remaining bits are the magnitude
+    SignMagnitude,
+    TwosComplement,
+    /// Exactly one bit is set; the value is its index, e.g. an enum variant
+    OneHot,
+    /// Signed Q-format: two's complement scaled by 2^-`frac_bits`
+    FixedPoint {
+        frac_bits: u8,
+    },
+}
+
+/// Constraint on private inputs, checked in MPC before any output is revealed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum InputConstraint {
+    /// The unsigned value of the inputs (least significant bit first) is below `bound`
+    LessThan { bits: Vec<String>, bound: u64 },
+    /// Exactly one of the inputs is set
+    OneHot { bits: Vec<String> },
+}
+
+impl fmt::Display for InputConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputConstraint::LessThan { bits, bound } => {
+                write!(f, "[{}] < {}", bits.join(", "), bound)
+            }
+            InputConstraint::OneHot { bits } => write!(f, "one_hot([{}])", bits.join(", ")),
+        }
+    }
+}
@@ -0,0 +1,804 @@
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::circuit::{Circuit, PreparedCircuit};
+use crate::errors::ErrorCode;
+use crate::gates::{
+    ot_sender, CrossTermReceiver, CrossTermRequest, CrossTermResponse, CrossTermSender,
+};
+use crate::kernel::algebra::{reconstruct, split_shares};
+use crate::kernel::{PartyEngine, Step};
+use crate::ot::{
+    ExtensionReceiver, ExtensionSender, ReceiverSetup, SenderSetup, SetupOffer, SetupRequest,
+    SetupResponse,
+};
+use crate::production::{local_simulation_refused, LocalSimulationRefused};
+use crate::simulation::NetworkModel;
+
+/// How long a party waits for a peer's message before giving up on it
+const PEER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of a `PartyDemo` run
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DemoReport {
+    pub parties: usize,
+    /// Outputs in metadata order, as every party opened them
+    pub outputs: Vec<(String, bool)>,
+    /// Messages each party sent to the others
+    pub messages: Vec<usize>,
+    pub elapsed: Duration,
+    /// Seed of a deterministic run, which replays it exactly
+    pub seed: Option<u64>,
+    /// Simulated network time a deterministic run took
+    pub network_time: Option<Duration>,
+    /// Every message of a deterministic run in delivery order
+    pub deliveries: Vec<DemoDelivery>,
+}
+
+/// One message delivered during a deterministic run
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DemoDelivery {
+    /// Simulated time of arrival
+    pub at: Duration,
+    pub from: usize,
+    pub to: usize,
+    /// Kind of message and the input, gate or phase it belongs to, like
+    /// `Offer(3)`
+    pub message: String,
+}
+
+/// Runs a circuit with one thread per party, so a newcomer can watch the
+/// parties compute apart: each thread holds a `PartyEngine` with only its
+/// own shares and talks to the others through channels. Input owners split
+/// their inputs and send one share to each party, every AND/OR gate runs the
+/// cross-term OTs pairwise as request and response messages, and the
+/// outputs are opened by broadcasting output shares. The cross terms go
+/// through an OT extension per sender and receiver, set up in three more
+/// messages the first time the pair meets in those roles
+/// The threads still share one process, so production builds refuse it
+///
+/// With `with_seed` the run is deterministic, so a failure seen once can be
+/// replayed from its seed: the seed draws every input share and cross-term
+/// mask, only one party runs at a time, and a party that waits hands over
+/// to another chosen by the seed. Messages travel over a simulated network,
+/// arriving after the link latency plus a seeded jitter of up to one more
+/// latency, in order of arrival. The base-OT key pairs of the extension
+/// setups are the one exception: the OT crate draws them itself, but they
+/// never change a share
+pub struct PartyDemo {
+    parties: usize,
+    seed: Option<u64>,
+    network: NetworkModel,
+}
+
+/// Protocol message between two demo parties
+enum Message {
+    InputShare {
+        input: usize,
+        share: bool,
+    },
+    /// Extension setup, sent at the first gate a pair meets in its roles
+    SetupOffer {
+        gate: usize,
+        offer: SetupOffer,
+    },
+    SetupRequest {
+        gate: usize,
+        request: SetupRequest,
+    },
+    SetupResponse {
+        gate: usize,
+        response: SetupResponse,
+    },
+    Request {
+        gate: usize,
+        request: CrossTermRequest,
+    },
+    Response {
+        gate: usize,
+        response: CrossTermResponse,
+    },
+    /// Shares of the input checks (phase 0) or the outputs (phase 1)
+    Opening {
+        phase: u8,
+        shares: Vec<bool>,
+    },
+}
+
+/// What a party waits for, so messages arriving early can be set aside
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tag {
+    InputShare(usize),
+    SetupOffer(usize),
+    SetupRequest(usize),
+    SetupResponse(usize),
+    Request(usize),
+    Response(usize),
+    Opening(u8),
+}
+
+impl Message {
+    fn tag(&self) -> Tag {
+        match self {
+            Message::InputShare { input, .. } => Tag::InputShare(*input),
+            Message::SetupOffer { gate, .. } => Tag::SetupOffer(*gate),
+            Message::SetupRequest { gate, .. } => Tag::SetupRequest(*gate),
+            Message::SetupResponse { gate, .. } => Tag::SetupResponse(*gate),
+            Message::Request { gate, .. } => Tag::Request(*gate),
+            Message::Response { gate, .. } => Tag::Response(*gate),
+            Message::Opening { phase, .. } => Tag::Opening(*phase),
+        }
+    }
+}
+
+/// One party's end of the transport, and its randomness
+struct Party<'s> {
+    id: usize,
+    party_count: usize,
+    link: Link<'s>,
+    rng: StdRng,
+    sent: usize,
+    /// OT extension to each peer with this party as sender, once set up
+    sending: Vec<Option<ExtensionSender>>,
+    /// OT extension from each peer with this party as receiver
+    receiving: Vec<Option<ExtensionReceiver>>,
+}
+
+/// How messages reach a party
+enum Link<'s> {
+    /// Real channels between free-running threads
+    Channels {
+        peers: Vec<Sender<(usize, Message)>>,
+        inbox: Receiver<(usize, Message)>,
+        /// Messages received before the party asked for them
+        early: Vec<(usize, Message)>,
+    },
+    /// The deterministic scheduler
+    Scheduled(&'s Scheduler),
+}
+
+/// Turn-taking and the simulated network of a deterministic run
+struct Scheduler {
+    state: Mutex<SchedulerState>,
+    turn: Condvar,
+}
+
+struct SchedulerState {
+    rng: StdRng,
+    network: NetworkModel,
+    now: Duration,
+    /// Party allowed to run, if any
+    running: Option<usize>,
+    parties: Vec<PartyState>,
+    /// Delivered messages each party has not taken yet
+    delivered: Vec<Vec<(usize, Message)>>,
+    in_flight: Vec<InFlight>,
+    sent: u64,
+    deliveries: Vec<DemoDelivery>,
+    /// Every party left is waiting and no message is in flight
+    stalled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PartyState {
+    Ready,
+    Waiting(usize, Tag),
+    Done,
+}
+
+struct InFlight {
+    at: Duration,
+    /// Send order, breaking ties between equal arrival times
+    order: u64,
+    from: usize,
+    to: usize,
+    message: Message,
+}
+
+impl PartyDemo {
+    pub fn new(parties: usize) -> Self {
+        Self {
+            parties,
+            seed: None,
+            network: NetworkModel::lan(),
+        }
+    }
+
+    /// Run deterministically from `seed` over the simulated network
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Link model of a deterministic run (default: LAN)
+    pub fn with_network(mut self, network: NetworkModel) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Evaluate `circuit` on `inputs`; an input is provided by its declared
+    /// owner, or by party 0 if the circuit declares none. Input checks are
+    /// opened before the outputs, so a violation reveals no output
+    pub fn run(&self, circuit: &Circuit, inputs: &[bool]) -> Result<DemoReport> {
+        if local_simulation_refused() {
+            return Err(LocalSimulationRefused::new("PartyDemo").into());
+        }
+        if self.parties < 2 {
+            return Err(anyhow::anyhow!("Need at least 2 parties for computation"));
+        }
+        let prepared = PreparedCircuit::new(circuit);
+        let metadata = &prepared.metadata;
+        if inputs.len() != metadata.inputs.len() {
+            return Err(ErrorCode::InputMismatch.tag(anyhow::anyhow!(
+                "Circuit expects {} inputs but got {}",
+                metadata.inputs.len(),
+                inputs.len()
+            )));
+        }
+        let owners: Vec<usize> = metadata
+            .inputs
+            .iter()
+            .map(|input| {
+                let owners = metadata.parties.as_ref().map(|spec| &spec.owners);
+                owners
+                    .and_then(|owners| owners.get(&input.name).copied())
+                    .unwrap_or(0)
+            })
+            .collect();
+        if let Some(&owner) = owners.iter().find(|&&owner| owner >= self.parties) {
+            return Err(anyhow::anyhow!(
+                "Party {} owns an input but only {} parties run",
+                owner,
+                self.parties
+            ));
+        }
+
+        let start = Instant::now();
+        let mut master = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let scheduler = self
+            .seed
+            .map(|_| Scheduler::new(self.parties, &mut master, self.network));
+        let (peers, inboxes): (Vec<_>, Vec<_>) = (0..self.parties).map(|_| mpsc::channel()).unzip();
+        let results: Vec<Result<(Vec<bool>, usize)>> = thread::scope(|scope| {
+            let handles: Vec<_> = inboxes
+                .into_iter()
+                .enumerate()
+                .map(|(id, inbox)| {
+                    let link = match &scheduler {
+                        Some(scheduler) => Link::Scheduled(scheduler),
+                        None => Link::Channels {
+                            peers: peers.clone(),
+                            inbox,
+                            early: Vec::new(),
+                        },
+                    };
+                    let party = Party {
+                        id,
+                        party_count: self.parties,
+                        link,
+                        rng: StdRng::seed_from_u64(master.gen()),
+                        sent: 0,
+                        sending: (0..self.parties).map(|_| None).collect(),
+                        receiving: (0..self.parties).map(|_| None).collect(),
+                    };
+                    let (prepared, owners) = (&prepared, &owners);
+                    scope.spawn(move || party.run(prepared, owners, inputs))
+                })
+                .collect();
+            drop(peers);
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow::anyhow!("Party thread panicked")))
+                })
+                .collect()
+        });
+
+        let mut opened = Vec::with_capacity(self.parties);
+        let mut messages = Vec::with_capacity(self.parties);
+        for (id, result) in results.into_iter().enumerate() {
+            let (outputs, sent) = result.map_err(|error| error.context(format!("Party {id}")))?;
+            opened.push(outputs);
+            messages.push(sent);
+        }
+        if opened.windows(2).any(|pair| pair[0] != pair[1]) {
+            return Err(anyhow::anyhow!("Parties opened different outputs"));
+        }
+
+        Ok(DemoReport {
+            parties: self.parties,
+            outputs: metadata
+                .outputs
+                .iter()
+                .map(|output| output.name.clone())
+                .zip(opened.swap_remove(0))
+                .collect(),
+            messages,
+            elapsed: start.elapsed(),
+            seed: self.seed,
+            network_time: scheduler.as_ref().map(|scheduler| scheduler.lock().now),
+            deliveries: scheduler
+                .map(|scheduler| {
+                    scheduler
+                        .state
+                        .into_inner()
+                        .unwrap_or_else(|e| e.into_inner())
+                })
+                .map(|state| state.deliveries)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl Party<'_> {
+    /// Take turns if scheduled, then run the party to completion
+    fn run(
+        mut self,
+        prepared: &PreparedCircuit,
+        owners: &[usize],
+        inputs: &[bool],
+    ) -> Result<(Vec<bool>, usize)> {
+        let Link::Scheduled(scheduler) = self.link else {
+            return self.compute(prepared, owners, inputs);
+        };
+        let state = scheduler.wait_turn(self.id, scheduler.lock());
+        drop(state);
+        let result = self.compute(prepared, owners, inputs);
+        scheduler.finish(self.id);
+        result
+    }
+
+    /// Share the owned inputs, evaluate, and open the outputs; returns the
+    /// opened outputs and the number of messages sent
+    fn compute(
+        &mut self,
+        prepared: &PreparedCircuit,
+        owners: &[usize],
+        inputs: &[bool],
+    ) -> Result<(Vec<bool>, usize)> {
+        let party_count = self.party_count;
+        let metadata = &prepared.metadata;
+        let mut engine = PartyEngine::new(self.id, &prepared.gates, prepared.index.len());
+        let slot = |input: usize| {
+            let info = &metadata.inputs[input];
+            prepared
+                .index
+                .slot(info.id)
+                .ok_or_else(|| anyhow::anyhow!("Input {} not found", info.name))
+        };
+
+        for (input, (&owner, &value)) in owners.iter().zip(inputs).enumerate() {
+            if owner != self.id {
+                continue;
+            }
+            let shares = split_shares(value, party_count, || self.rng.gen());
+            for (party, share) in shares.into_iter().enumerate() {
+                if party == self.id {
+                    engine.set_share(slot(input)?, share);
+                } else {
+                    self.send(party, Message::InputShare { input, share })?;
+                }
+            }
+        }
+        for (input, &owner) in owners.iter().enumerate() {
+            if owner != self.id {
+                let Message::InputShare { share, .. } = self.take(owner, Tag::InputShare(input))?
+                else {
+                    unreachable!("take matches the tag");
+                };
+                engine.set_share(slot(input)?, share);
+            }
+        }
+
+        let mut interactive = 0;
+        while let Step::Interactive { gate, inputs } = engine.advance()? {
+            let cross_share = self.cross_term(gate, inputs, interactive)?;
+            engine.provide_cross_term(cross_share)?;
+            interactive += 1;
+        }
+
+        let share = |slot: usize| {
+            engine
+                .share(slot)
+                .ok_or_else(|| anyhow::anyhow!("Slot {} has no share", slot))
+        };
+        let checks = prepared.input_checks()?;
+        let check_shares = checks
+            .iter()
+            .map(|&(_, slot)| share(slot))
+            .collect::<Result<Vec<_>>>()?;
+        let check_values = self.open(0, check_shares)?;
+        if let Some(((constraint, _), _)) =
+            checks.iter().zip(check_values).find(|(_, holds)| !holds)
+        {
+            return Err(anyhow::anyhow!(
+                "Input constraint {} violated; outputs withheld",
+                constraint
+            ));
+        }
+
+        let output_shares = metadata
+            .outputs
+            .iter()
+            .map(|output| {
+                let slot = prepared
+                    .index
+                    .slot(output.id)
+                    .ok_or_else(|| anyhow::anyhow!("Missing output gate {}", output.id))?;
+                share(slot)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let outputs = self.open(1, output_shares)?;
+        Ok((outputs, self.sent))
+    }
+
+    /// This party's share of the cross terms of interactive gate `gate`,
+    /// running one OT with every other party; `rotation` picks the OT
+    /// sender of each pair as the in-process protocol does
+    fn cross_term(&mut self, gate: usize, inputs: (bool, bool), rotation: usize) -> Result<bool> {
+        let id = self.id;
+        let (sending, receiving): (Vec<usize>, Vec<usize>) = (0..self.party_count)
+            .filter(|&peer| peer != id)
+            .partition(|&peer| ot_sender(id.min(peer), id.max(peer), rotation) == id);
+
+        self.connect(gate, &sending, &receiving)?;
+
+        // Requests go out before waiting on anyone, so no pair blocks another
+        let mut receivers = Vec::with_capacity(receiving.len());
+        for &peer in &receiving {
+            let extension = self.receiving[peer]
+                .as_mut()
+                .expect("connect sets up every extension");
+            let (receiver, request) = CrossTermReceiver::new(inputs, extension);
+            self.send(peer, Message::Request { gate, request })?;
+            receivers.push((peer, receiver));
+        }
+
+        let mut cross_share = false;
+        for peer in sending {
+            let sender = CrossTermSender::with_rng(inputs, &mut self.rng);
+            let Message::Request { request, .. } = self.take(peer, Tag::Request(gate))? else {
+                unreachable!("take matches the tag");
+            };
+            let extension = self.sending[peer]
+                .as_mut()
+                .expect("connect sets up every extension");
+            let (share, response) = sender.respond(request, extension)?;
+            cross_share ^= share;
+            self.send(peer, Message::Response { gate, response })?;
+        }
+        for (peer, receiver) in receivers {
+            let Message::Response { response, .. } = self.take(peer, Tag::Response(gate))? else {
+                unreachable!("take matches the tag");
+            };
+            cross_share ^= receiver.finish(response)?;
+        }
+        Ok(cross_share)
+    }
+
+    /// Set up the OT extension with every peer of gate `gate` that this
+    /// party has not met in the same roles yet. The extension receiver
+    /// offers the base OTs, the sender picks, and the receiver responds;
+    /// each stage is sent to every peer before waiting on any, as in
+    /// `cross_term`
+    fn connect(&mut self, gate: usize, sending: &[usize], receiving: &[usize]) -> Result<()> {
+        let sending: Vec<usize> = sending
+            .iter()
+            .copied()
+            .filter(|&peer| self.sending[peer].is_none())
+            .collect();
+        let receiving: Vec<usize> = receiving
+            .iter()
+            .copied()
+            .filter(|&peer| self.receiving[peer].is_none())
+            .collect();
+
+        let mut receivers = Vec::with_capacity(receiving.len());
+        for &peer in &receiving {
+            let (setup, offer) = ReceiverSetup::new(&mut self.rng)?;
+            self.send(peer, Message::SetupOffer { gate, offer })?;
+            receivers.push((peer, setup));
+        }
+        let mut senders = Vec::with_capacity(sending.len());
+        for peer in sending {
+            let Message::SetupOffer { offer, .. } = self.take(peer, Tag::SetupOffer(gate))? else {
+                unreachable!("take matches the tag");
+            };
+            let (setup, request) = SenderSetup::new(offer, &mut self.rng)?;
+            self.send(peer, Message::SetupRequest { gate, request })?;
+            senders.push((peer, setup));
+        }
+        for (peer, setup) in receivers {
+            let Message::SetupRequest { request, .. } = self.take(peer, Tag::SetupRequest(gate))?
+            else {
+                unreachable!("take matches the tag");
+            };
+            let (extension, response) = setup.respond(request)?;
+            self.send(peer, Message::SetupResponse { gate, response })?;
+            self.receiving[peer] = Some(extension);
+        }
+        for (peer, setup) in senders {
+            let Message::SetupResponse { response, .. } =
+                self.take(peer, Tag::SetupResponse(gate))?
+            else {
+                unreachable!("take matches the tag");
+            };
+            self.sending[peer] = Some(setup.finish(response)?);
+        }
+        Ok(())
+    }
+
+    /// Broadcast `shares` and XOR in every other party's shares
+    fn open(&mut self, phase: u8, shares: Vec<bool>) -> Result<Vec<bool>> {
+        let (id, party_count) = (self.id, self.party_count);
+        for peer in (0..party_count).filter(|&peer| peer != id) {
+            let shares = shares.clone();
+            self.send(peer, Message::Opening { phase, shares })?;
+        }
+        let mut all = vec![shares];
+        for peer in (0..party_count).filter(|&peer| peer != id) {
+            let Message::Opening { shares, .. } = self.take(peer, Tag::Opening(phase))? else {
+                unreachable!("take matches the tag");
+            };
+            all.push(shares);
+        }
+        (0..all[0].len())
+            .map(|i| {
+                let column = all
+                    .iter()
+                    .map(|shares| shares.get(i).copied())
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| anyhow::anyhow!("A party opened too few shares"))?;
+                Ok(reconstruct(&column))
+            })
+            .collect()
+    }
+
+    fn send(&mut self, peer: usize, message: Message) -> Result<()> {
+        match &self.link {
+            Link::Channels { peers, .. } => peers[peer].send((self.id, message)).map_err(|_| {
+                ErrorCode::Transport.tag(anyhow::anyhow!("Party {} has stopped", peer))
+            })?,
+            Link::Scheduled(scheduler) => scheduler.send(self.id, peer, message),
+        }
+        self.sent += 1;
+        Ok(())
+    }
+
+    /// Next message from `peer` with `tag`, keeping any other message that
+    /// arrives first for later
+    fn take(&mut self, peer: usize, tag: Tag) -> Result<Message> {
+        let (inbox, early) = match &mut self.link {
+            Link::Channels { inbox, early, .. } => (inbox, early),
+            Link::Scheduled(scheduler) => return scheduler.take(self.id, peer, tag),
+        };
+        if let Some(position) = early
+            .iter()
+            .position(|(from, message)| *from == peer && message.tag() == tag)
+        {
+            return Ok(early.swap_remove(position).1);
+        }
+        loop {
+            let (from, message) = inbox.recv_timeout(PEER_TIMEOUT).map_err(|_| {
+                ErrorCode::Transport.tag(anyhow::anyhow!(
+                    "No {:?} from party {} in time",
+                    tag,
+                    peer
+                ))
+            })?;
+            if from == peer && message.tag() == tag {
+                return Ok(message);
+            }
+            early.push((from, message));
+        }
+    }
+}
+
+impl Scheduler {
+    /// Every party ready, and the first one to run already picked
+    fn new(parties: usize, master: &mut StdRng, network: NetworkModel) -> Self {
+        let mut state = SchedulerState {
+            rng: StdRng::seed_from_u64(master.gen()),
+            network,
+            now: Duration::ZERO,
+            running: None,
+            parties: vec![PartyState::Ready; parties],
+            delivered: (0..parties).map(|_| Vec::new()).collect(),
+            in_flight: Vec::new(),
+            sent: 0,
+            deliveries: Vec::new(),
+            stalled: false,
+        };
+        state.schedule();
+        Self {
+            state: Mutex::new(state),
+            turn: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SchedulerState> {
+        // A party that panicked fails the run anyway; keep the state usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Block until it is `party`'s turn or the run has stalled
+    fn wait_turn<'a>(
+        &self,
+        party: usize,
+        state: MutexGuard<'a, SchedulerState>,
+    ) -> MutexGuard<'a, SchedulerState> {
+        self.turn
+            .wait_while(state, |state| {
+                state.running != Some(party) && !state.stalled
+            })
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn send(&self, from: usize, to: usize, message: Message) {
+        let mut state = self.lock();
+        let latency = state.network.latency;
+        let jitter = state.rng.gen_range(0..=latency.as_nanos() as u64);
+        let (at, order) = (
+            state.now + latency + Duration::from_nanos(jitter),
+            state.sent,
+        );
+        state.sent += 1;
+        state.in_flight.push(InFlight {
+            at,
+            order,
+            from,
+            to,
+            message,
+        });
+    }
+
+    /// The delivered message from `peer` with `tag`, handing the turn over
+    /// while it is still in flight
+    fn take(&self, party: usize, peer: usize, tag: Tag) -> Result<Message> {
+        let mut state = self.lock();
+        loop {
+            let delivered = &mut state.delivered[party];
+            if let Some(position) = delivered
+                .iter()
+                .position(|(from, message)| *from == peer && message.tag() == tag)
+            {
+                return Ok(delivered.remove(position).1);
+            }
+            if state.stalled {
+                return Err(ErrorCode::Transport.tag(anyhow::anyhow!(
+                    "No {:?} from party {}: every party is waiting",
+                    tag,
+                    peer
+                )));
+            }
+            state.parties[party] = PartyState::Waiting(peer, tag);
+            state.schedule();
+            self.turn.notify_all();
+            state = self.wait_turn(party, state);
+        }
+    }
+
+    fn finish(&self, party: usize) {
+        let mut state = self.lock();
+        state.parties[party] = PartyState::Done;
+        state.schedule();
+        self.turn.notify_all();
+    }
+}
+
+impl SchedulerState {
+    /// Pick the next party to run, delivering messages in order of arrival
+    /// until one can
+    fn schedule(&mut self) {
+        loop {
+            let ready: Vec<usize> = (0..self.parties.len())
+                .filter(|&party| self.parties[party] == PartyState::Ready)
+                .collect();
+            if !ready.is_empty() {
+                self.running = Some(ready[self.rng.gen_range(0..ready.len())]);
+                return;
+            }
+            let Some(next) = (0..self.in_flight.len())
+                .min_by_key(|&i| (self.in_flight[i].at, self.in_flight[i].order))
+            else {
+                self.running = None;
+                self.stalled = self.parties.iter().any(|&party| party != PartyState::Done);
+                return;
+            };
+            let InFlight {
+                at,
+                from,
+                to,
+                message,
+                ..
+            } = self.in_flight.swap_remove(next);
+            self.now = self.now.max(at);
+            let tag = message.tag();
+            self.deliveries.push(DemoDelivery {
+                at: self.now,
+                from,
+                to,
+                message: format!("{tag:?}"),
+            });
+            if self.parties[to] == PartyState::Waiting(from, tag) {
+                self.parties[to] = PartyState::Ready;
+            }
+            self.delivered[to].push((from, message));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+
+    #[test]
+    fn test_demo_parties_agree_with_plaintext() {
+        let circuit = reference::adder_comparator(2);
+        for parties in [2, 3] {
+            let demo = PartyDemo::new(parties);
+            for (x, y) in [(0u8, 0u8), (1, 2), (3, 3), (3, 1)] {
+                let inputs: Vec<bool> = (0..2)
+                    .map(|bit| x >> bit & 1 == 1)
+                    .chain((0..2).map(|bit| y >> bit & 1 == 1))
+                    .collect();
+                let report = demo.run(&circuit, &inputs).unwrap();
+                let output = |name| {
+                    let found = report.outputs.iter().find(|(output, _)| output == name);
+                    found.unwrap().1
+                };
+                assert_eq!(output("lt"), x < y);
+                assert_eq!(output("eq"), x == y);
+                assert_eq!(report.messages.len(), parties);
+                assert!(report.messages.iter().all(|&sent| sent > 0));
+            }
+        }
+
+        assert!(PartyDemo::new(3).run(&circuit, &[true]).is_err());
+        assert!(PartyDemo::new(1).run(&circuit, &[false; 4]).is_err());
+    }
+
+    #[test]
+    fn test_seeded_demo_replays_exactly() {
+        let circuit = reference::adder_comparator(2);
+        let inputs = [true, false, true, true];
+        let run = |seed| {
+            PartyDemo::new(3)
+                .with_seed(seed)
+                .with_network(NetworkModel::wan())
+                .run(&circuit, &inputs)
+                .unwrap()
+        };
+        let (first, replay, other) = (run(7), run(7), run(8));
+
+        assert_eq!(first.deliveries, replay.deliveries);
+        assert_eq!(first.network_time, replay.network_time);
+        assert_eq!(first.outputs, other.outputs);
+        assert_ne!(first.deliveries, other.deliveries);
+        assert_eq!(first.deliveries.len(), first.messages.iter().sum::<usize>());
+        // Every message waits at least one WAN latency
+        assert!(first.network_time.unwrap() >= Duration::from_millis(50));
+        assert!(first
+            .deliveries
+            .windows(2)
+            .all(|pair| pair[0].at <= pair[1].at));
+        assert!(PartyDemo::new(2)
+            .run(&circuit, &inputs)
+            .unwrap()
+            .deliveries
+            .is_empty());
+    }
+}
@@ -0,0 +1,228 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+
+use crate::circuit::WireId;
+use crate::kernel::EngineProgress;
+
+/// One party's progress at the time a run failed, as written to disk
+/// Hosts driving `PartyEngine`s over a real transport call
+/// `ProgressDump::new(circuit, engine.progress()).write(path)` for every
+/// party when a run fails or times out, then compare the files with
+/// `find_divergence` or the `diagnose` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressDump {
+    pub circuit: String,
+    pub progress: EngineProgress,
+}
+
+impl ProgressDump {
+    pub fn new(circuit: &str, progress: EngineProgress) -> Self {
+        Self {
+            circuit: circuit.to_string(),
+            progress,
+        }
+    }
+
+    pub fn write(&self, path: &str) -> Result<()> {
+        Ok(fs::write(path, serde_json::to_string(self)?)?)
+    }
+
+    pub fn read(path: &str) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// Where the parties' views of a run first disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Divergence {
+    /// First gate not evaluated by every party
+    pub gate: usize,
+    /// Last round every party acknowledged
+    pub round: usize,
+    /// Parties stopped at `gate`, or missing the share of `slot`
+    pub lagging: Vec<usize>,
+    /// First wire slot holding a share for some parties but not others
+    pub slot: Option<usize>,
+}
+
+/// Typed error of a differential check: the first plan step whose
+/// reconstructed shares disagree with plaintext evaluation of the circuit
+/// `step` and `slot` name the first gate that went wrong, which is usually
+/// the bug; later mismatches are consequences of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ShareMismatch {
+    pub circuit: String,
+    /// Position of the step in the evaluation plan
+    pub step: usize,
+    /// Arena slot the step wrote wrongly
+    pub slot: usize,
+    /// Wire of the slot, if the circuit declares it
+    pub wire: Option<WireId>,
+    /// Plaintext value of the wire; the shares open to its negation
+    pub expected: bool,
+}
+
+impl fmt::Display for ShareMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wire = self.wire.map_or_else(
+            || format!("slot {}", self.slot),
+            |wire| format!("wire {wire}"),
+        );
+        write!(
+            f,
+            "Step {} of {} opens {} to {} instead of {}",
+            self.step, self.circuit, wire, !self.expected, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ShareMismatch {}
+
+/// Correlate the dumps of one failed run; `None` if every party got equally far
+pub fn find_divergence(dumps: &[ProgressDump]) -> Result<Option<Divergence>> {
+    let first = dumps
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("No progress dumps to compare"))?;
+    for dump in dumps {
+        if dump.circuit != first.circuit {
+            return Err(anyhow::anyhow!(
+                "Dumps are from different circuits: {} and {}",
+                first.circuit,
+                dump.circuit
+            ));
+        }
+        if dump.progress.computed.len() != first.progress.computed.len() {
+            return Err(anyhow::anyhow!(
+                "Dumps of {} disagree on the wire count",
+                first.circuit
+            ));
+        }
+    }
+    let mut parties: Vec<usize> = dumps.iter().map(|dump| dump.progress.party_id).collect();
+    parties.sort_unstable();
+    if parties.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err(anyhow::anyhow!("Several dumps for the same party"));
+    }
+
+    let progress: Vec<&EngineProgress> = dumps.iter().map(|dump| &dump.progress).collect();
+    let gate = progress.iter().map(|p| p.cursor).min().unwrap_or(0);
+    let round = progress.iter().map(|p| p.acked_round).min().unwrap_or(0);
+    let slot = (0..first.progress.computed.len()).find(|&slot| {
+        progress
+            .iter()
+            .any(|p| p.computed[slot] != first.progress.computed[slot])
+    });
+
+    let lagging: Vec<usize> = match slot {
+        Some(slot) if progress.iter().all(|p| p.cursor == gate) => progress
+            .iter()
+            .filter(|p| !p.computed[slot])
+            .map(|p| p.party_id)
+            .collect(),
+        _ => progress
+            .iter()
+            .filter(|p| p.cursor == gate)
+            .map(|p| p.party_id)
+            .collect(),
+    };
+    let in_sync = progress
+        .iter()
+        .all(|p| p.cursor == gate && p.acked_round == round);
+    if in_sync && slot.is_none() {
+        return Ok(None);
+    }
+
+    Ok(Some(Divergence {
+        gate,
+        round,
+        lagging,
+        slot,
+    }))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::PreparedCircuit;
+    use crate::gates::cross_term_shares;
+    use crate::kernel::algebra::split_shares;
+    use crate::kernel::{PartyEngine, Step};
+
+    #[test]
+    fn test_diagnose_finds_lagging_party() {
+        let circuit = reference::full_adder();
+        let prepared = PreparedCircuit::new(&circuit);
+        let mut engines: Vec<PartyEngine> = (0..3)
+            .map(|party| PartyEngine::new(party, &prepared.gates, prepared.index.len()))
+            .collect();
+        for info in &prepared.metadata.inputs {
+            let slot = prepared.index.slot(info.id).unwrap();
+            let shares = split_shares(true, 3, rand::random::<bool>);
+            for (engine, share) in engines.iter_mut().zip(shares) {
+                engine.set_share(slot, share);
+            }
+        }
+        let dumps = |engines: &[PartyEngine]| -> Vec<ProgressDump> {
+            engines
+                .iter()
+                .map(|engine| ProgressDump::new(&circuit.name, engine.progress()))
+                .collect()
+        };
+
+        // Complete the first interactive round on every party
+        let steps: Vec<Step> = engines.iter_mut().map(|e| e.advance().unwrap()).collect();
+        let inputs: Vec<(bool, bool)> = steps
+            .iter()
+            .map(|step| match step {
+                Step::Interactive { inputs, .. } => *inputs,
+                Step::Done => panic!("full adder has interactive gates"),
+            })
+            .collect();
+        let cross_shares = cross_term_shares(&inputs).unwrap();
+        for (engine, cross_share) in engines.iter_mut().zip(cross_shares) {
+            engine.provide_cross_term(cross_share).unwrap();
+        }
+        for engine in &mut engines {
+            engine.advance().unwrap();
+        }
+        assert_eq!(find_divergence(&dumps(&engines)).unwrap(), None);
+        let stalled_at = engines[0].progress().cursor;
+
+        // Party 1's cross term for the second round is lost
+        let steps: Vec<Step> = engines.iter_mut().map(|e| e.advance().unwrap()).collect();
+        let inputs: Vec<(bool, bool)> = steps
+            .iter()
+            .map(|step| match step {
+                Step::Interactive { inputs, .. } => *inputs,
+                Step::Done => panic!("full adder has three interactive gates"),
+            })
+            .collect();
+        let cross_shares = cross_term_shares(&inputs).unwrap();
+        for (party, engine) in engines.iter_mut().enumerate() {
+            if party != 1 {
+                engine.provide_cross_term(cross_shares[party]).unwrap();
+                engine.advance().unwrap();
+            }
+        }
+
+        let dumps = dumps(&engines);
+        let path = std::env::temp_dir().join(format!("gmw-dump-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        dumps[1].write(path).unwrap();
+        assert_eq!(ProgressDump::read(path).unwrap(), dumps[1]);
+        fs::remove_file(path).unwrap();
+
+        let divergence = find_divergence(&dumps).unwrap().unwrap();
+        assert_eq!(divergence.gate, stalled_at);
+        assert_eq!(divergence.round, 1);
+        assert_eq!(divergence.lagging, vec![1]);
+        assert!(divergence.slot.is_some());
+
+        assert!(find_divergence(&[dumps[0].clone(), dumps[0].clone()]).is_err());
+    }
+}
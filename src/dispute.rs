@@ -0,0 +1,302 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::circuit::{Circuit, PreparedCircuit};
+use crate::errors::ErrorCode;
+use crate::kernel::algebra::reconstruct;
+use crate::protocol::GmwProtocol;
+use crate::receipt::{decode_hex, encode_hex, ReceiptHash};
+use crate::report::ExecutionReport;
+
+/// Version written into every commitment log; bumped on incompatible changes
+pub const COMMITMENT_LOG_VERSION: u32 = 1;
+
+/// Commitment to one party's share of one input:
+/// hash(session, input, party, nonce, share)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareCommitment {
+    pub input: String,
+    pub party: usize,
+    /// Hex-encoded hash
+    pub commitment: String,
+}
+
+/// Commitments to every input share of a run
+/// Published to and stored by all parties before evaluation, so a party
+/// cannot later claim a different share than the one it computed with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentLog {
+    pub version: u32,
+    /// Caller-chosen identifier of the run
+    pub session: u64,
+    pub party_count: usize,
+    pub commitments: Vec<ShareCommitment>,
+}
+
+/// Opening of a share commitment, kept privately by the party holding the share
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareOpening {
+    pub input: String,
+    pub party: usize,
+    pub share: bool,
+    /// Hex-encoded random nonce
+    pub nonce: String,
+}
+
+impl CommitmentLog {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let log: Self = serde_json::from_str(json)?;
+        if log.version != COMMITMENT_LOG_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported commitment log version {}",
+                log.version
+            ));
+        }
+        Ok(log)
+    }
+
+    /// Whether an opening matches the logged commitment to that share
+    pub fn verify(&self, hash: &dyn ReceiptHash, opening: &ShareOpening) -> bool {
+        let Some(nonce) = decode_hex(&opening.nonce) else {
+            return false;
+        };
+        let expected = commit(hash, self.session, opening, &nonce);
+        self.commitments.iter().any(|commitment| {
+            commitment.input == opening.input
+                && commitment.party == opening.party
+                && commitment.commitment == expected
+        })
+    }
+}
+
+impl GmwProtocol {
+    /// Run a circuit with every input share committed before evaluation
+    /// Returns the report, the commitment log every party stores, and each
+    /// party's openings of its own shares (`openings[party]`), which it keeps
+    /// until a dispute requires them
+    pub fn run_with_commitments(
+        &self,
+        circuit: &Circuit,
+        inputs: &[bool],
+        session: u64,
+        hash: &dyn ReceiptHash,
+    ) -> Result<(ExecutionReport, CommitmentLog, Vec<Vec<ShareOpening>>)> {
+        if inputs.len() != circuit.metadata.inputs.len() {
+            return Err(ErrorCode::InputMismatch.tag(anyhow::anyhow!(
+                "Circuit expects {} inputs but got {}",
+                circuit.metadata.inputs.len(),
+                inputs.len()
+            )));
+        }
+
+        self.security().check_hash(hash)?;
+
+        let input_shares = self.share_inputs(inputs);
+        let mut commitments = Vec::new();
+        let mut openings = vec![Vec::new(); self.party_count()];
+        for (info, shares) in circuit.metadata.inputs.iter().zip(&input_shares) {
+            for (party, &share) in shares.iter().enumerate() {
+                let nonce = self.security().nonce();
+                let opening = ShareOpening {
+                    input: info.name.clone(),
+                    party,
+                    share,
+                    nonce: encode_hex(&nonce),
+                };
+                commitments.push(ShareCommitment {
+                    input: info.name.clone(),
+                    party,
+                    commitment: commit(hash, session, &opening, &nonce),
+                });
+                openings[party].push(opening);
+            }
+        }
+        let log = CommitmentLog {
+            version: COMMITMENT_LOG_VERSION,
+            session,
+            party_count: self.party_count(),
+            commitments,
+        };
+
+        let report = self
+            .evaluate_shared(&PreparedCircuit::new(circuit), &input_shares)?
+            .reconstruct();
+        Ok((report, log, openings))
+    }
+}
+
+/// Forced opening of one committed input, e.g. under a contractual dispute
+/// The input is reconstructed only once at least `threshold` parties have
+/// approved the opening and every party's share has been opened against
+/// the commitment log; an opening that does not match is rejected and
+/// names the party that submitted it
+pub struct Dispute<'a> {
+    log: &'a CommitmentLog,
+    hash: &'a dyn ReceiptHash,
+    input: String,
+    threshold: usize,
+    approvals: Vec<bool>,
+    shares: Vec<Option<bool>>,
+}
+
+impl<'a> Dispute<'a> {
+    /// Open a dispute over `input`, to be resolved by `threshold` approvals
+    pub fn new(
+        log: &'a CommitmentLog,
+        hash: &'a dyn ReceiptHash,
+        input: &str,
+        threshold: usize,
+    ) -> Result<Self> {
+        if threshold == 0 || threshold > log.party_count {
+            return Err(anyhow::anyhow!(
+                "Approval threshold {} must be between 1 and {} parties",
+                threshold,
+                log.party_count
+            ));
+        }
+        if !log
+            .commitments
+            .iter()
+            .any(|commitment| commitment.input == input)
+        {
+            return Err(anyhow::anyhow!("No commitments to input {}", input));
+        }
+
+        Ok(Self {
+            log,
+            hash,
+            input: input.to_string(),
+            threshold,
+            approvals: vec![false; log.party_count],
+            shares: vec![None; log.party_count],
+        })
+    }
+
+    /// Record a party's approval of the opening; approving twice counts once
+    pub fn approve(&mut self, party_id: usize) -> Result<()> {
+        let approval = self
+            .approvals
+            .get_mut(party_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown party {}", party_id))?;
+        *approval = true;
+        Ok(())
+    }
+
+    /// Whether enough parties approved to open the input
+    pub fn is_approved(&self) -> bool {
+        self.approvals.iter().filter(|&&approved| approved).count() >= self.threshold
+    }
+
+    /// Accept a party's opening of its share; openings of other inputs are ignored
+    pub fn submit(&mut self, opening: &ShareOpening) -> Result<()> {
+        if opening.input != self.input {
+            return Ok(());
+        }
+        let share = self
+            .shares
+            .get_mut(opening.party)
+            .ok_or_else(|| anyhow::anyhow!("Unknown party {}", opening.party))?;
+        if !self.log.verify(self.hash, opening) {
+            return Err(anyhow::anyhow!(
+                "Party {} opened a share of {} that does not match its commitment",
+                opening.party,
+                self.input
+            ));
+        }
+        *share = Some(opening.share);
+        Ok(())
+    }
+
+    /// Parties whose share has not been opened yet
+    pub fn missing(&self) -> Vec<usize> {
+        (0..self.shares.len())
+            .filter(|&party| self.shares[party].is_none())
+            .collect()
+    }
+
+    /// Reconstruct the disputed input
+    pub fn resolve(&self) -> Result<bool> {
+        if !self.is_approved() {
+            return Err(anyhow::anyhow!(
+                "Opening {} needs {} approvals",
+                self.input,
+                self.threshold
+            ));
+        }
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Shares of {} not opened by parties {:?}",
+                self.input,
+                missing
+            ));
+        }
+        let shares: Vec<bool> = self.shares.iter().flatten().copied().collect();
+        Ok(reconstruct(&shares))
+    }
+}
+
+fn commit(hash: &dyn ReceiptHash, session: u64, opening: &ShareOpening, nonce: &[u8]) -> String {
+    let mut data = session.to_le_bytes().to_vec();
+    data.extend_from_slice(&(opening.input.len() as u64).to_le_bytes());
+    data.extend_from_slice(opening.input.as_bytes());
+    data.extend_from_slice(&(opening.party as u64).to_le_bytes());
+    data.extend_from_slice(nonce);
+    data.push(u8::from(opening.share));
+    encode_hex(&hash.hash(&data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::security::SecurityConfig;
+    use crate::sha256::Sha256;
+
+    #[test]
+    fn test_dispute_opens_committed_input() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(3).unwrap();
+        let inputs = [true, false, true];
+        let (report, log, openings) = protocol
+            .run_with_commitments(&circuit, &inputs, 7, &Sha256)
+            .unwrap();
+        assert_eq!(
+            report.outputs,
+            protocol.run_circuit(&circuit, &inputs).unwrap()
+        );
+        assert_eq!(log.commitments.len(), 9);
+
+        let log = CommitmentLog::from_json(&log.to_json().unwrap()).unwrap();
+        let name = &circuit.metadata.inputs[0].name;
+        let mut dispute = Dispute::new(&log, &Sha256, name, 2).unwrap();
+        for party_openings in &openings {
+            for opening in party_openings {
+                dispute.submit(opening).unwrap();
+            }
+        }
+        assert!(dispute.missing().is_empty());
+
+        // Opening needs a quorum of approvals
+        dispute.approve(0).unwrap();
+        assert!(dispute.resolve().is_err());
+        dispute.approve(2).unwrap();
+        assert!(dispute.resolve().unwrap());
+
+        // A party lying about its share is caught
+        let mut lie = openings[1][0].clone();
+        lie.share ^= true;
+        assert!(dispute.submit(&lie).is_err());
+        assert!(Dispute::new(&log, &Sha256, "missing", 2).is_err());
+
+        // 192-bit security needs longer nonces and a 384-bit hash
+        let protocol = protocol.with_security(SecurityConfig::new(192, 40).unwrap());
+        assert!(protocol
+            .run_with_commitments(&circuit, &inputs, 8, &Sha256)
+            .is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Evaluation a set of shares belongs to
+/// Shares from different evaluations XOR to garbage, and combining them can
+/// leak more than either evaluation reveals, so every share container carries
+/// its domain and operations over several containers check that they agree
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ShareDomain {
+    pub session: u64,
+    pub circuit: String,
+}
+
+impl ShareDomain {
+    pub fn new(session: u64, circuit: &str) -> Self {
+        Self {
+            session,
+            circuit: circuit.to_string(),
+        }
+    }
+
+    /// Domain of a new evaluation of `circuit`, with a random session
+    pub fn fresh(circuit: &str) -> Self {
+        Self::new(rand::random::<u64>(), circuit)
+    }
+
+    /// Fail with `DomainMismatch` unless `other` is the same domain
+    pub fn check(&self, other: &ShareDomain) -> Result<(), DomainMismatch> {
+        if self == other {
+            Ok(())
+        } else {
+            Err(DomainMismatch {
+                expected: self.clone(),
+                found: other.clone(),
+            })
+        }
+    }
+}
+
+impl fmt::Display for ShareDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} session {}", self.circuit, self.session)
+    }
+}
+
+/// Typed error returned when shares of different evaluations are mixed
+/// `expected` is the domain of the shares already in hand, `found` that of
+/// the shares being added
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DomainMismatch {
+    pub expected: ShareDomain,
+    pub found: ShareDomain,
+}
+
+impl fmt::Display for DomainMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Shares of {} mixed with shares of {}",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for DomainMismatch {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_check() {
+        let domain = ShareDomain::new(7, "adder");
+        assert!(domain.check(&ShareDomain::new(7, "adder")).is_ok());
+
+        let error = domain.check(&ShareDomain::new(8, "adder")).unwrap_err();
+        assert_eq!(error.found.session, 8);
+        assert_eq!(
+            error.to_string(),
+            "Shares of adder session 8 mixed with shares of adder session 7"
+        );
+        assert!(domain.check(&ShareDomain::new(7, "equality")).is_err());
+        assert_ne!(ShareDomain::fresh("adder"), ShareDomain::fresh("adder"));
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use crate::access::OpeningRefused;
+use crate::budget::BudgetExceeded;
+use crate::kernel::KernelError;
+use crate::production::LocalSimulationRefused;
+
+/// Stable class of a failure; the CLI exits with its number
+/// Attach one to an `anyhow::Error` with `ErrorCode::tag`, or as context;
+/// `ErrorCode::of` finds it again below any later context
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// Anything not classified below, including failed result checks
+    Failed,
+    /// The circuit could not be read, parsed or validated
+    BadCircuit,
+    /// The inputs do not fit the circuit: wrong count, unknown names or
+    /// values that do not fit their bus
+    InputMismatch,
+    /// Messages between parties were lost or never arrived
+    Transport,
+    /// The protocol stopped before producing outputs
+    ProtocolAbort,
+    /// A policy refused the run: production mode, a resource budget or an
+    /// opening policy
+    Refused,
+}
+
+/// Every code with its exit status, stable name and English message
+/// The only place the CLI's failure summaries are spelled, so a translation
+/// replaces this table and nothing else
+const MESSAGES: [(ErrorCode, i32, &str, &str); 6] = [
+    (ErrorCode::Failed, 1, "failed", "The run failed"),
+    (
+        ErrorCode::BadCircuit,
+        2,
+        "bad_circuit",
+        "The circuit is invalid",
+    ),
+    (
+        ErrorCode::InputMismatch,
+        3,
+        "input_mismatch",
+        "The inputs do not match the circuit",
+    ),
+    (
+        ErrorCode::Transport,
+        4,
+        "transport",
+        "The parties could not exchange messages",
+    ),
+    (
+        ErrorCode::ProtocolAbort,
+        5,
+        "protocol_abort",
+        "The protocol aborted",
+    ),
+    (
+        ErrorCode::Refused,
+        6,
+        "refused",
+        "The run was refused by policy",
+    ),
+];
+
+impl ErrorCode {
+    /// Every code, in exit status order
+    pub fn all() -> impl Iterator<Item = ErrorCode> {
+        MESSAGES.iter().map(|(code, ..)| *code)
+    }
+
+    fn entry(self) -> &'static (ErrorCode, i32, &'static str, &'static str) {
+        MESSAGES
+            .iter()
+            .find(|(code, ..)| *code == self)
+            .expect("every code has a message")
+    }
+
+    /// Process exit status
+    pub fn exit_status(self) -> i32 {
+        self.entry().1
+    }
+
+    /// Stable snake_case name, e.g. for the `code_name` field of JSON errors
+    pub fn name(self) -> &'static str {
+        self.entry().2
+    }
+
+    /// One-line summary shown before the error's details
+    pub fn message(self) -> &'static str {
+        self.entry().3
+    }
+
+    /// Class of `error`: an attached code, else the class of a typed error
+    /// it carries, else `Failed`
+    pub fn of(error: &anyhow::Error) -> Self {
+        Self::find(error).unwrap_or(ErrorCode::Failed)
+    }
+
+    /// Attach this code to `error` unless it is already classified, so the
+    /// most specific class, set closest to the failure, wins
+    pub fn tag(self, error: anyhow::Error) -> anyhow::Error {
+        if Self::find(&error).is_some() {
+            error
+        } else {
+            error.context(self)
+        }
+    }
+
+    fn find(error: &anyhow::Error) -> Option<Self> {
+        if let Some(code) = error.downcast_ref::<ErrorCode>() {
+            Some(*code)
+        } else if error.is::<LocalSimulationRefused>()
+            || error.is::<BudgetExceeded>()
+            || error.is::<OpeningRefused>()
+        {
+            Some(ErrorCode::Refused)
+        } else if error.is::<KernelError>() {
+            Some(ErrorCode::ProtocolAbort)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_stable_and_survive_context() {
+        let statuses: Vec<i32> = ErrorCode::all().map(ErrorCode::exit_status).collect();
+        assert_eq!(statuses, [1, 2, 3, 4, 5, 6]);
+
+        let error = ErrorCode::InputMismatch.tag(anyhow::anyhow!("Circuit expects 3 inputs"));
+        assert_eq!(
+            format!("{error:#}"),
+            "The inputs do not match the circuit: Circuit expects 3 inputs"
+        );
+        // Later context and later tags keep the first class
+        let error = ErrorCode::ProtocolAbort.tag(error.context("Party 1"));
+        assert_eq!(ErrorCode::of(&error), ErrorCode::InputMismatch);
+
+        let refused = ErrorCode::ProtocolAbort.tag(LocalSimulationRefused::new("demo").into());
+        assert_eq!(ErrorCode::of(&refused), ErrorCode::Refused);
+        assert_eq!(
+            ErrorCode::of(&anyhow::anyhow!("Unknown option")),
+            ErrorCode::Failed
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::circuit::WireId;
+use crate::domain::ShareDomain;
+use crate::kernel::algebra::reshare;
+use crate::packing::{pack_shares, unpack_shares, write_string, write_varint, Cursor};
+use crate::protocol::PartyShares;
+
+/// Version written into every export; bumped on incompatible format changes
+pub const EXPORT_FORMAT_VERSION: u32 = 2;
+
+/// First bytes of a packed export
+pub const PACKED_MAGIC: [u8; 4] = *b"GMWP";
+
+/// One party's XOR shares of designated wires, for hand-off to an external system
+/// Serialized as JSON:
+/// `{"version": 2, "session": 7, "circuit": "adder", "party": 0,
+///   "party_count": 2, "shares": [{"wire": 103, "share": true}]}`
+/// A wire's value is the XOR of its share across all `party_count` exports of
+/// the same session. Shares carry no MAC: the protocol is semi-honest only
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireShareExport {
+    pub version: u32,
+    /// Caller-chosen identifier tying together the exports of one evaluation
+    pub session: u64,
+    /// Name of the evaluated circuit
+    pub circuit: String,
+    pub party: usize,
+    pub party_count: usize,
+    pub shares: Vec<WireShare>,
+}
+
+/// Share of a single wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireShare {
+    pub wire: WireId,
+    pub share: bool,
+}
+
+impl WireShareExport {
+    /// Create an export in the current format version
+    pub fn new(
+        domain: &ShareDomain,
+        party: usize,
+        party_count: usize,
+        shares: Vec<(WireId, bool)>,
+    ) -> Self {
+        Self {
+            version: EXPORT_FORMAT_VERSION,
+            session: domain.session,
+            circuit: domain.circuit.clone(),
+            party,
+            party_count,
+            shares: shares
+                .into_iter()
+                .map(|(wire, share)| WireShare { wire, share })
+                .collect(),
+        }
+    }
+
+    pub fn domain(&self) -> ShareDomain {
+        ShareDomain::new(self.session, &self.circuit)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let export: Self = serde_json::from_str(json)?;
+        if export.version != EXPORT_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported share export version {}",
+                export.version
+            ));
+        }
+        Ok(export)
+    }
+
+    /// Compact binary form for transport: `PACKED_MAGIC`, then the header as
+    /// LEB128 integers and a length-prefixed circuit name, then the shares
+    /// bit-packed by `pack_shares`, about one byte per 8 shares instead of a
+    /// JSON object per share
+    pub fn to_packed(&self) -> Vec<u8> {
+        let mut bytes = PACKED_MAGIC.to_vec();
+        write_varint(&mut bytes, u64::from(self.version));
+        write_varint(&mut bytes, self.session);
+        write_string(&mut bytes, &self.circuit);
+        write_varint(&mut bytes, self.party as u64);
+        write_varint(&mut bytes, self.party_count as u64);
+        let shares: Vec<(WireId, bool)> = self
+            .shares
+            .iter()
+            .map(|share| (share.wire, share.share))
+            .collect();
+        bytes.extend(pack_shares(&shares));
+        bytes
+    }
+
+    pub fn from_packed(bytes: &[u8]) -> Result<Self> {
+        let body = bytes
+            .strip_prefix(&PACKED_MAGIC)
+            .ok_or_else(|| anyhow::anyhow!("Not a packed share export"))?;
+        let mut cursor = Cursor::new(body);
+        let version = u32::try_from(cursor.varint()?)?;
+        if version != EXPORT_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported share export version {}",
+                version
+            ));
+        }
+        Ok(Self {
+            version,
+            session: cursor.varint()?,
+            circuit: cursor.string()?,
+            party: cursor.varint()? as usize,
+            party_count: cursor.varint()? as usize,
+            shares: unpack_shares(cursor.rest())?
+                .into_iter()
+                .map(|(wire, share)| WireShare { wire, share })
+                .collect(),
+        })
+    }
+
+    /// Import one export per party as party shares for `GmwProtocol::execute_circuit`
+    /// All exports must belong to the same session and circuit, or the error
+    /// is a `DomainMismatch`, and must cover the same wires
+    pub fn combine(exports: &[WireShareExport]) -> Result<PartyShares> {
+        let first = exports
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No share exports to combine"))?;
+        if exports.len() != first.party_count {
+            return Err(anyhow::anyhow!(
+                "Expected {} share exports, got {}",
+                first.party_count,
+                exports.len()
+            ));
+        }
+
+        let mut shares: PartyShares = vec![HashMap::new(); first.party_count];
+        let mut seen = vec![false; first.party_count];
+        let domain = first.domain();
+        for export in exports {
+            domain.check(&export.domain())?;
+            if export.party_count != first.party_count {
+                return Err(anyhow::anyhow!(
+                    "Share export of party {} is for {} parties, not {}",
+                    export.party,
+                    export.party_count,
+                    first.party_count
+                ));
+            }
+            let slot = seen
+                .get_mut(export.party)
+                .filter(|seen| !**seen)
+                .ok_or_else(|| anyhow::anyhow!("Unexpected shares for party {}", export.party))?;
+            *slot = true;
+
+            let wires = || export.shares.iter().map(|share| share.wire);
+            if !wires().eq(first.shares.iter().map(|share| share.wire)) {
+                return Err(anyhow::anyhow!(
+                    "Share export of party {} covers different wires",
+                    export.party
+                ));
+            }
+            shares[export.party] = export
+                .shares
+                .iter()
+                .map(|share| (share.wire, share.share))
+                .collect();
+        }
+
+        Ok(shares)
+    }
+
+    /// Move a session's shares to a new party set of `new_party_count`,
+    /// adding or removing parties, e.g. to rotate the holders of long-lived
+    /// shared state between evaluations
+    /// Every current party must cooperate: each splits its share of every wire
+    /// into one fresh subshare per new party, and each new party XORs what it
+    /// receives. The old shares are useless against the new ones, so departing
+    /// parties learn nothing by keeping theirs. Returns one export per new
+    /// party, in the same session
+    pub fn reshare(
+        exports: &[WireShareExport],
+        new_party_count: usize,
+    ) -> Result<Vec<WireShareExport>> {
+        if new_party_count < 2 {
+            return Err(anyhow::anyhow!(
+                "Need at least 2 parties to hold shares, got {}",
+                new_party_count
+            ));
+        }
+        let shares = Self::combine(exports)?;
+        let first = &exports[0];
+
+        let mut new_shares = vec![Vec::with_capacity(first.shares.len()); new_party_count];
+        for share in &first.shares {
+            let wire_shares: Vec<bool> = shares.iter().map(|party| party[&share.wire]).collect();
+            let subshares = reshare(&wire_shares, new_party_count, rand::random::<bool>);
+            for (party, subshare) in subshares.into_iter().enumerate() {
+                new_shares[party].push((share.wire, subshare));
+            }
+        }
+
+        let domain = first.domain();
+        Ok(new_shares
+            .into_iter()
+            .enumerate()
+            .map(|(party, shares)| Self::new(&domain, party, new_party_count, shares))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::{LocalEvaluator, PreparedCircuit};
+    use crate::domain::DomainMismatch;
+    use crate::protocol::GmwProtocol;
+
+    #[test]
+    fn test_export_and_combine() {
+        let circuit = reference::full_adder();
+        let prepared = PreparedCircuit::new(&circuit);
+        let protocol = GmwProtocol::new(3).unwrap();
+        let inputs = [true, false, true];
+        let wires: Vec<WireId> = circuit.gates.iter().map(|gate| gate.id).collect();
+
+        let exports = protocol
+            .export_wires(&prepared, &inputs, &wires, 42)
+            .unwrap();
+        let exports: Vec<WireShareExport> = exports
+            .iter()
+            .map(|export| WireShareExport::from_json(&export.to_json().unwrap()).unwrap())
+            .collect();
+        for export in &exports {
+            let packed = export.to_packed();
+            assert!(packed.len() * 4 < export.to_json().unwrap().len());
+            assert_eq!(&WireShareExport::from_packed(&packed).unwrap(), export);
+        }
+        assert!(WireShareExport::from_packed(&exports[0].to_packed()[1..]).is_err());
+        assert_eq!(exports[0].circuit, circuit.name);
+        let shares = WireShareExport::combine(&exports).unwrap();
+
+        let expected = LocalEvaluator::evaluate(&circuit, &inputs).unwrap();
+        for wire in wires {
+            let value = shares.iter().fold(false, |acc, party| acc ^ party[&wire]);
+            assert_eq!(value, expected[&wire], "wire {wire}");
+        }
+    }
+
+    #[test]
+    fn test_reshare_changes_party_set() {
+        let circuit = reference::full_adder();
+        let prepared = PreparedCircuit::new(&circuit);
+        let inputs = [true, true, false];
+        let wires: Vec<WireId> = circuit.gates.iter().map(|gate| gate.id).collect();
+        let exports = GmwProtocol::new(3)
+            .unwrap()
+            .export_wires(&prepared, &inputs, &wires, 9)
+            .unwrap();
+        let expected = LocalEvaluator::evaluate(&circuit, &inputs).unwrap();
+
+        for new_party_count in [2, 5] {
+            let reshared = WireShareExport::reshare(&exports, new_party_count).unwrap();
+            assert_eq!(reshared.len(), new_party_count);
+            assert!(reshared.iter().all(|export| export.session == 9));
+            let shares = WireShareExport::combine(&reshared).unwrap();
+            for &wire in &wires {
+                let value = shares.iter().fold(false, |acc, party| acc ^ party[&wire]);
+                assert_eq!(value, expected[&wire], "wire {wire}");
+            }
+        }
+
+        assert!(WireShareExport::reshare(&exports, 1).is_err());
+        assert!(WireShareExport::reshare(&exports[..2], 2).is_err());
+    }
+
+    #[test]
+    fn test_combine_rejects_mismatched_exports() {
+        let export = |session, party| {
+            WireShareExport::new(&ShareDomain::new(session, "and"), party, 2, vec![(1, true)])
+        };
+
+        assert!(WireShareExport::combine(&[export(1, 0), export(1, 1)]).is_ok());
+        let error = WireShareExport::combine(&[export(1, 0), export(2, 1)]).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DomainMismatch>().unwrap().found,
+            ShareDomain::new(2, "and")
+        );
+        let mut other_circuit = export(1, 1);
+        other_circuit.circuit = "or".to_string();
+        assert!(WireShareExport::combine(&[export(1, 0), other_circuit]).is_err());
+        assert!(WireShareExport::combine(&[export(1, 0), export(1, 0)]).is_err());
+        assert!(WireShareExport::combine(&[export(1, 0)]).is_err());
+    }
+
+    #[test]
+    fn test_from_json_rejects_untagged_exports() {
+        let untagged =
+            r#"{"version": 1, "session": 7, "party": 0, "party_count": 2, "shares": []}"#;
+        assert!(WireShareExport::from_json(untagged).is_err());
+        // The circuit name is required, not defaulted
+        let unnamed = untagged.replace("\"version\": 1", "\"version\": 2");
+        assert!(WireShareExport::from_json(&unnamed).is_err());
+    }
+}
@@ -0,0 +1,385 @@
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::circuit::{Circuit, GateType, Layering, WireId};
+use crate::errors::ErrorCode;
+use crate::gates::ot_sender;
+use crate::kernel::algebra::{
+    and_local_term, combine_and_share, cross_term_messages, not_shares, reconstruct, split_shares,
+    xor_shares,
+};
+use crate::ot::OtChannels;
+use crate::production::{local_simulation_refused, LocalSimulationRefused};
+
+/// Step-by-step record of a GMW run, for teaching
+/// Every value comes from an actual evaluation: shares are drawn from a
+/// seeded RNG and cross terms go through real OTs, so the same seed and
+/// inputs always produce the same trace
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub circuit: String,
+    pub party_count: usize,
+    pub seed: u64,
+    pub steps: Vec<ExplainStep>,
+}
+
+/// One event of an explained run
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ExplainStep {
+    /// An input value split into one XOR share per party
+    ShareInput {
+        name: String,
+        wire: WireId,
+        value: bool,
+        shares: Vec<bool>,
+    },
+    /// XOR or NOT: every party works on its own shares, no communication
+    LocalGate {
+        wire: WireId,
+        gate_type: GateType,
+        inputs: Vec<WireId>,
+        layer: usize,
+        shares: Vec<bool>,
+    },
+    /// AND or OR: local terms plus one OT per pair of parties for the cross terms
+    InteractiveGate {
+        wire: WireId,
+        gate_type: GateType,
+        inputs: Vec<WireId>,
+        layer: usize,
+        ot_count: usize,
+        local_terms: Vec<bool>,
+        cross_shares: Vec<bool>,
+        shares: Vec<bool>,
+    },
+    /// All parties publish their shares of an output
+    Reconstruct {
+        name: String,
+        wire: WireId,
+        shares: Vec<bool>,
+        value: bool,
+    },
+}
+
+/// Runs a circuit while recording every share
+pub struct Explainer {
+    party_count: usize,
+    seed: u64,
+}
+
+impl Explainer {
+    pub fn new(party_count: usize, seed: u64) -> Result<Self> {
+        if party_count < 2 {
+            return Err(anyhow::anyhow!("Need at least 2 parties for computation"));
+        }
+        Ok(Self { party_count, seed })
+    }
+
+    /// Evaluate the circuit layer by layer and record each step
+    /// The trace shows every party's shares, so production builds refuse it
+    pub fn run(&self, circuit: &Circuit, inputs: &[bool]) -> Result<Explanation> {
+        if local_simulation_refused() {
+            return Err(LocalSimulationRefused::new("Explainer::run").into());
+        }
+        let metadata = &circuit.metadata;
+        if inputs.len() != metadata.inputs.len() {
+            return Err(ErrorCode::InputMismatch.tag(anyhow::anyhow!(
+                "Circuit expects {} inputs but got {}",
+                metadata.inputs.len(),
+                inputs.len()
+            )));
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut channels = OtChannels::new(self.party_count);
+        let mut wires: HashMap<WireId, Vec<bool>> = HashMap::new();
+        let mut steps = Vec::new();
+
+        for (info, &value) in metadata.inputs.iter().zip(inputs) {
+            let shares = split_shares(value, self.party_count, || rng.gen());
+            wires.insert(info.id, shares.clone());
+            steps.push(ExplainStep::ShareInput {
+                name: info.name.clone(),
+                wire: info.id,
+                value,
+                shares,
+            });
+        }
+
+        // Same order as the engine's schedule: layer by layer, interactive gates first
+        let layering = Layering::new(circuit)?;
+        let mut order: Vec<usize> = (0..circuit.gates.len()).collect();
+        order.sort_by_key(|&i| {
+            (
+                layering.gate_depths[i],
+                !circuit.gates[i].gate_type.is_interactive(),
+            )
+        });
+
+        let mut interactive = 0;
+        for i in order {
+            let gate = &circuit.gates[i];
+            let layer = layering.gate_depths[i];
+            let input_shares = gate
+                .inputs
+                .iter()
+                .map(|wire| {
+                    wires
+                        .get(wire)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Wire {} has no shares", wire))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let pairs = || -> Result<Vec<(bool, bool)>> {
+                match &input_shares[..] {
+                    [x, y] => Ok(x.iter().copied().zip(y.iter().copied()).collect()),
+                    _ => Err(anyhow::anyhow!("Gate {} needs two inputs", gate.id)),
+                }
+            };
+
+            let step = match gate.gate_type {
+                GateType::XOR | GateType::NOT => {
+                    let shares = if gate.gate_type == GateType::XOR {
+                        xor_shares(&pairs()?)
+                    } else {
+                        not_shares(&input_shares[0])
+                    };
+                    ExplainStep::LocalGate {
+                        wire: gate.id,
+                        gate_type: gate.gate_type.clone(),
+                        inputs: gate.inputs.clone(),
+                        layer,
+                        shares,
+                    }
+                }
+                GateType::AND | GateType::OR => {
+                    // OR is evaluated as ~(~x & ~y)
+                    let is_or = gate.gate_type == GateType::OR;
+                    let operands: Vec<(bool, bool)> = pairs()?
+                        .into_iter()
+                        .enumerate()
+                        .map(|(party, (x, y))| {
+                            let flip = is_or && party == 0;
+                            (x ^ flip, y ^ flip)
+                        })
+                        .collect();
+
+                    let local_terms: Vec<bool> =
+                        operands.iter().map(|&pair| and_local_term(pair)).collect();
+                    let mut cross_shares = vec![false; self.party_count];
+                    for i in 0..self.party_count {
+                        for j in (i + 1)..self.party_count {
+                            let sender = ot_sender(i, j, interactive);
+                            let receiver = i + j - sender;
+                            let mask = rng.gen();
+                            let messages = cross_term_messages(operands[sender], mask);
+                            cross_shares[sender] ^= mask;
+                            cross_shares[receiver] ^= channels
+                                .channel(sender, receiver)
+                                .transfer_1_out_of_4(messages, operands[receiver])?;
+                        }
+                    }
+                    interactive += 1;
+
+                    let mut shares: Vec<bool> = local_terms
+                        .iter()
+                        .zip(&cross_shares)
+                        .map(|(&local, &cross)| combine_and_share(local, [cross]))
+                        .collect();
+                    if is_or {
+                        shares = not_shares(&shares);
+                    }
+
+                    ExplainStep::InteractiveGate {
+                        wire: gate.id,
+                        gate_type: gate.gate_type.clone(),
+                        inputs: gate.inputs.clone(),
+                        layer,
+                        ot_count: self.party_count * (self.party_count - 1) / 2,
+                        local_terms,
+                        cross_shares,
+                        shares,
+                    }
+                }
+            };
+
+            let (ExplainStep::LocalGate { shares, .. }
+            | ExplainStep::InteractiveGate { shares, .. }) = &step
+            else {
+                unreachable!("gate steps carry shares");
+            };
+            wires.insert(gate.id, shares.clone());
+            steps.push(step);
+        }
+
+        for info in &metadata.outputs {
+            let shares = wires
+                .get(&info.id)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Missing output gate {}", info.id))?;
+            steps.push(ExplainStep::Reconstruct {
+                name: info.name.clone(),
+                wire: info.id,
+                value: reconstruct(&shares),
+                shares,
+            });
+        }
+
+        Ok(Explanation {
+            circuit: circuit.name.clone(),
+            party_count: self.party_count,
+            seed: self.seed,
+            steps,
+        })
+    }
+}
+
+/// Render shares as a bit string, party 0 first
+fn bits(shares: &[bool]) -> String {
+    shares
+        .iter()
+        .map(|&share| if share { '1' } else { '0' })
+        .collect()
+}
+
+impl Explanation {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Narrate the run as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# GMW run of `{}` with {} parties (seed {})\n",
+            self.circuit, self.party_count, self.seed
+        );
+        let _ = writeln!(
+            out,
+            "Shares are listed party 0 first; a value is the XOR of all its shares.\n"
+        );
+
+        let mut section = "";
+        for step in &self.steps {
+            let title = match step {
+                ExplainStep::ShareInput { .. } => "## Input sharing",
+                ExplainStep::Reconstruct { .. } => "## Output reconstruction",
+                _ => "## Gate evaluation",
+            };
+            if title != section {
+                if !section.is_empty() {
+                    out.push('\n');
+                }
+                let _ = writeln!(out, "{title}\n");
+                section = title;
+            }
+
+            let _ = match step {
+                ExplainStep::ShareInput {
+                    name,
+                    wire,
+                    value,
+                    shares,
+                } => writeln!(
+                    out,
+                    "- Input `{name}` (wire {wire}) = {} is split into shares `{}`",
+                    u8::from(*value),
+                    bits(shares)
+                ),
+                ExplainStep::LocalGate {
+                    wire,
+                    gate_type,
+                    inputs,
+                    layer,
+                    shares,
+                } => writeln!(
+                    out,
+                    "- Layer {layer}: {gate_type:?} gate {wire} on wires {inputs:?} is free: {} → shares `{}`",
+                    match gate_type {
+                        GateType::NOT => "party 0 flips its share, the others keep theirs",
+                        _ => "each party XORs its own shares",
+                    },
+                    bits(shares)
+                ),
+                ExplainStep::InteractiveGate {
+                    wire,
+                    gate_type,
+                    inputs,
+                    layer,
+                    ot_count,
+                    local_terms,
+                    cross_shares,
+                    shares,
+                } => writeln!(
+                    out,
+                    "- Layer {layer}: {gate_type:?} gate {wire} on wires {inputs:?} needs communication{}: local terms xᵢ·yᵢ = `{}`, {ot_count} OT(s) give cross-term shares `{}` → shares `{}`",
+                    if *gate_type == GateType::OR {
+                        " (evaluated as ¬(¬x ∧ ¬y))"
+                    } else {
+                        ""
+                    },
+                    bits(local_terms),
+                    bits(cross_shares),
+                    bits(shares)
+                ),
+                ExplainStep::Reconstruct {
+                    name,
+                    wire,
+                    shares,
+                    value,
+                } => writeln!(
+                    out,
+                    "- Output `{name}` (wire {wire}): shares `{}` XOR to {}",
+                    bits(shares),
+                    u8::from(*value)
+                ),
+            };
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::LocalEvaluator;
+
+    #[test]
+    fn test_explanation_is_deterministic_and_correct() {
+        let circuit = Circuit::from_file("circuits/mux_2to1.json").unwrap();
+        let explainer = Explainer::new(3, 7).unwrap();
+
+        for value in 0..8u8 {
+            let inputs: Vec<bool> = (0..3).map(|bit| value >> bit & 1 == 1).collect();
+            let explanation = explainer.run(&circuit, &inputs).unwrap();
+            assert_eq!(
+                explanation.to_json().unwrap(),
+                explainer.run(&circuit, &inputs).unwrap().to_json().unwrap()
+            );
+
+            let Some(ExplainStep::Reconstruct { wire, value, .. }) = explanation.steps.last()
+            else {
+                panic!("explanation ends with reconstruction");
+            };
+            assert_eq!(
+                *value,
+                LocalEvaluator::get_output(&circuit, &inputs, *wire).unwrap()
+            );
+        }
+
+        let markdown = explainer
+            .run(&circuit, &[true, false, true])
+            .unwrap()
+            .to_markdown();
+        assert!(markdown.contains("## Input sharing"));
+        assert!(markdown.contains("needs communication (evaluated as ¬(¬x ∧ ¬y))"));
+    }
+}
@@ -0,0 +1,263 @@
+//! C ABI for embedding the evaluator, declared in `include/gmw.h`
+//!
+//! Protocols and circuits are opaque handles owned by the caller and
+//! released with their `_free` function. Fallible calls return 0 on success
+//! and -1 on failure, or a null handle; `gmw_last_error` then describes the
+//! failure. Runs are local simulations: every party lives in this process
+
+use anyhow::Result;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+use std::slice;
+
+use crate::circuit::{Circuit, CircuitFormat, PreparedCircuit};
+use crate::protocol::GmwProtocol;
+use crate::report::ExecutionReport;
+
+/// Protocol handle
+pub struct GmwFfiProtocol(GmwProtocol);
+
+/// Circuit handle with its pending inputs and the outputs of the latest run
+pub struct GmwFfiCircuit {
+    prepared: PreparedCircuit,
+    inputs: Vec<bool>,
+    report: Option<ExecutionReport>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Record the outcome of a call for `gmw_last_error`
+fn status(result: Result<()>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            set_error(&error);
+            -1
+        }
+    }
+}
+
+fn set_error(error: &anyhow::Error) {
+    let message = format!("{error:#}").replace('\0', " ");
+    let message = CString::new(message).expect("interior NULs were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn handle<T>(result: Result<T>) -> *mut T {
+    match result {
+        Ok(value) => Box::into_raw(Box::new(value)),
+        Err(error) => {
+            set_error(&error);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+/// `pointer` must be null or valid for the lifetime of the result
+unsafe fn deref<'a, T>(pointer: *mut T, what: &str) -> Result<&'a mut T> {
+    pointer
+        .as_mut()
+        .ok_or_else(|| anyhow::anyhow!("{} handle is null", what))
+}
+
+/// Message of the latest failure on this thread, or null if none
+/// The string stays valid until the next failing call on this thread
+#[no_mangle]
+pub extern "C" fn gmw_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// New protocol for `parties` parties, or null
+#[no_mangle]
+pub extern "C" fn gmw_protocol_new(parties: usize) -> *mut GmwFfiProtocol {
+    handle(GmwProtocol::new(parties).map(GmwFfiProtocol))
+}
+
+/// # Safety
+/// `protocol` must be null or a handle from `gmw_protocol_new` not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn gmw_protocol_free(protocol: *mut GmwFfiProtocol) {
+    if !protocol.is_null() {
+        drop(Box::from_raw(protocol));
+    }
+}
+
+/// Parse a circuit from `len` bytes at `data` in `format` (`"json"`,
+/// `"bristol"`, `"binary"` or `"text"`) and prepare it for evaluation, or null
+///
+/// # Safety
+/// `data` must point to `len` readable bytes and `format` to a
+/// NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn gmw_circuit_load(
+    data: *const u8,
+    len: usize,
+    format: *const c_char,
+) -> *mut GmwFfiCircuit {
+    let load = || {
+        if data.is_null() || format.is_null() {
+            return Err(anyhow::anyhow!("Circuit data or format is null"));
+        }
+        let format: CircuitFormat = CStr::from_ptr(format).to_str()?.parse()?;
+        let circuit = Circuit::decode(slice::from_raw_parts(data, len), format)?;
+        Ok(GmwFfiCircuit {
+            inputs: vec![false; circuit.metadata.inputs.len()],
+            prepared: PreparedCircuit::new(&circuit),
+            report: None,
+        })
+    };
+    handle(load())
+}
+
+/// # Safety
+/// `circuit` must be null or a handle from `gmw_circuit_load` not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn gmw_circuit_free(circuit: *mut GmwFfiCircuit) {
+    if !circuit.is_null() {
+        drop(Box::from_raw(circuit));
+    }
+}
+
+/// Number of single-bit inputs, in metadata order; 0 for a null handle
+///
+/// # Safety
+/// `circuit` must be null or a live circuit handle
+#[no_mangle]
+pub unsafe extern "C" fn gmw_circuit_input_count(circuit: *const GmwFfiCircuit) -> usize {
+    circuit.as_ref().map_or(0, |circuit| circuit.inputs.len())
+}
+
+/// Number of single-bit outputs, in metadata order; 0 for a null handle
+///
+/// # Safety
+/// `circuit` must be null or a live circuit handle
+#[no_mangle]
+pub unsafe extern "C" fn gmw_circuit_output_count(circuit: *const GmwFfiCircuit) -> usize {
+    circuit
+        .as_ref()
+        .map_or(0, |circuit| circuit.prepared.metadata.outputs.len())
+}
+
+/// Set input `index` for the next run; inputs start out false
+///
+/// # Safety
+/// `circuit` must be null or a live circuit handle
+#[no_mangle]
+pub unsafe extern "C" fn gmw_circuit_set_input(
+    circuit: *mut GmwFfiCircuit,
+    index: usize,
+    value: bool,
+) -> c_int {
+    status((|| {
+        let circuit = deref(circuit, "Circuit")?;
+        let count = circuit.inputs.len();
+        let input = circuit
+            .inputs
+            .get_mut(index)
+            .ok_or_else(|| anyhow::anyhow!("Input {} out of range for {} inputs", index, count))?;
+        *input = value;
+        Ok(())
+    })())
+}
+
+/// Evaluate `circuit` on its current inputs with `protocol`, keeping the
+/// outputs for `gmw_circuit_output`
+///
+/// # Safety
+/// Both handles must be null or live
+#[no_mangle]
+pub unsafe extern "C" fn gmw_run(
+    protocol: *mut GmwFfiProtocol,
+    circuit: *mut GmwFfiCircuit,
+) -> c_int {
+    status((|| {
+        let protocol = deref(protocol, "Protocol")?;
+        let circuit = deref(circuit, "Circuit")?;
+        // A failed run leaves no stale outputs behind
+        circuit.report = None;
+        circuit.report = Some(
+            protocol
+                .0
+                .run_prepared(&circuit.prepared, &circuit.inputs)?,
+        );
+        Ok(())
+    })())
+}
+
+/// Write output `index` of the latest run to `value`
+///
+/// # Safety
+/// `circuit` must be null or live, and `value` null or writable
+#[no_mangle]
+pub unsafe extern "C" fn gmw_circuit_output(
+    circuit: *const GmwFfiCircuit,
+    index: usize,
+    value: *mut bool,
+) -> c_int {
+    status((|| {
+        let circuit = circuit
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Circuit handle is null"))?;
+        let report = circuit
+            .report
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Circuit has not been run"))?;
+        let (_, output) = report
+            .outputs
+            .get(index)
+            .ok_or_else(|| anyhow::anyhow!("Output {} out of range", index))?;
+        let value = value
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Output pointer is null"))?;
+        *value = *output;
+        Ok(())
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_ffi_full_adder() {
+        let json = fs::read("circuits/full_adder.json").unwrap();
+        unsafe {
+            let protocol = gmw_protocol_new(3);
+            let circuit = gmw_circuit_load(json.as_ptr(), json.len(), c"json".as_ptr());
+            assert!(!protocol.is_null() && !circuit.is_null());
+            assert_eq!(gmw_circuit_input_count(circuit), 3);
+
+            for (index, value) in [true, true, false].into_iter().enumerate() {
+                assert_eq!(gmw_circuit_set_input(circuit, index, value), 0);
+            }
+            let mut sum = true;
+            assert_eq!(gmw_circuit_output(circuit, 0, &mut sum), -1);
+            assert_eq!(gmw_run(protocol, circuit), 0);
+
+            // 1 + 1 + 0 = 0b10
+            let outputs: Vec<bool> = (0..gmw_circuit_output_count(circuit))
+                .map(|index| {
+                    let mut value = false;
+                    assert_eq!(gmw_circuit_output(circuit, index, &mut value), 0);
+                    value
+                })
+                .collect();
+            assert_eq!(outputs, vec![false, true]);
+
+            assert_eq!(gmw_circuit_set_input(circuit, 3, true), -1);
+            let error = CStr::from_ptr(gmw_last_error()).to_str().unwrap();
+            assert!(error.contains("out of range"));
+
+            assert!(gmw_protocol_new(1).is_null());
+            assert!(gmw_circuit_load(json.as_ptr(), 4, c"json".as_ptr()).is_null());
+
+            gmw_circuit_free(circuit);
+            gmw_protocol_free(protocol);
+        }
+    }
+}
@@ -1,93 +1,218 @@
-use crate::ot::BitOT;
+use crate::kernel::algebra::{and_local_term, combine_and_share, cross_term_messages};
+use crate::ot::{
+    quad_open, ExtensionChoices, ExtensionReceiver, ExtensionRequest, ExtensionResponse,
+    ExtensionSender, OtChannels, QuadMessages,
+};
+use crate::production::{local_simulation_refused, LocalSimulationRefused};
 use anyhow::Result;
+use rand::Rng;
 
 /// Compute AND gate for n parties using GMW protocol
 /// Each party has shares (xi, yi) and needs to compute xi & yi locally,
 /// then use OT to compute cross terms xi*yj ⊕ xj*yi for all pairs i,j
+/// Production builds refuse it, since it takes every party's shares
 pub fn and_gate(party_shares: &[(bool, bool)]) -> Result<Vec<bool>> {
+    if local_simulation_refused() {
+        return Err(LocalSimulationRefused::new("gates::and_gate").into());
+    }
+    and_gate_rotated(party_shares, 0, &mut OtChannels::new(party_shares.len()))
+}
+
+/// AND gate with OT roles assigned by `rotation` (see `ot_sender`), running
+/// its cross terms over `channels`
+/// Passing a per-gate counter spreads the sender-side work evenly, and
+/// keeping the channels across gates reuses their base OTs
+pub(crate) fn and_gate_rotated(
+    party_shares: &[(bool, bool)],
+    rotation: usize,
+    channels: &mut OtChannels,
+) -> Result<Vec<bool>> {
     let n = party_shares.len();
 
     if n < 2 {
         return Err(anyhow::anyhow!("Need at least 2 parties for AND gate"));
     }
 
-    // Step 1: Each party computes local term xi & yi
-    let local_terms: Vec<bool> = party_shares.iter().map(|(xi, yi)| *xi & *yi).collect();
+    // Step 1: Compute each party's share of the cross terms between all pairs
+    let cross_shares = cross_term_shares_rotated(party_shares, rotation, channels)?;
+
+    // Step 2: Each party combines its local term xi & yi with its cross-term share
+    let result_shares = party_shares
+        .iter()
+        .zip(cross_shares)
+        .map(|(&shares, cross_share)| combine_and_share(and_local_term(shares), [cross_share]))
+        .collect();
+
+    Ok(result_shares)
+}
+
+/// Compute each party's share of the cross terms xi*yj ⊕ xj*yi using OT
+/// Party i's result is the XOR of its shares over every other party j
+/// Production builds refuse it, as `and_gate`
+pub fn cross_term_shares(party_shares: &[(bool, bool)]) -> Result<Vec<bool>> {
+    if local_simulation_refused() {
+        return Err(LocalSimulationRefused::new("gates::cross_term_shares").into());
+    }
+    cross_term_shares_rotated(party_shares, 0, &mut OtChannels::new(party_shares.len()))
+}
 
-    // Step 2: Compute cross terms between all pairs of parties
-    let mut cross_terms: Vec<Vec<bool>> = vec![vec![false; n]; n];
+/// Cross-term shares with OT roles assigned by `rotation`
+/// Each sender draws its share ri and offers the four messages of
+/// `cross_term_messages` in a 1-out-of-4 OT on its channel to the receiver
+pub(crate) fn cross_term_shares_rotated(
+    party_shares: &[(bool, bool)],
+    rotation: usize,
+    channels: &mut OtChannels,
+) -> Result<Vec<bool>> {
+    let n = party_shares.len();
+    if channels.parties() != n {
+        return Err(anyhow::anyhow!(
+            "OT channels are for {} parties, not {}",
+            channels.parties(),
+            n
+        ));
+    }
+    let mut rng = rand::thread_rng();
+    let mut cross_shares = vec![false; n];
 
     for i in 0..n {
         for j in (i + 1)..n {
-            let (xi, yi) = party_shares[i];
-            let (xj, yj) = party_shares[j];
+            let sender = ot_sender(i, j, rotation);
+            let receiver = i + j - sender;
 
             // Compute cross term: xi*yj ⊕ xj*yi using OT
-            let (cross_ij, cross_ji) = compute_cross_term_ot((xi, yi), (xj, yj))?;
-
-            // Store cross terms for each party
-            cross_terms[i][j] = cross_ij;
-            cross_terms[j][i] = cross_ji;
+            let sender_share = rng.gen::<bool>();
+            let messages = cross_term_messages(party_shares[sender], sender_share);
+            let receiver_share = channels
+                .channel(sender, receiver)
+                .transfer_1_out_of_4(messages, party_shares[receiver])?;
+
+            cross_shares[sender] ^= sender_share;
+            cross_shares[receiver] ^= receiver_share;
         }
     }
 
-    // Step 3: Each party combines local term with all cross terms
-    let mut result_shares = Vec::with_capacity(n);
-    for i in 0..n {
-        let mut result = local_terms[i];
+    Ok(cross_shares)
+}
 
-        // XOR all cross terms involving party i
-        for j in 0..n {
-            if i != j {
-                result ^= cross_terms[i][j];
-            }
+/// Which of parties `i < j` acts as OT sender for their cross term
+/// Within one gate the roles follow a round-robin orientation, so with an
+/// odd party count every party sends equally often; an odd `rotation` flips
+/// every pair, so consecutive gates balance even party counts too.
+/// Rotation 0 keeps party i as sender for 2 parties
+pub fn ot_sender(i: usize, j: usize, rotation: usize) -> usize {
+    if (i + j + rotation) % 2 == 1 {
+        i
+    } else {
+        j
+    }
+}
+
+/// Receiver's first message of a cross term: an extension batch choosing
+/// with its shares (xj, then yj)
+pub struct CrossTermRequest {
+    pub request: ExtensionRequest,
+}
+
+/// Sender's masked key pairs and the four masked cross-term messages
+pub struct CrossTermResponse {
+    pub response: ExtensionResponse,
+    pub ciphertexts: [bool; 4],
+}
+
+/// Party i's half of a cross term: offers the four 1-out-of-4 messages
+/// The messages are masked under two key pairs (see `QuadMessages`), and
+/// the receiver obtains the xj-th key of the first pair and the yj-th key
+/// of the second through two transfers of the OT extension it shares with
+/// party i, so it learns the one message for its (xj, yj) and nothing about
+/// xi or yi
+pub struct CrossTermSender {
+    keys: [(Vec<u8>, Vec<u8>); 2],
+    ciphertexts: [bool; 4],
+    share: bool,
+}
+
+impl CrossTermSender {
+    /// Draw party i's random share and commit to the messages
+    pub fn new(party_shares: (bool, bool)) -> Self {
+        Self::with_rng(party_shares, &mut rand::thread_rng())
+    }
+
+    /// `new` drawing the share and the masking keys from `rng`, for seeded
+    /// runs
+    pub fn with_rng(party_shares: (bool, bool), rng: &mut impl Rng) -> Self {
+        // Party i generates random bit ri (will be party i's share)
+        let share = rng.gen::<bool>();
+
+        // Party i prepares 4 messages for all possible (xj, yj) values:
+        // (0,0): ri, (0,1): xi ⊕ ri, (1,0): yi ⊕ ri, (1,1): xi ⊕ yi ⊕ ri
+        let messages = cross_term_messages(party_shares, share);
+        let QuadMessages { keys, ciphertexts } = QuadMessages::new(messages, rng);
+        let [[k00, k01], [k10, k11]] = keys;
+
+        Self {
+            keys: [(k00, k01), (k10, k11)],
+            ciphertexts,
+            share,
         }
+    }
 
-        result_shares.push(result);
+    /// Answer the receiver's request over `extension`, the sending end of
+    /// the pair's OT extension; returns party i's cross-term share
+    pub fn respond(
+        self,
+        request: CrossTermRequest,
+        extension: &mut ExtensionSender,
+    ) -> Result<(bool, CrossTermResponse)> {
+        let response = extension.respond(request.request, &self.keys)?;
+        Ok((
+            self.share,
+            CrossTermResponse {
+                response,
+                ciphertexts: self.ciphertexts,
+            },
+        ))
     }
+}
 
-    Ok(result_shares)
+/// Party j's half of a cross term: chooses with its shares (xj, yj)
+pub struct CrossTermReceiver {
+    pending: ExtensionChoices,
+    choice: (bool, bool),
 }
 
-/// Compute cross term between two parties using OT
-/// Returns (share_for_party_i, share_for_party_j)
-fn compute_cross_term_ot(
-    party_i_shares: (bool, bool),
-    party_j_shares: (bool, bool),
-) -> Result<(bool, bool)> {
-    let (xi, yi) = party_i_shares;
-    let (xj, yj) = party_j_shares;
-
-    // Party i acts as sender, party j as receiver
-    // We need to compute xi·yj ⊕ xj·yi and split it into shares
-
-    // Party i generates random bit ri (will be party i's share)
-    let ri = rand::random::<bool>();
-
-    // Party j needs to receive: (xi·yj ⊕ xj·yi) ⊕ ri
-    // Using 1-out-of-4 OT based on (xj, yj) as choice bits
-
-    // Party i prepares 4 messages for all possible (xj, yj) values:
-    // (0,0): xi·0 ⊕ 0·yi ⊕ ri = 0 ⊕ ri = ri
-    // (0,1): xi·1 ⊕ 0·yi ⊕ ri = xi ⊕ ri
-    // (1,0): xi·0 ⊕ 1·yi ⊕ ri = yi ⊕ ri
-    // (1,1): xi·1 ⊕ 1·yi ⊕ ri = xi ⊕ yi ⊕ ri
-    let messages = (
-        ri,           // (0,0)
-        ri ^ xi,      // (0,1)
-        ri ^ yi,      // (1,0)
-        ri ^ xi ^ yi, // (1,1)
-    );
-
-    let choice = (xj, yj);
-    let rj = BitOT::execute_1_out_of_4(messages, choice)?;
-
-    Ok((ri, rj))
+impl CrossTermReceiver {
+    /// Request the xj-th and yj-th keys over `extension`, the receiving end
+    /// of the pair's OT extension
+    pub fn new(
+        party_shares: (bool, bool),
+        extension: &mut ExtensionReceiver,
+    ) -> (Self, CrossTermRequest) {
+        let (xj, yj) = party_shares;
+        let (pending, request) = extension.request(&[xj, yj]);
+        (
+            Self {
+                pending,
+                choice: party_shares,
+            },
+            CrossTermRequest { request },
+        )
+    }
+
+    /// Unmask the response; returns party j's share xi·yj ⊕ xj·yi ⊕ ri
+    pub fn finish(self, response: CrossTermResponse) -> Result<bool> {
+        let keys = self.pending.finish(response.response)?;
+        match &keys[..] {
+            [first, second] => Ok(quad_open(response.ciphertexts, first, second, self.choice)),
+            _ => Err(anyhow::anyhow!("OT returned {} keys", keys.len())),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ot::{ReceiverSetup, SenderSetup};
 
     #[test]
     fn test_and_gate_2_party() {
@@ -148,4 +273,97 @@ mod tests {
 
         assert_eq!(reconstructed, false);
     }
+
+    #[test]
+    fn test_rotated_roles_are_balanced() {
+        for n in 2..=6 {
+            let mut sent = vec![0; n];
+            for rotation in 0..2 {
+                for i in 0..n {
+                    for j in (i + 1)..n {
+                        sent[ot_sender(i, j, rotation)] += 1;
+                    }
+                }
+            }
+            // Over two consecutive gates every party sends to each peer once
+            assert!(sent.iter().all(|&count| count == n - 1), "{n} parties");
+        }
+
+        // x = 1 ⊕ 1 ⊕ 0 ⊕ 1 = 1, y = 0 ⊕ 1 ⊕ 1 ⊕ 1 = 1
+        let shares = [(true, false), (true, true), (false, true), (true, true)];
+        let mut channels = OtChannels::new(shares.len());
+        for rotation in 0..4 {
+            let result = and_gate_rotated(&shares, rotation, &mut channels).unwrap();
+            assert!(result.iter().fold(false, |acc, &share| acc ^ share));
+        }
+    }
+
+    #[test]
+    fn test_cross_term_halves() {
+        // One extension for all sixteen cross terms, set up from its halves
+        let mut rng = rand::thread_rng();
+        let (receiving, offer) = ReceiverSetup::new(&mut rng).unwrap();
+        let (sending, request) = SenderSetup::new(offer, &mut rng).unwrap();
+        let (mut receiving, response) = receiving.respond(request).unwrap();
+        let mut sending = sending.finish(response).unwrap();
+        for (xi, yi, xj, yj) in (0..16).map(|i| (i & 1 == 1, i & 2 == 2, i & 4 == 4, i & 8 == 8)) {
+            let sender = CrossTermSender::new((xi, yi));
+            let (receiver, request) = CrossTermReceiver::new((xj, yj), &mut receiving);
+            let (ri, response) = sender.respond(request, &mut sending).unwrap();
+            let rj = receiver.finish(response).unwrap();
+            assert_eq!(ri ^ rj, (xi & yj) ^ (xj & yi));
+        }
+    }
+
+    #[test]
+    fn test_channels_reused_across_gates() {
+        // x = 1, y = 1
+        let shares = [(true, false), (false, true)];
+        let mut channels = OtChannels::new(2);
+        for rotation in 0..400 {
+            let result = and_gate_rotated(&shares, rotation, &mut channels).unwrap();
+            assert!(result[0] ^ result[1]);
+        }
+        assert_eq!(channels.transfers(), 800);
+        // Each direction runs at most KAPPA direct OTs and one extension setup
+        assert!(channels.base_transfers() <= 4 * crate::ot::KAPPA);
+    }
+
+    /// The receiver unmasks only m_{xj,yj}: decoding any other message with
+    /// the keys it received is right about half the time, so the response
+    /// tells it nothing about the sender's shares
+    #[test]
+    fn test_cross_term_receiver_learns_one_message() {
+        let choices = [(false, false), (false, true), (true, false), (true, true)];
+        let (mut sending, mut receiving) = crate::ot::extension_setup().unwrap();
+        for sender_shares in choices {
+            for (chosen, &receiver_shares) in choices.iter().enumerate() {
+                let mut right = [0; 4];
+                for _ in 0..400 {
+                    let sender = CrossTermSender::new(sender_shares);
+                    let (receiver, request) =
+                        CrossTermReceiver::new(receiver_shares, &mut receiving);
+                    let (share, response) = sender.respond(request, &mut sending).unwrap();
+                    let (m00, m01, m10, m11) = cross_term_messages(sender_shares, share);
+
+                    let keys = receiver.pending.finish(response.response).unwrap();
+                    let (first_key, second_key) = (&keys[0], &keys[1]);
+                    for (index, (guess, message)) in
+                        choices.iter().zip([m00, m01, m10, m11]).enumerate()
+                    {
+                        let decoded =
+                            quad_open(response.ciphertexts, first_key, second_key, *guess);
+                        right[index] += usize::from(decoded == message);
+                    }
+                }
+                for (index, &count) in right.iter().enumerate() {
+                    if index == chosen {
+                        assert_eq!(count, 400);
+                    } else {
+                        assert!((120..=280).contains(&count), "message {index}: {count}/400");
+                    }
+                }
+            }
+        }
+    }
 }
@@ -3,7 +3,53 @@ pub mod not;
 pub mod or;
 pub mod xor;
 
-pub use and::and_gate;
+pub(crate) use and::and_gate_rotated;
+pub use and::{
+    and_gate, cross_term_shares, ot_sender, CrossTermReceiver, CrossTermRequest, CrossTermResponse,
+    CrossTermSender,
+};
 pub use not::not_gate;
 pub use or::or_gate;
+pub(crate) use or::or_gate_rotated;
 pub use xor::xor_gate;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ot::OtChannels;
+
+    /// Every 3-party sharing of every pair of input bits
+    fn sharings() -> impl Iterator<Item = (bool, bool, Vec<(bool, bool)>)> {
+        (0..64u8).map(|bits| {
+            let shares: Vec<(bool, bool)> = (0..3)
+                .map(|party| (bits >> party & 1 == 1, bits >> (party + 3) & 1 == 1))
+                .collect();
+            let x = shares.iter().fold(false, |acc, &(x, _)| acc ^ x);
+            let y = shares.iter().fold(false, |acc, &(_, y)| acc ^ y);
+            (x, y, shares)
+        })
+    }
+
+    fn open(shares: Vec<bool>) -> bool {
+        shares.into_iter().fold(false, |acc, share| acc ^ share)
+    }
+
+    #[test]
+    fn test_3_party_truth_tables() {
+        for (x, y, shares) in sharings() {
+            assert_eq!(open(and_gate(&shares).unwrap()), x & y, "{shares:?}");
+            assert_eq!(open(or_gate(&shares).unwrap()), x | y, "{shares:?}");
+            assert_eq!(open(xor_gate(&shares).unwrap()), x ^ y, "{shares:?}");
+            let mut channels = OtChannels::new(shares.len());
+            for rotation in 0..3 {
+                let and = and_gate_rotated(&shares, rotation, &mut channels).unwrap();
+                assert_eq!(open(and), x & y);
+                let or = or_gate_rotated(&shares, rotation, &mut channels).unwrap();
+                assert_eq!(open(or), x | y);
+            }
+
+            let x_shares: Vec<bool> = shares.iter().map(|&(x, _)| x).collect();
+            assert_eq!(open(not_gate(&x_shares).unwrap()), !x);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::kernel::algebra::not_shares;
 use anyhow::Result;
 
 /// Compute NOT gate for n parties
@@ -10,12 +11,8 @@ pub fn not_gate(party_shares: &[bool]) -> Result<Vec<bool>> {
         return Err(anyhow::anyhow!("Need at least 2 parties for NOT gate"));
     }
 
-    let mut result_shares = party_shares.to_vec();
-
     // Only party 0 flips their share
-    result_shares[0] = !result_shares[0];
-
-    Ok(result_shares)
+    Ok(not_shares(party_shares))
 }
 
 #[cfg(test)]
@@ -1,12 +1,29 @@
-use crate::gates::and::and_gate;
+use crate::gates::and::and_gate_rotated;
 use crate::gates::not::not_gate;
+use crate::ot::OtChannels;
+use crate::production::{local_simulation_refused, LocalSimulationRefused};
 use anyhow::Result;
 
 /// Compute OR gate for n parties using De Morgan's law: x | y = ~(~x & ~y)
 /// 1. NOT both inputs
 /// 2. AND the results
 /// 3. NOT the final result
+///
+/// Production builds refuse it, as `and_gate`
 pub fn or_gate(party_shares: &[(bool, bool)]) -> Result<Vec<bool>> {
+    if local_simulation_refused() {
+        return Err(LocalSimulationRefused::new("gates::or_gate").into());
+    }
+    or_gate_rotated(party_shares, 0, &mut OtChannels::new(party_shares.len()))
+}
+
+/// OR gate with OT roles assigned by `rotation` and cross terms run over
+/// `channels`, as in `and_gate_rotated`
+pub(crate) fn or_gate_rotated(
+    party_shares: &[(bool, bool)],
+    rotation: usize,
+    channels: &mut OtChannels,
+) -> Result<Vec<bool>> {
     let n = party_shares.len();
 
     if n < 2 {
@@ -31,7 +48,7 @@ pub fn or_gate(party_shares: &[(bool, bool)]) -> Result<Vec<bool>> {
     // Step 2: AND the NOT results: ~x & ~y
     let not_x_and_not_y_shares: Vec<(bool, bool)> = not_x.into_iter().zip(not_y).collect();
 
-    let and_result = and_gate(&not_x_and_not_y_shares)?;
+    let and_result = and_gate_rotated(&not_x_and_not_y_shares, rotation, channels)?;
 
     // Step 3: NOT the final result: ~(~x & ~y) = x | y
     let or_result = not_gate(&and_result)?;
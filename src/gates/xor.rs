@@ -1,3 +1,4 @@
+use crate::kernel::algebra::xor_shares;
 use anyhow::Result;
 
 /// Compute XOR gate for n parties
@@ -10,9 +11,7 @@ pub fn xor_gate(party_shares: &[(bool, bool)]) -> Result<Vec<bool>> {
     }
 
     // Each party computes xi ⊕ yi locally
-    let result_shares: Vec<bool> = party_shares.iter().map(|(xi, yi)| *xi ^ *yi).collect();
-
-    Ok(result_shares)
+    Ok(xor_shares(party_shares))
 }
 
 #[cfg(test)]
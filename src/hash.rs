@@ -0,0 +1,68 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::blake3::Blake3;
+use crate::receipt::ReceiptHash;
+use crate::sha256::Sha256;
+
+/// Built-in hash primitive for circuit digests, commitments and storage
+/// authentication, selectable by name so a deployment standardized on one
+/// primitive can use it throughout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn digest(self, data: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(data),
+            HashAlgorithm::Blake3 => Blake3::digest(data),
+        }
+    }
+
+    /// MAC and PRF: HMAC-SHA256, or BLAKE3 in keyed mode
+    pub fn keyed_hash(self, key: &[u8; 32], data: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::hmac(key, data),
+            HashAlgorithm::Blake3 => Blake3::keyed_hash(key, data),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+}
+
+impl ReceiptHash for HashAlgorithm {
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        self.digest(data).to_vec()
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sha256" | "sha-256" => Ok(HashAlgorithm::Sha256),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            _ => Err(anyhow::anyhow!("Unknown hash algorithm: {}", name)),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
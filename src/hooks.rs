@@ -0,0 +1,143 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::circuit::{GateType, WireId};
+
+/// Evaluation step reported to gate hooks
+/// Events carry gate metadata only, never share values
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum GateEvent<'a> {
+    /// A single gate, evaluated by every party
+    Gate {
+        circuit: &'a str,
+        /// Position of the step in the evaluation schedule
+        step: usize,
+        gate_type: &'a GateType,
+        /// Output wire, if the gate is declared in the circuit
+        wire: Option<WireId>,
+    },
+    /// A collapsed block of XOR/NOT gates, applied in one pass
+    Linear {
+        circuit: &'a str,
+        step: usize,
+        gate_count: usize,
+    },
+}
+
+/// Callbacks run around every evaluation step
+/// Returning an error from either callback aborts the evaluation before any
+/// output is opened. Hooks are shared across threads by `execute_many`
+pub trait GateHook: Send + Sync {
+    fn before_gate(&self, _event: &GateEvent<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    fn after_gate(&self, _event: &GateEvent<'_>, _elapsed: Duration) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A shared hook, so the caller keeps a handle to read what it recorded
+impl<H: GateHook + ?Sized> GateHook for Arc<H> {
+    fn before_gate(&self, event: &GateEvent<'_>) -> Result<()> {
+        self.as_ref().before_gate(event)
+    }
+
+    fn after_gate(&self, event: &GateEvent<'_>, elapsed: Duration) -> Result<()> {
+        self.as_ref().after_gate(event, elapsed)
+    }
+}
+
+/// Abort once more than `limit` interactive gates have been evaluated
+/// The budget is shared by every run of the protocol the hook is attached to
+#[derive(Debug)]
+pub struct AndBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl AndBudget {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Interactive gates evaluated so far
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+}
+
+impl GateHook for AndBudget {
+    fn before_gate(&self, event: &GateEvent<'_>) -> Result<()> {
+        let GateEvent::Gate { gate_type, .. } = event else {
+            return Ok(());
+        };
+        if gate_type.is_interactive() && self.used.fetch_add(1, Ordering::Relaxed) >= self.limit {
+            return Err(anyhow::anyhow!(
+                "AND budget of {} gates exceeded",
+                self.limit
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::PreparedCircuit;
+    use crate::protocol::GmwProtocol;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl GateHook for Recorder {
+        fn after_gate(&self, event: &GateEvent<'_>, _elapsed: Duration) -> Result<()> {
+            let entry = match event {
+                GateEvent::Gate { gate_type, .. } => format!("{gate_type:?}"),
+                GateEvent::Linear { gate_count, .. } => format!("linear({gate_count})"),
+            };
+            self.events.lock().unwrap().push(entry);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hooks_observe_every_step() {
+        let circuit = reference::full_adder();
+        let recorder = Arc::new(Recorder::default());
+        let protocol = GmwProtocol::new(2)
+            .unwrap()
+            .with_hook(Arc::clone(&recorder));
+
+        protocol
+            .run_with_report(&circuit, &[true, true, false])
+            .unwrap();
+
+        let prepared = PreparedCircuit::new(&circuit);
+        assert_eq!(
+            recorder.events.lock().unwrap().len(),
+            prepared.plan.steps.len()
+        );
+    }
+
+    #[test]
+    fn test_and_budget_aborts() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(2).unwrap().with_hook(AndBudget::new(4));
+
+        // The full adder has 3 interactive gates: the first run fits, the second does not
+        assert!(protocol.run_with_report(&circuit, &[true; 3]).is_ok());
+        assert!(protocol.run_with_report(&circuit, &[true; 3]).is_err());
+    }
+}
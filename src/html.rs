@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::circuit::{Circuit, WireId};
+use crate::report::ExecutionReport;
+use crate::stats::LayerStats;
+
+/// Circuits with more gates are reported without a drawing; the DOT source
+/// is still embedded for rendering with Graphviz
+pub const MAX_DRAWN_GATES: usize = 400;
+
+const COLUMN_WIDTH: usize = 90;
+const ROW_HEIGHT: usize = 36;
+const MARGIN: usize = 40;
+
+/// Standalone HTML page describing one run, for sharing outside the CLI
+/// The page has no external scripts or stylesheets: the circuit graph is an
+/// inline SVG with a tooltip per node, next to the circuit's DOT source
+pub struct HtmlReport<'a> {
+    circuit: &'a Circuit,
+    title: String,
+    parties: Option<usize>,
+    report: Option<&'a ExecutionReport>,
+    layers: Vec<LayerStats>,
+    timings: Vec<(String, Duration)>,
+}
+
+impl<'a> HtmlReport<'a> {
+    pub fn new(circuit: &'a Circuit) -> Self {
+        Self {
+            circuit,
+            title: format!("GMW run of {}", circuit.name),
+            parties: None,
+            report: None,
+            layers: Vec::new(),
+            timings: Vec::new(),
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn with_parties(mut self, parties: usize) -> Self {
+        self.parties = Some(parties);
+        self
+    }
+
+    /// Outputs and round counts of the run
+    pub fn with_report(mut self, report: &'a ExecutionReport) -> Self {
+        self.report = Some(report);
+        self
+    }
+
+    /// Per-layer stats, as recorded by `LayerStatsHook`
+    pub fn with_layer_stats(mut self, layers: Vec<LayerStats>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Add a named timing, e.g. `("evaluation", elapsed)`
+    pub fn with_timing(mut self, label: &str, elapsed: Duration) -> Self {
+        self.timings.push((label.to_string(), elapsed));
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n",
+            title = escape(&self.title)
+        );
+        self.summary(&mut html);
+        self.outputs(&mut html);
+        self.layers(&mut html);
+        self.graph(&mut html);
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn summary(&self, html: &mut String) {
+        let circuit = self.circuit;
+        let and_gates = circuit
+            .gates
+            .iter()
+            .filter(|gate| gate.gate_type.is_interactive())
+            .count();
+        html.push_str("<h2>Summary</h2>\n<table>\n");
+        let mut row = |name: &str, value: String| {
+            let _ = writeln!(html, "<tr><th>{name}</th><td>{}</td></tr>", escape(&value));
+        };
+        row("Circuit", circuit.name.clone());
+        if !circuit.description.is_empty() {
+            row("Description", circuit.description.clone());
+        }
+        if let Some(parties) = self.parties {
+            row("Parties", parties.to_string());
+        }
+        row(
+            "Gates",
+            format!("{} ({} interactive)", circuit.gates.len(), and_gates),
+        );
+        if let Some(report) = self.report {
+            row("Rounds", report.rounds.to_string());
+            row("AND-depth", report.and_depth.to_string());
+        }
+        for (label, elapsed) in &self.timings {
+            row(label, format!("{:.3} ms", elapsed.as_secs_f64() * 1000.0));
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn outputs(&self, html: &mut String) {
+        let Some(report) = self.report else {
+            return;
+        };
+        html.push_str("<h2>Outputs</h2>\n<table>\n<tr><th>Output</th><th>Value</th></tr>\n");
+        for bus in &report.buses {
+            if let Ok(value) = report.decode::<u64>(&bus.name) {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{} (bus)</td><td>{value}</td></tr>",
+                    escape(&bus.name)
+                );
+            }
+        }
+        for (name, value) in &report.outputs {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(name),
+                u8::from(*value)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    /// Stats table with a bar per layer scaled to the slowest layer
+    fn layers(&self, html: &mut String) {
+        if self.layers.is_empty() {
+            return;
+        }
+        let slowest = self.layers.iter().map(|layer| layer.ms).fold(0.0, f64::max);
+        html.push_str(
+            "<h2>Layers</h2>\n<table>\n<tr><th>Layer</th><th>AND gates</th><th>Gates</th>\
+             <th>Bytes</th><th>ms</th><th></th></tr>\n",
+        );
+        for layer in &self.layers {
+            let width = if slowest > 0.0 {
+                layer.ms / slowest * 100.0
+            } else {
+                0.0
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.3}</td>\
+                 <td class=\"bar\"><div style=\"width:{width:.1}%\"></div></td></tr>",
+                layer.layer, layer.and_gates, layer.gates, layer.bytes, layer.ms
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn graph(&self, html: &mut String) {
+        html.push_str("<h2>Circuit graph</h2>\n");
+        if self.circuit.gates.len() > MAX_DRAWN_GATES {
+            let _ = writeln!(
+                html,
+                "<p>{} gates are too many to draw; render the DOT source below with Graphviz.</p>",
+                self.circuit.gates.len()
+            );
+        } else {
+            self.svg(html);
+        }
+        let _ = writeln!(
+            html,
+            "<details><summary>DOT source</summary><pre>{}</pre></details>",
+            escape(&self.circuit.to_dot())
+        );
+    }
+
+    /// Inputs in the first column, each gate one column right of its
+    /// latest input
+    fn svg(&self, html: &mut String) {
+        let circuit = self.circuit;
+        let mut positions: HashMap<WireId, (usize, usize)> = HashMap::new();
+        let mut rows: Vec<usize> = vec![0];
+        let mut nodes = String::new();
+        let mut edges = String::new();
+
+        for input in &circuit.metadata.inputs {
+            let position = (0, rows[0]);
+            rows[0] += 1;
+            positions.insert(input.id, position);
+            node(&mut nodes, position, "input", &input.name, &input.name);
+        }
+        for gate in &circuit.gates {
+            let column = 1 + gate
+                .inputs
+                .iter()
+                .filter_map(|wire| positions.get(wire))
+                .map(|&(column, _)| column)
+                .max()
+                .unwrap_or(0);
+            if rows.len() <= column {
+                rows.resize(column + 1, 0);
+            }
+            let position = (column, rows[column]);
+            rows[column] += 1;
+            for wire in &gate.inputs {
+                if let Some(&from) = positions.get(wire) {
+                    edge(&mut edges, from, position);
+                }
+            }
+            positions.insert(gate.id, position);
+
+            let class = if gate.gate_type.is_interactive() {
+                "interactive"
+            } else {
+                "local"
+            };
+            let label = format!("{:?}", gate.gate_type);
+            let tooltip = format!("{label} gate, wire {}", gate.id);
+            node(&mut nodes, position, class, &label, &tooltip);
+        }
+
+        let width = rows.len() * COLUMN_WIDTH + MARGIN;
+        let height = rows.iter().max().copied().unwrap_or(0) * ROW_HEIGHT + MARGIN;
+        let _ = writeln!(
+            html,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\">\n\
+             {edges}{nodes}</svg>"
+        );
+    }
+}
+
+fn center((column, row): (usize, usize)) -> (usize, usize) {
+    (
+        MARGIN / 2 + column * COLUMN_WIDTH + COLUMN_WIDTH / 3,
+        MARGIN / 2 + row * ROW_HEIGHT + ROW_HEIGHT / 2,
+    )
+}
+
+fn node(svg: &mut String, position: (usize, usize), class: &str, label: &str, tooltip: &str) {
+    let (x, y) = center(position);
+    let _ = writeln!(
+        svg,
+        "<g class=\"{class}\"><title>{}</title><rect x=\"{}\" y=\"{}\" width=\"56\" \
+         height=\"24\" rx=\"4\"/><text x=\"{x}\" y=\"{}\">{}</text></g>",
+        escape(tooltip),
+        x - 28,
+        y - 12,
+        y + 4,
+        escape(label)
+    );
+}
+
+fn edge(svg: &mut String, from: (usize, usize), to: (usize, usize)) {
+    let (x1, y1) = center(from);
+    let (x2, y2) = center(to);
+    let _ = writeln!(
+        svg,
+        "<line x1=\"{}\" y1=\"{y1}\" x2=\"{}\" y2=\"{y2}\"/>",
+        x1 + 28,
+        x2 - 28
+    );
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+td.bar{width:200px}td.bar div{background:#4a7;height:12px}\
+svg line{stroke:#999}svg rect{fill:#fff;stroke:#333}\
+svg .interactive rect{fill:#fc9}svg .input rect{fill:#def}\
+svg text{font-size:11px;text-anchor:middle}\
+g:hover rect{stroke-width:3}pre{background:#f6f6f6;padding:1em;overflow:auto}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::PreparedCircuit;
+    use crate::protocol::GmwProtocol;
+    use crate::stats::LayerStatsHook;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_html_report() {
+        let circuit = reference::full_adder();
+        let prepared = PreparedCircuit::new(&circuit);
+        let hook = Arc::new(LayerStatsHook::new(&prepared, 2));
+        let protocol = GmwProtocol::new(2).unwrap().with_hook(Arc::clone(&hook));
+        let report = protocol
+            .run_prepared(&prepared, &[true, true, false])
+            .unwrap();
+
+        let html = HtmlReport::new(&circuit)
+            .with_title("Adder <demo>")
+            .with_parties(2)
+            .with_report(&report)
+            .with_layer_stats(hook.stats())
+            .with_timing("evaluation", Duration::from_millis(3))
+            .render();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Adder &lt;demo&gt;</h1>"));
+        assert!(html.contains("<tr><td>total (bus)</td><td>2</td></tr>"));
+        assert!(html.contains("<tr><th>evaluation</th><td>3.000 ms</td></tr>"));
+        assert_eq!(
+            html.matches("<g class=").count(),
+            circuit.metadata.inputs.len() + circuit.gates.len()
+        );
+        assert!(html.contains("digraph &quot;full_adder&quot;"));
+        assert!(!html.contains("<script"));
+    }
+}
@@ -0,0 +1,142 @@
+//! XOR secret-sharing algebra over GF(2)
+
+use alloc::vec::Vec;
+
+/// Split a value into `party_count` shares using host-provided randomness
+/// The value is split as: value = share0 ⊕ share1 ⊕ ... ⊕ share(n-1)
+pub fn split_shares(
+    value: bool,
+    party_count: usize,
+    mut random: impl FnMut() -> bool,
+) -> Vec<bool> {
+    let mut shares = Vec::with_capacity(party_count);
+    let mut accumulated_xor = value;
+
+    // Generate n-1 random shares
+    for _ in 0..party_count.saturating_sub(1) {
+        let share = random();
+        shares.push(share);
+        accumulated_xor ^= share;
+    }
+
+    // Last share ensures XOR of all shares equals the value
+    shares.push(accumulated_xor);
+
+    shares
+}
+
+/// Reconstruct a value from all parties' shares
+pub fn reconstruct(shares: &[bool]) -> bool {
+    shares.iter().fold(false, |acc, &share| acc ^ share)
+}
+
+/// Convert an n-party sharing into a `new_party_count`-party sharing of the
+/// same value
+/// Each current party splits its own share into fresh subshares, one per new
+/// party, and each new party XORs the subshares it receives; no party ever
+/// holds more than one share of the value
+pub fn reshare(
+    shares: &[bool],
+    new_party_count: usize,
+    mut random: impl FnMut() -> bool,
+) -> Vec<bool> {
+    let mut new_shares = alloc::vec![false; new_party_count];
+    for &share in shares {
+        for (new_share, subshare) in
+            new_shares
+                .iter_mut()
+                .zip(split_shares(share, new_party_count, &mut random))
+        {
+            *new_share ^= subshare;
+        }
+    }
+    new_shares
+}
+
+/// XOR is linear in GF(2), so each party XORs its shares locally
+pub fn xor_shares(party_shares: &[(bool, bool)]) -> Vec<bool> {
+    party_shares.iter().map(|(xi, yi)| *xi ^ *yi).collect()
+}
+
+/// Negate one party's share: only party 0 flips, the others keep theirs
+pub fn not_share(party_id: usize, share: bool) -> bool {
+    share ^ (party_id == 0)
+}
+
+/// Negate a shared value across all parties
+pub fn not_shares(party_shares: &[bool]) -> Vec<bool> {
+    party_shares
+        .iter()
+        .enumerate()
+        .map(|(party_id, &share)| not_share(party_id, share))
+        .collect()
+}
+
+/// Local term xi & yi of a party's AND share
+pub fn and_local_term(shares: (bool, bool)) -> bool {
+    shares.0 & shares.1
+}
+
+/// 1-out-of-4 OT messages offered by the sender of a cross term
+/// Indexed by the receiver's (xj, yj), each message is xi·yj ⊕ xj·yi ⊕ mask
+pub fn cross_term_messages(sender_shares: (bool, bool), mask: bool) -> (bool, bool, bool, bool) {
+    let (xi, yi) = sender_shares;
+
+    (
+        mask,           // (0,0)
+        mask ^ xi,      // (0,1)
+        mask ^ yi,      // (1,0)
+        mask ^ xi ^ yi, // (1,1)
+    )
+}
+
+/// Combine a party's local AND term with its shares of all cross terms
+pub fn combine_and_share(local_term: bool, cross_shares: impl IntoIterator<Item = bool>) -> bool {
+    cross_shares
+        .into_iter()
+        .fold(local_term, |acc, share| acc ^ share)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_reconstruct() {
+        let mut bits = [true, false, true].into_iter().cycle();
+        for party_count in 1..=5 {
+            for value in [false, true] {
+                let shares = split_shares(value, party_count, || bits.next().unwrap());
+                assert_eq!(shares.len(), party_count);
+                assert_eq!(reconstruct(&shares), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reshare() {
+        let mut bits = [true, true, false, true, false].into_iter().cycle();
+        for value in [false, true] {
+            let shares = split_shares(value, 3, || bits.next().unwrap());
+            for new_party_count in [2, 3, 5] {
+                let new_shares = reshare(&shares, new_party_count, || bits.next().unwrap());
+                assert_eq!(new_shares.len(), new_party_count);
+                assert_eq!(reconstruct(&new_shares), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cross_term_messages() {
+        for (xi, yi, xj, yj) in (0..16).map(|i| (i & 1 == 1, i & 2 == 2, i & 4 == 4, i & 8 == 8)) {
+            let (m00, m01, m10, m11) = cross_term_messages((xi, yi), true);
+            let received = match (xj, yj) {
+                (false, false) => m00,
+                (false, true) => m01,
+                (true, false) => m10,
+                (true, true) => m11,
+            };
+            assert_eq!(received ^ true, (xi & yj) ^ (xj & yi));
+        }
+    }
+}
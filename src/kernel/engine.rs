@@ -0,0 +1,490 @@
+//! Single-party circuit walker driven by a host-provided transport
+
+use alloc::collections::btree_map::{BTreeMap, Entry};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::algebra::{and_local_term, combine_and_share, not_share, xor_shares};
+use super::gate::{GateType, IndexedGate};
+
+/// Error raised while walking a circuit on one party
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KernelError {
+    /// A gate input has no share yet
+    MissingShare { gate: usize, slot: usize },
+    /// A cross-term share was provided while no interactive gate is pending
+    UnexpectedCrossTerm,
+    /// A cross-term share arrived for a gate that already completed
+    StaleCrossTerm { gate: usize },
+    /// A second cross-term share arrived for the same gate
+    DuplicateCrossTerm { gate: usize },
+    /// A cross-term share was addressed to a gate that is not interactive
+    NotInteractive { gate: usize },
+}
+
+impl fmt::Display for KernelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KernelError::MissingShare { gate, slot } => {
+                write!(f, "Gate {gate} is missing a share for slot {slot}")
+            }
+            KernelError::UnexpectedCrossTerm => {
+                write!(
+                    f,
+                    "Cross-term share provided with no interactive gate pending"
+                )
+            }
+            KernelError::StaleCrossTerm { gate } => {
+                write!(
+                    f,
+                    "Cross-term share for gate {gate}, which already completed"
+                )
+            }
+            KernelError::DuplicateCrossTerm { gate } => {
+                write!(f, "Second cross-term share for gate {gate}")
+            }
+            KernelError::NotInteractive { gate } => {
+                write!(
+                    f,
+                    "Cross-term share for gate {gate}, which is not interactive"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "circuit")]
+impl std::error::Error for KernelError {}
+
+/// Next action the host must take for a party engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Step {
+    /// Gate `gate` needs this party's share of the cross terms for the AND of `inputs`
+    /// The host runs the OTs with the other parties and calls `provide_cross_term`
+    Interactive { gate: usize, inputs: (bool, bool) },
+    /// All gates have been evaluated
+    Done,
+}
+
+/// Snapshot of one party engine's progress
+/// Hosts dump it when a run fails so the parties' views can be compared
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "circuit", derive(serde::Serialize, serde::Deserialize))]
+pub struct EngineProgress {
+    pub party_id: usize,
+    /// Index of the next gate to evaluate
+    pub cursor: usize,
+    /// Interactive gates completed, i.e. the last round whose cross term arrived
+    pub acked_round: usize,
+    /// Whether the gate at `cursor` is waiting for its cross-term share
+    pub pending: bool,
+    /// Which wire slots hold a share
+    pub computed: Vec<bool>,
+}
+
+/// One party's view of a circuit evaluation
+/// Local gates (XOR, NOT) are evaluated immediately; interactive gates
+/// (AND, OR) pause until the host supplies the party's cross-term share
+#[derive(Debug, Clone)]
+pub struct PartyEngine<'a> {
+    party_id: usize,
+    gates: &'a [IndexedGate],
+    shares: Vec<Option<bool>>,
+    cursor: usize,
+    pending: Option<(bool, bool)>,
+    acked_round: usize,
+    /// Cross-term shares delivered before the engine reached their gate
+    buffered: BTreeMap<usize, bool>,
+}
+
+impl<'a> PartyEngine<'a> {
+    /// Create an engine for `party_id` over resolved gates and `wire_count` slots
+    pub fn new(party_id: usize, gates: &'a [IndexedGate], wire_count: usize) -> Self {
+        Self {
+            party_id,
+            gates,
+            shares: vec![None; wire_count],
+            cursor: 0,
+            pending: None,
+            acked_round: 0,
+            buffered: BTreeMap::new(),
+        }
+    }
+
+    /// Set this party's share of a wire (typically an input)
+    pub fn set_share(&mut self, slot: usize, share: bool) {
+        self.shares[slot] = Some(share);
+    }
+
+    /// This party's share of a wire, if computed
+    pub fn share(&self, slot: usize) -> Option<bool> {
+        self.shares[slot]
+    }
+
+    /// Evaluate local gates until an interactive gate or the end of the circuit
+    /// Calling again while an interactive gate is pending returns the same step
+    pub fn advance(&mut self) -> Result<Step, KernelError> {
+        if let Some(inputs) = self.pending {
+            return Ok(Step::Interactive {
+                gate: self.cursor,
+                inputs,
+            });
+        }
+
+        while let Some(gate) = self.gates.get(self.cursor) {
+            match gate.gate_type {
+                GateType::XOR => {
+                    let inputs = self.binary_inputs(gate)?;
+                    self.shares[gate.output] = Some(xor_shares(&[inputs])[0]);
+                }
+                GateType::NOT => {
+                    let input = self.input(gate, 0)?;
+                    self.shares[gate.output] = Some(not_share(self.party_id, input));
+                }
+                GateType::AND => {
+                    let inputs = self.binary_inputs(gate)?;
+                    match self.pause(inputs)? {
+                        Some(step) => return Ok(step),
+                        None => continue,
+                    }
+                }
+                GateType::OR => {
+                    // De Morgan's law: x | y = ~(~x & ~y)
+                    let (x, y) = self.binary_inputs(gate)?;
+                    let inputs = (not_share(self.party_id, x), not_share(self.party_id, y));
+                    match self.pause(inputs)? {
+                        Some(step) => return Ok(step),
+                        None => continue,
+                    }
+                }
+            }
+            self.cursor += 1;
+        }
+
+        Ok(Step::Done)
+    }
+
+    /// Complete the pending interactive gate with this party's cross-term share
+    /// (the XOR of its shares of xi·yj ⊕ xj·yi over all other parties j)
+    pub fn provide_cross_term(&mut self, cross_share: bool) -> Result<(), KernelError> {
+        let inputs = self
+            .pending
+            .take()
+            .ok_or(KernelError::UnexpectedCrossTerm)?;
+        let gate = &self.gates[self.cursor];
+
+        let and_share = combine_and_share(and_local_term(inputs), [cross_share]);
+        let result = match gate.gate_type {
+            GateType::OR => not_share(self.party_id, and_share),
+            _ => and_share,
+        };
+
+        self.shares[gate.output] = Some(result);
+        self.cursor += 1;
+        self.acked_round += 1;
+        Ok(())
+    }
+
+    /// Deliver the cross-term share of interactive gate `gate`, which may
+    /// arrive before the engine reaches the gate, as messages from a network
+    /// can; early shares are buffered until `advance` gets there. Shares for
+    /// completed gates, second shares for one gate and shares for gates that
+    /// are not interactive are rejected without changing the engine
+    pub fn deliver_cross_term(
+        &mut self,
+        gate: usize,
+        cross_share: bool,
+    ) -> Result<(), KernelError> {
+        if !self
+            .gates
+            .get(gate)
+            .is_some_and(|indexed| indexed.gate_type.is_interactive())
+        {
+            return Err(KernelError::NotInteractive { gate });
+        }
+        if gate < self.cursor {
+            return Err(KernelError::StaleCrossTerm { gate });
+        }
+        if gate == self.cursor && self.pending.is_some() {
+            return self.provide_cross_term(cross_share);
+        }
+        match self.buffered.entry(gate) {
+            Entry::Occupied(_) => Err(KernelError::DuplicateCrossTerm { gate }),
+            Entry::Vacant(entry) => {
+                entry.insert(cross_share);
+                Ok(())
+            }
+        }
+    }
+
+    /// Snapshot of how far this party got
+    pub fn progress(&self) -> EngineProgress {
+        EngineProgress {
+            party_id: self.party_id,
+            cursor: self.cursor,
+            acked_round: self.acked_round,
+            pending: self.pending.is_some(),
+            computed: self.shares.iter().map(Option::is_some).collect(),
+        }
+    }
+
+    /// Wait at the interactive gate under the cursor, or complete it at once
+    /// if its cross-term share was delivered early
+    fn pause(&mut self, inputs: (bool, bool)) -> Result<Option<Step>, KernelError> {
+        self.pending = Some(inputs);
+        match self.buffered.remove(&self.cursor) {
+            Some(cross_share) => {
+                self.provide_cross_term(cross_share)?;
+                Ok(None)
+            }
+            None => Ok(Some(Step::Interactive {
+                gate: self.cursor,
+                inputs,
+            })),
+        }
+    }
+
+    fn input(&self, gate: &IndexedGate, position: usize) -> Result<bool, KernelError> {
+        let slot = gate.inputs[position];
+        self.shares[slot].ok_or(KernelError::MissingShare {
+            gate: self.cursor,
+            slot,
+        })
+    }
+
+    fn binary_inputs(&self, gate: &IndexedGate) -> Result<(bool, bool), KernelError> {
+        Ok((self.input(gate, 0)?, self.input(gate, 1)?))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::circuit::{Circuit, LocalEvaluator, PreparedCircuit};
+    use crate::gates::cross_term_shares;
+    use crate::kernel::algebra::{reconstruct, split_shares};
+
+    /// Drive one engine per party, acting as the host transport
+    fn run_engines(circuit: &Circuit, inputs: &[bool], party_count: usize) -> Vec<bool> {
+        let prepared = PreparedCircuit::new(circuit);
+        let mut engines: Vec<PartyEngine> = (0..party_count)
+            .map(|party_id| PartyEngine::new(party_id, &prepared.gates, prepared.index.len()))
+            .collect();
+
+        for (input, info) in inputs.iter().zip(&prepared.metadata.inputs) {
+            let slot = prepared.index.slot(info.id).unwrap();
+            let shares = split_shares(*input, party_count, rand::random::<bool>);
+            for (engine, share) in engines.iter_mut().zip(shares) {
+                engine.set_share(slot, share);
+            }
+        }
+
+        loop {
+            let steps: Vec<Step> = engines.iter_mut().map(|e| e.advance().unwrap()).collect();
+            if steps.iter().all(|step| *step == Step::Done) {
+                break;
+            }
+
+            let party_inputs: Vec<(bool, bool)> = steps
+                .iter()
+                .map(|step| match step {
+                    Step::Interactive { inputs, .. } => *inputs,
+                    Step::Done => panic!("parties out of sync"),
+                })
+                .collect();
+            let cross_shares = cross_term_shares(&party_inputs).unwrap();
+            for (engine, cross_share) in engines.iter_mut().zip(cross_shares) {
+                engine.provide_cross_term(cross_share).unwrap();
+            }
+        }
+
+        prepared
+            .metadata
+            .outputs
+            .iter()
+            .map(|info| {
+                let slot = prepared.index.slot(info.id).unwrap();
+                let shares: Vec<bool> = engines.iter().map(|e| e.share(slot).unwrap()).collect();
+                reconstruct(&shares)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_engines_match_local_evaluation() {
+        for file in ["circuits/full_adder.json", "circuits/mux_2to1.json"] {
+            let circuit = Circuit::from_file(file).unwrap();
+
+            for input in 0..8u8 {
+                let inputs: Vec<bool> = (0..3).map(|bit| input >> bit & 1 == 1).collect();
+                let outputs = run_engines(&circuit, &inputs, 3);
+
+                for (info, output) in circuit.metadata.outputs.iter().zip(outputs) {
+                    let expected = LocalEvaluator::get_output(&circuit, &inputs, info.id).unwrap();
+                    assert_eq!(output, expected, "{file} {inputs:?}");
+                }
+            }
+        }
+    }
+
+    /// Two ANDs reading only inputs, so both cross terms can be computed
+    /// up front and delivered in any order
+    fn independent_ands() -> PreparedCircuit {
+        let circuit = Circuit::from_json(
+            r#"{
+                "name": "two_ands",
+                "description": "(a & b) ^ (b & c)",
+                "metadata": {
+                    "inputs": [{"name": "a", "id": 1}, {"name": "b", "id": 2}, {"name": "c", "id": 3}],
+                    "outputs": [{"name": "out", "id": 12}]
+                },
+                "gates": [
+                    {"id": 10, "type": "AND", "in": [1, 2]},
+                    {"id": 11, "type": "AND", "in": [2, 3]},
+                    {"id": 12, "type": "XOR", "in": [10, 11]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        PreparedCircuit::new(&circuit)
+    }
+
+    /// Engines holding shares of `inputs`, and each AND gate's index with
+    /// every party's cross-term share
+    fn shared_engines<'a>(
+        prepared: &'a PreparedCircuit,
+        inputs: &[bool],
+    ) -> (Vec<PartyEngine<'a>>, Vec<(usize, Vec<bool>)>) {
+        let mut engines: Vec<PartyEngine> = (0..3)
+            .map(|party_id| PartyEngine::new(party_id, &prepared.gates, prepared.index.len()))
+            .collect();
+        for (input, info) in inputs.iter().zip(&prepared.metadata.inputs) {
+            let slot = prepared.index.slot(info.id).unwrap();
+            let shares = split_shares(*input, 3, rand::random::<bool>);
+            for (engine, share) in engines.iter_mut().zip(shares) {
+                engine.set_share(slot, share);
+            }
+        }
+
+        let cross_terms = prepared
+            .gates
+            .iter()
+            .enumerate()
+            .filter(|(_, gate)| gate.gate_type.is_interactive())
+            .map(|(index, gate)| {
+                let party_inputs: Vec<(bool, bool)> = engines
+                    .iter()
+                    .map(|e| {
+                        (
+                            e.share(gate.inputs[0]).unwrap(),
+                            e.share(gate.inputs[1]).unwrap(),
+                        )
+                    })
+                    .collect();
+                (index, cross_term_shares(&party_inputs).unwrap())
+            })
+            .collect();
+        (engines, cross_terms)
+    }
+
+    fn open(prepared: &PreparedCircuit, engines: &[PartyEngine]) -> bool {
+        let slot = prepared.index.slot(12).unwrap();
+        reconstruct(
+            &engines
+                .iter()
+                .map(|e| e.share(slot).unwrap())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_engine_buffers_reordered_cross_terms() {
+        let prepared = independent_ands();
+        for value in 0..8u8 {
+            let inputs: Vec<bool> = (0..3).map(|bit| value >> bit & 1 == 1).collect();
+            let expected = (inputs[0] & inputs[1]) ^ (inputs[1] & inputs[2]);
+            let (mut engines, cross_terms) = shared_engines(&prepared, &inputs);
+            let [(first, first_shares), (second, second_shares)] = &cross_terms[..] else {
+                panic!("expected two AND gates");
+            };
+
+            // The second gate's shares arrive before the engines start, and
+            // party 2 hears about the first gate only after the others finish
+            for (engine, &share) in engines.iter_mut().zip(second_shares) {
+                engine.deliver_cross_term(*second, share).unwrap();
+            }
+            for (party, engine) in engines.iter_mut().enumerate() {
+                assert_eq!(
+                    engine.advance().unwrap(),
+                    Step::Interactive {
+                        gate: *first,
+                        inputs: (
+                            engine.share(prepared.gates[*first].inputs[0]).unwrap(),
+                            engine.share(prepared.gates[*first].inputs[1]).unwrap()
+                        )
+                    }
+                );
+                if party < 2 {
+                    engine
+                        .deliver_cross_term(*first, first_shares[party])
+                        .unwrap();
+                    assert_eq!(engine.advance(), Ok(Step::Done));
+                }
+            }
+            assert!(engines[2].progress().pending);
+            engines[2]
+                .deliver_cross_term(*first, first_shares[2])
+                .unwrap();
+            assert_eq!(engines[2].advance(), Ok(Step::Done));
+
+            assert_eq!(open(&prepared, &engines), expected, "{inputs:?}");
+        }
+    }
+
+    #[test]
+    fn test_engine_rejects_duplicate_and_stale_cross_terms() {
+        let prepared = independent_ands();
+        let (mut engines, cross_terms) = shared_engines(&prepared, &[true, true, false]);
+        let (first, second) = (cross_terms[0].0, cross_terms[1].0);
+        let engine = &mut engines[0];
+
+        engine.deliver_cross_term(second, true).unwrap();
+        assert_eq!(
+            engine.deliver_cross_term(second, true),
+            Err(KernelError::DuplicateCrossTerm { gate: second })
+        );
+        let xor = prepared.gates.len() - 1;
+        assert_eq!(
+            engine.deliver_cross_term(xor, true),
+            Err(KernelError::NotInteractive { gate: xor })
+        );
+        assert_eq!(
+            engine.deliver_cross_term(99, true),
+            Err(KernelError::NotInteractive { gate: 99 })
+        );
+
+        engine.advance().unwrap();
+        engine.deliver_cross_term(first, false).unwrap();
+        assert_eq!(engine.advance(), Ok(Step::Done));
+        assert_eq!(
+            engine.deliver_cross_term(first, false),
+            Err(KernelError::StaleCrossTerm { gate: first })
+        );
+        assert_eq!(engine.progress().acked_round, 2);
+    }
+
+    #[test]
+    fn test_engine_rejects_unexpected_cross_term() {
+        let gates = [];
+        let mut engine = PartyEngine::new(0, &gates, 0);
+
+        assert_eq!(engine.advance(), Ok(Step::Done));
+        assert_eq!(
+            engine.provide_cross_term(true),
+            Err(KernelError::UnexpectedCrossTerm)
+        );
+    }
+}
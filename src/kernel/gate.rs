@@ -0,0 +1,26 @@
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "circuit", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum GateType {
+    XOR,
+    NOT,
+    AND,
+    OR,
+}
+
+impl GateType {
+    /// Whether the gate needs communication (OT) between parties
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, GateType::AND | GateType::OR)
+    }
+}
+
+/// Gate with its wires resolved to dense slots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedGate {
+    pub gate_type: GateType,
+    pub inputs: Vec<usize>,
+    pub output: usize,
+}
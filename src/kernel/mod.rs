@@ -0,0 +1,15 @@
+//! `no_std`-compatible computation kernel
+//!
+//! Share algebra, gate semantics and a per-party circuit walker that only
+//! depend on `core` and `alloc`. Everything else in the crate (parsing, OT,
+//! the multi-party simulation, CLI) requires the default `std` feature, so
+//! constrained devices can act as GMW parties with transport provided by the host.
+
+pub mod algebra;
+pub mod engine;
+pub mod gate;
+pub mod share;
+
+pub use engine::{EngineProgress, KernelError, PartyEngine, Step};
+pub use gate::{GateType, IndexedGate};
+pub use share::ShareBit;
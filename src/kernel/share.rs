@@ -0,0 +1,113 @@
+//! Constant-time secret bits for OT receiver selection
+//! Only the OT receivers pick messages through `ShareBit`; the rest of the
+//! crate still handles shares as plain `bool`s
+
+use core::fmt;
+use core::ops::{BitAnd, BitXor, BitXorAssign, Not};
+use subtle::{Choice, ConditionallySelectable};
+
+/// A secret bit, held as a `subtle::Choice`
+/// Operations go through `subtle`, whose optimization barriers keep the
+/// compiler from turning them back into branches on the bit. There is no
+/// `PartialEq` and `Debug` does not print the value; `reveal` is the only
+/// way back to a `bool`, so leaving constant time is visible at the call site
+#[derive(Clone, Copy)]
+pub struct ShareBit(Choice);
+
+impl ShareBit {
+    pub fn new(bit: bool) -> Self {
+        Self::from_lsb(u8::from(bit))
+    }
+
+    /// Lowest bit of `byte`, e.g. a bit message received through OT
+    pub fn from_lsb(byte: u8) -> Self {
+        Self(Choice::from(byte & 1))
+    }
+
+    /// The bit as a `bool`, for opening or handing to code that is not
+    /// constant-time
+    pub fn reveal(self) -> bool {
+        self.0.unwrap_u8() == 1
+    }
+
+    /// `one` when the bit is set, else `zero`, without branching
+    pub fn select(self, zero: u8, one: u8) -> u8 {
+        u8::conditional_select(&zero, &one, self.0)
+    }
+}
+
+impl Default for ShareBit {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl From<bool> for ShareBit {
+    fn from(bit: bool) -> Self {
+        Self::new(bit)
+    }
+}
+
+impl ConditionallySelectable for ShareBit {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(Choice::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl BitXor for ShareBit {
+    type Output = Self;
+
+    fn bitxor(self, other: Self) -> Self {
+        Self(self.0 ^ other.0)
+    }
+}
+
+impl BitXorAssign for ShareBit {
+    fn bitxor_assign(&mut self, other: Self) {
+        self.0 ^= other.0;
+    }
+}
+
+impl BitAnd for ShareBit {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+impl Not for ShareBit {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl fmt::Debug for ShareBit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ShareBit(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_bit_ops() {
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            let (x, y) = (ShareBit::new(a), ShareBit::new(b));
+            assert_eq!((x ^ y).reveal(), a ^ b);
+            assert_eq!((x & y).reveal(), a & b);
+            assert_eq!((!x).reveal(), !a);
+            assert_eq!(x.select(0x5a, 0xc3), if a { 0xc3 } else { 0x5a });
+            let chosen = ShareBit::conditional_select(&x, &y, Choice::from(1));
+            assert_eq!(chosen.reveal(), b);
+        }
+        assert!(ShareBit::from_lsb(0x03).reveal());
+        assert!(!ShareBit::from_lsb(0x02).reveal());
+        assert!(!ShareBit::default().reveal());
+        assert_eq!(format!("{:?}", ShareBit::new(true)), "ShareBit(..)");
+    }
+}
@@ -1,9 +1,269 @@
+#![cfg_attr(not(feature = "circuit"), no_std)]
+// The original gate tests spell out expected bits with `assert_eq!`
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
+
+//! GMW secure multi-party computation over boolean circuits
+//!
+//! The crate root re-exports the stable, high-level API; lower-level
+//! building blocks (gate functions, OT, share storage, circuit analyses)
+//! stay reachable through their modules. `prelude` covers the common case.
+
+extern crate alloc;
+
+pub mod kernel;
+
+#[cfg(feature = "std")]
+pub mod access;
+#[cfg(feature = "circuit")]
+pub mod arena;
+#[cfg(feature = "std")]
+pub mod audit;
+#[cfg(feature = "std")]
+pub mod blake3;
+#[cfg(feature = "std")]
+pub mod bmr;
+#[cfg(feature = "std")]
+pub mod budget;
+#[cfg(feature = "circuit")]
+pub mod cache;
+#[cfg(feature = "circuit")]
 pub mod circuit;
+#[cfg(feature = "std")]
+pub mod demo;
+#[cfg(feature = "circuit")]
+pub mod diagnose;
+#[cfg(feature = "std")]
+pub mod dispute;
+#[cfg(feature = "std")]
+pub mod domain;
+#[cfg(feature = "std")]
+pub mod errors;
+#[cfg(feature = "std")]
+pub mod exchange;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
 pub mod gates;
+#[cfg(feature = "std")]
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod hooks;
+#[cfg(feature = "std")]
+pub mod html;
+#[cfg(feature = "std")]
+pub mod lut;
+#[cfg(feature = "std")]
 pub mod ot;
+#[cfg(feature = "std")]
+pub mod packing;
+#[cfg(feature = "std")]
+pub mod prelude;
+#[cfg(feature = "std")]
+pub mod production;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "std")]
 pub mod protocol;
+#[cfg(feature = "std")]
+pub mod protocols;
+#[cfg(feature = "std")]
+pub mod receipt;
+#[cfg(feature = "std")]
+pub mod release;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod result_cache;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod security;
+#[cfg(feature = "std")]
+pub mod selftest;
+#[cfg(feature = "std")]
+pub mod sha256;
+#[cfg(feature = "std")]
+pub mod simulation;
+#[cfg(feature = "std")]
+pub mod state;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod store;
+
+#[cfg(feature = "std")]
+pub use access::OpeningRefused;
+#[cfg(feature = "std")]
+pub use audit::{AuditMismatch, AuditReport, CircuitAuditor};
+#[cfg(feature = "std")]
+pub use blake3::Blake3;
+#[cfg(feature = "std")]
+pub use budget::{BudgetExceeded, BudgetResource, ResourceBudget};
+#[cfg(feature = "circuit")]
+pub use cache::{CacheStats, CircuitCache};
+#[cfg(feature = "circuit")]
+pub use circuit::{
+    BusEncoding, BusInfo, Circuit, CircuitBuilder, CircuitFile, CircuitFormat, CircuitMetadata,
+    CircuitRegistry, CircuitTemplate, EncodedCircuit, Gate, GateType, InputConstraint,
+    InputEncoder, InputInfo, LocalEvaluator, OutputInfo, PreparedCircuit, WireId,
+};
+#[cfg(feature = "std")]
+pub use demo::{DemoDelivery, DemoReport, PartyDemo};
+#[cfg(feature = "circuit")]
+pub use diagnose::{Divergence, ProgressDump, ShareMismatch};
+#[cfg(feature = "std")]
+pub use dispute::{CommitmentLog, Dispute, ShareCommitment, ShareOpening};
+#[cfg(feature = "std")]
+pub use domain::{DomainMismatch, ShareDomain};
+#[cfg(feature = "std")]
+pub use errors::ErrorCode;
+#[cfg(feature = "std")]
+pub use exchange::{WireShare, WireShareExport};
+#[cfg(feature = "std")]
+pub use explain::{ExplainStep, Explainer, Explanation};
+#[cfg(feature = "std")]
+pub use hash::HashAlgorithm;
+#[cfg(feature = "std")]
+pub use hooks::{AndBudget, GateEvent, GateHook};
+#[cfg(feature = "std")]
+pub use html::HtmlReport;
+#[cfg(feature = "std")]
+pub use lut::{LookupTable, OneTimeTable};
+#[cfg(feature = "std")]
+pub use production::LocalSimulationRefused;
+#[cfg(feature = "std")]
+pub use progress::{Progress, ProgressHook};
+#[cfg(feature = "std")]
+pub use protocol::{Backend, FailurePolicy, GmwProtocol, OutputShares, PartyShares};
+#[cfg(feature = "std")]
+pub use receipt::{
+    ExecutionReceipt, InputCommitment, InputOpening, ReceiptConfig, ReceiptHash, ReceiptSigner,
+    ReceiptVerifier,
+};
+#[cfg(feature = "std")]
+pub use release::{ApprovalRelease, EscrowRelease, GradualRelease, ReleaseCheckpoint, ReleaseStep};
+#[cfg(feature = "std")]
+pub use report::{ExecutionReport, FromBits, LatencyBreakdown, LayerTiming};
+#[cfg(feature = "std")]
+pub use result_cache::ResultCache;
+#[cfg(feature = "std")]
+pub use schema::SchemaKind;
+#[cfg(feature = "std")]
+pub use security::SecurityConfig;
+#[cfg(feature = "std")]
+pub use selftest::{SelfTest, SelfTestReport, SelfTestStage};
+#[cfg(feature = "std")]
+pub use sha256::Sha256;
+#[cfg(feature = "std")]
+pub use simulation::{
+    CommunicationCost, NetworkModel, NetworkSimulator, OtBatching, ReconstructionTopology,
+    SimulationReport,
+};
+#[cfg(feature = "std")]
+pub use state::{SharedStateStore, SharedValue};
+#[cfg(feature = "std")]
+pub use stats::{LayerStats, LayerStatsHook};
+#[cfg(feature = "std")]
+pub use store::{KeyBackend, KeyFile, OsKeyring, SecretStore};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// `.rs` files under `dir`, sorted, without the CLI
+    fn sources(dir: &Path, files: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                sources(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "rs") && !path.ends_with("main.rs")
+            {
+                files.push(path);
+            }
+        }
+        files.sort();
+    }
+
+    /// One line per `pub` item of `source`, up to its body; test modules
+    /// and `pub(crate)` items are left out
+    fn public_items(source: &str) -> Vec<String> {
+        let mut items = Vec::new();
+        let mut lines = source.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            if line == "#[cfg(test)]" {
+                break;
+            }
+            if !line.starts_with("pub ") {
+                continue;
+            }
+            let mut item = line.to_string();
+            while !item_ends(&item) {
+                match lines.next() {
+                    Some(next) => {
+                        item.push(' ');
+                        item.push_str(next);
+                    }
+                    None => break,
+                }
+            }
+            if !item.starts_with("pub use ") {
+                item = item.split('{').next().unwrap().to_string();
+            }
+            let item = item.split_whitespace().collect::<Vec<_>>().join(" ");
+            items.push(item.trim_end_matches(',').to_string());
+        }
+        items
+    }
+
+    fn item_ends(item: &str) -> bool {
+        if item.starts_with("pub use ") {
+            return item.ends_with(';');
+        }
+        // Only function signatures and `where` clauses span several lines
+        let function = item.contains(" fn ");
+        let balanced = item.matches('(').count() == item.matches(')').count();
+        item.contains('{')
+            || item.ends_with(';')
+            || (balanced && !item.contains(" where ") && (!function || item.ends_with(',')))
+    }
+
+    /// Every `pub` item of the library sources against `public-api.txt`, so
+    /// API changes show up in review as a diff of that file
+    /// After an intended change, regenerate it with
+    /// `GMW_UPDATE_API=1 cargo test public_api`
+    #[test]
+    fn test_public_api_snapshot() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut files = Vec::new();
+        sources(&root.join("src"), &mut files);
+        let mut api = String::new();
+        for file in &files {
+            let name = file.strip_prefix(root).unwrap().display().to_string();
+            for item in public_items(&fs::read_to_string(file).unwrap()) {
+                api.push_str(&format!("{name}: {item}\n"));
+            }
+        }
 
-pub use circuit::*;
-pub use gates::*;
-pub use ot::*;
-pub use protocol::*;
+        let snapshot = root.join("public-api.txt");
+        if std::env::var_os("GMW_UPDATE_API").is_some() {
+            fs::write(&snapshot, &api).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&snapshot).unwrap_or_default();
+        let (old, new): (HashSet<&str>, HashSet<&str>) =
+            (expected.lines().collect(), api.lines().collect());
+        let changes: Vec<String> = (expected.lines().filter(|line| !new.contains(line)))
+            .map(|line| format!("- {line}"))
+            .chain((api.lines().filter(|line| !old.contains(line))).map(|line| format!("+ {line}")))
+            .collect();
+        assert!(
+            changes.is_empty(),
+            "Public API changed; rerun with GMW_UPDATE_API=1 if intended:\n{}",
+            changes.join("\n")
+        );
+    }
+}
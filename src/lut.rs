@@ -0,0 +1,301 @@
+use anyhow::Result;
+
+use crate::domain::ShareDomain;
+use crate::production::{local_simulation_refused, LocalSimulationRefused};
+
+/// Largest supported table input width; each party stores 2^k entries
+pub const MAX_LUT_INPUTS: usize = 16;
+
+/// Public k-input, m-output lookup table such as an S-box
+/// Inputs and outputs are packed least significant bit first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupTable {
+    input_bits: usize,
+    output_bits: usize,
+    entries: Vec<u64>,
+}
+
+impl LookupTable {
+    /// Table from its 2^`input_bits` entries, each below 2^`output_bits`
+    pub fn new(input_bits: usize, output_bits: usize, entries: Vec<u64>) -> Result<Self> {
+        if input_bits == 0 || input_bits > MAX_LUT_INPUTS {
+            return Err(anyhow::anyhow!(
+                "Lookup tables take 1 to {} input bits, got {}",
+                MAX_LUT_INPUTS,
+                input_bits
+            ));
+        }
+        if output_bits == 0 || output_bits > 64 {
+            return Err(anyhow::anyhow!(
+                "Lookup tables produce 1 to 64 output bits, got {}",
+                output_bits
+            ));
+        }
+        if entries.len() != 1 << input_bits {
+            return Err(anyhow::anyhow!(
+                "A {}-input table needs {} entries, got {}",
+                input_bits,
+                1usize << input_bits,
+                entries.len()
+            ));
+        }
+        if let Some(entry) = entries
+            .iter()
+            .find(|&&entry| entry & !mask(output_bits) != 0)
+        {
+            return Err(anyhow::anyhow!(
+                "Entry {} does not fit in {} output bits",
+                entry,
+                output_bits
+            ));
+        }
+
+        Ok(Self {
+            input_bits,
+            output_bits,
+            entries,
+        })
+    }
+
+    /// Table of `f` over every input
+    pub fn from_fn(input_bits: usize, output_bits: usize, f: impl Fn(u64) -> u64) -> Result<Self> {
+        if input_bits > MAX_LUT_INPUTS {
+            return Self::new(input_bits, output_bits, Vec::new());
+        }
+        Self::new(
+            input_bits,
+            output_bits,
+            (0..1u64 << input_bits).map(f).collect(),
+        )
+    }
+
+    pub fn input_bits(&self) -> usize {
+        self.input_bits
+    }
+
+    pub fn output_bits(&self) -> usize {
+        self.output_bits
+    }
+
+    /// Plaintext lookup, for checking shared results
+    pub fn lookup(&self, input: u64) -> u64 {
+        self.entries[(input & mask(self.input_bits)) as usize]
+    }
+
+    /// Offline phase: mask the table for one lookup among `party_count` parties
+    /// A random input mask r is XOR-shared, and the rotated table
+    /// T'(u) = T(u ⊕ r) is XOR-shared entry by entry. Online, the parties open
+    /// u = x ⊕ r, which is uniformly random and reveals nothing about x, and
+    /// each reads its share of T'(u) = T(x). The material is produced by a
+    /// dealer in process, standing in for a preprocessing protocol
+    pub fn preprocess(&self, party_count: usize) -> Result<Vec<OneTimeTable>> {
+        if party_count < 2 {
+            return Err(anyhow::anyhow!("Need at least 2 parties for a lookup"));
+        }
+
+        let domain = ShareDomain::fresh("lookup_table");
+        let input_mask = rand::random::<u64>() & mask(self.input_bits);
+        let mut tables: Vec<OneTimeTable> = (0..party_count)
+            .map(|party| OneTimeTable {
+                domain: domain.clone(),
+                party,
+                input_bits: self.input_bits,
+                mask_share: 0,
+                entries: Vec::with_capacity(self.entries.len()),
+            })
+            .collect();
+
+        // Every party but the last draws random shares; the last one fixes the XOR
+        let (last, others) = tables.split_last_mut().expect("at least two parties");
+        let mut remaining_mask = input_mask;
+        for table in others.iter_mut() {
+            table.mask_share = rand::random::<u64>() & mask(self.input_bits);
+            remaining_mask ^= table.mask_share;
+        }
+        last.mask_share = remaining_mask;
+
+        for masked in 0..self.entries.len() as u64 {
+            let mut remaining = self.lookup(masked ^ input_mask);
+            for table in others.iter_mut() {
+                let share = rand::random::<u64>() & mask(self.output_bits);
+                table.entries.push(share);
+                remaining ^= share;
+            }
+            last.entries.push(remaining);
+        }
+
+        Ok(tables)
+    }
+}
+
+/// One party's share of a masked truth table, good for exactly one lookup
+/// Opening a second input against the same mask would reveal the XOR of the
+/// two inputs, so the online steps consume the table
+#[derive(Debug)]
+pub struct OneTimeTable {
+    /// Preprocessing run the table comes from
+    domain: ShareDomain,
+    party: usize,
+    input_bits: usize,
+    mask_share: u64,
+    entries: Vec<u64>,
+}
+
+impl OneTimeTable {
+    pub fn party(&self) -> usize {
+        self.party
+    }
+
+    pub fn domain(&self) -> &ShareDomain {
+        &self.domain
+    }
+
+    /// Online step 1: the value this party broadcasts for its input share
+    pub fn mask_input(&self, input_share: u64) -> u64 {
+        (input_share ^ self.mask_share) & mask(self.input_bits)
+    }
+
+    /// Online step 2: this party's output share, given the XOR of every
+    /// party's broadcast
+    pub fn finish(self, opened: u64) -> u64 {
+        self.entries[(opened & mask(self.input_bits)) as usize]
+    }
+}
+
+/// Run one lookup over XOR-shared input, indexed by party, with a single
+/// reveal; independent lookups of a layer can share that round
+/// Tables from different preprocessing runs fail with `DomainMismatch`
+/// It takes every party's table and input share, so production builds
+/// refuse it; parties running apart use `OneTimeTable::mask_input` and
+/// `finish` instead
+pub fn lookup_shared(tables: Vec<OneTimeTable>, input_shares: &[u64]) -> Result<Vec<u64>> {
+    if local_simulation_refused() {
+        return Err(LocalSimulationRefused::new("lut::lookup_shared").into());
+    }
+    if tables.len() != input_shares.len() {
+        return Err(anyhow::anyhow!(
+            "Got {} table shares but {} input shares",
+            tables.len(),
+            input_shares.len()
+        ));
+    }
+    if let Some((position, table)) = tables
+        .iter()
+        .enumerate()
+        .find(|(position, table)| table.party != *position)
+    {
+        return Err(anyhow::anyhow!(
+            "Table share of party {} given in position {}",
+            table.party,
+            position
+        ));
+    }
+    if let Some(table) = tables.first() {
+        for other in &tables[1..] {
+            table.domain.check(&other.domain)?;
+        }
+    }
+
+    let opened = tables
+        .iter()
+        .zip(input_shares)
+        .fold(0, |opened, (table, &share)| {
+            opened ^ table.mask_input(share)
+        });
+    Ok(tables
+        .into_iter()
+        .map(|table| table.finish(opened))
+        .collect())
+}
+
+fn mask(bits: usize) -> u64 {
+    u64::MAX >> (64 - bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::DomainMismatch;
+
+    fn gf_mul(mut x: u8, mut y: u8) -> u8 {
+        let mut product = 0;
+        while y != 0 {
+            if y & 1 == 1 {
+                product ^= x;
+            }
+            x = (x << 1) ^ if x & 0x80 != 0 { 0x1b } else { 0 };
+            y >>= 1;
+        }
+        product
+    }
+
+    /// AES S-box: inverse in GF(2^8) (as x^254), then the affine map
+    fn sbox(x: u64) -> u64 {
+        let x = x as u8;
+        let mut inverse = 1;
+        for _ in 0..254 {
+            inverse = gf_mul(inverse, x);
+        }
+        let affine = (0..5).fold(0, |acc, shift| acc ^ inverse.rotate_left(shift));
+        u64::from(affine ^ 0x63)
+    }
+
+    fn xor_all(shares: &[u64]) -> u64 {
+        shares.iter().fold(0, |acc, share| acc ^ share)
+    }
+
+    #[test]
+    fn test_lookup_table_validation() {
+        assert!(LookupTable::new(0, 1, vec![0]).is_err());
+        assert!(LookupTable::new(2, 1, vec![0, 1, 1]).is_err());
+        assert!(LookupTable::new(2, 1, vec![0, 1, 1, 2]).is_err());
+        assert!(LookupTable::from_fn(MAX_LUT_INPUTS + 1, 1, |_| 0).is_err());
+        let table = LookupTable::new(1, 1, vec![1, 0]).unwrap();
+        assert!(table.preprocess(1).is_err());
+    }
+
+    #[test]
+    fn test_sbox_lookup() {
+        let table = LookupTable::from_fn(8, 8, sbox).unwrap();
+        assert_eq!(table.lookup(0x00), 0x63);
+        assert_eq!(table.lookup(0x53), 0xed);
+
+        for parties in 2..=4 {
+            for input in [0x00, 0x01, 0x53, 0xff] {
+                let mut input_shares: Vec<u64> =
+                    (1..parties).map(|_| rand::random::<u64>() & 0xff).collect();
+                input_shares.push(input ^ xor_all(&input_shares));
+
+                let tables = table.preprocess(parties).unwrap();
+                let output = lookup_shared(tables, &input_shares).unwrap();
+                assert_eq!(xor_all(&output), table.lookup(input), "{parties} parties");
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_time_table_online_steps() {
+        // 3-input majority
+        let table = LookupTable::from_fn(3, 1, |x| u64::from(x.count_ones() >= 2)).unwrap();
+        let input_shares = [0b101, 0b011, 0b000];
+        let tables = table.preprocess(3).unwrap();
+
+        let opened = tables
+            .iter()
+            .zip(input_shares)
+            .fold(0, |opened, (table, share)| opened ^ table.mask_input(share));
+        let output: Vec<u64> = tables.into_iter().map(|t| t.finish(opened)).collect();
+        assert_eq!(xor_all(&output), 1);
+
+        // Shares of two preprocessing runs
+        let mut tables = table.preprocess(2).unwrap();
+        tables[1] = table.preprocess(2).unwrap().pop().unwrap();
+        let error = lookup_shared(tables, &[0, 0]).unwrap_err();
+        assert!(error.downcast_ref::<DomainMismatch>().is_some());
+
+        let mut tables = table.preprocess(2).unwrap();
+        tables.swap(0, 1);
+        assert!(lookup_shared(tables, &[0, 0]).is_err());
+        assert!(lookup_shared(table.preprocess(2).unwrap(), &[0]).is_err());
+    }
+}
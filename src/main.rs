@@ -1,46 +1,1033 @@
 use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Instant;
 
-use gmw_rs::{Circuit, GmwProtocol, LocalEvaluator};
+use gmw_rs::diagnose::find_divergence;
+use gmw_rs::packing::{pack_bits, unpack_bits};
+use gmw_rs::{
+    Backend, BusEncoding, Circuit, CircuitAuditor, CircuitFormat, CircuitRegistry, CommitmentLog,
+    Dispute, ErrorCode, ExecutionReport, Explainer, GmwProtocol, HashAlgorithm, HtmlReport,
+    InputEncoder, KeyFile, LayerStatsHook, NetworkModel, NetworkSimulator, OsKeyring, OtBatching,
+    PartyDemo, PreparedCircuit, Progress, ProgressDump, ProgressHook, ReconstructionTopology,
+    ResultCache, SchemaKind, SecretStore, SelfTest, ShareOpening, SimulationReport,
+};
 
-/// Run a circuit with unified interface
-fn run_circuit(circuit_file: &str, inputs: Vec<bool>, party_count: usize) -> Result<()> {
-    let circuit = Circuit::from_file(circuit_file)?;
+/// Version of the `--format json` schema; bumped on incompatible changes
+const JSON_SCHEMA_VERSION: u32 = 1;
 
-    // Create GMW protocol instance and run circuit
-    let protocol = GmwProtocol::new(party_count)?;
-    let outputs = protocol.run_circuit(&circuit, &inputs)?;
+/// How subcommands report results on stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
 
-    println!("Inputs: {inputs:?}");
-    println!("Outputs:");
+/// Print a JSON result object tagged with the schema version and command
+fn print_json(command: &str, mut body: Value) {
+    if let Value::Object(fields) = &mut body {
+        fields.insert("schema_version".to_string(), json!(JSON_SCHEMA_VERSION));
+        fields.insert("command".to_string(), json!(command));
+    }
+    println!("{body:#}");
+}
+
+/// Draw a progress bar on stderr, ending the line once the run completes
+fn draw_progress(progress: &Progress) {
+    const WIDTH: usize = 30;
+    let filled = (progress.fraction() * WIDTH as f64) as usize;
+    let eta = progress
+        .eta()
+        .map_or_else(|| "?".to_string(), |eta| format!("{eta:.1?}"));
+
+    eprint!(
+        "\r[{}{}] {:3.0}% layer {}/{} ETA {eta}   ",
+        "#".repeat(filled),
+        ".".repeat(WIDTH - filled),
+        progress.fraction() * 100.0,
+        progress.layer,
+        progress.layer_count
+    );
+    if progress.gates_done == progress.gate_count {
+        eprintln!();
+    }
+    let _ = std::io::stderr().flush();
+}
+
+/// Directory of per-party storage keys unless `--key-dir` or `--keyring` is given
+const DEFAULT_KEY_DIR: &str = ".gmw-keys";
+
+/// Remove `--hash ALGORITHM` from anywhere in the arguments; SHA-256 if absent
+fn take_hash(args: &mut Vec<String>) -> Result<HashAlgorithm> {
+    let Some(position) = args.iter().position(|arg| arg == "--hash") else {
+        return Ok(HashAlgorithm::default());
+    };
+    let name = args
+        .get(position + 1)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Missing value for --hash"))?;
+    args.drain(position..position + 2);
+    name.parse()
+}
 
-    for (name, result) in outputs {
-        print!("  {name} = {result}");
+/// Remove `--key-dir DIR` and `--keyring` from anywhere in the arguments and
+/// open the secret store they select, sealing with `hash`
+fn take_secret_store(args: &mut Vec<String>, hash: HashAlgorithm) -> Result<SecretStore> {
+    if let Some(position) = args.iter().position(|arg| arg == "--keyring") {
+        args.remove(position);
+        return Ok(SecretStore::new(OsKeyring::new("gmw-rs")).with_hash(hash));
+    }
+    let Some(position) = args.iter().position(|arg| arg == "--key-dir") else {
+        return Ok(SecretStore::new(KeyFile::new(DEFAULT_KEY_DIR)).with_hash(hash));
+    };
+    let dir = args
+        .get(position + 1)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Missing value for --key-dir"))?;
+    args.drain(position..position + 2);
+    Ok(SecretStore::new(KeyFile::new(dir)).with_hash(hash))
+}
 
-        // Always verify using local circuit evaluation
-        let output_info = circuit
+/// Options of the `run` command
+struct RunOptions<'a> {
+    /// `--parties`, checked against the circuit's declared party count
+    party_count: Option<usize>,
+    /// `--backend`, how the gates are evaluated
+    backend: Backend,
+    network: Option<NetworkSimulator>,
+    progress: bool,
+    layer_stats: Option<&'a str>,
+    html: Option<&'a str>,
+    explain: Option<(&'a str, u64)>,
+    params: &'a HashMap<String, i64>,
+    commit_inputs: Option<&'a str>,
+    values: &'a [(String, String)],
+    hash: HashAlgorithm,
+    store: SecretStore,
+    format: OutputFormat,
+    /// `--verify-cache`, keeping expected outputs across runs
+    verify_cache: Option<&'a str>,
+    /// `--binary`: packed outputs on stdout instead of a report
+    binary: bool,
+    /// `--dry-run`: evaluate everything but open no output
+    dry_run: bool,
+}
+
+/// Build the input vector from `--value NAME=VALUE` options
+/// Input buses take integers, variant indices or decimals per their declared
+/// encoding; single inputs take 0 or 1
+fn encode_values(circuit: &Circuit, values: &[(String, String)]) -> Result<Vec<bool>> {
+    let mut encoder = InputEncoder::new(circuit);
+    for (name, text) in values {
+        let invalid = || anyhow::anyhow!("Invalid value for {}: {}", name, text);
+        let bus = circuit
             .metadata
-            .outputs
+            .input_buses
+            .iter()
+            .find(|bus| &bus.name == name);
+        match bus.map(|bus| bus.encoding) {
+            Some(BusEncoding::FixedPoint { .. }) => {
+                encoder.fixed(name, text.parse::<f64>().map_err(|_| invalid())?)?
+            }
+            Some(_) => encoder.int(name, text.parse::<i64>().map_err(|_| invalid())?)?,
+            None => encoder.bit(name, text.parse::<u8>().map_err(|_| invalid())? != 0)?,
+        };
+    }
+    encoder.finish()
+}
+
+/// Run a circuit with unified interface
+fn run_circuit(circuit_file: &str, inputs: Vec<bool>, options: RunOptions) -> Result<()> {
+    let RunOptions {
+        party_count,
+        backend,
+        network,
+        progress,
+        layer_stats,
+        html,
+        explain,
+        params,
+        commit_inputs,
+        values,
+        hash,
+        store,
+        format,
+        verify_cache,
+        binary,
+        dry_run,
+    } = options;
+    let circuit = Circuit::load_with_params(circuit_file, params)
+        .map_err(|err| ErrorCode::BadCircuit.tag(err))?;
+    // Without --parties, use the circuit's declaration or fall back to 2
+    let requested = party_count.or_else(|| circuit.metadata.parties.is_none().then_some(2));
+    let mut protocol = GmwProtocol::for_circuit(&circuit, requested)?.with_backend(backend);
+    let party_count = protocol.party_count();
+    let inputs = if values.is_empty() {
+        inputs
+    } else if inputs.is_empty() {
+        encode_values(&circuit, values).map_err(|err| ErrorCode::InputMismatch.tag(err))?
+    } else {
+        return Err(anyhow::anyhow!(
+            "--value cannot be combined with positional inputs"
+        ));
+    };
+
+    if let Some((format, seed)) = explain {
+        let explanation = Explainer::new(party_count, seed)?.run(&circuit, &inputs)?;
+        match format {
+            "markdown" | "md" => print!("{}", explanation.to_markdown()),
+            "json" => println!("{}", explanation.to_json()?),
+            _ => return Err(anyhow::anyhow!("Invalid explain format: {}", format)),
+        }
+        return Ok(());
+    }
+
+    // Run the circuit, optionally on a modelled network
+    if progress {
+        let prepared = PreparedCircuit::new(&circuit);
+        protocol = protocol.with_hook(ProgressHook::new(&prepared, draw_progress));
+    }
+    let stats_hook = (layer_stats.is_some() || html.is_some()).then(|| {
+        let prepared = PreparedCircuit::new(&circuit);
+        Arc::new(LayerStatsHook::new(&prepared, party_count))
+    });
+    if let Some(hook) = &stats_hook {
+        protocol = protocol.with_hook(Arc::clone(hook));
+    }
+    let start = Instant::now();
+    let simulation = match network {
+        Some(simulator) => Some(
+            simulator
+                .run(&protocol, &circuit, &inputs)
+                .map_err(|err| ErrorCode::ProtocolAbort.tag(err))?,
+        ),
+        None => None,
+    };
+    let report = match (&simulation, commit_inputs) {
+        (Some(_), Some(_)) => {
+            return Err(anyhow::anyhow!(
+                "--commit-inputs cannot be combined with --network"
+            ))
+        }
+        (Some(simulation), None) => simulation.execution.clone(),
+        (None, Some(prefix)) => {
+            let session = rand::random::<u64>();
+            let (report, log, openings) = protocol
+                .run_with_commitments(&circuit, &inputs, session, &hash)
+                .map_err(|err| ErrorCode::ProtocolAbort.tag(err))?;
+            // Every party stores the log; each keeps only its own openings,
+            // sealed under its storage key
+            fs::write(format!("{prefix}.log.json"), log.to_json()?)?;
+            for (party, openings) in openings.iter().enumerate() {
+                store.save(format!("{prefix}.party{party}.sealed"), party, openings)?;
+            }
+            report
+        }
+        (None, None) if dry_run => protocol
+            .dry_run(&circuit, &inputs)
+            .map_err(|err| ErrorCode::ProtocolAbort.tag(err))?,
+        (None, None) => protocol
+            .run_with_report(&circuit, &inputs)
+            .map_err(|err| ErrorCode::ProtocolAbort.tag(err))?,
+    };
+    let elapsed = start.elapsed();
+    if let (Some(path), Some(hook)) = (layer_stats, &stats_hook) {
+        let contents = if path.ends_with(".json") {
+            hook.to_json()?
+        } else {
+            hook.to_csv()
+        };
+        fs::write(path, contents)?;
+    }
+    if let (Some(path), Some(hook)) = (html, &stats_hook) {
+        let mut page = HtmlReport::new(&circuit)
+            .with_parties(party_count)
+            .with_report(&report)
+            .with_layer_stats(hook.stats())
+            .with_timing("Evaluation", elapsed);
+        if let Some(simulation) = &simulation {
+            page = page.with_timing("Projected network time", simulation.projected_time());
+        }
+        fs::write(path, page.render())?;
+    }
+
+    // Nothing was opened, so there is nothing to verify or show but stats
+    if dry_run {
+        if format == OutputFormat::Json {
+            print_json(
+                "run",
+                json!({
+                    "circuit": circuit.name,
+                    "parties": party_count,
+                    "dry_run": true,
+                    "stats": run_stats(&circuit, &report),
+                    "network": simulation.as_ref().map(network_json),
+                }),
+            );
+            return Ok(());
+        }
+        println!("Dry run of {}: evaluated, no output opened", circuit.name);
+        println!("Parties: {party_count}, gates: {}", circuit.gates.len());
+        println!("Rounds: {} (AND-depth {})", report.rounds, report.and_depth);
+        if let Some(simulation) = &simulation {
+            print_network(simulation);
+        }
+        return Ok(());
+    }
+
+    // Always verify using local circuit evaluation
+    let verify_cache = match verify_cache {
+        Some(dir) => ResultCache::with_dir(dir, store)?,
+        None => ResultCache::new(),
+    };
+    let expected = verify_cache.outputs(&circuit, &inputs)?;
+    let outputs = report
+        .outputs
+        .iter()
+        .map(|(name, result)| {
+            let (_, expected) = expected
+                .iter()
+                .find(|(output, _)| output == name)
+                .ok_or_else(|| anyhow::anyhow!("Output {} not found", name))?;
+            Ok((name, *result, *expected))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if binary {
+        // Nothing but the outputs goes to stdout, so a wrong result fails
+        // the run instead of being marked
+        if let Some((name, _, expected)) = outputs
             .iter()
-            .find(|info| info.name == name)
-            .ok_or_else(|| anyhow::anyhow!("Output {} not found", name))?;
+            .find(|(_, value, expected)| value != expected)
+        {
+            return Err(anyhow::anyhow!(
+                "Output {} does not match local evaluation (expected {})",
+                name,
+                expected
+            ));
+        }
+        let bits: Vec<bool> = outputs.iter().map(|&(_, value, _)| value).collect();
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&pack_bits(&bits))?;
+        stdout.flush()?;
+        return Ok(());
+    }
+    let buses = report
+        .buses
+        .iter()
+        .map(|bus| {
+            let value = match bus.encoding {
+                BusEncoding::Unsigned | BusEncoding::OneHot => {
+                    json!(report.decode::<u64>(&bus.name)?)
+                }
+                BusEncoding::FixedPoint { .. } => json!(report.decode_fixed(&bus.name)?),
+                _ => json!(report.decode::<i64>(&bus.name)?),
+            };
+            Ok((&bus.name, value, report.wrapped(&bus.name)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "run",
+            json!({
+                "circuit": circuit.name,
+                "parties": party_count,
+                "inputs": inputs,
+                "outputs": outputs.iter().map(|(name, value, expected)| json!({
+                    "name": name,
+                    "value": value,
+                    "expected": expected,
+                    "verified": value == expected,
+                })).collect::<Vec<_>>(),
+                "buses": buses.iter().map(|(name, value, wrapped)| json!({
+                    "name": name,
+                    "value": value,
+                    "wrapped": wrapped,
+                })).collect::<Vec<_>>(),
+                "verified": outputs.iter().all(|(_, value, expected)| value == expected),
+                "stats": run_stats(&circuit, &report),
+                "network": simulation.as_ref().map(network_json),
+            }),
+        );
+        return Ok(());
+    }
 
-        let expected = LocalEvaluator::get_output(&circuit, &inputs, output_info.id)?;
+    println!("Inputs: {inputs:?}");
+    println!("Outputs:");
+    for (name, result, expected) in &outputs {
         if result == expected {
-            println!(" ✓");
+            println!("  {name} = {result} ✓");
+        } else {
+            println!("  {name} = {result} ✗ (expected {expected})");
+        }
+    }
+    for (name, value, wrapped) in &buses {
+        match wrapped {
+            Some(true) => println!("  {name} = {value} (bus, wrapped around)"),
+            _ => println!("  {name} = {value} (bus)"),
+        }
+    }
+
+    println!("Rounds: {} (AND-depth {})", report.rounds, report.and_depth);
+
+    if let Some(simulation) = &simulation {
+        print_network(simulation);
+    }
+
+    Ok(())
+}
+
+/// `stats` object of a `run` result
+fn run_stats(circuit: &Circuit, report: &ExecutionReport) -> Value {
+    json!({
+        "gates": circuit.gates.len(),
+        "rounds": report.rounds,
+        "interactive_rounds": report.interactive_rounds,
+        "and_depth": report.and_depth,
+    })
+}
+
+/// `network` object of a `run` result
+fn network_json(simulation: &SimulationReport) -> Value {
+    json!({
+        "rounds": simulation.rounds,
+        "ot_batch_sizes": simulation.ot_batch_sizes,
+        "bytes": simulation.total_bytes,
+        "compute_seconds": simulation.compute_time.as_secs_f64(),
+        "network_seconds": simulation.network_time.as_secs_f64(),
+        "total_seconds": simulation.projected_time().as_secs_f64(),
+    })
+}
+
+fn print_network(simulation: &SimulationReport) {
+    println!("Network projection:");
+    println!("  rounds = {}", simulation.rounds);
+    println!("  ot batch sizes = {:?}", simulation.ot_batch_sizes);
+    println!("  bytes = {}", simulation.total_bytes);
+    println!("  compute = {:?}", simulation.compute_time);
+    println!("  network = {:?}", simulation.network_time);
+    println!("  total = {:?}", simulation.projected_time());
+}
+
+/// Compare GMW execution against local evaluation over random inputs
+fn run_audit(args: &[String], format: OutputFormat) -> Result<()> {
+    let mut party_count = 2;
+    let mut trials = 100;
+    let mut seed = rand::random::<u64>();
+    let mut circuit_file = None;
+    let mut arg_idx = 0;
+
+    while arg_idx < args.len() {
+        let arg = &args[arg_idx];
+        if !arg.starts_with("--") {
+            circuit_file = Some(arg.clone());
+            arg_idx += 1;
+            continue;
+        }
+
+        let value = args
+            .get(arg_idx + 1)
+            .ok_or_else(|| anyhow::anyhow!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--parties" => {
+                party_count = value
+                    .parse::<usize>()
+                    .map_err(|_| anyhow::anyhow!("Invalid party count: {}", value))?;
+            }
+            "--trials" => {
+                trials = value
+                    .parse::<usize>()
+                    .map_err(|_| anyhow::anyhow!("Invalid trial count: {}", value))?;
+            }
+            "--seed" => {
+                seed = value
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("Invalid seed: {}", value))?;
+            }
+            flag => return Err(anyhow::anyhow!("Unknown option: {}", flag)),
+        }
+        arg_idx += 2;
+    }
+
+    let circuit_file = circuit_file.ok_or_else(|| anyhow::anyhow!("Missing circuit file"))?;
+    let circuit = Circuit::load(&circuit_file).map_err(|err| ErrorCode::BadCircuit.tag(err))?;
+    let protocol = GmwProtocol::new(party_count)?;
+
+    if format == OutputFormat::Text {
+        println!("Auditing {circuit_file}: {trials} trials, {party_count} parties, seed {seed}");
+    }
+    let report = CircuitAuditor::new(trials, seed).run(&protocol, &circuit)?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "audit",
+            json!({
+                "circuit": circuit.name,
+                "parties": party_count,
+                "seed": seed,
+                "trials": report.trials,
+                "rejected": report.rejected,
+                "mismatches": report.mismatches.iter().map(|mismatch| json!({
+                    "trial": mismatch.trial,
+                    "seed": mismatch.seed,
+                    "inputs": mismatch.inputs,
+                    "output": mismatch.output,
+                    "actual": mismatch.actual,
+                    "expected": mismatch.expected,
+                })).collect::<Vec<_>>(),
+                "verified": report.passed(),
+            }),
+        );
+        // The mismatches are in the JSON already; signal failure by exit code only
+        if !report.passed() {
+            std::process::exit(ErrorCode::Failed.exit_status());
+        }
+        return Ok(());
+    }
+
+    for mismatch in &report.mismatches {
+        println!(
+            "  ✗ trial {} (seed {}): inputs {:?}, {} = {} (expected {})",
+            mismatch.trial,
+            mismatch.seed,
+            mismatch.inputs,
+            mismatch.output,
+            mismatch.actual,
+            mismatch.expected
+        );
+    }
+
+    if report.passed() {
+        if report.rejected > 0 {
+            println!("  {} trials rejected by input constraints", report.rejected);
+        }
+        println!("All {} trials matched ✓", report.trials);
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} mismatching outputs; rerun a trial with --seed <seed> --trials 1",
+            report.mismatches.len()
+        ))
+    }
+}
+
+/// Run the built-in battery and report each stage's time and result
+fn run_selftest(args: &[String], format: OutputFormat) -> Result<()> {
+    let mut party_count = 2;
+    let mut seed = rand::random::<u64>();
+
+    for pair in args.chunks(2) {
+        let [flag, value] = pair else {
+            return Err(anyhow::anyhow!("Missing value for {}", pair[0]));
+        };
+        match flag.as_str() {
+            "--parties" => {
+                party_count = value
+                    .parse::<usize>()
+                    .map_err(|_| anyhow::anyhow!("Invalid party count: {}", value))?;
+            }
+            "--seed" => {
+                seed = value
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("Invalid seed: {}", value))?;
+            }
+            flag => return Err(anyhow::anyhow!("Unknown option: {}", flag)),
+        }
+    }
+
+    let report = SelfTest::new(party_count, seed).run()?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "selftest",
+            json!({
+                "parties": report.parties,
+                "seed": report.seed,
+                "stages": report.stages.iter().map(|stage| json!({
+                    "name": stage.name,
+                    "ms": stage.elapsed.as_secs_f64() * 1000.0,
+                    "passed": stage.error.is_none(),
+                    "error": stage.error,
+                })).collect::<Vec<_>>(),
+                "passed": report.passed(),
+            }),
+        );
+        if !report.passed() {
+            std::process::exit(ErrorCode::Failed.exit_status());
+        }
+        return Ok(());
+    }
+
+    println!("Self-test: {party_count} parties, seed {seed}");
+    for stage in &report.stages {
+        let ms = stage.elapsed.as_secs_f64() * 1000.0;
+        match &stage.error {
+            None => println!("  ✓ {:<20} {ms:>9.1} ms", stage.name),
+            Some(error) => println!("  ✗ {:<20} {ms:>9.1} ms  {error}", stage.name),
+        }
+    }
+
+    let failed = report
+        .stages
+        .iter()
+        .filter(|stage| stage.error.is_some())
+        .count();
+    if failed == 0 {
+        println!("All {} stages passed ✓", report.stages.len());
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{} self-test stages failed", failed))
+    }
+}
+
+/// Run a circuit with one thread per party exchanging real protocol messages
+fn run_demo(args: &[String], format: OutputFormat) -> Result<()> {
+    let mut party_count = None;
+    let mut seed = None;
+    let mut network = None;
+    let mut positional = Vec::new();
+    let mut arg_idx = 0;
+
+    while arg_idx < args.len() {
+        let arg = &args[arg_idx];
+        if !arg.starts_with("--") {
+            positional.push(arg.clone());
+            arg_idx += 1;
+            continue;
+        }
+
+        let value = args
+            .get(arg_idx + 1)
+            .ok_or_else(|| anyhow::anyhow!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--parties" => {
+                party_count = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| anyhow::anyhow!("Invalid party count: {}", value))?,
+                );
+            }
+            "--seed" => {
+                seed = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| anyhow::anyhow!("Invalid seed: {}", value))?,
+                );
+            }
+            "--network" => {
+                network = Some(match value.as_str() {
+                    "lan" => NetworkModel::lan(),
+                    "wan" => NetworkModel::wan(),
+                    _ => return Err(anyhow::anyhow!("Invalid network model: {}", value)),
+                });
+            }
+            flag => return Err(anyhow::anyhow!("Unknown option: {}", flag)),
+        }
+        arg_idx += 2;
+    }
+    if network.is_some() && seed.is_none() {
+        return Err(anyhow::anyhow!("--network needs --seed in demo"));
+    }
+
+    let (circuit_file, inputs) = positional
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("Missing circuit file"))?;
+    let circuit = Circuit::load(circuit_file).map_err(|err| ErrorCode::BadCircuit.tag(err))?;
+    let inputs = inputs
+        .iter()
+        .map(|value| value.parse::<u8>().map(|bit| bit != 0))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| ErrorCode::InputMismatch.tag(err.into()))?;
+    let declared = circuit.metadata.parties.as_ref().map(|spec| spec.count);
+    let party_count = party_count.or(declared).unwrap_or(2);
+    let mut demo = PartyDemo::new(party_count);
+    if let Some(seed) = seed {
+        demo = demo.with_seed(seed);
+    }
+    if let Some(network) = network {
+        demo = demo.with_network(network);
+    }
+    let report = demo
+        .run(&circuit, &inputs)
+        .map_err(|err| ErrorCode::ProtocolAbort.tag(err))?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "demo",
+            json!({
+                "circuit": circuit.name,
+                "parties": report.parties,
+                "outputs": report.outputs.iter().map(|(name, value)| json!({
+                    "name": name,
+                    "value": value,
+                })).collect::<Vec<_>>(),
+                "messages": report.messages,
+                "ms": report.elapsed.as_secs_f64() * 1000.0,
+                "seed": report.seed,
+                "network_ms": report.network_time.map(|time| time.as_secs_f64() * 1000.0),
+                "deliveries": report.deliveries.iter().map(|delivery| json!({
+                    "ms": delivery.at.as_secs_f64() * 1000.0,
+                    "from": delivery.from,
+                    "to": delivery.to,
+                    "message": delivery.message,
+                })).collect::<Vec<_>>(),
+            }),
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Demo: {} with {} parties, one thread each",
+        circuit.name, report.parties
+    );
+    if let Some(seed) = report.seed {
+        println!("Deterministic run from seed {seed}; rerun with --seed {seed} to replay it");
+    }
+    for (party, sent) in report.messages.iter().enumerate() {
+        println!("  Party {party} sent {sent} messages");
+    }
+    for (name, value) in &report.outputs {
+        println!("{name}: {}", u8::from(*value));
+    }
+    println!("Finished in {:.1?}", report.elapsed);
+    if let Some(time) = report.network_time {
+        println!(
+            "Simulated network time {:.1?} over {} deliveries",
+            time,
+            report.deliveries.len()
+        );
+    }
+    Ok(())
+}
+
+/// Force the opening of a committed input from the log and party openings
+fn run_dispute(args: &[String], format: OutputFormat) -> Result<()> {
+    let mut args = args.to_vec();
+    let hash = take_hash(&mut args)?;
+    let store = take_secret_store(&mut args, hash)?;
+    let mut threshold = None;
+    let mut approvals = Vec::new();
+    let mut positional = Vec::new();
+    let mut arg_idx = 0;
+
+    while arg_idx < args.len() {
+        let arg = &args[arg_idx];
+        if !arg.starts_with("--") {
+            positional.push(arg.as_str());
+            arg_idx += 1;
+            continue;
+        }
+
+        let value = args
+            .get(arg_idx + 1)
+            .ok_or_else(|| anyhow::anyhow!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--threshold" => {
+                threshold = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| anyhow::anyhow!("Invalid threshold: {}", value))?,
+                );
+            }
+            "--approve" => {
+                for party in value.split(',') {
+                    approvals.push(
+                        party
+                            .parse::<usize>()
+                            .map_err(|_| anyhow::anyhow!("Invalid party: {}", party))?,
+                    );
+                }
+            }
+            flag => return Err(anyhow::anyhow!("Unknown option: {}", flag)),
+        }
+        arg_idx += 2;
+    }
+
+    let [log_file, input, ref opening_files @ ..] = positional[..] else {
+        return Err(anyhow::anyhow!(
+            "Usage: dispute <log.json> <input> [--threshold K] [--approve P,...] [--hash ALG] <openings.json>..."
+        ));
+    };
+    let log = CommitmentLog::from_json(&fs::read_to_string(log_file)?)?;
+    // Without an agreed threshold, every party must approve
+    let threshold = threshold.unwrap_or(log.party_count);
+
+    let mut dispute = Dispute::new(&log, &hash, input, threshold)?;
+    for &party in &approvals {
+        dispute.approve(party)?;
+    }
+    for file in opening_files {
+        let bytes = fs::read(file)?;
+        let openings: Vec<ShareOpening> = if SecretStore::is_sealed(&bytes) {
+            store.load(file)?
         } else {
-            println!(" ✗ (expected {expected})");
+            serde_json::from_slice(&bytes)?
+        };
+        for opening in &openings {
+            dispute.submit(opening)?;
+        }
+    }
+    let value = dispute.resolve()?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "dispute",
+            json!({
+                "session": log.session,
+                "input": input,
+                "value": value,
+                "threshold": threshold,
+                "approvals": approvals,
+            }),
+        );
+    } else {
+        println!(
+            "Input {input} of session {} = {} (opened with approvals from {approvals:?})",
+            log.session,
+            u8::from(value)
+        );
+    }
+    Ok(())
+}
+
+/// Correlate per-party progress dumps of a failed run
+fn run_diagnose(args: &[String], format: OutputFormat) -> Result<()> {
+    if args.is_empty() {
+        return Err(anyhow::anyhow!("Usage: diagnose <dump.json>..."));
+    }
+    let dumps = args
+        .iter()
+        .map(|path| ProgressDump::read(path))
+        .collect::<Result<Vec<_>>>()?;
+    let divergence = find_divergence(&dumps)?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "diagnose",
+            json!({
+                "circuit": dumps[0].circuit,
+                "parties": dumps.iter().map(|dump| json!({
+                    "party": dump.progress.party_id,
+                    "gate": dump.progress.cursor,
+                    "acked_round": dump.progress.acked_round,
+                    "pending": dump.progress.pending,
+                })).collect::<Vec<_>>(),
+                "divergence": divergence.as_ref().map(|divergence| json!({
+                    "gate": divergence.gate,
+                    "round": divergence.round,
+                    "lagging": divergence.lagging,
+                    "slot": divergence.slot,
+                })),
+            }),
+        );
+        return Ok(());
+    }
+
+    for dump in &dumps {
+        let progress = &dump.progress;
+        println!(
+            "  party {}: gate {}, acked round {}{}",
+            progress.party_id,
+            progress.cursor,
+            progress.acked_round,
+            if progress.pending {
+                ", waiting for cross term"
+            } else {
+                ""
+            }
+        );
+    }
+    match divergence {
+        None => println!("All {} parties reached the same point", dumps.len()),
+        Some(divergence) => {
+            println!(
+                "First divergence at gate {} after round {}: parties {:?} are behind",
+                divergence.gate, divergence.round, divergence.lagging
+            );
+            if let Some(slot) = divergence.slot {
+                println!("  first wire slot with mismatched progress: {slot}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Translate a circuit between JSON, Bristol, binary and text formats
+fn run_convert(args: &[String], format: OutputFormat) -> Result<()> {
+    let mut from = None;
+    let mut to = None;
+    let mut files = Vec::new();
+    let mut arg_idx = 0;
+
+    while arg_idx < args.len() {
+        let arg = &args[arg_idx];
+        if !arg.starts_with("--") {
+            files.push(arg.clone());
+            arg_idx += 1;
+            continue;
+        }
+
+        let value = args
+            .get(arg_idx + 1)
+            .ok_or_else(|| anyhow::anyhow!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--from" => from = Some(value.parse::<CircuitFormat>()?),
+            "--to" => to = Some(value.parse::<CircuitFormat>()?),
+            flag => return Err(anyhow::anyhow!("Unknown option: {}", flag)),
         }
+        arg_idx += 2;
+    }
+
+    let [input, output] = &files[..] else {
+        return Err(anyhow::anyhow!("Expected an input and an output file"));
+    };
+    let detect = |path: &str, given: Option<CircuitFormat>| {
+        given
+            .or_else(|| CircuitFormat::from_path(path))
+            .ok_or_else(|| anyhow::anyhow!("Cannot infer the format of {}; use --from/--to", path))
+    };
+    let from = detect(input, from)?;
+    let to = detect(output, to)?;
+
+    let circuit = std::fs::read(input)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| Circuit::decode(&bytes, from))
+        .map_err(|err| ErrorCode::BadCircuit.tag(err))?;
+    let encoded = circuit.encode(to)?;
+    std::fs::write(output, &encoded.bytes)?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "convert",
+            json!({
+                "input": input,
+                "from": from.to_string(),
+                "output": output,
+                "to": to.to_string(),
+                "gates": circuit.gates.len(),
+                "warnings": encoded.warnings,
+            }),
+        );
+        return Ok(());
+    }
+
+    for warning in &encoded.warnings {
+        eprintln!("Warning: {warning}");
+    }
+    println!(
+        "Converted {input} ({from}) to {output} ({to}): {} gates",
+        circuit.gates.len()
+    );
+
+    Ok(())
+}
+
+/// List the circuits in a single- or multi-circuit JSON file
+fn run_list(args: &[String], format: OutputFormat) -> Result<()> {
+    let path = args
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("Missing circuit file"))?;
+    let registry =
+        CircuitRegistry::from_file(path).map_err(|err| ErrorCode::BadCircuit.tag(err))?;
+
+    if format == OutputFormat::Json {
+        print_json(
+            "list",
+            json!({
+                "file": path,
+                "circuits": registry.iter().map(|circuit| json!({
+                    "name": circuit.name,
+                    "inputs": circuit.metadata.inputs.len(),
+                    "outputs": circuit.metadata.outputs.len(),
+                    "gates": circuit.gates.len(),
+                })).collect::<Vec<_>>(),
+            }),
+        );
+        return Ok(());
+    }
+
+    for circuit in registry.iter() {
+        println!(
+            "{path}#{}: {} inputs, {} outputs, {} gates",
+            circuit.name,
+            circuit.metadata.inputs.len(),
+            circuit.metadata.outputs.len(),
+            circuit.gates.len()
+        );
     }
 
     Ok(())
 }
 
+/// Print the JSON Schema of a file format, the circuit format by default
+/// The output is a schema document in any `--format`
+fn run_schema(args: &[String]) -> Result<()> {
+    let kind = match args.first() {
+        Some(name) => name.parse()?,
+        None => SchemaKind::Circuit,
+    };
+    println!("{:#}", kind.schema());
+    Ok(())
+}
+
 fn print_usage() {
-    println!("Usage: cargo run -- [--parties N] <circuit.json> <input1> [input2] [input3] ...");
+    println!(
+        "Usage: cargo run -- [run] [--parties N] [--backend gmw|bmr] [--network lan|wan] [--ot-batch N|auto] [--reconstruction all|star[:P]] [--progress] [--layer-stats FILE] [--html FILE] [--explain md|json [--seed S]] [--param NAME=VALUE]... [--commit-inputs PREFIX] [--verify-cache DIR] [--binary] [--dry-run] [--value NAME=VALUE]... <circuit.json>[#name] [input1] [input2] ..."
+    );
+    println!("       cargo run -- audit <circuit.json> [--trials N] [--parties N] [--seed S]");
+    println!("       cargo run -- convert <input> <output> [--from FORMAT] [--to FORMAT]");
+    println!("       cargo run -- selftest [--parties N] [--seed S]");
+    println!("       cargo run -- demo [--parties N] [--seed S [--network lan|wan]] <circuit.json> [input1] [input2] ...");
+    println!("       cargo run -- list <circuits.json>");
+    println!("       cargo run -- schema [circuit|library|shares|commitments|receipt|progress]");
+    println!("       cargo run -- diagnose <party0.dump.json> <party1.dump.json> ...");
+    println!("       cargo run -- dispute <PREFIX.log.json> <input> [--threshold K] [--approve P,...] [--hash ALG] <PREFIX.partyN.sealed>...");
     println!();
     println!("Options:");
-    println!("  --parties N    Use N-party computation (default: 2)");
+    println!("  --parties N        Use N-party computation (default: the circuit's declared count, else 2)");
+    println!(
+        "  --backend gmw|bmr  Evaluate with GMW or the experimental constant-round BMR backend (default: gmw)"
+    );
+    println!("  --network lan|wan  Project wall-clock time on a modelled network");
+    println!("  --ot-batch N|auto  OTs per message in the network projection (default: auto)");
+    println!(
+        "  --reconstruction all|star[:P]  Output exchange in the projection: all-to-all, or via party P (default: all)"
+    );
+    println!("  --progress         Show a progress bar with ETA on stderr");
+    println!(
+        "  --layer-stats FILE Write per-layer AND gates, bytes and time as CSV (JSON for .json)"
+    );
+    println!(
+        "  --html FILE        Write a standalone HTML report with graph, layer stats and outputs"
+    );
+    println!("  --explain md|json  Narrate the run step by step instead of running it plainly");
+    println!("  --param NAME=VALUE Set a parameter of a circuit template (repeatable)");
+    println!("  --value NAME=VALUE Set an input or input bus in its declared encoding instead of bits (repeatable)");
+    println!("  --commit-inputs PREFIX  Commit to input shares; write PREFIX.log.json and sealed PREFIX.partyN.sealed");
+    println!("  --verify-cache DIR Keep the results used for verification in DIR, sealed");
+    println!("  --binary           Read packed input bits from stdin unless given, write packed outputs to stdout");
+    println!(
+        "  --dry-run          Share and evaluate everything but open no output; print stats only"
+    );
+    println!("  --key-dir DIR      Per-party keys for sealed files (default: .gmw-keys)");
+    println!("  --keyring          Keep per-party keys in the OS keyring instead");
+    println!(
+        "  --hash sha256|blake3  Hash for input commitments and sealed files (default: sha256)"
+    );
+    println!(
+        "  --threshold K      Approvals needed to open a disputed input (default: all parties)"
+    );
+    println!("  --approve P,...    Parties approving the opening of a disputed input");
+    println!("  --trials N         Number of random audit trials (default: 100)");
+    println!("  --seed S           Seed for audit inputs, explained shares or a replayable demo (default: random)");
+    println!(
+        "  --from/--to FORMAT json, bristol, binary, text or sieve (default: from file extension)"
+    );
+    println!("  --format text|json Output format of any subcommand (default: text)");
     println!();
     println!("Examples:");
     println!("  cargo run -- circuits/not.json 1");
@@ -49,28 +1036,235 @@ fn print_usage() {
     println!("  cargo run -- --parties 3 circuits/and.json 1 0");
     println!("  cargo run -- --parties 4 circuits/xor.json 1 0");
     println!("  cargo run -- --parties 5 circuits/and.json 1 1");
+    println!("  cargo run -- --network wan circuits/full_adder.json 1 1 0");
+    println!("  cargo run -- audit circuits/full_adder.json --trials 1000 --parties 4");
+    println!("  cargo run -- convert circuits/full_adder.json full_adder.gmwt");
+    println!("  cargo run -- selftest --parties 3");
+    println!("  cargo run -- demo --parties 3 circuits/full_adder.json 1 1 0");
+    println!("  cargo run -- demo --seed 42 --network wan circuits/full_adder.json 1 1 0");
+    println!("  cargo run -- run circuits.json#half_adder 1 1");
+    println!("  cargo run -- schema circuit > circuit.schema.json");
+    println!("  cargo run -- --explain md circuits/mux_2to1.json 1 0 1");
+    println!("  cargo run -- --format json circuits/full_adder.json 1 1 0");
+    println!("  cargo run -- --layer-stats layers.csv circuits/full_adder.json 1 1 0");
+    println!("  cargo run -- --html report.html circuits/two_bit_adder.json 1 0 1 1");
+    println!("  cargo run -- --param N=2 circuits/ripple_adder.json 1 0 1 1");
+    println!("  cargo run -- --commit-inputs run1 circuits/and.json 1 0");
+    println!("  producer | cargo run -- --binary circuits/full_adder.json | consumer");
+    println!("  cargo run -- dispute run1.log.json a --threshold 2 --approve 0,1 run1.party0.sealed run1.party1.sealed");
+}
+
+/// Remove `--format text|json` from anywhere in the arguments
+fn take_format(args: &mut Vec<String>) -> Result<OutputFormat> {
+    let Some(position) = args.iter().position(|arg| arg == "--format") else {
+        return Ok(OutputFormat::Text);
+    };
+    let format = match args.get(position + 1).map(String::as_str) {
+        Some("text") => OutputFormat::Text,
+        Some("json") => OutputFormat::Json,
+        other => return Err(anyhow::anyhow!("Invalid output format: {:?}", other)),
+    };
+    args.drain(position..position + 2);
+    Ok(format)
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let format = take_format(&mut args)?;
+
+    let result = run_command(&args, format);
+    match result {
+        Err(error) if format == OutputFormat::Json => {
+            let command = [
+                "audit", "convert", "list", "dispute", "diagnose", "selftest", "demo", "schema",
+            ]
+            .into_iter()
+            .find(|command| args.get(1).is_some_and(|arg| arg == command))
+            .unwrap_or("run");
+            let code = ErrorCode::of(&error);
+            print_json(
+                command,
+                json!({
+                    "error": format!("{error:#}"),
+                    "code": code.exit_status(),
+                    "code_name": code.name(),
+                }),
+            );
+            std::process::exit(code.exit_status());
+        }
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            std::process::exit(ErrorCode::of(&error).exit_status());
+        }
+        Ok(()) => Ok(()),
+    }
+}
 
+fn run_command(args: &[String], format: OutputFormat) -> Result<()> {
     if args.len() < 2 {
         print_usage();
         return Ok(());
     }
 
+    if args[1] == "audit" {
+        return run_audit(&args[2..], format);
+    }
+
+    if args[1] == "selftest" {
+        return run_selftest(&args[2..], format);
+    }
+
+    if args[1] == "convert" {
+        return run_convert(&args[2..], format);
+    }
+
+    if args[1] == "list" {
+        return run_list(&args[2..], format);
+    }
+
+    if args[1] == "diagnose" {
+        return run_diagnose(&args[2..], format);
+    }
+
+    if args[1] == "dispute" {
+        return run_dispute(&args[2..], format);
+    }
+
+    if args[1] == "demo" {
+        return run_demo(&args[2..], format);
+    }
+
+    if args[1] == "schema" {
+        return run_schema(&args[2..]);
+    }
+
+    let mut args = args.to_vec();
+    let hash = take_hash(&mut args)?;
+    let store = take_secret_store(&mut args, hash)?;
+
     // Parse command line arguments
-    let mut party_count = 2; // Default to 2-party
-    let mut arg_idx = 1;
+    let mut party_count = None; // Circuit's declaration, else 2-party
+    let mut backend = Backend::Gmw;
+    let mut network = None;
+    let mut batching = OtBatching::Auto;
+    let mut reconstruction = ReconstructionTopology::AllToAll;
+    let mut progress = false;
+    let mut binary = false;
+    let mut dry_run = false;
+    let mut layer_stats = None;
+    let mut html = None;
+    let mut explain = None;
+    let mut seed = rand::random::<u64>();
+    let mut params = HashMap::new();
+    let mut commit_inputs = None;
+    let mut verify_cache = None;
+    let mut values = Vec::new();
+    // `run` is optional: `run <circuit> ...` and `<circuit> ...` are equivalent
+    let mut arg_idx = if args[1] == "run" { 2 } else { 1 };
 
-    // Check for --parties flag
-    if args.len() > 1 && args[1] == "--parties" && args.len() > 2 {
-        party_count = args[2]
-            .parse::<usize>()
-            .map_err(|_| anyhow::anyhow!("Invalid party count: {}", args[2]))?;
-        arg_idx = 3;
+    // Parse leading option flags
+    while arg_idx + 1 < args.len() && args[arg_idx].starts_with("--") {
+        if args[arg_idx] == "--progress" {
+            progress = true;
+            arg_idx += 1;
+            continue;
+        }
+        if args[arg_idx] == "--binary" {
+            binary = true;
+            arg_idx += 1;
+            continue;
+        }
+        if args[arg_idx] == "--dry-run" {
+            dry_run = true;
+            arg_idx += 1;
+            continue;
+        }
+
+        let value = &args[arg_idx + 1];
+        match args[arg_idx].as_str() {
+            "--parties" => {
+                party_count = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| anyhow::anyhow!("Invalid party count: {}", value))?,
+                );
+            }
+            "--backend" => {
+                backend = match value.as_str() {
+                    "gmw" => Backend::Gmw,
+                    "bmr" => Backend::Bmr,
+                    _ => return Err(anyhow::anyhow!("Invalid backend: {}", value)),
+                };
+            }
+            "--network" => {
+                network = Some(match value.as_str() {
+                    "lan" => NetworkModel::lan(),
+                    "wan" => NetworkModel::wan(),
+                    _ => return Err(anyhow::anyhow!("Invalid network model: {}", value)),
+                });
+            }
+            "--explain" => explain = Some(value.clone()),
+            "--layer-stats" => layer_stats = Some(value.clone()),
+            "--html" => html = Some(value.clone()),
+            "--commit-inputs" => commit_inputs = Some(value.clone()),
+            "--verify-cache" => verify_cache = Some(value.clone()),
+            "--value" => {
+                let (name, text) = value
+                    .split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("Invalid input value: {}", value))?;
+                values.push((name.to_string(), text.to_string()));
+            }
+            "--seed" => {
+                seed = value
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("Invalid seed: {}", value))?;
+            }
+            "--param" => {
+                let (name, number) = value
+                    .split_once('=')
+                    .and_then(|(name, number)| Some((name, number.parse::<i64>().ok()?)))
+                    .ok_or_else(|| anyhow::anyhow!("Invalid template parameter: {}", value))?;
+                params.insert(name.to_string(), number);
+            }
+            "--ot-batch" => {
+                batching = match value.as_str() {
+                    "auto" => OtBatching::Auto,
+                    size => OtBatching::Fixed(
+                        size.parse::<usize>()
+                            .ok()
+                            .filter(|&size| size > 0)
+                            .ok_or_else(|| anyhow::anyhow!("Invalid OT batch size: {}", value))?,
+                    ),
+                };
+            }
+            "--reconstruction" => {
+                reconstruction = match value.split_once(':') {
+                    None if value == "all" => ReconstructionTopology::AllToAll,
+                    None if value == "star" => ReconstructionTopology::Star { collector: 0 },
+                    Some(("star", party)) => ReconstructionTopology::Star {
+                        collector: party
+                            .parse::<usize>()
+                            .map_err(|_| anyhow::anyhow!("Invalid collector: {}", party))?,
+                    },
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "Invalid reconstruction topology: {}",
+                            value
+                        ))
+                    }
+                };
+            }
+            flag => return Err(anyhow::anyhow!("Unknown option: {}", flag)),
+        }
+        arg_idx += 2;
     }
 
+    let network = network.map(|model| {
+        NetworkSimulator::new(model)
+            .with_ot_batching(batching)
+            .with_reconstruction(reconstruction)
+            .with_dry_run(dry_run)
+    });
     let remaining_args = &args[arg_idx..];
 
     if remaining_args.is_empty() {
@@ -86,11 +1280,52 @@ fn main() -> Result<()> {
         .map(|s| s.parse::<u8>().map(|v| v != 0))
         .collect();
 
-    let inputs = inputs?;
+    let mut inputs = inputs.map_err(|err| ErrorCode::InputMismatch.tag(err.into()))?;
+    if binary && explain.is_some() {
+        return Err(anyhow::anyhow!(
+            "--binary cannot be combined with --explain"
+        ));
+    }
+    if dry_run && (binary || explain.is_some() || commit_inputs.is_some()) {
+        return Err(anyhow::anyhow!(
+            "--dry-run cannot be combined with --binary, --explain or --commit-inputs"
+        ));
+    }
+    if binary && inputs.is_empty() && values.is_empty() {
+        let mut packed = Vec::new();
+        std::io::stdin().lock().read_to_end(&mut packed)?;
+        inputs = unpack_bits(&packed).map_err(|err| ErrorCode::InputMismatch.tag(err))?;
+    }
 
-    if inputs.is_empty() && !circuit_file.contains("help") {
+    if inputs.is_empty()
+        && values.is_empty()
+        && !circuit_file.contains("help")
+        && format == OutputFormat::Text
+        && !binary
+    {
         println!("Warning: No inputs provided");
     }
 
-    run_circuit(circuit_file, inputs, party_count)
+    run_circuit(
+        circuit_file,
+        inputs,
+        RunOptions {
+            party_count,
+            backend,
+            network,
+            progress,
+            layer_stats: layer_stats.as_deref(),
+            html: html.as_deref(),
+            explain: explain.as_deref().map(|format| (format, seed)),
+            params: &params,
+            commit_inputs: commit_inputs.as_deref(),
+            values: &values,
+            hash,
+            store,
+            format,
+            verify_cache: verify_cache.as_deref(),
+            binary,
+            dry_run,
+        },
+    )
 }
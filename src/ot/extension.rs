@@ -0,0 +1,394 @@
+//! IKNP OT extension (semi-honest): `KAPPA` base OTs, run once, stretched
+//! into any number of 1-out-of-2 transfers that cost only hashing
+//!
+//! The extension is written here rather than taken from a crate: the Rust
+//! OT-extension implementations come with their own base OTs, PRGs and
+//! channel types, and none can extend the base OTs of
+//! `oblivious-transfer-rs` that the rest of the crate runs. Semi-honest
+//! IKNP needs only a PRG and a hash, both BLAKE3 from the `blake3` crate,
+//! and a constant-time pick (`ShareBit`) when opening a transfer. The tests
+//! below, every `OtChannel` past its first `KAPPA` transfers and the split
+//! cross terms of `gates::CrossTermSender` exercise it. It has not been
+//! audited; an audited extension over a compatible base OT should replace
+//! it once one exists
+//!
+//! Setup also comes as message halves, `ReceiverSetup` and `SenderSetup`,
+//! for parties that run apart
+
+use crate::kernel::share::ShareBit;
+use crate::ot::{OtReceiverHalf, OtSenderHalf};
+use anyhow::Result;
+use oblivious_transfer_rs::{ReceiverEncryptedValues, SenderMaskedMessages, SenderPublicKey};
+use rand::Rng;
+
+/// Number of base OTs behind an extension, and its security parameter
+pub const KAPPA: usize = 128;
+
+const SEED_LEN: usize = 32;
+const ROW_LEN: usize = KAPPA / 8;
+
+/// Extension sender: its secret string Δ and, for every base OT, the seed
+/// it chose by the matching bit of Δ
+pub struct ExtensionSender {
+    delta: [u8; ROW_LEN],
+    seeds: Vec<[u8; SEED_LEN]>,
+    /// Bytes of every seed's stream used so far
+    position: u64,
+}
+
+/// Extension receiver: both seeds of every base OT
+pub struct ExtensionReceiver {
+    seeds: Vec<[[u8; SEED_LEN]; 2]>,
+    position: u64,
+}
+
+/// Receiver's message for one batch: column i is G(k_i^0) ⊕ G(k_i^1) ⊕ r,
+/// where r packs the choice bits and G stretches a seed
+pub struct ExtensionRequest {
+    /// Offset of the batch in the seed streams; the sender refuses a
+    /// request that does not continue where its last batch ended
+    pub position: u64,
+    pub columns: Vec<Vec<u8>>,
+}
+
+/// Sender's reply: both messages of every transfer, masked with a hash of
+/// the transfer's row
+pub struct ExtensionResponse {
+    pub masked: Vec<[Vec<u8>; 2]>,
+}
+
+/// Receiver's state between its request and the sender's response
+pub struct ExtensionChoices {
+    choices: Vec<bool>,
+    rows: Vec<[u8; ROW_LEN]>,
+    position: u64,
+}
+
+/// Run the `KAPPA` base OTs with the roles reversed: the extension receiver
+/// offers seed pairs and the extension sender picks one of each by the bits
+/// of a random Δ. This is the only public-key work of the extension
+pub fn extension_setup() -> Result<(ExtensionSender, ExtensionReceiver)> {
+    let mut rng = rand::thread_rng();
+    let (receiver, offer) = ReceiverSetup::new(&mut rng)?;
+    let (sender, request) = SenderSetup::new(offer, &mut rng)?;
+    let (receiver, response) = receiver.respond(request)?;
+    Ok((sender.finish(response)?, receiver))
+}
+
+/// Extension receiver's first setup message: a base-OT public key for each
+/// of its seed pairs
+pub struct SetupOffer {
+    pub public_keys: Vec<SenderPublicKey>,
+}
+
+/// Extension sender's reply picking one seed of each pair by a bit of Δ
+pub struct SetupRequest {
+    pub encrypted_values: Vec<ReceiverEncryptedValues>,
+}
+
+/// Extension receiver's seed pairs, masked for the sender's picks
+pub struct SetupResponse {
+    pub masked: Vec<SenderMaskedMessages>,
+}
+
+/// Extension receiver between its offer and the sender's request
+pub struct ReceiverSetup {
+    seeds: Vec<[[u8; SEED_LEN]; 2]>,
+    ots: Vec<OtSenderHalf>,
+}
+
+impl ReceiverSetup {
+    /// Draw the seed pairs and offer them as base OTs
+    pub fn new(rng: &mut impl Rng) -> Result<(Self, SetupOffer)> {
+        let mut seeds = Vec::with_capacity(KAPPA);
+        let mut ots = Vec::with_capacity(KAPPA);
+        let mut public_keys = Vec::with_capacity(KAPPA);
+        for _ in 0..KAPPA {
+            let pair: [[u8; SEED_LEN]; 2] = [rng.gen(), rng.gen()];
+            let (ot, public_key) = OtSenderHalf::new(pair[0].to_vec(), pair[1].to_vec())?;
+            seeds.push(pair);
+            ots.push(ot);
+            public_keys.push(public_key);
+        }
+        Ok((Self { seeds, ots }, SetupOffer { public_keys }))
+    }
+
+    /// Answer the sender's picks; the receiver is ready once this is sent
+    pub fn respond(self, request: SetupRequest) -> Result<(ExtensionReceiver, SetupResponse)> {
+        if request.encrypted_values.len() != KAPPA {
+            return Err(anyhow::anyhow!(
+                "Extension setup picks {} seeds, not {}",
+                request.encrypted_values.len(),
+                KAPPA
+            ));
+        }
+        let masked = self
+            .ots
+            .into_iter()
+            .zip(request.encrypted_values)
+            .map(|(ot, encrypted_values)| ot.respond(encrypted_values))
+            .collect::<Result<Vec<_>>>()?;
+        Ok((
+            ExtensionReceiver {
+                seeds: self.seeds,
+                position: 0,
+            },
+            SetupResponse { masked },
+        ))
+    }
+}
+
+/// Extension sender between its picks and the receiver's response
+pub struct SenderSetup {
+    delta: [u8; ROW_LEN],
+    ots: Vec<OtReceiverHalf>,
+}
+
+impl SenderSetup {
+    /// Draw Δ and pick one seed of every offered pair by its bits
+    pub fn new(offer: SetupOffer, rng: &mut impl Rng) -> Result<(Self, SetupRequest)> {
+        if offer.public_keys.len() != KAPPA {
+            return Err(anyhow::anyhow!(
+                "Extension setup offers {} seed pairs, not {}",
+                offer.public_keys.len(),
+                KAPPA
+            ));
+        }
+        let delta: [u8; ROW_LEN] = rng.gen();
+        let mut ots = Vec::with_capacity(KAPPA);
+        let mut encrypted_values = Vec::with_capacity(KAPPA);
+        for (i, public_key) in offer.public_keys.into_iter().enumerate() {
+            let choice = ShareBit::from_lsb(delta[i / 8] >> (i % 8)).reveal();
+            let (ot, values) = OtReceiverHalf::new(choice, public_key)?;
+            ots.push(ot);
+            encrypted_values.push(values);
+        }
+        Ok((Self { delta, ots }, SetupRequest { encrypted_values }))
+    }
+
+    /// Unmask the picked seeds
+    pub fn finish(self, response: SetupResponse) -> Result<ExtensionSender> {
+        if response.masked.len() != KAPPA {
+            return Err(anyhow::anyhow!(
+                "Extension setup returned {} seeds, not {}",
+                response.masked.len(),
+                KAPPA
+            ));
+        }
+        let seeds = self
+            .ots
+            .into_iter()
+            .zip(response.masked)
+            .enumerate()
+            .map(|(i, (ot, masked))| {
+                ot.finish(masked)?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Base OT {} returned a malformed seed", i))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ExtensionSender {
+            delta: self.delta,
+            seeds,
+            position: 0,
+        })
+    }
+}
+
+impl ExtensionReceiver {
+    /// Start a batch of transfers, one per choice bit
+    pub fn request(&mut self, choices: &[bool]) -> (ExtensionChoices, ExtensionRequest) {
+        let len = choices.len().div_ceil(8);
+        let mut packed = vec![0u8; len];
+        for (j, &choice) in choices.iter().enumerate() {
+            packed[j / 8] |= u8::from(choice) << (j % 8);
+        }
+
+        let mut t_columns = Vec::with_capacity(KAPPA);
+        let mut columns = Vec::with_capacity(KAPPA);
+        for [first, second] in &self.seeds {
+            let t = stream(first, self.position, len);
+            let column = t
+                .iter()
+                .zip(stream(second, self.position, len))
+                .zip(&packed)
+                .map(|((t, g), r)| t ^ g ^ r)
+                .collect();
+            t_columns.push(t);
+            columns.push(column);
+        }
+
+        let position = self.position;
+        self.position += len as u64;
+        (
+            ExtensionChoices {
+                choices: choices.to_vec(),
+                rows: transpose(&t_columns, choices.len()),
+                position,
+            },
+            ExtensionRequest { position, columns },
+        )
+    }
+}
+
+impl ExtensionSender {
+    /// Answer a batch request with `messages`, one pair per choice
+    /// Row j of the columns is t_j ⊕ r_j·Δ, so m0 is masked under the row
+    /// and m1 under the row ⊕ Δ; the receiver knows t_j, which opens only
+    /// the message of its choice
+    pub fn respond(
+        &mut self,
+        request: ExtensionRequest,
+        messages: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<ExtensionResponse> {
+        let len = messages.len().div_ceil(8);
+        if request.position != self.position {
+            return Err(anyhow::anyhow!(
+                "Extension request at offset {} but the sender is at {}",
+                request.position,
+                self.position
+            ));
+        }
+        if request.columns.len() != KAPPA || request.columns.iter().any(|c| c.len() != len) {
+            return Err(anyhow::anyhow!(
+                "Extension request does not cover {} transfers",
+                messages.len()
+            ));
+        }
+
+        let q_columns: Vec<Vec<u8>> = self
+            .seeds
+            .iter()
+            .zip(&request.columns)
+            .enumerate()
+            .map(|(i, (seed, column))| {
+                let mask = ShareBit::from_lsb(self.delta[i / 8] >> (i % 8)).select(0, 0xff);
+                stream(seed, self.position, len)
+                    .iter()
+                    .zip(column)
+                    .map(|(g, u)| g ^ (u & mask))
+                    .collect()
+            })
+            .collect();
+
+        let masked = messages
+            .iter()
+            .zip(transpose(&q_columns, messages.len()))
+            .enumerate()
+            .map(|(j, ((m0, m1), row))| {
+                let index = self.position * 8 + j as u64;
+                let mut flipped = row;
+                for (byte, delta) in flipped.iter_mut().zip(&self.delta) {
+                    *byte ^= delta;
+                }
+                [
+                    xor(m0, &pad(index, &row, m0.len())),
+                    xor(m1, &pad(index, &flipped, m1.len())),
+                ]
+            })
+            .collect();
+
+        self.position += len as u64;
+        Ok(ExtensionResponse { masked })
+    }
+}
+
+impl ExtensionChoices {
+    /// Unmask the chosen message of every transfer in the batch
+    pub fn finish(self, response: ExtensionResponse) -> Result<Vec<Vec<u8>>> {
+        if response.masked.len() != self.choices.len() {
+            return Err(anyhow::anyhow!(
+                "Expected {} masked message pairs, got {}",
+                self.choices.len(),
+                response.masked.len()
+            ));
+        }
+
+        self.choices
+            .iter()
+            .zip(&self.rows)
+            .zip(response.masked)
+            .enumerate()
+            .map(|(j, ((&choice, row), [m0, m1]))| {
+                if m0.len() != m1.len() {
+                    return Err(anyhow::anyhow!("Masked messages differ in length"));
+                }
+                // Pick with masks rather than by the choice bit
+                let choice = ShareBit::new(choice);
+                let chosen: Vec<u8> = m0
+                    .iter()
+                    .zip(&m1)
+                    .map(|(&a, &b)| choice.select(a, b))
+                    .collect();
+                let index = self.position * 8 + j as u64;
+                Ok(xor(&chosen, &pad(index, row, chosen.len())))
+            })
+            .collect()
+    }
+}
+
+/// `len` bytes of the stream G(seed) from byte `position` on
+fn stream(seed: &[u8; SEED_LEN], position: u64, len: usize) -> Vec<u8> {
+    let mut output = vec![0u8; len];
+    let mut hasher = ::blake3::Hasher::new_keyed(seed);
+    hasher.update(b"gmw-rs ot extension stream");
+    let mut reader = hasher.finalize_xof();
+    reader.set_position(position);
+    reader.fill(&mut output);
+    output
+}
+
+/// Mask of transfer `index`: H(index, row) stretched to `len` bytes
+fn pad(index: u64, row: &[u8; ROW_LEN], len: usize) -> Vec<u8> {
+    let mut output = vec![0u8; len];
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(b"gmw-rs ot extension pad");
+    hasher.update(&index.to_le_bytes());
+    hasher.update(row);
+    hasher.finalize_xof().fill(&mut output);
+    output
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+/// Rows 0..count of the bit matrix whose columns are `columns`
+fn transpose(columns: &[Vec<u8>], count: usize) -> Vec<[u8; ROW_LEN]> {
+    let mut rows = vec![[0u8; ROW_LEN]; count];
+    for (i, column) in columns.iter().enumerate() {
+        for (j, row) in rows.iter_mut().enumerate() {
+            row[i / 8] |= (column[j / 8] >> (j % 8) & 1) << (i % 8);
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_batches() -> Result<()> {
+        let (mut sender, mut receiver) = extension_setup()?;
+        // Batches not a multiple of 8 long must keep the offsets in step
+        for size in [1, 13, 64] {
+            let messages: Vec<(Vec<u8>, Vec<u8>)> = (0..size)
+                .map(|j| (vec![j as u8; 3], vec![!(j as u8); 3]))
+                .collect();
+            let choices: Vec<bool> = (0..size).map(|j| j % 3 == 1).collect();
+            let (pending, request) = receiver.request(&choices);
+            let response = sender.respond(request, &messages)?;
+            let received = pending.finish(response)?;
+            for ((message, choice), got) in messages.iter().zip(&choices).zip(received) {
+                assert_eq!(&got, if *choice { &message.1 } else { &message.0 });
+            }
+        }
+
+        // A request replayed out of order is refused
+        let (_, request) = receiver.request(&[true]);
+        let (_, stale) = receiver.request(&[true]);
+        assert!(sender.respond(stale, &[(vec![0], vec![1])]).is_err());
+        assert!(sender.respond(request, &[(vec![0], vec![1])]).is_ok());
+        Ok(())
+    }
+}
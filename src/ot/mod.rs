@@ -1,174 +1,432 @@
+pub mod extension;
+
+pub use extension::{
+    extension_setup, ExtensionChoices, ExtensionReceiver, ExtensionRequest, ExtensionResponse,
+    ExtensionSender, ReceiverSetup, SenderSetup, SetupOffer, SetupRequest, SetupResponse, KAPPA,
+};
+
+use crate::blake3::Blake3;
+use crate::kernel::share::ShareBit;
 use anyhow::Result;
 use oblivious_transfer_rs::{
     Choice, OTReceiver, OTSender, ReceiverEncryptedValues, SenderMaskedMessages, SenderPublicKey,
 };
+use rand::Rng;
+use std::fmt;
+use std::time::{Duration, Instant};
 
-/// OT wrapper for GMW protocol
-/// Makes it easier to handle bit-based OT
-pub struct BitOT;
+/// Length of the keys masking the messages of a 1-out-of-4 OT
+pub const QUAD_KEY_LEN: usize = 16;
 
-impl BitOT {
-    /// Execute 1-out-of-2 OT for single bit messages
-    ///
-    /// # Arguments
-    /// * `messages` - (m0, m1) two bit messages
-    /// * `choice` - selection bit (0 for m0, 1 for m1)
-    ///
-    /// # Returns
-    /// * Sender state and receiver state containing selected bit
-    pub fn execute(
-        messages: (bool, bool),
+/// Sender side of a single 1-out-of-2 OT, for running the parties apart
+pub struct OtSenderHalf {
+    sender: OTSender,
+}
+
+impl OtSenderHalf {
+    /// Commit to the two messages and produce the public key for the receiver
+    pub fn new(m0: Vec<u8>, m1: Vec<u8>) -> Result<(Self, SenderPublicKey)> {
+        let mut sender = OTSender::new(m0, m1)?;
+        let public_key = sender.generate_keys()?;
+        Ok((Self { sender }, public_key))
+    }
+
+    /// Mask both messages against the receiver's encrypted values
+    pub fn respond(
+        mut self,
+        encrypted_values: ReceiverEncryptedValues,
+    ) -> Result<SenderMaskedMessages> {
+        self.sender.create_masked_messages(encrypted_values)
+    }
+}
+
+/// Receiver side of a single 1-out-of-2 OT
+pub struct OtReceiverHalf {
+    receiver: OTReceiver,
+}
+
+impl OtReceiverHalf {
+    /// Encode the choice bit against the sender's public key
+    /// The OT crate takes the choice as an enum, so building it is the one
+    /// place a share bit still passes through a branch
+    pub fn new(
         choice: bool,
-    ) -> Result<(OTSenderState, OTReceiverState)> {
-        // Convert bits to byte vectors
-        let m0 = vec![messages.0 as u8];
-        let m1 = vec![messages.1 as u8];
-
-        // Execute core OT protocol
-        let (sender_state, receiver, encrypted_values, result) =
-            Self::execute_ot_core(m0, m1, choice)?;
-        let received_bit = result.first().copied().unwrap_or(0) != 0;
-
-        Ok((
-            sender_state,
-            OTReceiverState {
-                receiver,
-                encrypted_values,
-                received_bit,
-            },
-        ))
-    }
-
-    /// Extract message on receiver side
-    pub fn extract_bit(
-        receiver_state: &OTReceiverState,
-        masked_messages: SenderMaskedMessages,
-    ) -> Result<bool> {
-        let result = receiver_state.receiver.extract_message(masked_messages)?;
+        public_key: SenderPublicKey,
+    ) -> Result<(Self, ReceiverEncryptedValues)> {
+        let mut receiver = OTReceiver::new(if choice { Choice::One } else { Choice::Zero });
+        let encrypted_values = receiver.generate_encrypted_values(public_key)?;
+        Ok((Self { receiver }, encrypted_values))
+    }
 
-        // Convert Vec<u8> to bit
-        Ok(result.first().copied().unwrap_or(0) != 0)
+    /// Unmask the chosen message
+    pub fn finish(self, masked_messages: SenderMaskedMessages) -> Result<Vec<u8>> {
+        self.receiver.extract_message(masked_messages)
     }
+}
 
-    /// Execute 1-out-of-4 OT using two sequential 1-out-of-2 OTs
-    ///
-    /// # Arguments
-    /// * `messages` - (m00, m01, m10, m11) four bit messages indexed by 2-bit choice
-    /// * `choice_bits` - (b0, b1) two choice bits where b0||b1 selects message index
-    ///
-    /// # Returns
-    /// * Selected bit value m_{b0,b1}
-    pub fn execute_1_out_of_4(
-        messages: (bool, bool, bool, bool),
-        choice_bits: (bool, bool),
-    ) -> Result<bool> {
+/// Sender side of a 1-out-of-4 bit OT built from two 1-out-of-2 OTs
+/// Message m_ab is masked with a hash of key a of the first pair and key b
+/// of the second. The receiver gets one key of each pair through OT, by its
+/// first and second choice bit, so it can unmask m_ab for its own choice
+/// and nothing about the other three messages
+pub struct QuadMessages {
+    /// Key pairs to transfer: the first by the high choice bit, the second
+    /// by the low one
+    pub keys: [[Vec<u8>; 2]; 2],
+    /// Masked m00, m01, m10 and m11, sent in the clear
+    pub ciphertexts: [bool; 4],
+}
+
+impl QuadMessages {
+    /// Mask `messages` (m00, m01, m10, m11) under fresh keys from `rng`
+    pub fn new(messages: (bool, bool, bool, bool), rng: &mut impl Rng) -> Self {
+        let mut key = || rng.gen::<[u8; QUAD_KEY_LEN]>().to_vec();
+        let keys = [[key(), key()], [key(), key()]];
         let (m00, m01, m10, m11) = messages;
-        let (b0, b1) = choice_bits;
+        let ciphertexts = [(0, 0, m00), (0, 1, m01), (1, 0, m10), (1, 1, m11)]
+            .map(|(a, b, message)| message ^ quad_pad(&keys[0][a], &keys[1][b], (2 * a + b) as u8));
+        Self { keys, ciphertexts }
+    }
+}
+
+/// Unmask the message selected by `choice` with the two keys received for it
+pub fn quad_open(
+    ciphertexts: [bool; 4],
+    first_key: &[u8],
+    second_key: &[u8],
+    choice: (bool, bool),
+) -> bool {
+    let (high, low) = (ShareBit::new(choice.0), ShareBit::new(choice.1));
+    // Pick the ciphertext with masks rather than an index
+    let [c00, c01, c10, c11] = ciphertexts.map(u8::from);
+    let ciphertext = high.select(low.select(c00, c01), low.select(c10, c11));
+    let index = high.select(0, 2) | low.select(0, 1);
+    ShareBit::from_lsb(ciphertext).reveal() ^ quad_pad(first_key, second_key, index)
+}
+
+/// Pad bit of message `index` = 2a + b: a hash of its two keys and index
+fn quad_pad(first_key: &[u8], second_key: &[u8], index: u8) -> bool {
+    let mut input = b"gmw-rs 1-out-of-4".to_vec();
+    input.extend_from_slice(first_key);
+    input.extend_from_slice(second_key);
+    input.push(index);
+    ShareBit::from_lsb(Blake3::digest(&input)[0]).reveal()
+}
+
+/// Bit OT session between one sender and one receiver for GMW
+///
+/// The OT crate binds a key pair to a single message pair, so public-key
+/// work is paid per base OT. A channel runs its first transfers as base OTs
+/// directly; once those would pass `KAPPA`, it sets up an OT extension with
+/// `KAPPA` more base OTs, and every later transfer costs only hashing. A
+/// channel therefore runs at most `2 * KAPPA` base OTs, however many
+/// transfers go through it, plus `KAPPA` each time a health check sets its
+/// extension up again.
+pub struct OtChannel {
+    extension: Option<(ExtensionSender, ExtensionReceiver)>,
+    stats: OtStats,
+    /// The last batch failed, which may have left the extension's two ends
+    /// at different offsets
+    degraded: bool,
+}
+
+/// Transfer counts and timing of one channel
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub struct OtStats {
+    /// 1-out-of-2 transfers completed
+    pub transfers: usize,
+    /// Base OTs run, each with its own key generation
+    pub base_transfers: usize,
+    /// Batches attempted, failed ones included
+    pub batches: usize,
+    /// Batches that failed
+    pub failures: usize,
+    /// Times a health check set the base OTs up again
+    pub reestablished: usize,
+    /// Time spent in all batches
+    pub latency: Duration,
+}
+
+impl OtStats {
+    pub fn average_latency(&self) -> Duration {
+        match u32::try_from(self.batches) {
+            Ok(0) => Duration::ZERO,
+            Ok(batches) => self.latency / batches,
+            Err(_) => self.latency.div_f64(self.batches as f64),
+        }
+    }
+
+    /// Share of batches that failed
+    pub fn failure_rate(&self) -> f64 {
+        self.rate(self.failures)
+    }
+
+    /// Base OT re-establishments per batch
+    pub fn retry_rate(&self) -> f64 {
+        self.rate(self.reestablished)
+    }
 
-        // First OT: Choose between message pairs based on b0
-        let message_pairs = ((m00, m01), (m10, m11));
-        let (_sender_state1, receiver_state1) = Self::execute_bit_pairs(message_pairs, b0)?;
+    fn rate(&self, count: usize) -> f64 {
+        if self.batches == 0 {
+            0.0
+        } else {
+            count as f64 / self.batches as f64
+        }
+    }
 
-        // Second OT: Choose from selected pair based on b1
-        let selected_pair = receiver_state1.received_pair;
-        let (_sender_state2, receiver_state2) = Self::execute(selected_pair, b1)?;
+    /// Add `other`'s counts and time to these
+    pub fn merge(&mut self, other: &OtStats) {
+        self.transfers += other.transfers;
+        self.base_transfers += other.base_transfers;
+        self.batches += other.batches;
+        self.failures += other.failures;
+        self.reestablished += other.reestablished;
+        self.latency += other.latency;
+    }
+}
 
-        Ok(receiver_state2.received_bit)
+/// `OtStats` of the channel on which `sender` sends to `receiver`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeerOtStats {
+    pub sender: usize,
+    pub receiver: usize,
+    pub stats: OtStats,
+}
+
+impl OtChannel {
+    /// Open a channel with no transfers yet
+    pub fn new() -> Self {
+        Self {
+            extension: None,
+            stats: OtStats::default(),
+            degraded: false,
+        }
     }
 
-    /// Execute 1-out-of-2 OT where messages are bit pairs
+    /// Number of 1-out-of-2 transfers run so far
+    pub fn transfers(&self) -> usize {
+        self.stats.transfers
+    }
+
+    /// Number of base OTs, each with its own key generation, run so far
+    pub fn base_transfers(&self) -> usize {
+        self.stats.base_transfers
+    }
+
+    pub fn stats(&self) -> OtStats {
+        self.stats
+    }
+
+    /// Whether the last batch failed
+    pub fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// Replace the OT extension with one on fresh base OTs, so a batch that
+    /// failed halfway cannot leave later batches at mismatched offsets
+    /// A channel still running base OTs directly keeps no state to renew
+    pub fn reestablish(&mut self) -> Result<()> {
+        if self.extension.is_some() {
+            self.extension = Some(extension_setup()?);
+            self.stats.base_transfers += KAPPA;
+            self.stats.reestablished += 1;
+        }
+        self.degraded = false;
+        Ok(())
+    }
+
+    /// Execute 1-out-of-2 OT for byte messages
     ///
     /// # Arguments
-    /// * `message_pairs` - ((m00, m01), (m10, m11)) two pairs of bits
-    /// * `choice` - selection bit (0 for first pair, 1 for second pair)
+    /// * `m0` - first message as bytes
+    /// * `m1` - second message as bytes
+    /// * `choice` - receiver's choice bit
     ///
     /// # Returns
-    /// * Sender state and receiver state containing selected bit pair
-    fn execute_bit_pairs(
-        message_pairs: ((bool, bool), (bool, bool)),
-        choice: bool,
-    ) -> Result<(OTSenderState, OTReceiverPairState)> {
-        let ((m00, m01), (m10, m11)) = message_pairs;
+    /// * Message selected by the receiver
+    pub fn transfer(&mut self, m0: Vec<u8>, m1: Vec<u8>, choice: bool) -> Result<Vec<u8>> {
+        self.transfer_batch(vec![(m0, m1)], &[choice])?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("OT returned no message"))
+    }
 
-        // Convert bit pairs to byte vectors
-        let pair0_bytes = vec![m00 as u8, m01 as u8];
-        let pair1_bytes = vec![m10 as u8, m11 as u8];
+    /// Execute one 1-out-of-2 OT per choice bit, in a single extension batch
+    /// once the channel has set one up
+    pub fn transfer_batch(
+        &mut self,
+        messages: Vec<(Vec<u8>, Vec<u8>)>,
+        choices: &[bool],
+    ) -> Result<Vec<Vec<u8>>> {
+        let start = Instant::now();
+        let result = self.run_batch(messages, choices);
+        self.stats.latency += start.elapsed();
+        self.stats.batches += 1;
+        self.degraded = result.is_err();
+        match &result {
+            Ok(_) => self.stats.transfers += choices.len(),
+            Err(_) => self.stats.failures += 1,
+        }
+        result
+    }
 
-        // Execute core OT protocol
-        let (sender_state, receiver, encrypted_values, result) =
-            Self::execute_ot_core(pair0_bytes, pair1_bytes, choice)?;
+    fn run_batch(
+        &mut self,
+        messages: Vec<(Vec<u8>, Vec<u8>)>,
+        choices: &[bool],
+    ) -> Result<Vec<Vec<u8>>> {
+        if messages.len() != choices.len() {
+            return Err(anyhow::anyhow!(
+                "Got {} message pairs but {} choices",
+                messages.len(),
+                choices.len()
+            ));
+        }
 
-        // Convert result bytes back to bit pair
-        let received_pair = (
-            result.first().copied().unwrap_or(0) != 0,
-            result.get(1).copied().unwrap_or(0) != 0,
-        );
+        let received = match &mut self.extension {
+            None if self.stats.base_transfers + messages.len() <= KAPPA => {
+                self.stats.base_transfers += messages.len();
+                messages
+                    .into_iter()
+                    .zip(choices)
+                    .map(|((m0, m1), &choice)| {
+                        let (sender, sender_pk) = OtSenderHalf::new(m0, m1)?;
+                        let (receiver, encrypted_values) = OtReceiverHalf::new(choice, sender_pk)?;
+                        receiver.finish(sender.respond(encrypted_values)?)
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            extension => {
+                let (sender, receiver) = match extension {
+                    Some(extension) => extension,
+                    None => {
+                        self.stats.base_transfers += KAPPA;
+                        extension.insert(extension_setup()?)
+                    }
+                };
+                let (pending, request) = receiver.request(choices);
+                pending.finish(sender.respond(request, &messages)?)?
+            }
+        };
 
-        Ok((
-            sender_state,
-            OTReceiverPairState {
-                receiver,
-                encrypted_values,
-                received_pair,
-            },
-        ))
+        Ok(received)
     }
 
-    /// Execute the core OT protocol with byte messages
+    /// Execute 1-out-of-2 OT for single bit messages
     ///
     /// # Arguments
-    /// * `m0` - first message as bytes
-    /// * `m1` - second message as bytes
-    /// * `choice` - receiver's choice bit
+    /// * `messages` - (m0, m1) two bit messages
+    /// * `choice` - selection bit (0 for m0, 1 for m1)
+    pub fn transfer_bit(&mut self, messages: (bool, bool), choice: bool) -> Result<bool> {
+        let result = self.transfer(vec![messages.0 as u8], vec![messages.1 as u8], choice)?;
+        Ok(ShareBit::from_lsb(result.first().copied().unwrap_or(0)).reveal())
+    }
+
+    /// Execute 1-out-of-4 OT as two 1-out-of-2 OTs of masking keys (see
+    /// `QuadMessages`), so the receiver learns only the message it chose
+    ///
+    /// # Arguments
+    /// * `messages` - (m00, m01, m10, m11) four bit messages indexed by 2-bit choice
+    /// * `choice_bits` - (b0, b1) two choice bits where b0||b1 selects message index
     ///
     /// # Returns
-    /// * Sender state, receiver instance, encrypted values, and selected message
-    fn execute_ot_core(
-        m0: Vec<u8>,
-        m1: Vec<u8>,
-        choice: bool,
-    ) -> Result<(OTSenderState, OTReceiver, ReceiverEncryptedValues, Vec<u8>)> {
-        // Initialize OT participants
-        let mut sender = OTSender::new(m0, m1)?;
-        let choice_enum = if choice { Choice::One } else { Choice::Zero };
-        let mut receiver = OTReceiver::new(choice_enum);
-
-        // Execute OT protocol phases
-        let sender_pk = sender.generate_keys()?;
-        let encrypted_values = receiver.generate_encrypted_values(sender_pk.clone())?;
-        let masked_messages = sender.create_masked_messages(encrypted_values.clone())?;
-        let result = receiver.extract_message(masked_messages.clone())?;
-
-        Ok((
-            OTSenderState {
-                sender_pk,
-                masked_messages,
-            },
-            receiver,
-            encrypted_values,
-            result,
-        ))
+    /// * Selected bit value m_{b0,b1}
+    pub fn transfer_1_out_of_4(
+        &mut self,
+        messages: (bool, bool, bool, bool),
+        choice_bits: (bool, bool),
+    ) -> Result<bool> {
+        let QuadMessages { keys, ciphertexts } =
+            QuadMessages::new(messages, &mut rand::thread_rng());
+        let [[k00, k01], [k10, k11]] = keys;
+        let received = self.transfer_batch(
+            vec![(k00, k01), (k10, k11)],
+            &[choice_bits.0, choice_bits.1],
+        )?;
+        match &received[..] {
+            [first, second] => Ok(quad_open(ciphertexts, first, second, choice_bits)),
+            _ => Err(anyhow::anyhow!("OT returned {} keys", received.len())),
+        }
     }
 }
 
-/// OT sender state
-pub struct OTSenderState {
-    pub sender_pk: SenderPublicKey,
-    pub masked_messages: SenderMaskedMessages,
+impl Default for OtChannel {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-/// OT receiver state for single bit messages
-pub struct OTReceiverState {
-    pub receiver: OTReceiver,
-    pub encrypted_values: ReceiverEncryptedValues,
-    pub received_bit: bool,
+impl fmt::Debug for OtChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OtChannel")
+            .field("extended", &self.extension.is_some())
+            .field("stats", &self.stats)
+            .field("degraded", &self.degraded)
+            .finish()
+    }
 }
 
-/// OT receiver state for bit pair messages
-pub struct OTReceiverPairState {
-    pub receiver: OTReceiver,
-    pub encrypted_values: ReceiverEncryptedValues,
-    pub received_pair: (bool, bool),
+/// One `OtChannel` for every ordered pair of parties
+/// An evaluation keeps a set for its whole run, so each pair pays for its
+/// base OTs once rather than per gate
+#[derive(Debug)]
+pub struct OtChannels {
+    parties: usize,
+    channels: Vec<OtChannel>,
+}
+
+impl OtChannels {
+    /// Channels between `parties` parties, none set up yet
+    pub fn new(parties: usize) -> Self {
+        Self {
+            parties,
+            channels: (0..parties * parties).map(|_| OtChannel::new()).collect(),
+        }
+    }
+
+    pub fn parties(&self) -> usize {
+        self.parties
+    }
+
+    /// The channel on which `sender` sends to `receiver`
+    pub fn channel(&mut self, sender: usize, receiver: usize) -> &mut OtChannel {
+        &mut self.channels[sender * self.parties + receiver]
+    }
+
+    /// Base OTs run over every channel so far
+    pub fn base_transfers(&self) -> usize {
+        self.channels.iter().map(OtChannel::base_transfers).sum()
+    }
+
+    /// 1-out-of-2 transfers run over every channel so far
+    pub fn transfers(&self) -> usize {
+        self.channels.iter().map(OtChannel::transfers).sum()
+    }
+
+    /// Statistics of every channel that has run a batch, by party pair
+    pub fn stats(&self) -> Vec<PeerOtStats> {
+        self.channels
+            .iter()
+            .enumerate()
+            .filter(|(_, channel)| channel.stats.batches > 0)
+            .map(|(i, channel)| PeerOtStats {
+                sender: i / self.parties,
+                receiver: i % self.parties,
+                stats: channel.stats,
+            })
+            .collect()
+    }
+
+    /// Set up the base OTs of every degraded channel again before more
+    /// transfers go through it; returns how many were re-established
+    pub fn check_health(&mut self) -> Result<usize> {
+        let mut reestablished = 0;
+        for channel in self.channels.iter_mut().filter(|channel| channel.degraded) {
+            channel.reestablish()?;
+            reestablished += 1;
+        }
+        Ok(reestablished)
+    }
 }
 
 #[cfg(test)]
@@ -178,13 +436,11 @@ mod tests {
     #[test]
     fn test_bit_ot() -> Result<()> {
         // Basic OT test
-        let sender_bits = (false, true);
-        let receiver_choice = true;
-
-        let (sender_state, receiver_state) = BitOT::execute(sender_bits, receiver_choice)?;
-        let result = BitOT::extract_bit(&receiver_state, sender_state.masked_messages)?;
+        let mut channel = OtChannel::new();
+        let result = channel.transfer_bit((false, true), true)?;
 
         assert_eq!(result, true); // choice=1 so we get m1=true
+        assert_eq!(channel.transfers(), 1);
         Ok(())
     }
 
@@ -192,6 +448,7 @@ mod tests {
     fn test_1_out_of_4_ot() -> Result<()> {
         // Test 1-out-of-4 OT
         let messages = (false, true, true, false); // m00, m01, m10, m11
+        let mut channel = OtChannel::new();
 
         // Test all possible choices
         let test_cases = [
@@ -202,10 +459,71 @@ mod tests {
         ];
 
         for (choice_bits, expected) in test_cases {
-            let result = BitOT::execute_1_out_of_4(messages, choice_bits)?;
+            let result = channel.transfer_1_out_of_4(messages, choice_bits)?;
             assert_eq!(result, expected, "Failed for choice {:?}", choice_bits);
         }
 
+        // Every 1-out-of-4 runs two transfers over the same channel
+        assert_eq!(channel.transfers(), 2 * test_cases.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_channel_reuses_base_ots() -> Result<()> {
+        let mut channel = OtChannel::new();
+        for i in 0..3 * KAPPA {
+            let choice = i % 2 == 1;
+            assert_eq!(channel.transfer_bit((false, true), choice)?, choice);
+        }
+        assert_eq!(channel.transfers(), 3 * KAPPA);
+        // KAPPA direct transfers, then one extension setup for the rest
+        assert_eq!(channel.base_transfers(), 2 * KAPPA);
+
+        // A batch too large to run directly goes straight to the extension
+        let mut channel = OtChannel::new();
+        let messages = (0..2 * KAPPA)
+            .map(|i| (vec![i as u8], vec![!(i as u8)]))
+            .collect();
+        let received = channel.transfer_batch(messages, &[true; 2 * KAPPA])?;
+        assert_eq!(received[5], vec![!5]);
+        assert_eq!(channel.base_transfers(), KAPPA);
+        Ok(())
+    }
+
+    #[test]
+    fn test_health_check_reestablishes_degraded_channels() -> Result<()> {
+        let mut channels = OtChannels::new(2);
+        let messages = |count: usize| (0..count).map(|i| (vec![i as u8], vec![!(i as u8)]));
+        let channel = channels.channel(0, 1);
+        channel.transfer_batch(messages(2 * KAPPA).collect(), &[false; 2 * KAPPA])?;
+
+        // A request the sender never answers puts the receiver ahead
+        if let Some((_, receiver)) = &mut channel.extension {
+            receiver.request(&[true]);
+        }
+        assert!(channel.transfer(vec![0], vec![1], true).is_err());
+        assert!(channel.is_degraded());
+        assert_eq!(channels.check_health()?, 1);
+        assert_eq!(channels.check_health()?, 0);
+
+        let channel = channels.channel(0, 1);
+        assert_eq!(channel.transfer(vec![0], vec![1], true)?, vec![1]);
+        let stats = channels.stats();
+        assert_eq!(stats.len(), 1);
+        let PeerOtStats {
+            sender,
+            receiver,
+            stats,
+        } = stats[0];
+        assert_eq!((sender, receiver), (0, 1));
+        assert_eq!(stats.transfers, 2 * KAPPA + 1);
+        assert_eq!(stats.base_transfers, 2 * KAPPA);
+        assert_eq!(
+            (stats.batches, stats.failures, stats.reestablished),
+            (3, 1, 1)
+        );
+        assert!((stats.failure_rate() - 1.0 / 3.0).abs() < 1e-9);
+        assert!(stats.average_latency() <= stats.latency);
         Ok(())
     }
 }
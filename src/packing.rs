@@ -0,0 +1,206 @@
+use anyhow::Result;
+
+use crate::circuit::WireId;
+
+/// Bit-packed encoding of a list of wire shares, for the phases that move
+/// many shares at once (input sharing, reconstruction, share exports)
+/// Layout: LEB128 run count, then each run of consecutive wires as start
+/// wire and length, then the shares 8 per byte, least significant bit first,
+/// in list order. Wires may come in any order; gaps and jumps start a new run
+pub fn pack_shares(shares: &[(WireId, bool)]) -> Vec<u8> {
+    let mut runs: Vec<(WireId, u64)> = Vec::new();
+    for &(wire, _) in shares {
+        match runs.last_mut() {
+            Some((start, len)) if start.checked_add(*len) == Some(wire) => *len += 1,
+            _ => runs.push((wire, 1)),
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(packed_len(runs.len(), shares.len()));
+    write_varint(&mut bytes, runs.len() as u64);
+    for &(start, len) in &runs {
+        write_varint(&mut bytes, start);
+        write_varint(&mut bytes, len);
+    }
+    let mut bits = vec![0u8; shares.len().div_ceil(8)];
+    for (i, &(_, share)) in shares.iter().enumerate() {
+        bits[i / 8] |= u8::from(share) << (i % 8);
+    }
+    bytes.extend(bits);
+    bytes
+}
+
+/// Decode `pack_shares` output, restoring the original order
+pub fn unpack_shares(bytes: &[u8]) -> Result<Vec<(WireId, bool)>> {
+    let mut cursor = Cursor::new(bytes);
+    let run_count = cursor.varint()?;
+    let mut wires: Vec<WireId> = Vec::new();
+    for _ in 0..run_count {
+        let start = cursor.varint()?;
+        let len = cursor.varint()?;
+        if len > 0 && start.checked_add(len - 1).is_none() {
+            return Err(anyhow::anyhow!("Wire run at {} overflows wire IDs", start));
+        }
+        if len as usize > bytes.len() * 8 {
+            return Err(anyhow::anyhow!("Wire run of {} shares is truncated", len));
+        }
+        wires.extend((0..len).map(|offset| start + offset));
+    }
+
+    let bits = cursor.rest();
+    if bits.len() != wires.len().div_ceil(8) {
+        return Err(anyhow::anyhow!(
+            "Expected {} bytes of packed shares, got {}",
+            wires.len().div_ceil(8),
+            bits.len()
+        ));
+    }
+    Ok(wires
+        .into_iter()
+        .enumerate()
+        .map(|(i, wire)| (wire, bits[i / 8] >> (i % 8) & 1 == 1))
+        .collect())
+}
+
+/// Magic bytes at the start of a packed bit vector
+pub const BITS_MAGIC: [u8; 4] = *b"GMWB";
+
+/// Plain bit vector with a small header, as read and written by the CLI's
+/// `--binary` mode: the `GMWB` magic, the bit count as LEB128, then the bits
+/// 8 per byte, least significant bit first
+pub fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = BITS_MAGIC.to_vec();
+    write_varint(&mut bytes, bits.len() as u64);
+    let mut packed = vec![0u8; bits.len().div_ceil(8)];
+    for (i, &bit) in bits.iter().enumerate() {
+        packed[i / 8] |= u8::from(bit) << (i % 8);
+    }
+    bytes.extend(packed);
+    bytes
+}
+
+/// Decode `pack_bits` output; padding bits of the last byte must be zero
+pub fn unpack_bits(bytes: &[u8]) -> Result<Vec<bool>> {
+    let mut cursor = Cursor::new(bytes);
+    if cursor.take(BITS_MAGIC.len()).ok() != Some(&BITS_MAGIC[..]) {
+        return Err(anyhow::anyhow!("Not a packed bit vector"));
+    }
+    let count = cursor.varint()? as usize;
+    let packed = cursor.rest();
+    if Some(packed.len()) != count.checked_add(7).map(|bits| bits / 8) {
+        return Err(anyhow::anyhow!(
+            "Expected {} bits, got {} bytes",
+            count,
+            packed.len()
+        ));
+    }
+    if !count.is_multiple_of(8) && packed.last().is_some_and(|&last| last >> (count % 8) != 0) {
+        return Err(anyhow::anyhow!("Padding bits after bit {} are set", count));
+    }
+    Ok((0..count)
+        .map(|i| packed[i / 8] >> (i % 8) & 1 == 1)
+        .collect())
+}
+
+/// Upper bound on the packed size of `shares` in `runs` runs
+fn packed_len(runs: usize, shares: usize) -> usize {
+    10 + runs * 20 + shares.div_ceil(8)
+}
+
+pub(crate) fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+pub(crate) fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    write_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+/// Read position over packed bytes
+pub(crate) struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow::anyhow!("Packed data ends early"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    /// Everything not read yet
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.bytes[self.position..];
+        self.position = self.bytes.len();
+        rest
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String> {
+        let len = self.varint()? as usize;
+        Ok(std::str::from_utf8(self.take(len)?)?.to_string())
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow::anyhow!("Varint longer than 64 bits"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_preserves_order() {
+        let shares: Vec<(WireId, bool)> = (100..1100)
+            .map(|wire| (wire, wire % 3 == 0))
+            .chain([(7, true), (5, false), (6, true), (WireId::MAX, true)])
+            .collect();
+        let packed = pack_shares(&shares);
+        assert_eq!(unpack_shares(&packed).unwrap(), shares);
+
+        // Four runs of headers, then 1004 shares in 126 bytes instead of 1004
+        assert!(packed.len() < 126 + 4 * 12);
+        assert_eq!(unpack_shares(&pack_shares(&[])).unwrap(), vec![]);
+
+        assert!(unpack_shares(&packed[..packed.len() - 1]).is_err());
+        assert!(unpack_shares(&[1, 0, 0xff, 0xff, 0xff, 0xff, 0x0f]).is_err());
+    }
+
+    #[test]
+    fn test_pack_bits() {
+        let bits: Vec<bool> = (0..13).map(|i| i % 3 == 0).collect();
+        let packed = pack_bits(&bits);
+        assert_eq!(
+            packed,
+            [b'G', b'M', b'W', b'B', 13, 0b0100_1001, 0b0001_0010]
+        );
+        assert_eq!(unpack_bits(&packed).unwrap(), bits);
+        assert!(unpack_bits(&pack_bits(&[])).unwrap().is_empty());
+
+        assert!(unpack_bits(&packed[..6]).is_err());
+        assert!(unpack_bits(&[b'G', b'M', b'W', b'B', 3, 0b1000]).is_err());
+        assert!(unpack_bits(b"GMWP\x00").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+//! Common imports for running circuits: `use gmw_rs::prelude::*;`
+
+pub use crate::circuit::{Circuit, CircuitFormat, LocalEvaluator, PreparedCircuit};
+pub use crate::protocol::GmwProtocol;
+pub use crate::report::{ExecutionReport, FromBits};
@@ -0,0 +1,129 @@
+#[cfg(test)]
+use std::cell::Cell;
+use std::fmt;
+
+/// Whether this build refuses in-process simulation by default
+/// Set by the `production` feature. Every evaluation path of this crate
+/// holds all parties' shares in one process, which reveals the inputs to
+/// whoever runs it; a production build refuses such runs unless a protocol
+/// opts in with `GmwProtocol::allow_local_simulation`
+pub const PRODUCTION: bool = cfg!(feature = "production");
+
+#[cfg(test)]
+thread_local! {
+    static REFUSING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether simulation entry points refuse to run
+/// The crate's own tests simulate freely in a production build too, except
+/// inside `refusing`, so the whole suite runs under the feature
+pub(crate) fn local_simulation_refused() -> bool {
+    #[cfg(test)]
+    return PRODUCTION && REFUSING.with(Cell::get);
+    #[cfg(not(test))]
+    return PRODUCTION;
+}
+
+/// Run `f` with the production refusals of this thread in force
+#[cfg(all(test, feature = "production"))]
+pub(crate) fn refusing<T>(f: impl FnOnce() -> T) -> T {
+    REFUSING.with(|refusing| refusing.set(true));
+    let result = f();
+    REFUSING.with(|refusing| refusing.set(false));
+    result
+}
+
+/// Typed error returned when a production build refuses a local simulation
+/// `ErrorCode::of` classes it as `Refused`, so the CLI exits with that
+/// code rather than a generic failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LocalSimulationRefused {
+    /// Function that would have simulated every party
+    pub api: &'static str,
+}
+
+impl LocalSimulationRefused {
+    pub(crate) fn new(api: &'static str) -> Self {
+        Self { api }
+    }
+}
+
+impl fmt::Display for LocalSimulationRefused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} holds every party's shares in one process, which production builds refuse; \
+             call GmwProtocol::allow_local_simulation for tests and demos",
+            self.api
+        )
+    }
+}
+
+impl std::error::Error for LocalSimulationRefused {}
+
+#[cfg(all(test, feature = "production"))]
+mod tests {
+    use super::*;
+    use crate::circuit::Circuit;
+    use crate::explain::Explainer;
+    use crate::gates::{and_gate, cross_term_shares, or_gate};
+    use crate::lut::{lookup_shared, LookupTable};
+    use crate::protocol::GmwProtocol;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_production_refuses_local_simulation() {
+        let circuit = Circuit::from_file("circuits/and.json").unwrap();
+        let error = refusing(|| {
+            GmwProtocol::new(2)
+                .unwrap()
+                .run_with_report(&circuit, &[true, true])
+        })
+        .unwrap_err();
+        let refused = error.downcast_ref::<LocalSimulationRefused>().unwrap();
+        assert_eq!(refused.api, "GmwProtocol evaluation");
+        assert!(refusing(|| and_gate(&[(true, true), (false, false)])).is_err());
+
+        let allowed = refusing(|| GmwProtocol::new(2).unwrap().allow_local_simulation());
+        let report = allowed.run_with_report(&circuit, &[true, true]).unwrap();
+        assert!(report.outputs[0].1);
+    }
+
+    #[test]
+    fn test_production_refuses_every_simulation_helper() {
+        let refused = |call: &dyn Fn() -> anyhow::Result<()>| {
+            refusing(call)
+                .unwrap_err()
+                .downcast_ref::<LocalSimulationRefused>()
+                .unwrap()
+                .api
+        };
+        let shares = [(true, false), (false, true)];
+        let circuit = Circuit::from_file("circuits/and.json").unwrap();
+        let table = LookupTable::from_fn(2, 1, |x| x & 1).unwrap();
+
+        assert_eq!(refused(&|| and_gate(&shares).map(drop)), "gates::and_gate");
+        assert_eq!(refused(&|| or_gate(&shares).map(drop)), "gates::or_gate");
+        assert_eq!(
+            refused(&|| cross_term_shares(&shares).map(drop)),
+            "gates::cross_term_shares"
+        );
+        let explainer = Explainer::new(2, 7).unwrap();
+        assert_eq!(
+            refused(&|| explainer.run(&circuit, &[true, true]).map(drop)),
+            "Explainer::run"
+        );
+        assert_eq!(
+            refused(&|| lookup_shared(table.preprocess(2).unwrap(), &[1, 0]).map(drop)),
+            "lut::lookup_shared"
+        );
+        let execute = || {
+            GmwProtocol::new(2)
+                .unwrap()
+                .execute_circuit(&circuit, vec![HashMap::new(), HashMap::new()])
+                .map(drop)
+        };
+        assert_eq!(refused(&execute), "GmwProtocol::execute_circuit");
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::circuit::{EvaluationStep, PreparedCircuit};
+use crate::hooks::{GateEvent, GateHook};
+
+/// Snapshot of an evaluation in progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Gates evaluated so far, counting every gate of a collapsed linear block
+    pub gates_done: usize,
+    pub gate_count: usize,
+    /// Interactive layer reached, starting at 1 with the first AND/OR gate
+    pub layer: usize,
+    pub layer_count: usize,
+    /// Time since the first step of the run
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Completed share of the circuit, from 0.0 to 1.0
+    pub fn fraction(&self) -> f64 {
+        if self.gate_count == 0 {
+            1.0
+        } else {
+            self.gates_done as f64 / self.gate_count as f64
+        }
+    }
+
+    /// Estimated time to completion at the rate observed so far
+    pub fn eta(&self) -> Option<Duration> {
+        let fraction = self.fraction();
+        (fraction > 0.0).then(|| self.elapsed.mul_f64((1.0 - fraction) / fraction))
+    }
+}
+
+/// Hook reporting the progress of runs of one prepared circuit
+/// The callback fires whenever the completed percentage changes, so a
+/// multi-million-gate circuit triggers it at most about a hundred times
+pub struct ProgressHook<F> {
+    circuit: String,
+    /// Gates done and layer reached once each plan step has run
+    steps: Vec<(usize, usize)>,
+    layer_count: usize,
+    state: Mutex<ProgressState<F>>,
+}
+
+struct ProgressState<F> {
+    start: Instant,
+    last_percent: Option<u64>,
+    callback: F,
+}
+
+impl<F: FnMut(&Progress) + Send> ProgressHook<F> {
+    pub fn new(prepared: &PreparedCircuit, callback: F) -> Self {
+        let mut gates_done = 0;
+        let steps = prepared
+            .plan
+            .steps
+            .iter()
+            .zip(prepared.plan.step_rounds())
+            .map(|(step, layer)| {
+                gates_done += match step {
+                    EvaluationStep::Linear(block) => block.gate_count(),
+                    EvaluationStep::Gate(_) => 1,
+                };
+                (gates_done, layer)
+            })
+            .collect();
+
+        Self {
+            circuit: prepared.name.clone(),
+            steps,
+            layer_count: prepared.rounds,
+            state: Mutex::new(ProgressState {
+                start: Instant::now(),
+                last_percent: None,
+                callback,
+            }),
+        }
+    }
+}
+
+impl<F: FnMut(&Progress) + Send> GateHook for ProgressHook<F> {
+    fn before_gate(&self, event: &GateEvent<'_>) -> Result<()> {
+        if let GateEvent::Gate {
+            circuit, step: 0, ..
+        }
+        | GateEvent::Linear {
+            circuit, step: 0, ..
+        } = event
+        {
+            if *circuit == self.circuit {
+                let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+                state.start = Instant::now();
+                state.last_percent = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn after_gate(&self, event: &GateEvent<'_>, _elapsed: Duration) -> Result<()> {
+        let (GateEvent::Gate { circuit, step, .. } | GateEvent::Linear { circuit, step, .. }) =
+            *event;
+        let Some(&(gates_done, layer)) = self.steps.get(step) else {
+            return Ok(());
+        };
+        if circuit != self.circuit {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let progress = Progress {
+            gates_done,
+            gate_count: self.steps.last().map_or(0, |&(gates, _)| gates),
+            layer,
+            layer_count: self.layer_count,
+            elapsed: state.start.elapsed(),
+        };
+        let percent = (progress.fraction() * 100.0) as u64;
+        if state.last_percent != Some(percent) {
+            state.last_percent = Some(percent);
+            (state.callback)(&progress);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::protocol::GmwProtocol;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_progress_reaches_completion() {
+        let circuit = reference::adder_comparator(2);
+        let prepared = PreparedCircuit::new(&circuit);
+        let updates = Arc::new(Mutex::new(Vec::new()));
+
+        let sink = Arc::clone(&updates);
+        let hook = ProgressHook::new(&prepared, move |progress: &Progress| {
+            sink.lock().unwrap().push(*progress);
+        });
+        let protocol = GmwProtocol::new(2).unwrap().with_hook(hook);
+        protocol.run_prepared(&prepared, &[true; 4]).unwrap();
+
+        let updates = updates.lock().unwrap();
+        let last = updates.last().unwrap();
+        assert_eq!(last.gates_done, last.gate_count);
+        assert_eq!(last.layer, prepared.rounds);
+        assert_eq!(last.eta(), Some(Duration::ZERO));
+        assert!(updates
+            .windows(2)
+            .all(|pair| pair[0].gates_done < pair[1].gates_done));
+    }
+}
@@ -1,15 +1,139 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::circuit::{Circuit, GateType, WireId};
-use crate::gates::{and_gate, not_gate, or_gate, xor_gate};
+use crate::arena::ShareArena;
+use crate::bmr::{self, GARBLING_ROUNDS};
+use crate::budget::{BudgetGuard, ResourceBudget};
+use crate::circuit::{
+    BusInfo, Circuit, EvaluationStep, GateType, IndexedGate, LinearBlock, LocalEvaluator,
+    PreparedCircuit, WireId, WireIndex,
+};
+use crate::diagnose::ShareMismatch;
+use crate::domain::ShareDomain;
+use crate::errors::ErrorCode;
+use crate::exchange::WireShareExport;
+use crate::gates::{and_gate_rotated, not_gate, or_gate_rotated, xor_gate};
+use crate::hooks::{GateEvent, GateHook};
+use crate::kernel::algebra::{reconstruct, split_shares};
+use crate::ot::{OtChannels, OtStats, PeerOtStats};
+use crate::production::{local_simulation_refused, LocalSimulationRefused};
+use crate::report::{ExecutionReport, LatencyBreakdown, LayerTiming};
+use crate::security::SecurityConfig;
 
 /// Party shares for multi-party computation
 pub type PartyShares = Vec<HashMap<WireId, bool>>;
 
+/// Output shares held by the parties before reconstruction
+#[derive(Debug, Clone)]
+pub struct OutputShares {
+    /// Evaluation the shares come from; a fresh session per evaluation
+    pub domain: ShareDomain,
+    /// Output names in metadata order
+    pub names: Vec<String>,
+    /// Shares of each output, indexed as `shares[output][party]`
+    pub shares: Vec<Vec<bool>>,
+    /// Bus declarations copied from the circuit metadata
+    pub buses: Vec<BusInfo>,
+    /// Outputs left out of `names` because a gate they depend on failed
+    /// under `FailurePolicy::Poison`, with the failure
+    pub poisoned: Vec<(String, String)>,
+    /// Communication rounds used before outputs are opened
+    pub rounds: usize,
+    /// Rounds spent on interactive gates
+    pub interactive_rounds: usize,
+    /// AND-depth of the evaluated circuit
+    pub and_depth: usize,
+    /// Latency so far; `reconstruct` adds the reconstruction time
+    pub timings: LatencyBreakdown,
+    /// OT statistics of every party pair that ran transfers
+    pub ot: Vec<PeerOtStats>,
+}
+
+impl OutputShares {
+    /// Reconstruct all outputs by XORing every party's shares
+    pub fn reconstruct(&self) -> ExecutionReport {
+        let start = Instant::now();
+        let outputs = self
+            .names
+            .iter()
+            .zip(&self.shares)
+            .map(|(name, shares)| (name.clone(), reconstruct(shares)))
+            .collect();
+        self.report(outputs, start.elapsed())
+    }
+
+    /// Finish a dry run: the shares are dropped without combining any, so
+    /// `outputs` is empty while rounds, depth and timings are those of the
+    /// full run, output opening round included
+    pub fn withhold(self) -> ExecutionReport {
+        self.report(Vec::new(), Duration::ZERO)
+    }
+
+    fn report(&self, outputs: Vec<(String, bool)>, reconstruction: Duration) -> ExecutionReport {
+        let mut timings = self.timings.clone();
+        timings.reconstruction += reconstruction;
+
+        ExecutionReport {
+            outputs,
+            buses: self.buses.clone(),
+            poisoned: self.poisoned.clone(),
+            rounds: self.rounds + 1,
+            interactive_rounds: self.interactive_rounds,
+            and_depth: self.and_depth,
+            timings,
+            ot: self.ot.clone(),
+        }
+    }
+}
+
+/// What an evaluation does when a gate fails
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FailurePolicy {
+    /// Fail the whole run with the gate's error
+    #[default]
+    Abort,
+    /// Mark the gate's output as poisoned and keep going; every gate reading
+    /// a poisoned wire is poisoned in turn, and outputs that end up poisoned
+    /// are reported instead of opened. Hook errors, such as an exceeded
+    /// budget, still abort
+    Poison,
+}
+
+/// How a protocol instance evaluates the gates of a circuit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Backend {
+    /// GMW: one round of OTs per AND layer
+    #[default]
+    Gmw,
+    /// Experimental multiparty BMR garbling: a constant
+    /// `bmr::GARBLING_ROUNDS` rounds whatever the AND-depth, for far more
+    /// data per AND gate. Gate hooks are not supported
+    Bmr,
+}
+
 /// GMW Protocol implementation for secure multi-party computation
 pub struct GmwProtocol {
     party_count: usize,
+    hooks: Vec<Box<dyn GateHook>>,
+    security: SecurityConfig,
+    /// Whether runs may hold every party's shares in this process
+    allow_local: bool,
+    /// Worker threads for the interactive gates of one layer
+    threads: usize,
+    /// Pool of `threads` workers, built on the first parallel layer and
+    /// kept for every later run of this protocol
+    pool: OnceLock<rayon::ThreadPool>,
+    /// Whether every step is checked against plaintext evaluation
+    differential: bool,
+    failure_policy: FailurePolicy,
+    backend: Backend,
 }
 
 impl GmwProtocol {
@@ -19,35 +143,137 @@ impl GmwProtocol {
             return Err(anyhow::anyhow!("Need at least 2 parties for computation"));
         }
 
-        Ok(Self { party_count })
+        Ok(Self {
+            party_count,
+            hooks: Vec::new(),
+            security: SecurityConfig::default(),
+            allow_local: !local_simulation_refused(),
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            pool: OnceLock::new(),
+            differential: false,
+            failure_policy: FailurePolicy::default(),
+            backend: Backend::default(),
+        })
     }
 
-    /// Create secret shares for n-party computation
-    /// The secret value is split as: value = share0 ⊕ share1 ⊕ ... ⊕ share(n-1)
-    pub fn secret_share(&self, value: bool) -> Vec<bool> {
-        let mut shares = Vec::with_capacity(self.party_count);
-        let mut accumulated_xor = value;
+    /// Create a protocol instance for the parties `circuit` declares
+    /// `requested` is the count from the caller's configuration; it may be
+    /// left out when the circuit declares one and must match it otherwise
+    pub fn for_circuit(circuit: &Circuit, requested: Option<usize>) -> Result<Self> {
+        let declared = circuit.metadata.parties.as_ref();
+        if let Some(spec) = declared {
+            spec.validate(&circuit.metadata)?;
+        }
+        match (declared.map(|spec| spec.count), requested) {
+            (Some(declared), Some(requested)) if declared != requested => Err(anyhow::anyhow!(
+                "Circuit {} is written for {} parties but {} were requested",
+                circuit.name,
+                declared,
+                requested
+            )),
+            (Some(count), _) | (None, Some(count)) => Self::new(count),
+            (None, None) => Err(anyhow::anyhow!(
+                "Circuit {} does not declare a party count; give one explicitly",
+                circuit.name
+            )),
+        }
+    }
+
+    /// Register a hook run before and after every evaluation step
+    pub fn with_hook(mut self, hook: impl GateHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Abort any evaluation that would exceed `budget` with `BudgetExceeded`
+    pub fn with_budget(self, budget: ResourceBudget) -> Self {
+        let guard = BudgetGuard::new(budget, self.party_count);
+        self.with_hook(guard)
+    }
+
+    /// Security parameters for commitments (default: 128-bit computational,
+    /// 40-bit statistical)
+    pub fn with_security(mut self, security: SecurityConfig) -> Self {
+        self.security = security;
+        self
+    }
+
+    pub fn security(&self) -> SecurityConfig {
+        self.security
+    }
+
+    /// Spread the interactive gates of each layer over up to `threads`
+    /// worker threads (default: the available parallelism); 1 evaluates
+    /// every gate in plan order on the calling thread
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self.pool = OnceLock::new();
+        self
+    }
+
+    /// Check every plan step against plaintext evaluation of the circuit
+    /// (default: off; it re-evaluates the circuit in plaintext). The
+    /// simulation holds every party's shares, so it knows the plaintext
+    /// inputs; the first step whose shares open to the wrong value fails the
+    /// run with `ShareMismatch`, pinpointing where a protocol bug corrupts
+    /// values
+    pub fn with_differential_check(mut self, enabled: bool) -> Self {
+        self.differential = enabled;
+        self
+    }
+
+    /// Whether a failing gate aborts the run or only poisons the outputs
+    /// that depend on it (default: abort)
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Evaluate with `backend` (default: GMW)
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Run in-process simulations even in a `production` build, for tests
+    /// and demos that knowingly hold every party's shares
+    pub fn allow_local_simulation(mut self) -> Self {
+        self.allow_local = true;
+        self
+    }
 
-        // Generate n-1 random shares
-        for _ in 0..self.party_count - 1 {
-            let share = rand::random::<bool>();
-            shares.push(share);
-            accumulated_xor ^= share;
+    /// Fail with `LocalSimulationRefused` unless local simulation is allowed
+    fn check_local(&self, api: &'static str) -> Result<()> {
+        if self.allow_local {
+            Ok(())
+        } else {
+            Err(LocalSimulationRefused::new(api).into())
         }
+    }
 
-        // Last share ensures XOR of all shares equals the value
-        shares.push(accumulated_xor);
+    /// Number of parties taking part in the computation
+    pub fn party_count(&self) -> usize {
+        self.party_count
+    }
 
-        shares
+    /// Create secret shares for n-party computation
+    /// The secret value is split as: value = share0 ⊕ share1 ⊕ ... ⊕ share(n-1)
+    pub fn secret_share(&self, value: bool) -> Vec<bool> {
+        split_shares(value, self.party_count, rand::random::<bool>)
     }
 
     /// Reconstruct secret from n shares
     pub fn reconstruct_shares(&self, shares: &[bool]) -> bool {
-        shares.iter().fold(false, |acc, &share| acc ^ share)
+        reconstruct(shares)
     }
 
     /// Evaluate a complete circuit with multi-party support
     pub fn execute_circuit(&self, circuit: &Circuit, shares: PartyShares) -> Result<PartyShares> {
+        self.check_local("GmwProtocol::execute_circuit")?;
         if shares.len() != self.party_count {
             return Err(anyhow::anyhow!(
                 "Party count mismatch: expected {}, got {}",
@@ -56,31 +282,42 @@ impl GmwProtocol {
             ));
         }
 
-        let mut output_shares: Vec<HashMap<WireId, bool>> = shares.clone();
-
-        for gate in &circuit.gates {
-            let result_shares = match gate.gate_type {
-                GateType::XOR | GateType::AND | GateType::OR => {
-                    // Binary gates: collect two inputs from each party
-                    let party_inputs = self.collect_binary_inputs(&output_shares, &gate.inputs)?;
+        // Move shares into dense per-party storage indexed by wire slot
+        let index = WireIndex::new(circuit);
+        let gates = index.resolve_gates(circuit);
+        let mut arena = ShareArena::new(self.party_count, index.len());
 
-                    match gate.gate_type {
-                        GateType::XOR => xor_gate(&party_inputs)?,
-                        GateType::AND => and_gate(&party_inputs)?,
-                        GateType::OR => or_gate(&party_inputs)?,
-                        _ => unreachable!(),
-                    }
-                }
-                GateType::NOT => {
-                    // Unary gate: collect one input from each party
-                    let party_inputs = self.collect_unary_inputs(&output_shares, gate.inputs[0])?;
-                    not_gate(&party_inputs)?
+        for (party_id, party_share) in shares.iter().enumerate() {
+            for (&wire, &share) in party_share {
+                if let Some(slot) = index.slot(wire) {
+                    arena.set(party_id, slot, share);
                 }
+            }
+        }
+
+        let mut interactive = 0;
+        let mut channels = OtChannels::new(self.party_count);
+        for (step, gate) in gates.iter().enumerate() {
+            let event = GateEvent::Gate {
+                circuit: &circuit.name,
+                step,
+                gate_type: &gate.gate_type,
+                wire: index.wire(gate.output),
             };
+            self.hooked(&event, || {
+                self.evaluate_gate(gate, &mut arena, &mut channels, &mut interactive)
+            })?;
+        }
 
-            // Store results for all parties
-            for (party_id, result) in result_shares.into_iter().enumerate() {
-                output_shares[party_id].insert(gate.id, result);
+        // Convert gate outputs back to external wire IDs
+        let mut output_shares = shares;
+        for (party_id, party_share) in output_shares.iter_mut().enumerate() {
+            for gate in &gates {
+                if let (Some(wire), Some(share)) =
+                    (index.wire(gate.output), arena.get(party_id, gate.output))
+                {
+                    party_share.insert(wire, share);
+                }
             }
         }
 
@@ -89,71 +326,809 @@ impl GmwProtocol {
 
     /// Create party shares from inputs and run circuit with n parties
     pub fn run_circuit(&self, circuit: &Circuit, inputs: &[bool]) -> Result<Vec<(String, bool)>> {
-        if circuit.metadata.outputs.is_empty() {
+        Ok(self.run_with_report(circuit, inputs)?.outputs)
+    }
+
+    /// Run circuit with n parties and return a report for decoding outputs
+    pub fn run_with_report(&self, circuit: &Circuit, inputs: &[bool]) -> Result<ExecutionReport> {
+        self.run_prepared(&PreparedCircuit::new(circuit), inputs)
+    }
+
+    /// Share the inputs and evaluate every gate, input checks included, but
+    /// open no output; the report carries statistics and timings only, so a
+    /// configuration can be validated and costed on a production circuit
+    /// without revealing its results
+    pub fn dry_run(&self, circuit: &Circuit, inputs: &[bool]) -> Result<ExecutionReport> {
+        Ok(self
+            .evaluate_prepared(&PreparedCircuit::new(circuit), inputs)?
+            .withhold())
+    }
+
+    /// Run a prepared circuit, reusing its share arena
+    pub fn run_prepared(
+        &self,
+        prepared: &PreparedCircuit,
+        inputs: &[bool],
+    ) -> Result<ExecutionReport> {
+        Ok(self.evaluate_prepared(prepared, inputs)?.reconstruct())
+    }
+
+    /// Run several unrelated prepared circuits concurrently
+    /// Circuits are spread over worker threads and their rounds overlap, so the
+    /// batch takes as many rounds as its deepest circuit; reports are returned
+    /// in circuit order and the first failure is reported with its circuit name
+    pub fn execute_many(
+        &self,
+        circuits: &[PreparedCircuit],
+        inputs: &[Vec<bool>],
+    ) -> Result<Vec<ExecutionReport>> {
+        if circuits.len() != inputs.len() {
             return Err(anyhow::anyhow!(
-                "Circuit has no output metadata. Please add metadata to the circuit JSON file."
+                "Got {} circuits but {} input vectors",
+                circuits.len(),
+                inputs.len()
             ));
         }
 
-        let expected_inputs = circuit.metadata.inputs.len();
-        if expected_inputs > 0 && inputs.len() != expected_inputs {
+        let workers = thread::available_parallelism()
+            .map_or(1, |workers| workers.get())
+            .min(circuits.len());
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..circuits.len()).map(|_| None).collect::<Vec<_>>());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let (Some(prepared), Some(inputs)) = (circuits.get(i), inputs.get(i)) else {
+                        break;
+                    };
+                    let result = self.run_prepared(prepared, inputs);
+                    results.lock().unwrap_or_else(|err| err.into_inner())[i] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+            .into_iter()
+            .zip(circuits)
+            .map(|(result, prepared)| {
+                result
+                    .unwrap_or_else(|| Err(anyhow::anyhow!("Circuit was not evaluated")))
+                    .map_err(|err| anyhow::anyhow!("Circuit {}: {}", prepared.name, err))
+            })
+            .collect()
+    }
+
+    /// Evaluate a prepared circuit up to, but not including, output reconstruction
+    pub fn evaluate_prepared(
+        &self,
+        prepared: &PreparedCircuit,
+        inputs: &[bool],
+    ) -> Result<OutputShares> {
+        let start = Instant::now();
+        let input_shares = self.share_inputs(inputs);
+        let sharing = start.elapsed();
+        let mut shares = self.evaluate_shared(prepared, &input_shares)?;
+        shares.timings.input_sharing += sharing;
+        Ok(shares)
+    }
+
+    /// `evaluate_prepared` from inputs already split into shares, indexed as
+    /// `input_shares[input][party]`
+    pub(crate) fn evaluate_shared(
+        &self,
+        prepared: &PreparedCircuit,
+        input_shares: &[Vec<bool>],
+    ) -> Result<OutputShares> {
+        let mut timings = LatencyBreakdown::default();
+        let (arena, ot) =
+            self.evaluate_checked(prepared, input_shares, &mut timings, &mut |_, _| Ok(()))?;
+        self.output_shares(prepared, &arena, timings, ot)
+    }
+
+    /// Collect every party's output shares from an evaluated arena
+    fn output_shares(
+        &self,
+        prepared: &PreparedCircuit,
+        arena: &ShareArena,
+        timings: LatencyBreakdown,
+        ot: Vec<PeerOtStats>,
+    ) -> Result<OutputShares> {
+        let metadata = &prepared.metadata;
+        let interactive_rounds = match self.backend {
+            Backend::Gmw => prepared.rounds,
+            Backend::Bmr => GARBLING_ROUNDS,
+        };
+        let mut names = Vec::with_capacity(metadata.outputs.len());
+        let mut shares = Vec::with_capacity(metadata.outputs.len());
+        let mut poisoned = Vec::new();
+        for output_info in &metadata.outputs {
+            let missing = || anyhow::anyhow!("Missing output gate {}", output_info.id);
+            let slot = prepared.index.slot(output_info.id).ok_or_else(missing)?;
+            if let Some(reason) = arena.poisoned(slot) {
+                poisoned.push((output_info.name.clone(), reason.to_string()));
+                continue;
+            }
+            let output_shares: Vec<bool> = (0..self.party_count)
+                .map(|party_id| arena.get(party_id, slot).ok_or_else(missing))
+                .collect::<Result<Vec<_>>>()?;
+            names.push(output_info.name.clone());
+            shares.push(output_shares);
+        }
+
+        Ok(OutputShares {
+            domain: ShareDomain::fresh(&prepared.name),
+            names,
+            shares,
+            buses: metadata.buses.clone(),
+            poisoned,
+            // Input sharing, interactive rounds, then opening the input checks
+            rounds: 1 + interactive_rounds + usize::from(!prepared.input_checks()?.is_empty()),
+            interactive_rounds,
+            and_depth: prepared.and_depth,
+            timings,
+            ot,
+        })
+    }
+
+    /// Evaluate a prepared circuit, delivering each output to `on_output` as
+    /// soon as the plan step computing it has run instead of after the whole
+    /// circuit. Poisoned outputs are skipped and only listed in the returned
+    /// shares. Outputs named in `withheld` are never streamed, so a release
+    /// policy such as `ApprovalRelease` can govern them from the returned
+    /// shares. Input checks are opened before the first output is delivered,
+    /// so a violation still reveals nothing; an error from `on_output` aborts
+    /// the evaluation
+    pub fn evaluate_streaming(
+        &self,
+        prepared: &PreparedCircuit,
+        inputs: &[bool],
+        withheld: &[&str],
+        mut on_output: impl FnMut(&str, bool) -> Result<()>,
+    ) -> Result<OutputShares> {
+        let metadata = &prepared.metadata;
+        if let Some(unknown) = withheld
+            .iter()
+            .find(|name| !metadata.outputs.iter().any(|output| output.name == **name))
+        {
+            return Err(anyhow::anyhow!("Unknown output {}", unknown));
+        }
+
+        // An output is ready once the step writing it and every step writing
+        // an input check have run; inputs are ready before the first step
+        let slot_steps = prepared.plan.slot_steps();
+        let ready = |slot: usize| slot_steps.get(&slot).copied();
+        let checks = prepared.input_checks()?;
+        let checks_ready = checks.iter().map(|&(_, slot)| ready(slot)).max().flatten();
+        let mut pending = Vec::new();
+        for output in &metadata.outputs {
+            if withheld.contains(&output.name.as_str()) {
+                continue;
+            }
+            let slot = prepared
+                .index
+                .slot(output.id)
+                .ok_or_else(|| anyhow::anyhow!("Missing output gate {}", output.id))?;
+            pending.push((ready(slot).max(checks_ready), output.name.as_str(), slot));
+        }
+        pending.sort_by_key(|&(step, _, _)| step);
+
+        let mut delivered = 0;
+        let mut reconstruction = Duration::ZERO;
+        let mut deliver = |position: Option<usize>, arena: &ShareArena| -> Result<()> {
+            let start = Instant::now();
+            if delivered == 0
+                && pending
+                    .first()
+                    .is_some_and(|&(step, _, _)| step <= position)
+            {
+                self.open_checks(checks, arena)?;
+            }
+            while let Some(&(_, name, slot)) = pending
+                .get(delivered)
+                .filter(|&&(step, _, _)| step <= position)
+            {
+                if arena.poisoned(slot).is_some() {
+                    delivered += 1;
+                    continue;
+                }
+                let shares = (0..self.party_count)
+                    .map(|party_id| arena.get(party_id, slot))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| anyhow::anyhow!("Missing shares for output {}", name))?;
+                delivered += 1;
+                on_output(name, reconstruct(&shares))?;
+            }
+            reconstruction += start.elapsed();
+            Ok(())
+        };
+
+        let start = Instant::now();
+        let input_shares = self.share_inputs(inputs);
+        let sharing = start.elapsed();
+        let mut timings = LatencyBreakdown::default();
+        let (arena, ot) = self.evaluate_checked(
+            prepared,
+            &input_shares,
+            &mut timings,
+            &mut |position, arena| deliver(Some(position), arena),
+        )?;
+        // Whatever is left, such as outputs read straight from the inputs of
+        // a circuit without steps
+        deliver(Some(usize::MAX), &arena)?;
+
+        let mut shares = self.output_shares(prepared, &arena, timings, ot)?;
+        shares.timings.input_sharing += sharing;
+        shares.timings.reconstruction += reconstruction;
+        Ok(shares)
+    }
+
+    /// Evaluate a prepared circuit and export each party's shares of `wires`
+    /// instead of opening outputs, so an external system can continue the
+    /// computation; returns one export per party, tagged with `session` and
+    /// the circuit name
+    pub fn export_wires(
+        &self,
+        prepared: &PreparedCircuit,
+        inputs: &[bool],
+        wires: &[WireId],
+        session: u64,
+    ) -> Result<Vec<WireShareExport>> {
+        let mut timings = LatencyBreakdown::default();
+        let (arena, _) = self.evaluate_checked(
+            prepared,
+            &self.share_inputs(inputs),
+            &mut timings,
+            &mut |_, _| Ok(()),
+        )?;
+        let domain = ShareDomain::new(session, &prepared.name);
+
+        let slots = wires
+            .iter()
+            .map(|&wire| {
+                prepared
+                    .index
+                    .slot(wire)
+                    .map(|slot| (wire, slot))
+                    .ok_or_else(|| anyhow::anyhow!("Wire {} not found", wire))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        (0..self.party_count)
+            .map(|party| {
+                let shares = slots
+                    .iter()
+                    .map(|&(wire, slot)| {
+                        if let Some(reason) = arena.poisoned(slot) {
+                            return Err(anyhow::anyhow!("Wire {} is poisoned: {}", wire, reason));
+                        }
+                        arena
+                            .get(party, slot)
+                            .map(|share| (wire, share))
+                            .ok_or_else(|| anyhow::anyhow!("Wire {} has no value", wire))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(WireShareExport::new(
+                    &domain,
+                    party,
+                    self.party_count,
+                    shares,
+                ))
+            })
+            .collect()
+    }
+
+    /// Split each input into n-party secret shares
+    pub(crate) fn share_inputs(&self, inputs: &[bool]) -> Vec<Vec<bool>> {
+        inputs
+            .iter()
+            .map(|&input| self.secret_share(input))
+            .collect()
+    }
+
+    /// Load input shares, run the evaluation plan and open the input checks
+    /// Returns the locked arena holding every party's shares and the OT
+    /// statistics of each party pair; the time of loading and of each layer
+    /// is added to `timings`. `after_step` runs after each plan step with
+    /// its position and the shares so far
+    fn evaluate_checked<'a>(
+        &self,
+        prepared: &'a PreparedCircuit,
+        input_shares: &[Vec<bool>],
+        timings: &mut LatencyBreakdown,
+        after_step: &mut dyn FnMut(usize, &ShareArena) -> Result<()>,
+    ) -> Result<(MutexGuard<'a, ShareArena>, Vec<PeerOtStats>)> {
+        self.check_local("GmwProtocol evaluation")?;
+        let metadata = &prepared.metadata;
+
+        if metadata.outputs.is_empty() {
             return Err(anyhow::anyhow!(
+                "Circuit has no output metadata. Please add metadata to the circuit JSON file."
+            ));
+        }
+
+        let expected_inputs = metadata.inputs.len();
+        if expected_inputs > 0 && input_shares.len() != expected_inputs {
+            return Err(ErrorCode::InputMismatch.tag(anyhow::anyhow!(
                 "Circuit expects {} inputs but got {}",
                 expected_inputs,
-                inputs.len()
-            ));
+                input_shares.len()
+            )));
         }
 
-        // Create n-party secret shares
-        let mut party_shares: Vec<HashMap<WireId, bool>> = vec![HashMap::new(); self.party_count];
+        let checks = prepared.input_checks()?;
 
-        for (i, &input) in inputs.iter().enumerate() {
-            let shares = self.secret_share(input);
-            let wire_id = circuit.metadata.inputs[i].id;
+        let start = Instant::now();
+        let mut arena = prepared.arena();
+        arena.reset(self.party_count, prepared.index.len());
 
-            for (party_id, share) in shares.into_iter().enumerate() {
-                party_shares[party_id].insert(wire_id, share);
+        // Load the n-party secret shares
+        for (i, shares) in input_shares.iter().enumerate() {
+            let slot = prepared
+                .index
+                .slot(metadata.inputs[i].id)
+                .ok_or_else(|| anyhow::anyhow!("Input {} not found", metadata.inputs[i].name))?;
+
+            for (party_id, &share) in shares.iter().enumerate() {
+                arena.set(party_id, slot, share);
             }
         }
+        timings.input_sharing += start.elapsed();
 
-        // Execute circuit
-        let result_shares = self.execute_circuit(circuit, party_shares)?;
+        let expected = if self.differential {
+            let inputs: Vec<bool> = input_shares
+                .iter()
+                .map(|shares| reconstruct(shares))
+                .collect();
+            Some(LocalEvaluator::evaluate_slots(prepared, &inputs)?)
+        } else {
+            None
+        };
+
+        if self.backend == Backend::Bmr {
+            return self.evaluate_garbled(prepared, arena, checks, expected, timings, after_step);
+        }
 
-        // Collect outputs
-        let mut outputs = Vec::new();
-        for output_info in &circuit.metadata.outputs {
-            let output_shares: Vec<bool> = result_shares
+        // Execute circuit, applying collapsed linear blocks in one pass and
+        // timing each layer as a whole, since per-step clocks cost too much
+        timings.layers = (0..=prepared.rounds)
+            .map(|layer| LayerTiming {
+                layer,
+                ..LayerTiming::default()
+            })
+            .collect();
+        let steps = &prepared.plan.steps;
+        let step_layers = prepared.step_layers();
+        let mut layer = 0;
+        let mut layer_start = Instant::now();
+        let mut interactive = 0;
+        // One set of OT channels per worker, kept for the whole run so every
+        // party pair sets up its OT extension once
+        let mut channels: Vec<Mutex<OtChannels>> = (0..self.threads)
+            .map(|_| Mutex::new(OtChannels::new(self.party_count)))
+            .collect();
+        let mut position = 0;
+        while let Some(step) = steps.get(position) {
+            if step_layers[position] != layer {
+                timings.layers[layer].compute += layer_start.elapsed();
+                layer = step_layers[position];
+                layer_start = Instant::now();
+                // A transfer that failed under `FailurePolicy::Poison` may
+                // have left its channel out of step; renew it before the
+                // layer's transfers
+                for set in &mut channels {
+                    set.get_mut()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .check_health()?;
+                }
+            }
+            // Consecutive AND/OR steps of one layer share a round, so none
+            // reads another's output and they can run on several threads;
+            // local gates are too cheap to be worth handing to a worker
+            let run: Vec<&IndexedGate> = steps[position..]
                 .iter()
-                .map(|party| {
-                    party
-                        .get(&output_info.id)
-                        .copied()
-                        .ok_or_else(|| anyhow::anyhow!("Missing output gate {}", output_info.id))
+                .zip(&step_layers[position..])
+                .map_while(|(step, &step_layer)| match step {
+                    EvaluationStep::Gate(gate)
+                        if step_layer == layer && gate.gate_type.is_interactive() =>
+                    {
+                        Some(gate)
+                    }
+                    _ => None,
                 })
-                .collect::<Result<Vec<_>>>()?;
-            let result = self.reconstruct_shares(&output_shares);
-            outputs.push((output_info.name.clone(), result));
+                .take(if self.threads > 1 { usize::MAX } else { 1 })
+                .collect();
+            match step {
+                _ if run.len() > 1 => {
+                    self.evaluate_parallel(
+                        prepared,
+                        position,
+                        &run,
+                        &mut arena,
+                        &channels,
+                        &mut interactive,
+                    )?;
+                }
+                EvaluationStep::Linear(block) => {
+                    let event = GateEvent::Linear {
+                        circuit: &prepared.name,
+                        step: position,
+                        gate_count: block.gate_count(),
+                    };
+                    let broken = self.broken_inputs(prepared, block, &mut arena);
+                    self.hooked(&event, || block.apply(&mut arena))?;
+                    for row in &block.rows {
+                        if let Some((_, reason)) = broken
+                            .iter()
+                            .find(|&&(input, _)| row.terms[input / 64] >> (input % 64) & 1 == 1)
+                        {
+                            arena.poison(row.output, reason.clone());
+                        }
+                    }
+                }
+                EvaluationStep::Gate(gate) => {
+                    if let Some(reason) = poisoned_input(&arena, &gate.inputs) {
+                        arena.poison(gate.output, reason);
+                    } else {
+                        let event = self.gate_event(prepared, position, gate);
+                        let mut failure = None;
+                        self.hooked(&event, || {
+                            match self.evaluate_gate(
+                                gate,
+                                &mut arena,
+                                channels[0].get_mut().unwrap_or_else(|err| err.into_inner()),
+                                &mut interactive,
+                            ) {
+                                Err(error) if self.failure_policy == FailurePolicy::Poison => {
+                                    failure = Some(format!("{error:#}"));
+                                    Ok(())
+                                }
+                                result => result,
+                            }
+                        })?;
+                        if let Some(reason) = failure {
+                            let name = slot_name(prepared, gate.output);
+                            arena.poison(gate.output, format!("Gate at {name}: {reason}"));
+                        }
+                    }
+                }
+            }
+            for done in position..position + run.len().max(1) {
+                if let Some(expected) = &expected {
+                    self.check_step(prepared, done, &arena, expected)?;
+                }
+                after_step(done, &arena)?;
+            }
+            position += run.len().max(1);
+        }
+        timings.layers[layer].compute += layer_start.elapsed();
+
+        // Open input checks first; a violation aborts before any output is revealed
+        self.open_checks(checks, &arena)?;
+
+        Ok((arena, peer_stats(channels)))
+    }
+
+    /// Rest of `evaluate_checked` under `Backend::Bmr`: garble and evaluate
+    /// the whole circuit at once, then run the per-step checks
+    fn evaluate_garbled<'a>(
+        &self,
+        prepared: &PreparedCircuit,
+        mut arena: MutexGuard<'a, ShareArena>,
+        checks: &[(String, usize)],
+        expected: Option<Vec<Option<bool>>>,
+        timings: &mut LatencyBreakdown,
+        after_step: &mut dyn FnMut(usize, &ShareArena) -> Result<()>,
+    ) -> Result<(MutexGuard<'a, ShareArena>, Vec<PeerOtStats>)> {
+        if !self.hooks.is_empty() {
+            return Err(anyhow::anyhow!(
+                "The BMR backend evaluates no gate on its own, so it cannot run gate hooks"
+            ));
+        }
+        let start = Instant::now();
+        let mut channels = OtChannels::new(self.party_count);
+        bmr::evaluate(prepared, &mut arena, &mut channels)?;
+        timings.layers = vec![LayerTiming {
+            layer: 0,
+            compute: start.elapsed(),
+            ..LayerTiming::default()
+        }];
+        for position in 0..prepared.plan.steps.len() {
+            if let Some(expected) = &expected {
+                self.check_step(prepared, position, &arena, expected)?;
+            }
+            after_step(position, &arena)?;
+        }
+        self.open_checks(checks, &arena)?;
+
+        Ok((arena, channels.stats()))
+    }
+
+    /// Under `FailurePolicy::Poison`, the positions of the inputs of `block`
+    /// that are poisoned or were never written, with the reason; those
+    /// inputs are zeroed so the rest of the block can still be applied
+    fn broken_inputs(
+        &self,
+        prepared: &PreparedCircuit,
+        block: &LinearBlock,
+        arena: &mut ShareArena,
+    ) -> Vec<(usize, String)> {
+        if self.failure_policy != FailurePolicy::Poison {
+            return Vec::new();
+        }
+        let mut broken = Vec::new();
+        for (position, &slot) in block.inputs.iter().enumerate() {
+            let reason = arena.poisoned(slot).map(str::to_string).or_else(|| {
+                (0..self.party_count)
+                    .any(|party_id| arena.get(party_id, slot).is_none())
+                    .then(|| format!("{} has no value", slot_name(prepared, slot)))
+            });
+            if let Some(reason) = reason {
+                for party_id in 0..self.party_count {
+                    arena.set(party_id, slot, false);
+                }
+                broken.push((position, reason));
+            }
         }
+        broken
+    }
 
-        Ok(outputs)
+    /// Fail with `ShareMismatch` if a slot written by plan step `position`
+    /// opens to anything but its plaintext value
+    fn check_step(
+        &self,
+        prepared: &PreparedCircuit,
+        position: usize,
+        arena: &ShareArena,
+        expected: &[Option<bool>],
+    ) -> Result<()> {
+        let written: Vec<usize> = match &prepared.plan.steps[position] {
+            EvaluationStep::Gate(gate) => vec![gate.output],
+            EvaluationStep::Linear(block) => block.rows.iter().map(|row| row.output).collect(),
+        };
+        for slot in written {
+            if arena.poisoned(slot).is_some() {
+                continue;
+            }
+            let shares = (0..self.party_count)
+                .map(|party_id| arena.get(party_id, slot))
+                .collect::<Option<Vec<_>>>();
+            let (Some(shares), Some(Some(expected))) = (shares, expected.get(slot).copied()) else {
+                continue;
+            };
+            if reconstruct(&shares) != expected {
+                return Err(ShareMismatch {
+                    circuit: prepared.name.clone(),
+                    step: position,
+                    slot,
+                    wire: prepared.index.wire(slot),
+                    expected,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Open the input checks, failing if any constraint is violated
+    fn open_checks(&self, checks: &[(String, usize)], arena: &ShareArena) -> Result<()> {
+        for (constraint, slot) in checks {
+            if let Some(reason) = arena.poisoned(*slot) {
+                return Err(anyhow::anyhow!(
+                    "Input check {} could not be evaluated: {}",
+                    constraint,
+                    reason
+                ));
+            }
+            let check_shares = (0..self.party_count)
+                .map(|party_id| arena.get(party_id, *slot))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| anyhow::anyhow!("Missing shares for check {}", constraint))?;
+            if !reconstruct(&check_shares) {
+                return Err(anyhow::anyhow!(
+                    "Input constraint {} violated; outputs withheld",
+                    constraint
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `evaluate` between the registered hooks' callbacks for `event`
+    fn hooked(&self, event: &GateEvent<'_>, evaluate: impl FnOnce() -> Result<()>) -> Result<()> {
+        if self.hooks.is_empty() {
+            return evaluate();
+        }
+
+        for hook in &self.hooks {
+            hook.before_gate(event)?;
+        }
+        let start = Instant::now();
+        evaluate()?;
+        let elapsed = start.elapsed();
+        for hook in &self.hooks {
+            hook.after_gate(event, elapsed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hook event for the gate at plan step `position`
+    fn gate_event<'a>(
+        &self,
+        prepared: &'a PreparedCircuit,
+        position: usize,
+        gate: &'a IndexedGate,
+    ) -> GateEvent<'a> {
+        GateEvent::Gate {
+            circuit: &prepared.name,
+            step: position,
+            gate_type: &gate.gate_type,
+            wire: prepared.index.wire(gate.output),
+        }
+    }
+
+    /// Evaluate `gates`, the AND/OR plan steps from `first` on, which read
+    /// none of each other's outputs, on the protocol's pool of `threads`
+    /// workers. Workers steal gates from each other until the layer is done,
+    /// so one slow gate does not hold up a fixed share of it, and each runs
+    /// its OTs over its own set of `channels`. Hooks see the gates in plan
+    /// order: every `before_gate` first, then every `after_gate` with the
+    /// gate's own evaluation time
+    fn evaluate_parallel(
+        &self,
+        prepared: &PreparedCircuit,
+        first: usize,
+        gates: &[&IndexedGate],
+        arena: &mut ShareArena,
+        channels: &[Mutex<OtChannels>],
+        interactive: &mut usize,
+    ) -> Result<()> {
+        // Gates of a run read none of each other's outputs, so the poisoned
+        // ones are known up front. Like the sequential path, they are
+        // poisoned without being evaluated and fire no hooks
+        let mut live = Vec::with_capacity(gates.len());
+        for (i, gate) in gates.iter().enumerate() {
+            match poisoned_input(arena, &gate.inputs) {
+                Some(reason) => arena.poison(gate.output, reason),
+                None => live.push((self.gate_event(prepared, first + i, gate), *gate)),
+            }
+        }
+        for (event, _) in &live {
+            for hook in &self.hooks {
+                hook.before_gate(event)?;
+            }
+        }
+
+        let rotation = *interactive;
+        let shares: &ShareArena = arena;
+        let results: Vec<(Result<Vec<bool>>, Duration)> = self.pool()?.install(|| {
+            live.par_iter()
+                .enumerate()
+                .map(|(i, (_, gate))| {
+                    // Only this worker uses its channels, so the lock is never contended
+                    let worker = rayon::current_thread_index().unwrap_or(0);
+                    let mut channels = channels[worker]
+                        .lock()
+                        .unwrap_or_else(|err| err.into_inner());
+                    let start = Instant::now();
+                    let result = self.gate_shares(gate, shares, rotation + i, &mut channels);
+                    (result, start.elapsed())
+                })
+                .collect()
+        });
+
+        // Every evaluated gate gets its `after_gate`, as in `hooked`; a gate
+        // that failed under `FailurePolicy::Poison` counts as evaluated
+        for ((event, gate), (result, elapsed)) in live.iter().zip(results) {
+            let result_shares = match result {
+                Ok(result_shares) => Some(result_shares),
+                Err(error) if self.failure_policy == FailurePolicy::Poison => {
+                    let name = slot_name(prepared, gate.output);
+                    arena.poison(gate.output, format!("Gate at {name}: {error:#}"));
+                    None
+                }
+                Err(error) => return Err(error),
+            };
+            for hook in &self.hooks {
+                hook.after_gate(event, elapsed)?;
+            }
+            for (party_id, result) in result_shares.into_iter().flatten().enumerate() {
+                arena.set(party_id, gate.output, result);
+            }
+        }
+        // Every evaluated gate of a run is interactive, so each takes one rotation
+        *interactive += live.len();
+
+        Ok(())
+    }
+
+    /// The worker pool, built with `threads` workers on first use
+    fn pool(&self) -> Result<&rayon::ThreadPool> {
+        if self.pool.get().is_none() {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(self.threads)
+                .thread_name(|worker| format!("gmw-worker-{worker}"))
+                .build()?;
+            // A concurrent run may have won the race; either pool will do
+            let _ = self.pool.set(pool);
+        }
+        self.pool
+            .get()
+            .ok_or_else(|| anyhow::anyhow!("Worker pool was not built"))
+    }
+
+    /// Every party's share of a single resolved gate's output
+    /// `rotation` picks the OT sender roles of an AND/OR gate, whose cross
+    /// terms run over `channels`
+    fn gate_shares(
+        &self,
+        gate: &IndexedGate,
+        arena: &ShareArena,
+        rotation: usize,
+        channels: &mut OtChannels,
+    ) -> Result<Vec<bool>> {
+        Ok(match gate.gate_type {
+            GateType::XOR | GateType::AND | GateType::OR => {
+                // Binary gates: collect two inputs from each party
+                let party_inputs = self.collect_binary_inputs(arena, &gate.inputs)?;
+
+                match gate.gate_type {
+                    GateType::XOR => xor_gate(&party_inputs)?,
+                    GateType::AND => and_gate_rotated(&party_inputs, rotation, channels)?,
+                    GateType::OR => or_gate_rotated(&party_inputs, rotation, channels)?,
+                    _ => unreachable!(),
+                }
+            }
+            GateType::NOT => {
+                // Unary gate: collect one input from each party
+                let party_inputs = self.collect_unary_inputs(arena, gate.inputs[0])?;
+                not_gate(&party_inputs)?
+            }
+        })
+    }
+
+    /// Evaluate a single resolved gate for all parties
+    /// `interactive` counts the AND/OR gates evaluated so far in this run and
+    /// rotates OT sender roles between them
+    fn evaluate_gate(
+        &self,
+        gate: &IndexedGate,
+        arena: &mut ShareArena,
+        channels: &mut OtChannels,
+        interactive: &mut usize,
+    ) -> Result<()> {
+        let result_shares = self.gate_shares(gate, arena, *interactive, channels)?;
+
+        if gate.gate_type.is_interactive() {
+            *interactive += 1;
+        }
+
+        // Store results for all parties
+        for (party_id, result) in result_shares.into_iter().enumerate() {
+            arena.set(party_id, gate.output, result);
+        }
+
+        Ok(())
     }
 
     /// Collect binary inputs (two inputs per party) for gates like XOR, AND, OR
     fn collect_binary_inputs(
         &self,
-        output_shares: &[HashMap<WireId, bool>],
-        gate_inputs: &[WireId],
+        arena: &ShareArena,
+        gate_inputs: &[usize],
     ) -> Result<Vec<(bool, bool)>> {
         let mut party_inputs = Vec::with_capacity(self.party_count);
 
-        for (party_id, party_share) in output_shares.iter().enumerate().take(self.party_count) {
-            let input_a = party_share
-                .get(&gate_inputs[0])
-                .copied()
+        for party_id in 0..self.party_count {
+            let input_a = arena
+                .get(party_id, gate_inputs[0])
                 .ok_or_else(|| anyhow::anyhow!("Missing Party {} input A", party_id))?;
-            let input_b = party_share
-                .get(&gate_inputs[1])
-                .copied()
+            let input_b = arena
+                .get(party_id, gate_inputs[1])
                 .ok_or_else(|| anyhow::anyhow!("Missing Party {} input B", party_id))?;
             party_inputs.push((input_a, input_b));
         }
@@ -162,17 +1137,12 @@ impl GmwProtocol {
     }
 
     /// Collect unary inputs (one input per party) for gates like NOT
-    fn collect_unary_inputs(
-        &self,
-        output_shares: &[HashMap<WireId, bool>],
-        input_wire: WireId,
-    ) -> Result<Vec<bool>> {
+    fn collect_unary_inputs(&self, arena: &ShareArena, input_slot: usize) -> Result<Vec<bool>> {
         let mut party_inputs = Vec::with_capacity(self.party_count);
 
-        for (party_id, party_share) in output_shares.iter().enumerate().take(self.party_count) {
-            let input = party_share
-                .get(&input_wire)
-                .copied()
+        for party_id in 0..self.party_count {
+            let input = arena
+                .get(party_id, input_slot)
                 .ok_or_else(|| anyhow::anyhow!("Missing Party {} input", party_id))?;
             party_inputs.push(input);
         }
@@ -181,9 +1151,48 @@ impl GmwProtocol {
     }
 }
 
+/// First failure among the poisoned slots in `inputs`, if any
+fn poisoned_input(arena: &ShareArena, inputs: &[usize]) -> Option<String> {
+    inputs
+        .iter()
+        .find_map(|&slot| arena.poisoned(slot))
+        .map(str::to_string)
+}
+
+/// Wire stored in `slot`, for failure messages
+fn slot_name(prepared: &PreparedCircuit, slot: usize) -> String {
+    prepared
+        .index
+        .wire(slot)
+        .map_or_else(|| format!("slot {slot}"), |wire| format!("wire {wire}"))
+}
+
+/// OT statistics of each party pair, summed over the workers' channel sets
+fn peer_stats(channels: Vec<Mutex<OtChannels>>) -> Vec<PeerOtStats> {
+    let mut pairs: BTreeMap<(usize, usize), OtStats> = BTreeMap::new();
+    for set in channels {
+        let set = set.into_inner().unwrap_or_else(|err| err.into_inner());
+        for peer in set.stats() {
+            pairs
+                .entry((peer.sender, peer.receiver))
+                .or_default()
+                .merge(&peer.stats);
+        }
+    }
+    pairs
+        .into_iter()
+        .map(|((sender, receiver), stats)| PeerOtStats {
+            sender,
+            receiver,
+            stats,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::circuit::reference;
 
     #[test]
     fn test_secret_share() {
@@ -230,6 +1239,7 @@ mod tests {
                     name: "result".to_string(),
                     id: 3,
                 }],
+                ..Default::default()
             },
         };
 
@@ -256,4 +1266,502 @@ mod tests {
 
         assert_eq!(protocol.reconstruct_shares(&output_shares), true);
     }
+
+    #[test]
+    fn test_for_circuit() {
+        use crate::circuit::PartySpec;
+
+        let mut circuit = Circuit::from_file("circuits/and.json").unwrap();
+        assert_eq!(
+            GmwProtocol::for_circuit(&circuit, Some(4))
+                .unwrap()
+                .party_count(),
+            4
+        );
+        assert!(GmwProtocol::for_circuit(&circuit, None).is_err());
+
+        let first = circuit.metadata.inputs[0].name.clone();
+        circuit.metadata.parties = Some(PartySpec {
+            count: 3,
+            owners: [(first.clone(), 2)].into(),
+        });
+        assert_eq!(
+            GmwProtocol::for_circuit(&circuit, None)
+                .unwrap()
+                .party_count(),
+            3
+        );
+        assert!(GmwProtocol::for_circuit(&circuit, Some(3)).is_ok());
+        let err = GmwProtocol::for_circuit(&circuit, Some(2)).err().unwrap();
+        assert!(err.to_string().contains("written for 3 parties"));
+
+        // Owners must name a declared input and an existing party
+        let spec = circuit.metadata.parties.as_mut().unwrap();
+        spec.owners.insert(first, 3);
+        assert!(GmwProtocol::for_circuit(&circuit, None).is_err());
+        let spec = circuit.metadata.parties.as_mut().unwrap();
+        spec.owners.clear();
+        spec.owners.insert("missing".to_string(), 0);
+        assert!(GmwProtocol::for_circuit(&circuit, None).is_err());
+    }
+
+    #[test]
+    fn test_run_prepared_reuses_arena() {
+        let circuit = reference::full_adder();
+        let prepared = PreparedCircuit::new(&circuit);
+        let protocol = GmwProtocol::new(3).unwrap();
+
+        for input in 0..8u8 {
+            let inputs: Vec<bool> = (0..3).map(|bit| input >> bit & 1 == 1).collect();
+            let report = protocol.run_prepared(&prepared, &inputs).unwrap();
+            assert_eq!(
+                report.decode::<u8>("total").unwrap(),
+                input.count_ones() as u8
+            );
+        }
+    }
+
+    #[test]
+    fn test_input_constraint_withholds_outputs() {
+        let mut circuit = Circuit::from_file("circuits/mux_2to1.json").unwrap();
+        let names: Vec<String> = circuit
+            .metadata
+            .inputs
+            .iter()
+            .map(|input| input.name.clone())
+            .collect();
+        circuit
+            .metadata
+            .constraints
+            .push(crate::circuit::InputConstraint::LessThan {
+                bits: names,
+                bound: 4,
+            });
+        let protocol = GmwProtocol::new(3).unwrap();
+
+        assert!(protocol.run_circuit(&circuit, &[true, true, false]).is_ok());
+        let err = protocol
+            .run_circuit(&circuit, &[true, true, true])
+            .unwrap_err();
+        assert!(err.to_string().contains("violated"));
+    }
+
+    #[test]
+    fn test_rounds_match_and_depth() {
+        let protocol = GmwProtocol::new(3).unwrap();
+
+        for entry in std::fs::read_dir("circuits").unwrap() {
+            let path = entry.unwrap().path();
+            let circuit = Circuit::load(path.to_str().unwrap()).unwrap();
+            let inputs = vec![true; circuit.metadata.inputs.len()];
+            let report = protocol.run_with_report(&circuit, &inputs).unwrap();
+
+            assert!(report.is_depth_optimal(), "{path:?}");
+            assert_eq!(report.rounds, report.and_depth + 2, "{path:?}");
+        }
+    }
+
+    #[test]
+    fn test_dry_run_opens_nothing() {
+        let circuit = reference::adder_comparator(3);
+        let protocol = GmwProtocol::new(3).unwrap();
+        let inputs = [true, false, true, true, true, false];
+        let full = protocol.run_with_report(&circuit, &inputs).unwrap();
+        let dry = protocol.dry_run(&circuit, &inputs).unwrap();
+
+        assert!(dry.outputs.is_empty());
+        assert!(dry.decode::<u64>("sum").is_err());
+        assert_eq!(
+            (dry.rounds, dry.interactive_rounds, dry.and_depth),
+            (full.rounds, full.interactive_rounds, full.and_depth)
+        );
+        assert_eq!(dry.timings.reconstruction, std::time::Duration::ZERO);
+        // Input mistakes still surface
+        assert!(protocol.dry_run(&circuit, &inputs[1..]).is_err());
+    }
+
+    #[test]
+    fn test_latency_breakdown() {
+        let circuit = reference::adder_comparator(2);
+        let prepared = PreparedCircuit::new(&circuit);
+        let report = GmwProtocol::new(3)
+            .unwrap()
+            .run_prepared(&prepared, &[true; 4])
+            .unwrap();
+
+        // One entry per layer, and in-process parties never wait on a network
+        let timings = &report.timings;
+        let layers: Vec<usize> = timings.layers.iter().map(|layer| layer.layer).collect();
+        assert_eq!(layers, (0..=prepared.rounds).collect::<Vec<_>>());
+        assert_eq!(timings.communication(), std::time::Duration::ZERO);
+        assert!(!timings.is_network_bound());
+        assert_eq!(
+            timings.total(),
+            timings.input_sharing + timings.compute() + timings.reconstruction
+        );
+    }
+
+    #[test]
+    fn test_streaming_releases_outputs_early() {
+        use crate::hooks::GateEvent;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        #[derive(Default)]
+        struct Steps(AtomicUsize);
+        impl GateHook for Steps {
+            fn after_gate(&self, _event: &GateEvent<'_>, _elapsed: Duration) -> Result<()> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+        }
+
+        let circuit = reference::adder_comparator(2);
+        let prepared = PreparedCircuit::new(&circuit);
+        let steps = Arc::new(Steps::default());
+        let protocol = GmwProtocol::new(3).unwrap().with_hook(steps.clone());
+        let inputs = [true, false, true, true];
+
+        let mut streamed = Vec::new();
+        let shares = protocol
+            .evaluate_streaming(&prepared, &inputs, &["lt", "eq"], |name, value| {
+                streamed.push((name.to_string(), value, steps.0.load(Ordering::Relaxed)));
+                Ok(())
+            })
+            .unwrap();
+
+        // sum0 = a0 ^ b0 needs none of the carry chain
+        let total = prepared.plan.steps.len();
+        assert_eq!(streamed[0].0, "sum0");
+        assert!(streamed[0].2 < total);
+        let report = shares.reconstruct();
+        let mut names: Vec<&str> = streamed.iter().map(|(name, ..)| name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["sum0", "sum1", "sum_carry", "sum_overflow"]);
+        for (name, value, _) in &streamed {
+            assert!(report.outputs.contains(&(name.clone(), *value)));
+        }
+
+        assert!(protocol
+            .evaluate_streaming(&prepared, &inputs, &["nope"], |_, _| Ok(()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_parallel_layers_match_sequential() {
+        use crate::hooks::GateEvent;
+        use std::sync::Arc;
+
+        #[derive(Default)]
+        struct Order(Mutex<Vec<usize>>);
+        impl GateHook for Order {
+            fn before_gate(&self, event: &GateEvent<'_>) -> Result<()> {
+                if let GateEvent::Gate { step, .. } = event {
+                    self.0.lock().unwrap().push(*step);
+                }
+                Ok(())
+            }
+        }
+
+        let circuit = reference::adder_comparator(2);
+        let prepared = PreparedCircuit::new(&circuit);
+        let order = Arc::new(Order::default());
+        let parallel = GmwProtocol::new(3)
+            .unwrap()
+            .with_threads(4)
+            .with_hook(order.clone());
+        for value in 0..16u8 {
+            let inputs: Vec<bool> = (0..4).map(|bit| value >> bit & 1 == 1).collect();
+            let report = parallel.run_prepared(&prepared, &inputs).unwrap();
+            for (name, output) in &report.outputs {
+                let id = circuit.metadata.outputs.iter().find(|o| &o.name == name);
+                let expected = LocalEvaluator::get_output(&circuit, &inputs, id.unwrap().id);
+                assert_eq!(*output, expected.unwrap(), "{name} {inputs:?}");
+            }
+        }
+
+        // Hooks still see the gates in plan order
+        let steps = order.0.lock().unwrap();
+        let per_run = steps.len() / 16;
+        assert!(steps[..per_run].windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_parallel_runs_hold_interactive_gates_only() {
+        let circuit: Circuit = serde_json::from_value(serde_json::json!({
+            "name": "wide_layer",
+            "description": "",
+            "gates": [
+                {"id": 5, "type": "AND", "in": [1, 2]},
+                {"id": 6, "type": "OR", "in": [3, 4]},
+                {"id": 7, "type": "AND", "in": [1, 3]},
+                {"id": 8, "type": "AND", "in": [2, 4]}
+            ],
+            "metadata": {
+                "inputs": [
+                    {"name": "a", "id": 1}, {"name": "b", "id": 2},
+                    {"name": "c", "id": 3}, {"name": "d", "id": 4}
+                ],
+                "outputs": [
+                    {"name": "ab", "id": 5}, {"name": "cd", "id": 6},
+                    {"name": "ac", "id": 7}, {"name": "bd", "id": 8}
+                ]
+            }
+        }))
+        .unwrap();
+        let mut prepared = PreparedCircuit::new(&circuit);
+        // A local gate in the middle of a layer's AND/OR steps splits the run
+        let layers = prepared.step_layers().to_vec();
+        let steps = &prepared.plan.steps;
+        let step = (1..steps.len() - 1)
+            .find(|&i| {
+                (i - 1..=i + 1)
+                    .all(|j| layers[j] == layers[i] && matches!(steps[j], EvaluationStep::Gate(_)))
+            })
+            .unwrap();
+        if let EvaluationStep::Gate(gate) = &mut prepared.plan.steps[step] {
+            gate.gate_type = GateType::XOR;
+        }
+
+        let sequential = GmwProtocol::new(3).unwrap().with_threads(1);
+        let parallel = GmwProtocol::new(3).unwrap().with_threads(4);
+        for value in 0..16u8 {
+            let inputs: Vec<bool> = (0..4).map(|bit| value >> bit & 1 == 1).collect();
+            let expected = sequential.run_prepared(&prepared, &inputs).unwrap();
+            let report = parallel.run_prepared(&prepared, &inputs).unwrap();
+            assert_eq!(report.outputs, expected.outputs, "{inputs:?}");
+        }
+
+        // The pool is built once and kept across runs
+        let pool = parallel.pool().unwrap() as *const rayon::ThreadPool;
+        parallel.run_prepared(&prepared, &[false; 4]).unwrap();
+        assert_eq!(parallel.pool().unwrap() as *const _, pool);
+        assert_eq!(parallel.pool().unwrap().current_num_threads(), 4);
+    }
+
+    #[test]
+    fn test_differential_check_finds_first_corrupted_gate() {
+        let circuit = reference::full_adder();
+        let mut prepared = PreparedCircuit::new(&circuit);
+        // Stand in for a protocol bug: evaluate the AND of a and b as an OR
+        let step = prepared
+            .plan
+            .steps
+            .iter()
+            .position(|step| matches!(step, EvaluationStep::Gate(gate) if gate.gate_type == GateType::AND))
+            .unwrap();
+        if let EvaluationStep::Gate(gate) = &mut prepared.plan.steps[step] {
+            gate.gate_type = GateType::OR;
+        }
+
+        let checked = GmwProtocol::new(3).unwrap().with_differential_check(true);
+        // Inputs where AND and OR agree do not expose the bug
+        assert!(checked
+            .run_prepared(&prepared, &[true, true, false])
+            .is_ok());
+        let error = checked
+            .run_prepared(&prepared, &[true, false, false])
+            .unwrap_err();
+        let mismatch = error.downcast_ref::<ShareMismatch>().unwrap();
+        assert_eq!((mismatch.step, mismatch.wire), (step, Some(5)));
+        assert!(!mismatch.expected);
+
+        let unchecked = GmwProtocol::new(3).unwrap().with_differential_check(false);
+        let report = unchecked
+            .run_prepared(&prepared, &[true, false, false])
+            .unwrap();
+        assert_eq!(report.decode::<u8>("total").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_failure_policy_poisons_dependent_outputs() {
+        // Wire 9 is never written, so the AND and NOT reading it fail
+        let circuit: Circuit = serde_json::from_value(serde_json::json!({
+            "name": "half_broken",
+            "description": "",
+            "gates": [
+                {"id": 3, "type": "AND", "in": [1, 2]},
+                {"id": 4, "type": "AND", "in": [1, 9]},
+                {"id": 5, "type": "XOR", "in": [4, 2]},
+                {"id": 6, "type": "NOT", "in": [9]}
+            ],
+            "metadata": {
+                "inputs": [{"name": "a", "id": 1}, {"name": "b", "id": 2}],
+                "outputs": [
+                    {"name": "ok", "id": 3},
+                    {"name": "after_and", "id": 5},
+                    {"name": "after_not", "id": 6}
+                ]
+            }
+        }))
+        .unwrap();
+
+        for threads in [1, 4] {
+            let protocol = GmwProtocol::new(3).unwrap().with_threads(threads);
+            assert!(protocol.run_with_report(&circuit, &[true, true]).is_err());
+
+            let protocol = protocol.with_failure_policy(FailurePolicy::Poison);
+            let report = protocol.run_with_report(&circuit, &[true, true]).unwrap();
+            assert_eq!(report.outputs, vec![("ok".to_string(), true)]);
+            let poisoned: Vec<&str> = report
+                .poisoned
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            assert_eq!(poisoned, ["after_and", "after_not"]);
+            assert!(report.poisoned[0].1.starts_with("Gate at wire 4"));
+            assert!(report.poisoned[1].1.contains("wire 9 has no value"));
+        }
+    }
+
+    #[test]
+    fn test_report_counts_ot_per_peer() {
+        let circuit = reference::adder_comparator(2);
+        let inputs = [true, false, true, true];
+        let totals: Vec<usize> = [1, 4]
+            .into_iter()
+            .map(|threads| {
+                let protocol = GmwProtocol::new(3).unwrap().with_threads(threads);
+                let report = protocol.run_with_report(&circuit, &inputs).unwrap();
+                // Every ordered pair of distinct parties runs cross terms
+                let pairs: Vec<(usize, usize)> = report
+                    .ot
+                    .iter()
+                    .map(|peer| (peer.sender, peer.receiver))
+                    .collect();
+                assert_eq!(pairs, [(0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1)]);
+                assert!(report.ot.iter().all(|peer| peer.stats.failures == 0));
+                report.ot.iter().map(|peer| peer.stats.transfers).sum()
+            })
+            .collect();
+        assert!(totals[0] > 0);
+        assert_eq!(totals[0], totals[1]);
+    }
+
+    #[test]
+    fn test_bmr_backend_matches_plaintext() {
+        let mut circuits: Vec<Circuit> = ["full_adder", "mux_2to1", "complex_circuit", "not"]
+            .iter()
+            .map(|name| Circuit::from_file(&format!("circuits/{name}.json")).unwrap())
+            .collect();
+        circuits.push(reference::adder_comparator(2));
+        for parties in [2, 3] {
+            let protocol = GmwProtocol::new(parties)
+                .unwrap()
+                .with_backend(Backend::Bmr)
+                .with_differential_check(true);
+            for circuit in &circuits {
+                let width = circuit.metadata.inputs.len();
+                for value in 0..1u32 << width {
+                    let inputs: Vec<bool> = (0..width).map(|bit| value >> bit & 1 == 1).collect();
+                    let report = protocol.run_with_report(circuit, &inputs).unwrap();
+                    // Garbling takes the same rounds whatever the depth
+                    assert_eq!(report.interactive_rounds, bmr::GARBLING_ROUNDS);
+                    for (name, output) in &report.outputs {
+                        let info = circuit.metadata.outputs.iter().find(|o| &o.name == name);
+                        let expected =
+                            LocalEvaluator::get_output(circuit, &inputs, info.unwrap().id);
+                        assert_eq!(
+                            *output,
+                            expected.unwrap(),
+                            "{} {name} {inputs:?}",
+                            circuit.name
+                        );
+                    }
+                }
+            }
+        }
+
+        let hooked = GmwProtocol::new(2)
+            .unwrap()
+            .with_backend(Backend::Bmr)
+            .with_budget(ResourceBudget::default());
+        assert!(hooked
+            .run_circuit(&circuits[0], &[true, false, true])
+            .is_err());
+    }
+
+    #[test]
+    fn test_poisoned_gates_pair_their_hooks() {
+        use crate::hooks::GateEvent;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        #[derive(Default)]
+        struct Calls {
+            before: AtomicUsize,
+            after: AtomicUsize,
+        }
+        impl GateHook for Calls {
+            fn before_gate(&self, _event: &GateEvent<'_>) -> Result<()> {
+                self.before.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            fn after_gate(&self, _event: &GateEvent<'_>, _elapsed: Duration) -> Result<()> {
+                self.after.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+        }
+
+        // Gate 4 fails next to gate 3, and gate 7 reads its poisoned output
+        // next to gate 8
+        let circuit: Circuit = serde_json::from_value(serde_json::json!({
+            "name": "failing_layer",
+            "description": "",
+            "gates": [
+                {"id": 3, "type": "AND", "in": [1, 2]},
+                {"id": 4, "type": "AND", "in": [1, 9]},
+                {"id": 7, "type": "AND", "in": [4, 2]},
+                {"id": 8, "type": "AND", "in": [3, 1]}
+            ],
+            "metadata": {
+                "inputs": [{"name": "a", "id": 1}, {"name": "b", "id": 2}],
+                "outputs": [{"name": "broken", "id": 7}, {"name": "ok", "id": 8}]
+            }
+        }))
+        .unwrap();
+
+        for threads in [1, 4] {
+            let calls = Arc::new(Calls::default());
+            let protocol = GmwProtocol::new(3)
+                .unwrap()
+                .with_threads(threads)
+                .with_failure_policy(FailurePolicy::Poison)
+                .with_hook(calls.clone());
+            let report = protocol.run_with_report(&circuit, &[true, true]).unwrap();
+            assert_eq!(report.outputs, vec![("ok".to_string(), true)]);
+
+            // Gates 3, 4 and 8 are evaluated; gate 7 is poisoned unseen
+            assert_eq!(calls.before.load(Ordering::Relaxed), 3, "threads {threads}");
+            assert_eq!(calls.after.load(Ordering::Relaxed), 3, "threads {threads}");
+        }
+    }
+
+    #[test]
+    fn test_execute_many() {
+        use crate::circuit::LocalEvaluator;
+
+        let protocol = GmwProtocol::new(3).unwrap();
+        let files = ["and", "xor", "half_adder", "full_adder"];
+        let circuits: Vec<Circuit> = files
+            .iter()
+            .map(|name| Circuit::from_file(&format!("circuits/{name}.json")).unwrap())
+            .collect();
+        let prepared: Vec<PreparedCircuit> = circuits.iter().map(PreparedCircuit::new).collect();
+        let inputs: Vec<Vec<bool>> = circuits
+            .iter()
+            .map(|circuit| vec![true; circuit.metadata.inputs.len()])
+            .collect();
+
+        let reports = protocol.execute_many(&prepared, &inputs).unwrap();
+        for ((circuit, inputs), report) in circuits.iter().zip(&inputs).zip(&reports) {
+            for (name, value) in &report.outputs {
+                let info = circuit.metadata.outputs.iter().find(|o| &o.name == name);
+                let expected = LocalEvaluator::get_output(circuit, inputs, info.unwrap().id);
+                assert_eq!(*value, expected.unwrap(), "{} {name}", circuit.name);
+            }
+        }
+
+        assert!(protocol.execute_many(&prepared, &inputs[1..]).is_err());
+    }
 }
@@ -0,0 +1,255 @@
+//! Turnkey sub-protocols built on 2-party GMW
+
+use anyhow::Result;
+
+use crate::circuit::{Bus, BusEncoding, Circuit, CircuitBuilder, PreparedCircuit, Wire};
+use crate::production::{local_simulation_refused, LocalSimulationRefused};
+use crate::protocol::{GmwProtocol, OutputShares};
+
+/// Largest map `oblivious_map_lookup` accepts; the circuit grows linearly
+pub const MAX_MAP_ENTRIES: usize = 1024;
+
+/// Circuit testing two `len`-byte strings for equality
+/// Inputs are party A's bits (`a0`, `a1`, ...) then party B's (`b0`, ...),
+/// least significant bit of the first byte first. Equal bits are combined
+/// with a balanced AND tree, so the AND-depth is ceil(log2(8 * len))
+pub fn equality_circuit(len: usize) -> Result<Circuit> {
+    CircuitBuilder::build(&format!("equality_{len}"), |builder| {
+        builder.description(&format!("Equality of two {len}-byte strings"));
+        let a = builder.input_bus("a", 8 * len);
+        let b = builder.input_bus("b", 8 * len);
+
+        if len > 0 {
+            let equal = equal_buses(builder, &a, &b);
+            builder.output("equal", equal);
+        }
+        Ok(())
+    })
+}
+
+/// Simulate a private equality test of two byte strings
+/// Both parties run in this process, so whoever calls it sees both strings;
+/// it shows what `equality_circuit` computes and costs, and production
+/// builds refuse it. String lengths are public: strings of different
+/// lengths are unequal without running the protocol
+pub fn simulate_private_equality(a_bytes: &[u8], b_bytes: &[u8]) -> Result<bool> {
+    if local_simulation_refused() {
+        return Err(LocalSimulationRefused::new("protocols::simulate_private_equality").into());
+    }
+    if a_bytes.len() != b_bytes.len() {
+        return Ok(false);
+    }
+    if a_bytes.is_empty() {
+        return Ok(true);
+    }
+
+    let bits = |bytes: &[u8]| -> Vec<bool> {
+        bytes
+            .iter()
+            .flat_map(|&byte| (0..8).map(move |bit| byte >> bit & 1 == 1))
+            .collect()
+    };
+    let inputs = [bits(a_bytes), bits(b_bytes)].concat();
+
+    let report =
+        GmwProtocol::new(2)?.run_with_report(&equality_circuit(a_bytes.len())?, &inputs)?;
+    report
+        .output("equal")
+        .ok_or_else(|| anyhow::anyhow!("Equality circuit has no output"))
+}
+
+/// Circuit looking up party B's key in party A's `entries`-entry map
+/// Party A provides keys `k{i}_{bit}` and values `v{i}_{bit}` entry by entry,
+/// then party B the query `q{bit}`, least significant bit first. Each entry's
+/// key is compared with the query, and the matching value is selected by
+/// masking every value with its match bit and XORing the results, so keys
+/// must be distinct. Outputs are the `value` bus and a `found` bit
+pub fn map_lookup_circuit(entries: usize, key_bits: usize, value_bits: usize) -> Result<Circuit> {
+    CircuitBuilder::build(&format!("map_lookup_{entries}"), |builder| {
+        builder.description(&format!(
+            "Lookup of a {key_bits}-bit key in a {entries}-entry map of {value_bits}-bit values"
+        ));
+        builder.parties(2);
+        let map: Vec<(Bus, Bus)> = (0..entries)
+            .map(|i| {
+                let key = builder.party_input_bus(0, &format!("k{i}_"), key_bits);
+                let value = builder.party_input_bus(0, &format!("v{i}_"), value_bits);
+                (key, value)
+            })
+            .collect();
+        let query = builder.party_input_bus(1, "q", key_bits);
+
+        let mut found: Option<Wire> = None;
+        let mut selected: Option<Vec<Wire>> = None;
+        for (key, value) in &map {
+            let matched = equal_buses(builder, key, &query);
+            let masked: Vec<Wire> = value
+                .bits()
+                .iter()
+                .map(|&bit| builder.and(matched, bit))
+                .collect();
+            found = Some(match found {
+                Some(found) => builder.xor(found, matched),
+                None => matched,
+            });
+            selected = Some(match selected {
+                Some(selected) => selected
+                    .iter()
+                    .zip(masked)
+                    .map(|(&acc, bit)| builder.xor(acc, bit))
+                    .collect(),
+                None => masked,
+            });
+        }
+
+        if let (Some(found), Some(selected)) = (found, selected) {
+            builder.output_bus("value", &Bus::new(selected), BusEncoding::Unsigned);
+            builder.output("found", found);
+        }
+        Ok(())
+    })
+}
+
+/// Bitwise XNOR of two non-empty buses combined with a balanced AND tree
+fn equal_buses<'id>(builder: &mut CircuitBuilder<'id>, a: &Bus<'id>, b: &Bus<'id>) -> Wire<'id> {
+    // Bit i is equal when ~(a_i ^ b_i)
+    let mut level: Vec<_> = a
+        .bits()
+        .iter()
+        .zip(b.bits())
+        .map(|(&x, &y)| {
+            let differ = builder.xor(x, y);
+            builder.not(differ)
+        })
+        .collect();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match *pair {
+                [x, y] => builder.and(x, y),
+                _ => pair[0],
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Look up party B's private `key` in party A's private map, returning the
+/// matched value as shares instead of opening it
+/// The shares hold the `value` bus (zero when the key is absent) and the
+/// `found` bit; `reconstruct()` opens them. The number of entries and the
+/// widths are public; keys must be distinct and every key and value must fit
+/// its width
+/// Both parties run in this process, so the caller holds the map and the
+/// key; it is a simulation helper and production builds refuse it
+pub fn oblivious_map_lookup(
+    map: &[(u64, u64)],
+    key: u64,
+    key_bits: usize,
+    value_bits: usize,
+) -> Result<OutputShares> {
+    if local_simulation_refused() {
+        return Err(LocalSimulationRefused::new("protocols::oblivious_map_lookup").into());
+    }
+    if map.is_empty() || map.len() > MAX_MAP_ENTRIES {
+        return Err(anyhow::anyhow!(
+            "Maps hold 1 to {} entries, got {}",
+            MAX_MAP_ENTRIES,
+            map.len()
+        ));
+    }
+    for (what, width) in [("Key", key_bits), ("Value", value_bits)] {
+        if width == 0 || width > 64 {
+            return Err(anyhow::anyhow!(
+                "{} width must be 1 to 64 bits, got {}",
+                what,
+                width
+            ));
+        }
+    }
+    let fits = |value: u64, width: usize| width == 64 || value >> width == 0;
+    for (i, &(entry_key, value)) in map.iter().enumerate() {
+        if !fits(entry_key, key_bits) || !fits(value, value_bits) {
+            return Err(anyhow::anyhow!("Entry {} does not fit the map widths", i));
+        }
+        if map[..i].iter().any(|&(other, _)| other == entry_key) {
+            return Err(anyhow::anyhow!("Duplicate map key {}", entry_key));
+        }
+    }
+    if !fits(key, key_bits) {
+        return Err(anyhow::anyhow!(
+            "Key {} does not fit in {} bits",
+            key,
+            key_bits
+        ));
+    }
+
+    let bits = |value: u64, width: usize| (0..width).map(move |bit| value >> bit & 1 == 1);
+    let mut inputs: Vec<bool> = map
+        .iter()
+        .flat_map(|&(entry_key, value)| bits(entry_key, key_bits).chain(bits(value, value_bits)))
+        .collect();
+    inputs.extend(bits(key, key_bits));
+
+    let circuit = map_lookup_circuit(map.len(), key_bits, value_bits)?;
+    GmwProtocol::for_circuit(&circuit, None)?
+        .evaluate_prepared(&PreparedCircuit::new(&circuit), &inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::Layering;
+
+    #[test]
+    fn test_simulate_private_equality() {
+        assert!(simulate_private_equality(b"secret", b"secret").unwrap());
+        assert!(!simulate_private_equality(b"secret", b"secreT").unwrap());
+        assert!(!simulate_private_equality(b"abc", b"abcd").unwrap());
+        assert!(simulate_private_equality(b"", b"").unwrap());
+        assert!(!simulate_private_equality(&[0x01], &[0x00]).unwrap());
+    }
+
+    #[test]
+    fn test_oblivious_map_lookup() {
+        let map = [(3, 30), (7, 70), (12, 120), (0, 5)];
+        for (key, expected, found) in [(7, 70, true), (0, 5, true), (9, 0, false)] {
+            let shares = oblivious_map_lookup(&map, key, 4, 8).unwrap();
+            // Shares are XOR shares of two parties; neither alone is the value
+            assert!(shares.shares.iter().all(|bit| bit.len() == 2));
+            let report = shares.reconstruct();
+            assert_eq!(
+                report.decode::<u64>("value").unwrap(),
+                expected,
+                "key {key}"
+            );
+            assert_eq!(report.output("found"), Some(found), "key {key}");
+        }
+
+        assert!(oblivious_map_lookup(&[], 1, 4, 8).is_err());
+        assert!(oblivious_map_lookup(&[(3, 1), (3, 2)], 3, 4, 8).is_err());
+        assert!(oblivious_map_lookup(&[(16, 1)], 3, 4, 8).is_err());
+        assert!(oblivious_map_lookup(&[(1, 256)], 1, 4, 8).is_err());
+        assert!(oblivious_map_lookup(&[(1, 1)], 16, 4, 8).is_err());
+        assert!(oblivious_map_lookup(&[(1, 1)], 1, 65, 8).is_err());
+        let too_big: Vec<(u64, u64)> = (0..=MAX_MAP_ENTRIES as u64).map(|i| (i, 0)).collect();
+        assert!(oblivious_map_lookup(&too_big, 1, 16, 1).is_err());
+    }
+
+    #[test]
+    fn test_map_lookup_circuit_parties() {
+        let circuit = map_lookup_circuit(2, 3, 2).unwrap();
+        let parties = circuit.metadata.parties.unwrap();
+        assert_eq!(parties.count, 2);
+        assert_eq!(parties.inputs_of(0).len(), 2 * (3 + 2));
+        assert_eq!(parties.inputs_of(1), ["q0", "q1", "q2"]);
+    }
+
+    #[test]
+    fn test_equality_circuit_depth() {
+        for (len, depth) in [(1, 3), (2, 4), (3, 5)] {
+            let circuit = equality_circuit(len).unwrap();
+            assert_eq!(Layering::new(&circuit).unwrap().depth, depth, "{len} bytes");
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::circuit::Circuit;
+use crate::protocol::GmwProtocol;
+use crate::report::ExecutionReport;
+
+/// Version written into every receipt; bumped on incompatible format changes
+pub const RECEIPT_FORMAT_VERSION: u32 = 1;
+
+/// Collision-resistant hash for circuit digests and input commitments
+/// `Sha256`, `Blake3` and the name-selectable `HashAlgorithm` are built in;
+/// plug in another function from your stack if needed
+pub trait ReceiptHash: Send + Sync {
+    fn hash(&self, data: &[u8]) -> Vec<u8>;
+}
+
+/// One party's signing key
+pub trait ReceiptSigner: Send + Sync {
+    /// Identifier verifiers use to look up the matching public key
+    fn key_id(&self) -> String;
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Public-key lookup and signature check for archived receipts
+pub trait ReceiptVerifier {
+    fn verify(&self, key_id: &str, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// Hash function and per-party signing keys used to issue receipts
+pub struct ReceiptConfig {
+    hash: Box<dyn ReceiptHash>,
+    signers: Vec<Box<dyn ReceiptSigner>>,
+}
+
+impl ReceiptConfig {
+    pub fn new(hash: impl ReceiptHash + 'static) -> Self {
+        Self {
+            hash: Box::new(hash),
+            signers: Vec::new(),
+        }
+    }
+
+    /// Add the signing key of the next party (party 0 first)
+    pub fn with_signer(mut self, signer: impl ReceiptSigner + 'static) -> Self {
+        self.signers.push(Box::new(signer));
+        self
+    }
+}
+
+/// Commitment to one circuit input: hash(session, name, nonce, value)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputCommitment {
+    pub name: String,
+    /// Hex-encoded hash
+    pub commitment: String,
+}
+
+/// Opening of an input commitment, kept by whoever supplied the input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputOpening {
+    pub name: String,
+    pub value: bool,
+    /// Hex-encoded random nonce
+    pub nonce: String,
+}
+
+/// Signed record of one party's view of a run, for archiving as evidence
+/// Serialized as JSON. The signature covers every other field. The
+/// in-process simulator does not assign inputs to parties, so every receipt
+/// of a run carries the same commitments to all inputs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionReceipt {
+    pub version: u32,
+    /// Caller-chosen identifier of the run
+    pub session: u64,
+    pub party: usize,
+    pub party_count: usize,
+    pub circuit: String,
+    /// Hex-encoded hash of the circuit's JSON encoding
+    pub circuit_hash: String,
+    pub input_commitments: Vec<InputCommitment>,
+    pub outputs: Vec<(String, bool)>,
+    /// Milliseconds since the Unix epoch
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub key_id: String,
+    /// Hex-encoded signature
+    pub signature: String,
+}
+
+impl ExecutionReceipt {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let receipt: Self = serde_json::from_str(json)?;
+        if receipt.version != RECEIPT_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported receipt version {}",
+                receipt.version
+            ));
+        }
+        Ok(receipt)
+    }
+
+    /// Bytes covered by the signature: the receipt's JSON with an empty signature
+    pub fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let unsigned = Self {
+            signature: String::new(),
+            ..self.clone()
+        };
+        Ok(serde_json::to_vec(&unsigned)?)
+    }
+
+    /// Whether the signature is valid for the receipt's key
+    pub fn verify(&self, verifier: &dyn ReceiptVerifier) -> bool {
+        match (self.signed_bytes(), decode_hex(&self.signature)) {
+            (Ok(message), Some(signature)) => verifier.verify(&self.key_id, &message, &signature),
+            _ => false,
+        }
+    }
+
+    /// Whether the receipt was issued for this circuit
+    pub fn matches_circuit(&self, hash: &dyn ReceiptHash, circuit: &Circuit) -> bool {
+        circuit_hash(hash, circuit).is_ok_and(|digest| digest == self.circuit_hash)
+    }
+
+    /// Whether an opening matches the receipt's commitment to that input
+    pub fn opens(&self, hash: &dyn ReceiptHash, opening: &InputOpening) -> bool {
+        let Some(nonce) = decode_hex(&opening.nonce) else {
+            return false;
+        };
+        let expected = commit(hash, self.session, &opening.name, &nonce, opening.value);
+        self.input_commitments
+            .iter()
+            .any(|input| input.name == opening.name && input.commitment == expected)
+    }
+}
+
+impl GmwProtocol {
+    /// Run a circuit and issue one signed receipt per party
+    /// Returns the report, the receipts in party order and the input openings,
+    /// which stay with the caller until the commitments need to be opened
+    pub fn run_with_receipts(
+        &self,
+        circuit: &Circuit,
+        inputs: &[bool],
+        session: u64,
+        config: &ReceiptConfig,
+    ) -> Result<(ExecutionReport, Vec<ExecutionReceipt>, Vec<InputOpening>)> {
+        if config.signers.len() != self.party_count() {
+            return Err(anyhow::anyhow!(
+                "Expected {} receipt signers, got {}",
+                self.party_count(),
+                config.signers.len()
+            ));
+        }
+
+        let started_at = unix_millis();
+        let report = self.run_with_report(circuit, inputs)?;
+        let finished_at = unix_millis();
+
+        let hash = config.hash.as_ref();
+        self.security().check_hash(hash)?;
+        let (openings, input_commitments): (Vec<_>, Vec<_>) = circuit
+            .metadata
+            .inputs
+            .iter()
+            .zip(inputs)
+            .map(|(info, &value)| {
+                let nonce = self.security().nonce();
+                let commitment = InputCommitment {
+                    name: info.name.clone(),
+                    commitment: commit(hash, session, &info.name, &nonce, value),
+                };
+                let opening = InputOpening {
+                    name: info.name.clone(),
+                    value,
+                    nonce: encode_hex(&nonce),
+                };
+                (opening, commitment)
+            })
+            .unzip();
+        let circuit_hash = circuit_hash(hash, circuit)?;
+
+        let receipts = config
+            .signers
+            .iter()
+            .enumerate()
+            .map(|(party, signer)| {
+                let mut receipt = ExecutionReceipt {
+                    version: RECEIPT_FORMAT_VERSION,
+                    session,
+                    party,
+                    party_count: self.party_count(),
+                    circuit: circuit.name.clone(),
+                    circuit_hash: circuit_hash.clone(),
+                    input_commitments: input_commitments.clone(),
+                    outputs: report.outputs.clone(),
+                    started_at,
+                    finished_at,
+                    key_id: signer.key_id(),
+                    signature: String::new(),
+                };
+                receipt.signature = encode_hex(&signer.sign(&receipt.signed_bytes()?)?);
+                Ok(receipt)
+            })
+            .collect::<Result<_>>()?;
+
+        Ok((report, receipts, openings))
+    }
+}
+
+fn circuit_hash(hash: &dyn ReceiptHash, circuit: &Circuit) -> Result<String> {
+    Ok(encode_hex(&hash.hash(&serde_json::to_vec(circuit)?)))
+}
+
+fn commit(hash: &dyn ReceiptHash, session: u64, name: &str, nonce: &[u8], value: bool) -> String {
+    let mut data = session.to_le_bytes().to_vec();
+    data.extend_from_slice(&(name.len() as u64).to_le_bytes());
+    data.extend_from_slice(name.as_bytes());
+    data.extend_from_slice(nonce);
+    data.push(u8::from(value));
+    encode_hex(&hash.hash(&data))
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub(crate) fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::sha256::Sha256;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    // Insecure stand-ins: SipHash as the hash, and a keyed hash as a
+    // "signature" the verifier recomputes from a shared table of keys.
+    // Commitments need a 256-bit hash, so receipts use SHA-256
+    struct TestHash;
+
+    impl ReceiptHash for TestHash {
+        fn hash(&self, data: &[u8]) -> Vec<u8> {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(data);
+            hasher.finish().to_le_bytes().to_vec()
+        }
+    }
+
+    struct TestKey(u64);
+
+    impl ReceiptSigner for TestKey {
+        fn key_id(&self) -> String {
+            format!("party-key-{}", self.0)
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+            Ok(TestHash.hash(&[&self.0.to_le_bytes(), message].concat()))
+        }
+    }
+
+    impl ReceiptVerifier for Vec<TestKey> {
+        fn verify(&self, key_id: &str, message: &[u8], signature: &[u8]) -> bool {
+            self.iter()
+                .find(|key| key.key_id() == key_id)
+                .is_some_and(|key| key.sign(message).unwrap() == signature)
+        }
+    }
+
+    #[test]
+    fn test_receipts_verify_and_open() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(3).unwrap();
+        let config = (0..3).fold(ReceiptConfig::new(Sha256), |config, key| {
+            config.with_signer(TestKey(key))
+        });
+        let keys = vec![TestKey(0), TestKey(1), TestKey(2)];
+
+        let (report, receipts, openings) = protocol
+            .run_with_receipts(&circuit, &[true, false, true], 42, &config)
+            .unwrap();
+        assert_eq!(receipts.len(), 3);
+
+        for receipt in &receipts {
+            let archived = ExecutionReceipt::from_json(&receipt.to_json().unwrap()).unwrap();
+            assert!(archived.verify(&keys));
+            assert!(archived.matches_circuit(&Sha256, &circuit));
+            assert_eq!(archived.outputs, report.outputs);
+            assert!(openings
+                .iter()
+                .all(|opening| archived.opens(&Sha256, opening)));
+        }
+
+        // Tampering with any field or opening breaks verification
+        let mut forged = receipts[0].clone();
+        forged.outputs[0].1 ^= true;
+        assert!(!forged.verify(&keys));
+        let lie = InputOpening {
+            value: !openings[0].value,
+            ..openings[0].clone()
+        };
+        assert!(!receipts[0].opens(&Sha256, &lie));
+
+        assert!(protocol
+            .run_with_receipts(&circuit, &[true; 3], 1, &ReceiptConfig::new(Sha256))
+            .is_err());
+        // A 64-bit hash cannot bind 128-bit commitments
+        let weak = (0..3).fold(ReceiptConfig::new(TestHash), |config, key| {
+            config.with_signer(TestKey(key))
+        });
+        assert!(protocol
+            .run_with_receipts(&circuit, &[true; 3], 1, &weak)
+            .is_err());
+    }
+}
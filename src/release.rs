@@ -0,0 +1,569 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::domain::ShareDomain;
+use crate::kernel::algebra::reconstruct;
+use crate::protocol::OutputShares;
+use crate::report::ExecutionReport;
+use crate::store::SecretStore;
+
+/// Bit-by-bit output release with per-party acknowledgements
+/// Each output bit is opened only after every party has acknowledged the
+/// previous one, so no party learns the full output long before the others
+#[derive(Debug, Clone)]
+pub struct GradualRelease {
+    shares: OutputShares,
+    revealed: Vec<bool>,
+    acks: Vec<bool>,
+    timeout: Duration,
+    opened_at: Instant,
+}
+
+/// Outcome of trying to open the next output bit
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReleaseStep {
+    /// A bit was opened to all parties
+    Opened { name: String, value: bool },
+    /// The previous bit is still waiting for acknowledgements from these parties
+    Waiting { parties: Vec<usize> },
+    /// Every bit has been opened and acknowledged
+    Complete,
+}
+
+/// Resumable progress of a gradual release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseCheckpoint {
+    /// Evaluation whose outputs are being released
+    pub domain: ShareDomain,
+    /// Values of the bits opened so far
+    pub revealed: Vec<bool>,
+    /// Which parties acknowledged the last opened bit
+    pub acks: Vec<bool>,
+}
+
+impl GradualRelease {
+    /// Start releasing outputs; a party that does not acknowledge an opened
+    /// bit within `timeout` aborts the release
+    pub fn new(shares: OutputShares, timeout: Duration) -> Self {
+        let party_count = shares.shares.first().map_or(0, Vec::len);
+
+        Self {
+            shares,
+            revealed: Vec::new(),
+            acks: vec![true; party_count],
+            timeout,
+            opened_at: Instant::now(),
+        }
+    }
+
+    /// Resume an interrupted release from a checkpoint
+    /// A checkpoint of another evaluation fails with `DomainMismatch`
+    pub fn resume(
+        shares: OutputShares,
+        checkpoint: ReleaseCheckpoint,
+        timeout: Duration,
+    ) -> Result<Self> {
+        shares.domain.check(&checkpoint.domain)?;
+        let mut release = Self::new(shares, timeout);
+
+        if checkpoint.revealed.len() > release.shares.names.len() {
+            return Err(anyhow::anyhow!(
+                "Checkpoint has {} revealed bits but there are only {} outputs",
+                checkpoint.revealed.len(),
+                release.shares.names.len()
+            ));
+        }
+        if checkpoint.acks.len() != release.acks.len() {
+            return Err(anyhow::anyhow!(
+                "Checkpoint has {} parties but the outputs have {}",
+                checkpoint.acks.len(),
+                release.acks.len()
+            ));
+        }
+        for (i, &value) in checkpoint.revealed.iter().enumerate() {
+            if reconstruct(&release.shares.shares[i]) != value {
+                return Err(anyhow::anyhow!(
+                    "Checkpoint does not match output {}",
+                    release.shares.names[i]
+                ));
+            }
+        }
+
+        release.revealed = checkpoint.revealed;
+        release.acks = checkpoint.acks;
+        Ok(release)
+    }
+
+    /// Save progress so the release can be resumed later
+    pub fn checkpoint(&self) -> ReleaseCheckpoint {
+        ReleaseCheckpoint {
+            domain: self.shares.domain.clone(),
+            revealed: self.revealed.clone(),
+            acks: self.acks.clone(),
+        }
+    }
+
+    /// Open the next output bit if every party acknowledged the previous one
+    pub fn open_next(&mut self) -> Result<ReleaseStep> {
+        let waiting: Vec<usize> = (0..self.acks.len()).filter(|&p| !self.acks[p]).collect();
+
+        if !waiting.is_empty() {
+            if self.opened_at.elapsed() > self.timeout {
+                return Err(anyhow::anyhow!(
+                    "Timed out waiting for acknowledgement of output {} from parties {:?}",
+                    self.shares.names[self.revealed.len() - 1],
+                    waiting
+                ));
+            }
+            return Ok(ReleaseStep::Waiting { parties: waiting });
+        }
+
+        let index = self.revealed.len();
+        if index == self.shares.names.len() {
+            return Ok(ReleaseStep::Complete);
+        }
+
+        // Every party broadcasts its share of this bit
+        let value = reconstruct(&self.shares.shares[index]);
+        self.revealed.push(value);
+        self.acks.iter_mut().for_each(|ack| *ack = false);
+        self.opened_at = Instant::now();
+
+        Ok(ReleaseStep::Opened {
+            name: self.shares.names[index].clone(),
+            value,
+        })
+    }
+
+    /// Record that a party received the last opened bit
+    pub fn acknowledge(&mut self, party_id: usize) -> Result<()> {
+        let ack = self
+            .acks
+            .get_mut(party_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown party {}", party_id))?;
+        *ack = true;
+        Ok(())
+    }
+
+    /// Whether every bit has been opened and acknowledged
+    pub fn is_complete(&self) -> bool {
+        self.revealed.len() == self.shares.names.len() && self.acks.iter().all(|&ack| ack)
+    }
+
+    /// Report of all outputs once the release is complete
+    pub fn report(&self) -> Option<ExecutionReport> {
+        self.is_complete().then(|| ExecutionReport {
+            outputs: self
+                .shares
+                .names
+                .iter()
+                .cloned()
+                .zip(self.revealed.iter().copied())
+                .collect(),
+            buses: self.shares.buses.clone(),
+            poisoned: self.shares.poisoned.clone(),
+            // One round per gradually released bit
+            rounds: self.shares.rounds + self.revealed.len(),
+            interactive_rounds: self.shares.interactive_rounds,
+            and_depth: self.shares.and_depth,
+            timings: self.shares.timings.clone(),
+            ot: self.shares.ot.clone(),
+        })
+    }
+}
+
+/// Output release where restricted outputs need approval from k of n parties
+/// Unrestricted outputs are reconstructed right away; the parties withhold
+/// their shares of restricted outputs until at least `threshold` distinct
+/// parties have sent an approval
+#[derive(Debug, Clone)]
+pub struct ApprovalRelease {
+    shares: OutputShares,
+    /// Whether each output, in metadata order, is restricted
+    restricted: Vec<bool>,
+    threshold: usize,
+    approvals: Vec<bool>,
+}
+
+impl ApprovalRelease {
+    /// Restrict the named outputs behind a `threshold`-of-n approval
+    pub fn new(shares: OutputShares, restricted: &[&str], threshold: usize) -> Result<Self> {
+        let party_count = shares.shares.first().map_or(0, Vec::len);
+        if threshold == 0 || threshold > party_count {
+            return Err(anyhow::anyhow!(
+                "Approval threshold {} must be between 1 and {} parties",
+                threshold,
+                party_count
+            ));
+        }
+        if let Some(unknown) = restricted
+            .iter()
+            .find(|name| !shares.names.iter().any(|output| output == *name))
+        {
+            return Err(anyhow::anyhow!("Unknown output {}", unknown));
+        }
+
+        Ok(Self {
+            restricted: shares
+                .names
+                .iter()
+                .map(|name| restricted.contains(&name.as_str()))
+                .collect(),
+            shares,
+            threshold,
+            approvals: vec![false; party_count],
+        })
+    }
+
+    /// Record a party's approval; approving twice counts once
+    pub fn approve(&mut self, party_id: usize) -> Result<()> {
+        let approval = self
+            .approvals
+            .get_mut(party_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown party {}", party_id))?;
+        *approval = true;
+        Ok(())
+    }
+
+    /// Number of distinct parties that approved so far
+    pub fn approvals(&self) -> usize {
+        self.approvals.iter().filter(|&&approved| approved).count()
+    }
+
+    /// Whether enough parties approved to open the restricted outputs
+    pub fn is_approved(&self) -> bool {
+        self.approvals() >= self.threshold
+    }
+
+    /// Restricted outputs that are still withheld
+    pub fn withheld(&self) -> Vec<&str> {
+        if self.is_approved() {
+            return Vec::new();
+        }
+        self.shares
+            .names
+            .iter()
+            .zip(&self.restricted)
+            .filter(|(_, &restricted)| restricted)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Reconstruct every output the policy currently allows
+    /// Buses that include a withheld output are left out of the report
+    pub fn report(&self) -> ExecutionReport {
+        let approved = self.is_approved();
+        let outputs: Vec<(String, bool)> = self
+            .shares
+            .names
+            .iter()
+            .zip(&self.shares.shares)
+            .zip(&self.restricted)
+            .filter(|(_, &restricted)| approved || !restricted)
+            .map(|((name, shares), _)| (name.clone(), reconstruct(shares)))
+            .collect();
+        let buses = self
+            .shares
+            .buses
+            .iter()
+            .filter(|bus| {
+                bus.bits
+                    .iter()
+                    .all(|bit| outputs.iter().any(|(name, _)| name == bit))
+            })
+            .cloned()
+            .collect();
+
+        ExecutionReport {
+            outputs,
+            buses,
+            poisoned: self.shares.poisoned.clone(),
+            rounds: self.shares.rounds + 1,
+            interactive_rounds: self.shares.interactive_rounds,
+            and_depth: self.shares.and_depth,
+            timings: self.shares.timings.clone(),
+            ot: self.shares.ot.clone(),
+        }
+    }
+}
+
+/// One party's output shares as deposited with an escrow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EscrowDeposit {
+    domain: ShareDomain,
+    party: usize,
+    shares: Vec<bool>,
+}
+
+/// Output release through an escrow that opens every output at once
+/// Each party seals its output shares under the escrow's key and hands them
+/// over, or leaves them in a drop-box file. The escrow opens nothing until
+/// every party has deposited and then releases the outputs to all parties
+/// together, so no party can learn the result and abort before the others
+/// have given up their shares. The escrow learns the outputs too
+pub struct EscrowRelease {
+    shares: OutputShares,
+    store: SecretStore,
+    escrow: usize,
+    deposits: Vec<Option<Vec<bool>>>,
+}
+
+impl EscrowRelease {
+    /// Release `shares` through the escrow whose key `store` holds as party
+    /// number `escrow`
+    pub fn new(shares: OutputShares, store: SecretStore, escrow: usize) -> Self {
+        let party_count = shares.shares.first().map_or(0, Vec::len);
+        Self {
+            shares,
+            store,
+            escrow,
+            deposits: vec![None; party_count],
+        }
+    }
+
+    /// `party`'s output shares sealed for the escrow, as sent to it or
+    /// written to its drop box
+    pub fn deposit(&self, party: usize) -> Result<Vec<u8>> {
+        if party >= self.deposits.len() {
+            return Err(anyhow::anyhow!("Unknown party {}", party));
+        }
+        let deposit = EscrowDeposit {
+            domain: self.shares.domain.clone(),
+            party,
+            shares: self
+                .shares
+                .shares
+                .iter()
+                .map(|shares| shares[party])
+                .collect(),
+        };
+        self.store.seal(self.escrow, &serde_json::to_vec(&deposit)?)
+    }
+
+    /// Escrow side: check a sealed deposit and hold it until release
+    /// Deposits sealed for another key, from another evaluation, with the
+    /// wrong number of shares or repeating a party are rejected
+    pub fn receive(&mut self, sealed: &[u8]) -> Result<()> {
+        let sealed_for = SecretStore::party(sealed)?;
+        if sealed_for != self.escrow {
+            return Err(anyhow::anyhow!(
+                "Deposit is sealed for party {} instead of the escrow {}",
+                sealed_for,
+                self.escrow
+            ));
+        }
+        let deposit: EscrowDeposit = serde_json::from_slice(&self.store.unseal(sealed)?)?;
+        self.shares.domain.check(&deposit.domain)?;
+        if deposit.shares.len() != self.shares.names.len() {
+            return Err(anyhow::anyhow!(
+                "Deposit of party {} has {} shares but there are {} outputs",
+                deposit.party,
+                deposit.shares.len(),
+                self.shares.names.len()
+            ));
+        }
+        let slot = self
+            .deposits
+            .get_mut(deposit.party)
+            .ok_or_else(|| anyhow::anyhow!("Unknown party {}", deposit.party))?;
+        if slot.is_some() {
+            return Err(anyhow::anyhow!("Party {} deposited twice", deposit.party));
+        }
+        *slot = Some(deposit.shares);
+        Ok(())
+    }
+
+    /// Parties whose deposit has not arrived yet
+    pub fn missing(&self) -> Vec<usize> {
+        self.deposits
+            .iter()
+            .enumerate()
+            .filter(|(_, deposit)| deposit.is_none())
+            .map(|(party, _)| party)
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.deposits.iter().all(Option::is_some)
+    }
+
+    /// Open every output from the deposited shares, once all have arrived
+    pub fn release(&self) -> Result<ExecutionReport> {
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Escrow still waits for deposits from parties {:?}",
+                missing
+            ));
+        }
+        let outputs = self
+            .shares
+            .names
+            .iter()
+            .enumerate()
+            .map(|(output, name)| {
+                let shares: Vec<bool> = self
+                    .deposits
+                    .iter()
+                    .flatten()
+                    .map(|deposit| deposit[output])
+                    .collect();
+                (name.clone(), reconstruct(&shares))
+            })
+            .collect();
+
+        Ok(ExecutionReport {
+            outputs,
+            buses: self.shares.buses.clone(),
+            poisoned: self.shares.poisoned.clone(),
+            // Deposits to the escrow, then its broadcast of the outputs
+            rounds: self.shares.rounds + 2,
+            interactive_rounds: self.shares.interactive_rounds,
+            and_depth: self.shares.and_depth,
+            timings: self.shares.timings.clone(),
+            ot: self.shares.ot.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::PreparedCircuit;
+    use crate::domain::DomainMismatch;
+    use crate::protocol::GmwProtocol;
+
+    fn full_adder_shares(inputs: &[bool]) -> OutputShares {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(3).unwrap();
+        protocol
+            .evaluate_prepared(&PreparedCircuit::new(&circuit), inputs)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_gradual_release_requires_acknowledgements() {
+        let mut release = GradualRelease::new(
+            full_adder_shares(&[true, true, true]),
+            Duration::from_secs(60),
+        );
+
+        assert_eq!(
+            release.open_next().unwrap(),
+            ReleaseStep::Opened {
+                name: "sum".to_string(),
+                value: true
+            }
+        );
+
+        release.acknowledge(0).unwrap();
+        assert_eq!(
+            release.open_next().unwrap(),
+            ReleaseStep::Waiting {
+                parties: vec![1, 2]
+            }
+        );
+
+        release.acknowledge(1).unwrap();
+        release.acknowledge(2).unwrap();
+        assert!(matches!(
+            release.open_next().unwrap(),
+            ReleaseStep::Opened { value: true, .. }
+        ));
+        assert!(release.report().is_none());
+
+        (0..3).for_each(|party| release.acknowledge(party).unwrap());
+        assert_eq!(release.open_next().unwrap(), ReleaseStep::Complete);
+        assert_eq!(release.report().unwrap().decode::<u8>("total").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_gradual_release_timeout_and_resume() {
+        let shares = full_adder_shares(&[true, false, false]);
+        let mut release = GradualRelease::new(shares.clone(), Duration::ZERO);
+
+        release.open_next().unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(release.open_next().is_err());
+
+        // Resume with a fresh timeout from where the release stopped
+        let checkpoint = release.checkpoint();
+        let other = full_adder_shares(&[true, false, false]);
+        let error =
+            GradualRelease::resume(other, checkpoint.clone(), Duration::from_secs(60)).unwrap_err();
+        assert!(error.downcast_ref::<DomainMismatch>().is_some());
+        let mut resumed =
+            GradualRelease::resume(shares, checkpoint, Duration::from_secs(60)).unwrap();
+        (0..3).for_each(|party| resumed.acknowledge(party).unwrap());
+        assert_eq!(
+            resumed.open_next().unwrap(),
+            ReleaseStep::Opened {
+                name: "carry".to_string(),
+                value: false
+            }
+        );
+    }
+
+    #[test]
+    fn test_approval_release_threshold() {
+        let shares = full_adder_shares(&[true, true, false]);
+        assert!(ApprovalRelease::new(shares.clone(), &["carry"], 4).is_err());
+        assert!(ApprovalRelease::new(shares.clone(), &["missing"], 2).is_err());
+
+        let mut release = ApprovalRelease::new(shares, &["carry"], 2).unwrap();
+        let report = release.report();
+        assert_eq!(report.output("sum"), Some(false));
+        assert_eq!(report.output("carry"), None);
+        assert!(report.buses.is_empty());
+        assert_eq!(release.withheld(), vec!["carry"]);
+
+        // A repeated approval from the same party does not count twice
+        release.approve(1).unwrap();
+        release.approve(1).unwrap();
+        assert!(!release.is_approved());
+
+        release.approve(2).unwrap();
+        assert!(release.withheld().is_empty());
+        assert_eq!(release.report().decode::<u8>("total").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_escrow_release_waits_for_every_deposit() {
+        use crate::store::KeyFile;
+
+        let dir = std::env::temp_dir().join(format!("gmw-escrow-{}", std::process::id()));
+        let shares = full_adder_shares(&[true, true, true]);
+        let mut escrow =
+            EscrowRelease::new(shares.clone(), SecretStore::new(KeyFile::new(&dir)), 3);
+        let deposits: Vec<Vec<u8>> = (0..3).map(|party| escrow.deposit(party).unwrap()).collect();
+
+        escrow.receive(&deposits[2]).unwrap();
+        escrow.receive(&deposits[0]).unwrap();
+        assert!(escrow.receive(&deposits[0]).is_err());
+        assert_eq!(escrow.missing(), vec![1]);
+        assert!(escrow.release().is_err());
+
+        // A deposit sealed under a party's own key is not for the escrow
+        let store = SecretStore::new(KeyFile::new(&dir));
+        assert!(escrow.receive(&store.seal(1, b"{}").unwrap()).is_err());
+        // Nor is one from another evaluation
+        let other = EscrowRelease::new(
+            full_adder_shares(&[true, true, true]),
+            SecretStore::new(KeyFile::new(&dir)),
+            3,
+        );
+        let error = escrow.receive(&other.deposit(1).unwrap()).unwrap_err();
+        assert!(error.downcast_ref::<DomainMismatch>().is_some());
+
+        escrow.receive(&deposits[1]).unwrap();
+        assert!(escrow.is_complete());
+        let report = escrow.release().unwrap();
+        assert_eq!(report.decode::<u8>("total").unwrap(), 3);
+        assert_eq!(report.rounds, shares.rounds + 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
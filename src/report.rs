@@ -0,0 +1,353 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::circuit::encoding::raw_to_fixed;
+use crate::circuit::{BusEncoding, BusInfo};
+use crate::ot::PeerOtStats;
+
+/// Result of a GMW circuit run
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ExecutionReport {
+    /// Reconstructed single-bit outputs in metadata order
+    pub outputs: Vec<(String, bool)>,
+    /// Bus declarations copied from the circuit metadata
+    pub buses: Vec<BusInfo>,
+    /// Outputs that could not be computed because a gate they depend on
+    /// failed under `FailurePolicy::Poison`, with the failure; they are
+    /// missing from `outputs`
+    pub poisoned: Vec<(String, String)>,
+    /// Communication rounds of the run: input sharing, interactive rounds,
+    /// opening input checks (if any) and opening outputs
+    pub rounds: usize,
+    /// Rounds spent on interactive (AND/OR) gates
+    pub interactive_rounds: usize,
+    /// AND-depth of the evaluated circuit, the minimum for `interactive_rounds`
+    pub and_depth: usize,
+    /// Where the time of the run went
+    pub timings: LatencyBreakdown,
+    /// OT statistics of every party pair that ran transfers
+    pub ot: Vec<PeerOtStats>,
+}
+
+/// Time of one interactive layer, numbered as in `LayerStatsHook`
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct LayerTiming {
+    pub layer: usize,
+    /// Local evaluation of the layer's gates, hooks included
+    pub compute: Duration,
+    /// Waiting for the layer's OT messages; zero for in-process parties
+    pub communication: Duration,
+}
+
+/// Phase-by-phase latency of a run, to tell compute-bound runs from
+/// network-bound ones
+/// Runs of `GmwProtocol` measure compute only, since their parties share a
+/// process; `NetworkSimulator` fills in the projected network waits
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct LatencyBreakdown {
+    /// Connection setup between the parties before input sharing
+    pub handshake: Duration,
+    /// Splitting inputs into shares and loading them
+    pub input_sharing: Duration,
+    /// Layer 0 is the local work before the first interactive gate
+    pub layers: Vec<LayerTiming>,
+    /// Opening the outputs
+    pub reconstruction: Duration,
+}
+
+impl LatencyBreakdown {
+    /// Evaluation time over all layers
+    pub fn compute(&self) -> Duration {
+        self.layers.iter().map(|layer| layer.compute).sum()
+    }
+
+    /// Handshake plus the communication wait of every layer
+    pub fn communication(&self) -> Duration {
+        self.handshake
+            + self
+                .layers
+                .iter()
+                .map(|layer| layer.communication)
+                .sum::<Duration>()
+    }
+
+    /// End-to-end latency of the run
+    pub fn total(&self) -> Duration {
+        self.input_sharing + self.compute() + self.communication() + self.reconstruction
+    }
+
+    /// Whether waiting on the network took longer than evaluating gates
+    pub fn is_network_bound(&self) -> bool {
+        self.communication() > self.compute()
+    }
+}
+
+impl ExecutionReport {
+    /// Whether interactive rounds match the AND-depth, i.e. no layer was serialized
+    pub fn is_depth_optimal(&self) -> bool {
+        self.interactive_rounds == self.and_depth
+    }
+
+    /// Get a single-bit output by name
+    pub fn output(&self, name: &str) -> Option<bool> {
+        self.outputs
+            .iter()
+            .find(|(output_name, _)| output_name == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Collect the bits of a bus, least significant bit first
+    pub fn bus_bits(&self, name: &str) -> Result<Vec<bool>> {
+        let bus = self.bus(name)?;
+
+        bus.bits
+            .iter()
+            .map(|bit| {
+                self.output(bit).ok_or_else(|| {
+                    match self.poisoned.iter().find(|(output, _)| output == bit) {
+                        Some((_, reason)) => {
+                            anyhow::anyhow!(
+                                "Bus {} reads poisoned output {}: {}",
+                                name,
+                                bit,
+                                reason
+                            )
+                        }
+                        None => anyhow::anyhow!("Bus {} references missing output {}", name, bit),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Decode a bus using the encoding declared in metadata
+    pub fn decode<T: FromBits>(&self, name: &str) -> Result<T> {
+        let encoding = self.bus(name)?.encoding;
+        self.decode_as(name, encoding)
+    }
+
+    /// Decode a bus with an explicit encoding, overriding metadata
+    pub fn decode_as<T: FromBits>(&self, name: &str, encoding: BusEncoding) -> Result<T> {
+        let bits = self.bus_bits(name)?;
+        T::from_bits(&bits, encoding)
+    }
+
+    /// Decode a fixed-point bus into the number it represents
+    pub fn decode_fixed(&self, name: &str) -> Result<f64> {
+        let BusEncoding::FixedPoint { frac_bits } = self.bus(name)?.encoding else {
+            return Err(anyhow::anyhow!("Bus {} is not fixed point", name));
+        };
+        let raw = decode_i128(&self.bus_bits(name)?, BusEncoding::TwosComplement)?;
+        Ok(raw_to_fixed(raw, frac_bits))
+    }
+
+    /// Carry-out or borrow flag of an arithmetic bus
+    pub fn carry(&self, name: &str) -> Result<bool> {
+        let carry = self.bus(name)?.carry.as_deref();
+        let carry = carry.ok_or_else(|| anyhow::anyhow!("Bus {} has no carry flag", name))?;
+        self.flag(name, carry)
+    }
+
+    /// Two's-complement overflow flag of an arithmetic bus
+    pub fn overflow(&self, name: &str) -> Result<bool> {
+        let overflow = self.bus(name)?.overflow.as_deref();
+        let overflow =
+            overflow.ok_or_else(|| anyhow::anyhow!("Bus {} has no overflow flag", name))?;
+        self.flag(name, overflow)
+    }
+
+    /// Whether a bus wrapped around under its declared encoding: the carry
+    /// flag for unsigned buses, the overflow flag for signed ones
+    /// `None` if the bus has no such flag
+    pub fn wrapped(&self, name: &str) -> Result<Option<bool>> {
+        let bus = self.bus(name)?;
+        let flag = match bus.encoding {
+            BusEncoding::Unsigned => bus.carry.as_deref(),
+            _ => bus.overflow.as_deref(),
+        };
+        flag.map(|flag| self.flag(name, flag)).transpose()
+    }
+
+    /// Decode a bus like `decode`, failing if its flags show a wraparound
+    pub fn decode_checked<T: FromBits>(&self, name: &str) -> Result<T> {
+        match self.wrapped(name)? {
+            Some(true) => Err(anyhow::anyhow!("Bus {} wrapped around", name)),
+            Some(false) => self.decode(name),
+            None => Err(anyhow::anyhow!("Bus {} has no wraparound flag", name)),
+        }
+    }
+
+    fn flag(&self, name: &str, output: &str) -> Result<bool> {
+        self.output(output).ok_or_else(|| {
+            anyhow::anyhow!("Bus {} references missing flag output {}", name, output)
+        })
+    }
+
+    fn bus(&self, name: &str) -> Result<&BusInfo> {
+        self.buses
+            .iter()
+            .find(|bus| bus.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Bus {} not found", name))
+    }
+}
+
+mod sealed {
+    /// Restricts `FromBits` to the integer types implemented here
+    pub trait Sealed {}
+}
+
+/// Integer types that can be decoded from a bus
+/// Sealed: the supported types may grow without breaking downstream code,
+/// and other crates cannot add their own
+/// (`tests/compile_fail/sealed_from_bits.rs`)
+pub trait FromBits: Sized + sealed::Sealed {
+    /// Decode bits (least significant bit first) with the given encoding
+    fn from_bits(bits: &[bool], encoding: BusEncoding) -> Result<Self>;
+}
+
+/// Decode bits into an i128, which holds every value of the supported types
+/// Fixed-point buses decode to their raw integer, one-hot buses to the
+/// index of the set bit
+fn decode_i128(bits: &[bool], encoding: BusEncoding) -> Result<i128> {
+    if bits.is_empty() {
+        return Err(anyhow::anyhow!("Cannot decode an empty bus"));
+    }
+    if bits.len() > 127 {
+        return Err(anyhow::anyhow!(
+            "Bus width {} exceeds the 127-bit decoding limit",
+            bits.len()
+        ));
+    }
+
+    let magnitude = |bits: &[bool]| {
+        bits.iter()
+            .rev()
+            .fold(0i128, |acc, &bit| (acc << 1) | i128::from(bit))
+    };
+
+    let (sign, rest) = bits.split_last().expect("bus is not empty");
+    let value = match encoding {
+        BusEncoding::Unsigned => magnitude(bits),
+        BusEncoding::SignMagnitude if *sign => -magnitude(rest),
+        BusEncoding::SignMagnitude => magnitude(rest),
+        BusEncoding::TwosComplement | BusEncoding::FixedPoint { .. } if *sign => {
+            magnitude(rest) - (1i128 << rest.len())
+        }
+        BusEncoding::TwosComplement | BusEncoding::FixedPoint { .. } => magnitude(rest),
+        BusEncoding::OneHot => match bits.iter().filter(|&&bit| bit).count() {
+            1 => bits.iter().position(|&bit| bit).expect("one bit is set") as i128,
+            set => {
+                return Err(anyhow::anyhow!(
+                    "One-hot bus has {} bits set instead of one",
+                    set
+                ))
+            }
+        },
+    };
+
+    Ok(value)
+}
+
+macro_rules! impl_from_bits {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl FromBits for $ty {
+                fn from_bits(bits: &[bool], encoding: BusEncoding) -> Result<Self> {
+                    let value = decode_i128(bits, encoding)?;
+                    <$ty>::try_from(value).map_err(|_| {
+                        anyhow::anyhow!("Value {} does not fit in {}", value, stringify!($ty))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_from_bits!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(bits: &[bool], encoding: BusEncoding) -> ExecutionReport {
+        let names: Vec<String> = (0..bits.len()).map(|i| format!("s{i}")).collect();
+
+        ExecutionReport {
+            outputs: names.iter().cloned().zip(bits.iter().copied()).collect(),
+            buses: vec![BusInfo {
+                name: "value".to_string(),
+                bits: names,
+                encoding,
+                carry: None,
+                overflow: None,
+                extend: None,
+            }],
+            poisoned: Vec::new(),
+            rounds: 2,
+            interactive_rounds: 0,
+            and_depth: 0,
+            timings: LatencyBreakdown::default(),
+            ot: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_decode_unsigned() {
+        // 0b0110 = 6, least significant bit first
+        let report = report(&[false, true, true, false], BusEncoding::Unsigned);
+        assert_eq!(report.decode::<u32>("value").unwrap(), 6);
+        assert_eq!(report.decode::<i8>("value").unwrap(), 6);
+    }
+
+    #[test]
+    fn test_decode_signed_encodings() {
+        // 0b1101 is -3 in two's complement and -5 in sign-magnitude
+        let report = report(&[true, false, true, true], BusEncoding::TwosComplement);
+        assert_eq!(report.decode::<i32>("value").unwrap(), -3);
+        assert_eq!(
+            report
+                .decode_as::<i32>("value", BusEncoding::SignMagnitude)
+                .unwrap(),
+            -5
+        );
+        assert_eq!(
+            report
+                .decode_as::<u8>("value", BusEncoding::Unsigned)
+                .unwrap(),
+            13
+        );
+
+        // Negative values cannot be decoded into unsigned types
+        assert!(report.decode::<u32>("value").is_err());
+    }
+
+    #[test]
+    fn test_decode_one_hot_and_fixed_point() {
+        let one_hot = report(&[false, false, true, false], BusEncoding::OneHot);
+        assert_eq!(one_hot.decode::<u8>("value").unwrap(), 2);
+        assert!(one_hot.decode_fixed("value").is_err());
+        let two_hot = report(&[false, true, true, false], BusEncoding::OneHot);
+        assert!(two_hot.decode::<u8>("value").is_err());
+
+        // 0b1101 is -3, i.e. -0.75 in Q2.2
+        let fixed = report(
+            &[true, false, true, true],
+            BusEncoding::FixedPoint { frac_bits: 2 },
+        );
+        assert_eq!(fixed.decode_fixed("value").unwrap(), -0.75);
+        assert_eq!(fixed.decode::<i8>("value").unwrap(), -3);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let report = report(&[true; 9], BusEncoding::Unsigned);
+        assert!(report.decode::<u8>("value").is_err());
+        assert_eq!(report.decode::<u16>("value").unwrap(), 511);
+        assert!(report.decode::<u16>("missing").is_err());
+    }
+}
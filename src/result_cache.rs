@@ -0,0 +1,226 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+use crate::cache::CacheStats;
+use crate::circuit::{Circuit, LocalEvaluator};
+use crate::hash::HashAlgorithm;
+use crate::store::SecretStore;
+
+/// Party whose storage key seals cached results: the local simulation that
+/// fills the cache holds every party's inputs anyway
+const CACHE_PARTY: usize = 0;
+
+/// Cache of local-evaluation results, keyed by a hash of the circuit's
+/// expanded JSON and the input bits
+/// Verifying many runs of the same case then walks the circuit once. With a
+/// directory, results are also kept as one sealed file per case, so they
+/// survive across processes such as separate test runs; only `hits` and
+/// `misses` are counted
+pub struct ResultCache {
+    disk: Option<ResultDir>,
+    state: Mutex<ResultState>,
+}
+
+/// Where results are kept on disk, and how they are sealed and named
+struct ResultDir {
+    dir: PathBuf,
+    store: SecretStore,
+    /// Keyed-hash key for file names, so a name reveals nothing about the
+    /// inputs to someone without the storage key
+    names: [u8; 32],
+}
+
+#[derive(Default)]
+struct ResultState {
+    /// Result key -> outputs in metadata order
+    results: HashMap<String, Vec<(String, bool)>>,
+    stats: CacheStats,
+}
+
+impl ResultCache {
+    /// Cache results in memory only
+    pub fn new() -> Self {
+        Self {
+            disk: None,
+            state: Mutex::new(ResultState::default()),
+        }
+    }
+
+    /// Also keep results in `dir`, creating it on the first store
+    /// Each file is sealed by `store` and named by a keyed hash with the
+    /// store's algorithm, under keys derived from party 0's storage key
+    pub fn with_dir(dir: impl Into<PathBuf>, store: SecretStore) -> Result<Self> {
+        let names = store.derive_key(CACHE_PARTY, b"gmw-rs result cache names")?;
+        Ok(Self {
+            disk: Some(ResultDir {
+                dir: dir.into(),
+                store,
+                names,
+            }),
+            ..Self::new()
+        })
+    }
+
+    /// Every output of `circuit` on `inputs`, in metadata order, evaluated
+    /// in the clear unless cached in memory or on disk
+    pub fn outputs(&self, circuit: &Circuit, inputs: &[bool]) -> Result<Vec<(String, bool)>> {
+        let key = self.result_key(circuit, inputs)?;
+        let path = self
+            .disk
+            .as_ref()
+            .map(|disk| disk.dir.join(format!("{key}.sealed")));
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let ResultState { results, stats } = &mut *state;
+        if let Some(outputs) = results.get(&key) {
+            stats.hits += 1;
+            return Ok(outputs.clone());
+        }
+        // A missing, unreadable or tampered file is a miss rather than an error
+        if let Some(outputs) = self
+            .disk
+            .as_ref()
+            .zip(path.as_ref())
+            .and_then(|(disk, path)| {
+                let plaintext = disk.store.unseal(&fs::read(path).ok()?).ok()?;
+                serde_json::from_slice::<Vec<(String, bool)>>(&plaintext).ok()
+            })
+        {
+            stats.hits += 1;
+            results.insert(key, outputs.clone());
+            return Ok(outputs);
+        }
+
+        stats.misses += 1;
+        let values = LocalEvaluator::evaluate(circuit, inputs)?;
+        let outputs = circuit
+            .metadata
+            .outputs
+            .iter()
+            .map(|info| {
+                values
+                    .get(&info.id)
+                    .map(|&value| (info.name.clone(), value))
+                    .ok_or_else(|| anyhow::anyhow!("Output {} was not computed", info.name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let (Some(disk), Some(path)) = (&self.disk, &path) {
+            fs::create_dir_all(&disk.dir)?;
+            let sealed = disk
+                .store
+                .seal(CACHE_PARTY, &serde_json::to_vec(&outputs)?)?;
+            fs::write(path, sealed)?;
+        }
+        results.insert(key, outputs.clone());
+        Ok(outputs)
+    }
+
+    /// Hex keyed hash of the circuit's expanded JSON followed by one byte
+    /// per input; a plain SHA-256 when results stay in memory
+    fn result_key(&self, circuit: &Circuit, inputs: &[bool]) -> Result<String> {
+        let mut data = serde_json::to_vec(circuit)?;
+        data.extend(inputs.iter().map(|&input| u8::from(input)));
+        let digest = match &self.disk {
+            Some(disk) => disk.store.hash().keyed_hash(&disk.names, &data),
+            None => HashAlgorithm::Sha256.digest(&data),
+        };
+        let mut key = String::with_capacity(64);
+        for byte in digest {
+            write!(key, "{byte:02x}")?;
+        }
+        Ok(key)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stats
+    }
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::store::KeyFile;
+    use std::env;
+
+    #[test]
+    fn test_result_cache_memory_and_disk() {
+        let dir = env::temp_dir().join(format!("gmw-results-{}", std::process::id()));
+        let keys = dir.join("keys");
+        let store = || SecretStore::new(KeyFile::new(&keys));
+        let results = dir.join("results");
+        let adder = reference::full_adder();
+        let inputs = [true, true, false];
+
+        let cache = ResultCache::with_dir(&results, store()).unwrap();
+        let outputs = cache.outputs(&adder, &inputs).unwrap();
+        assert_eq!(
+            outputs,
+            vec![("sum".to_string(), false), ("carry".to_string(), true)]
+        );
+        assert_eq!(cache.outputs(&adder, &inputs).unwrap(), outputs);
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+        cache.outputs(&adder, &[true, false, false]).unwrap();
+        assert_eq!(cache.stats().misses, 2);
+
+        // A fresh cache over the same directory reads the stored result
+        let reopened = ResultCache::with_dir(&results, store()).unwrap();
+        assert_eq!(reopened.outputs(&adder, &inputs).unwrap(), outputs);
+        assert_eq!((reopened.stats().hits, reopened.stats().misses), (1, 0));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_result_cache_files_are_sealed() {
+        let dir = env::temp_dir().join(format!("gmw-results-sealed-{}", std::process::id()));
+        let adder = reference::full_adder();
+        let inputs = [true, true, false];
+        let cache = ResultCache::with_dir(
+            dir.join("results"),
+            SecretStore::new(KeyFile::new(dir.join("keys"))),
+        )
+        .unwrap();
+        let outputs = cache.outputs(&adder, &inputs).unwrap();
+
+        let entries: Vec<_> = fs::read_dir(dir.join("results"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        let bytes = fs::read(&entries[0]).unwrap();
+        assert!(SecretStore::is_sealed(&bytes));
+        let plaintext = serde_json::to_vec(&outputs).unwrap();
+        assert!(!bytes
+            .windows(plaintext.len())
+            .any(|window| window == plaintext));
+        assert!(!String::from_utf8_lossy(&bytes).contains("carry"));
+
+        // The name is keyed: it is not the plain digest of the case
+        let name = entries[0]
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        assert_eq!(name, cache.result_key(&adder, &inputs).unwrap());
+        assert_ne!(
+            name,
+            ResultCache::new().result_key(&adder, &inputs).unwrap()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
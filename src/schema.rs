@@ -0,0 +1,618 @@
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::dispute::COMMITMENT_LOG_VERSION;
+use crate::exchange::EXPORT_FORMAT_VERSION;
+use crate::receipt::RECEIPT_FORMAT_VERSION;
+
+/// File formats gmw-rs reads, each with a JSON Schema (draft 2020-12) for
+/// tools that produce them
+/// The schemas are stricter than the parser in one way: fields gmw-rs does
+/// not know are rejected rather than ignored, so misspelt fields surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SchemaKind {
+    /// One circuit, as loaded by `Circuit::from_json`; macro gates allowed
+    Circuit,
+    /// Several named circuits, as loaded by `CircuitRegistry::from_file`
+    Library,
+    /// A party's `WireShareExport`
+    WireShares,
+    /// A run's `CommitmentLog`
+    CommitmentLog,
+    /// A party's `ExecutionReceipt`
+    Receipt,
+    /// A party's `ProgressDump`, as read by `diagnose`
+    ProgressDump,
+}
+
+/// Every kind with its stable name and title
+const KINDS: [(SchemaKind, &str, &str); 6] = [
+    (SchemaKind::Circuit, "circuit", "gmw-rs circuit"),
+    (SchemaKind::Library, "library", "gmw-rs circuit library"),
+    (SchemaKind::WireShares, "shares", "gmw-rs wire share export"),
+    (
+        SchemaKind::CommitmentLog,
+        "commitments",
+        "gmw-rs input commitment log",
+    ),
+    (SchemaKind::Receipt, "receipt", "gmw-rs execution receipt"),
+    (SchemaKind::ProgressDump, "progress", "gmw-rs progress dump"),
+];
+
+impl SchemaKind {
+    pub fn all() -> impl Iterator<Item = SchemaKind> {
+        KINDS.iter().map(|(kind, ..)| *kind)
+    }
+
+    fn entry(self) -> &'static (SchemaKind, &'static str, &'static str) {
+        KINDS
+            .iter()
+            .find(|(kind, ..)| *kind == self)
+            .expect("every kind has a name")
+    }
+
+    /// Stable name, as given to the `schema` subcommand
+    pub fn name(self) -> &'static str {
+        self.entry().1
+    }
+
+    /// The JSON Schema document
+    pub fn schema(self) -> Value {
+        let (body, definitions) = match self {
+            SchemaKind::Circuit => (json!({ "$ref": "#/$defs/circuit" }), circuit_definitions()),
+            SchemaKind::Library => (
+                object(
+                    json!({ "circuits": { "type": "array", "items": { "$ref": "#/$defs/circuit" } } }),
+                    &["circuits"],
+                ),
+                circuit_definitions(),
+            ),
+            SchemaKind::WireShares => (wire_shares(), Map::new()),
+            SchemaKind::CommitmentLog => (commitment_log(), Map::new()),
+            SchemaKind::Receipt => (receipt(), Map::new()),
+            SchemaKind::ProgressDump => (progress_dump(), Map::new()),
+        };
+
+        let mut schema = Map::new();
+        schema.insert(
+            "$schema".to_string(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        schema.insert("title".to_string(), json!(self.entry().2));
+        if let Value::Object(body) = body {
+            schema.extend(body);
+        }
+        if !definitions.is_empty() {
+            schema.insert("$defs".to_string(), Value::Object(definitions));
+        }
+        Value::Object(schema)
+    }
+}
+
+impl FromStr for SchemaKind {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        SchemaKind::all()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = SchemaKind::all().map(SchemaKind::name).collect();
+                anyhow::anyhow!(
+                    "Unknown schema {}; expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for SchemaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Object with exactly the given `properties`, of which `required` must appear
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn count() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn hex() -> Value {
+    json!({ "type": "string", "pattern": "^[0-9a-f]*$" })
+}
+
+fn names() -> Value {
+    json!({ "type": "array", "items": { "type": "string" } })
+}
+
+/// Map keyed by wire ID, as JSON objects key everything by string
+fn by_wire(values: Value) -> Value {
+    json!({
+        "type": "object",
+        "propertyNames": { "pattern": "^[0-9]+$" },
+        "additionalProperties": values,
+    })
+}
+
+/// `$defs` of the circuit format, one per type of `circuit::types`
+fn circuit_definitions() -> Map<String, Value> {
+    let wire = json!({ "$ref": "#/$defs/wire" });
+    let definitions = json!({
+        "wire": count(),
+        "circuit": object(
+            json!({
+                "name": { "type": "string" },
+                "description": { "type": "string" },
+                "gates": { "type": "array", "items": { "$ref": "#/$defs/gate" } },
+                "metadata": { "$ref": "#/$defs/metadata" },
+            }),
+            &["name", "description", "gates", "metadata"],
+        ),
+        "gate": object(
+            json!({
+                "id": wire,
+                "type": {
+                    "enum": [
+                        "XOR", "NOT", "AND", "OR", "HALF_ADDER", "FULL_ADDER",
+                        "HALF_SUBTRACTOR", "FULL_SUBTRACTOR", "COMPARATOR_LT",
+                    ],
+                },
+                "in": { "type": "array", "items": wire },
+                "const": { "type": "boolean" },
+            }),
+            &["id", "type", "in"],
+        ),
+        "metadata": object(
+            json!({
+                "inputs": { "type": "array", "items": { "$ref": "#/$defs/port" } },
+                "outputs": { "type": "array", "items": { "$ref": "#/$defs/port" } },
+                "buses": { "type": "array", "items": { "$ref": "#/$defs/bus" } },
+                "input_buses": { "type": "array", "items": { "$ref": "#/$defs/bus" } },
+                "constraints": { "type": "array", "items": { "$ref": "#/$defs/constraint" } },
+                "parties": { "$ref": "#/$defs/parties" },
+                "public_inputs": names(),
+                "schedule": by_wire(json!({ "$ref": "#/$defs/schedule_hint" })),
+                "openings": by_wire(json!({ "type": "array", "items": count() })),
+            }),
+            &["inputs", "outputs"],
+        ),
+        "port": object(json!({ "name": { "type": "string" }, "id": wire }), &["name", "id"]),
+        "bus": object(
+            json!({
+                "name": { "type": "string" },
+                "bits": names(),
+                "encoding": { "$ref": "#/$defs/encoding" },
+                "carry": { "type": "string" },
+                "overflow": { "type": "string" },
+                "extend": { "enum": ["zero", "sign"] },
+            }),
+            &["name", "bits"],
+        ),
+        "encoding": {
+            "oneOf": [
+                { "enum": ["unsigned", "sign_magnitude", "twos_complement", "one_hot"] },
+                object(
+                    json!({
+                        "fixed_point": object(
+                            json!({ "frac_bits": { "type": "integer", "minimum": 0, "maximum": 255 } }),
+                            &["frac_bits"],
+                        ),
+                    }),
+                    &["fixed_point"],
+                ),
+            ],
+        },
+        "constraint": {
+            "oneOf": [
+                object(
+                    json!({ "kind": { "const": "less_than" }, "bits": names(), "bound": count() }),
+                    &["kind", "bits", "bound"],
+                ),
+                object(
+                    json!({ "kind": { "const": "one_hot" }, "bits": names() }),
+                    &["kind", "bits"],
+                ),
+            ],
+        },
+        "parties": object(
+            json!({
+                "count": { "type": "integer", "minimum": 2 },
+                "owners": { "type": "object", "additionalProperties": count() },
+            }),
+            &["count"],
+        ),
+        "schedule_hint": object(
+            json!({ "layer": count(), "group": { "type": "string" }, "priority": count() }),
+            &[],
+        ),
+    });
+    match definitions {
+        Value::Object(definitions) => definitions,
+        _ => unreachable!("definitions are an object"),
+    }
+}
+
+fn wire_shares() -> Value {
+    object(
+        json!({
+            "version": { "type": "integer", "minimum": 1, "maximum": EXPORT_FORMAT_VERSION },
+            "session": count(),
+            "circuit": { "type": "string" },
+            "party": count(),
+            "party_count": count(),
+            "shares": {
+                "type": "array",
+                "items": object(
+                    json!({ "wire": count(), "share": { "type": "boolean" } }),
+                    &["wire", "share"],
+                ),
+            },
+        }),
+        &["version", "session", "party", "party_count", "shares"],
+    )
+}
+
+fn commitment_log() -> Value {
+    object(
+        json!({
+            "version": { "const": COMMITMENT_LOG_VERSION },
+            "session": count(),
+            "party_count": count(),
+            "commitments": {
+                "type": "array",
+                "items": object(
+                    json!({ "input": { "type": "string" }, "party": count(), "commitment": hex() }),
+                    &["input", "party", "commitment"],
+                ),
+            },
+        }),
+        &["version", "session", "party_count", "commitments"],
+    )
+}
+
+fn receipt() -> Value {
+    let fields = [
+        "version",
+        "session",
+        "party",
+        "party_count",
+        "circuit",
+        "circuit_hash",
+        "input_commitments",
+        "outputs",
+        "started_at",
+        "finished_at",
+        "key_id",
+        "signature",
+    ];
+    object(
+        json!({
+            "version": { "const": RECEIPT_FORMAT_VERSION },
+            "session": count(),
+            "party": count(),
+            "party_count": count(),
+            "circuit": { "type": "string" },
+            "circuit_hash": hex(),
+            "input_commitments": {
+                "type": "array",
+                "items": object(
+                    json!({ "name": { "type": "string" }, "commitment": hex() }),
+                    &["name", "commitment"],
+                ),
+            },
+            "outputs": {
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "prefixItems": [{ "type": "string" }, { "type": "boolean" }],
+                    "minItems": 2,
+                    "items": false,
+                },
+            },
+            "started_at": count(),
+            "finished_at": count(),
+            "key_id": { "type": "string" },
+            "signature": hex(),
+        }),
+        &fields,
+    )
+}
+
+fn progress_dump() -> Value {
+    object(
+        json!({
+            "circuit": { "type": "string" },
+            "progress": object(
+                json!({
+                    "party_id": count(),
+                    "cursor": count(),
+                    "acked_round": count(),
+                    "pending": { "type": "boolean" },
+                    "computed": { "type": "array", "items": { "type": "boolean" } },
+                }),
+                &["party_id", "cursor", "acked_round", "pending", "computed"],
+            ),
+        }),
+        &["circuit", "progress"],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Circuit, CircuitFile};
+    use crate::diagnose::ProgressDump;
+    use crate::dispute::{CommitmentLog, ShareCommitment, ShareOpening};
+    use crate::domain::ShareDomain;
+    use crate::exchange::WireShareExport;
+    use crate::hash::HashAlgorithm;
+    use crate::kernel::EngineProgress;
+    use crate::receipt::{ExecutionReceipt, InputCommitment, InputOpening};
+    use crate::release::ReleaseCheckpoint;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::fs;
+    use std::path::Path;
+
+    /// Circuit using every field of the format
+    const CIRCUIT: &str = r#"{
+        "name": "everything",
+        "description": "Uses every field of the circuit format",
+        "gates": [
+            {"id": 3, "type": "AND", "in": [0, 1]},
+            {"id": 4, "type": "XOR", "in": [3, 2]},
+            {"id": 5, "type": "HALF_ADDER", "in": [0, 2]},
+            {"id": 7, "type": "XOR", "in": [1], "const": true},
+            {"id": 8, "type": "NOT", "in": [4]},
+            {"id": 9, "type": "OR", "in": [8, 7]}
+        ],
+        "metadata": {
+            "inputs": [{"name": "a", "id": 0}, {"name": "b", "id": 1}, {"name": "c", "id": 2}],
+            "outputs": [
+                {"name": "x", "id": 3}, {"name": "y", "id": 4}, {"name": "sum", "id": 5},
+                {"name": "carry", "id": 6}, {"name": "nb", "id": 7}, {"name": "z", "id": 9}
+            ],
+            "buses": [
+                {"name": "pair", "bits": ["x", "y"], "encoding": "twos_complement", "overflow": "z"},
+                {"name": "half", "bits": ["sum", "carry"], "encoding": {"fixed_point": {"frac_bits": 1}}, "carry": "nb"},
+                {"name": "choice", "bits": ["x", "z"], "encoding": "one_hot"},
+                {"name": "signed", "bits": ["y", "z"], "encoding": "sign_magnitude"}
+            ],
+            "input_buses": [{"name": "ab", "bits": ["a", "b"], "encoding": "unsigned", "extend": "zero"}],
+            "constraints": [
+                {"kind": "less_than", "bits": ["a", "b"], "bound": 3},
+                {"kind": "one_hot", "bits": ["a", "b"]}
+            ],
+            "parties": {"count": 3, "owners": {"a": 0, "b": 1}},
+            "public_inputs": ["c"],
+            "schedule": {"3": {"layer": 1, "group": "g", "priority": 2}},
+            "openings": {"3": [0, 1]}
+        }
+    }"#;
+
+    /// Check `value` against `schema`, supporting the keywords the schemas
+    /// above use; `pattern` is not checked
+    fn check(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+        let fail = |reason: &str| Err(format!("{path}: {reason}"));
+        if schema == &Value::Bool(false) {
+            return fail("no value allowed");
+        }
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            return check(root, &root["$defs"][name], value, path);
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            let matches = (options.iter())
+                .filter(|option| check(root, option, value, path).is_ok())
+                .count();
+            if matches != 1 {
+                return fail(&format!("matches {matches} alternatives"));
+            }
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return fail(&format!("{value} is not one of {allowed:?}"));
+            }
+        }
+        if !schema["const"].is_null() && schema["const"] != *value {
+            return fail(&format!("{value} is not {}", schema["const"]));
+        }
+        let type_matches = match schema["type"].as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("boolean") => value.is_boolean(),
+            Some("integer") => value.is_u64() || value.is_i64(),
+            _ => true,
+        };
+        if !type_matches {
+            return fail(&format!("{value} is not of type {}", schema["type"]));
+        }
+        if let (Some(minimum), Some(number)) = (schema["minimum"].as_i64(), value.as_i64()) {
+            if number < minimum {
+                return fail(&format!("{number} is below {minimum}"));
+            }
+        }
+        if let (Some(maximum), Some(number)) = (schema["maximum"].as_u64(), value.as_u64()) {
+            if number > maximum {
+                return fail(&format!("{number} is above {maximum}"));
+            }
+        }
+
+        if let Some(fields) = value.as_object() {
+            for required in schema["required"].as_array().into_iter().flatten() {
+                if !fields.contains_key(required.as_str().unwrap()) {
+                    return fail(&format!("missing {required}"));
+                }
+            }
+            for (name, field) in fields {
+                let field_schema = match schema["properties"].get(name) {
+                    Some(field_schema) => field_schema,
+                    None if schema["additionalProperties"].is_null() => continue,
+                    None => &schema["additionalProperties"],
+                };
+                check(root, field_schema, field, &format!("{path}.{name}"))?;
+            }
+        }
+        if let Some(items) = value.as_array() {
+            if let Some(minimum) = schema["minItems"].as_u64() {
+                if (items.len() as u64) < minimum {
+                    return fail("too few items");
+                }
+            }
+            let prefix = schema["prefixItems"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            for (index, item) in items.iter().enumerate() {
+                let item_schema = prefix.get(index).unwrap_or(&schema["items"]);
+                if !item_schema.is_null() {
+                    check(root, item_schema, item, &format!("{path}[{index}]"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(kind: SchemaKind, value: &Value) -> Result<(), String> {
+        let schema = kind.schema();
+        check(&schema, &schema, value, kind.name())
+    }
+
+    /// Serialize, deserialize and serialize again; both encodings must agree
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> Value {
+        let json = serde_json::to_value(value).unwrap();
+        let decoded: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+        json
+    }
+
+    #[test]
+    fn test_serde_round_trips_match_schemas() {
+        let source: Value = serde_json::from_str(CIRCUIT).unwrap();
+        validate(SchemaKind::Circuit, &source).unwrap();
+        let circuit = Circuit::from_json(CIRCUIT).unwrap();
+        let json = round_trip(&circuit);
+        validate(SchemaKind::Circuit, &json).unwrap();
+        let library = round_trip(&CircuitFile {
+            circuits: vec![circuit.clone(), circuit],
+        });
+        validate(SchemaKind::Library, &library).unwrap();
+
+        let domain = round_trip(&ShareDomain::new(7, "everything"));
+        let domain: ShareDomain = serde_json::from_value(domain).unwrap();
+        let shares = WireShareExport::new(&domain, 1, 3, vec![(3, true), (9, false)]);
+        validate(SchemaKind::WireShares, &round_trip(&shares)).unwrap();
+
+        let log = CommitmentLog {
+            version: COMMITMENT_LOG_VERSION,
+            session: 7,
+            party_count: 2,
+            commitments: vec![ShareCommitment {
+                input: "a".to_string(),
+                party: 1,
+                commitment: "0a1b".to_string(),
+            }],
+        };
+        validate(SchemaKind::CommitmentLog, &round_trip(&log)).unwrap();
+
+        let receipt = ExecutionReceipt {
+            version: RECEIPT_FORMAT_VERSION,
+            session: 7,
+            party: 0,
+            party_count: 2,
+            circuit: "everything".to_string(),
+            circuit_hash: "ff00".to_string(),
+            input_commitments: vec![InputCommitment {
+                name: "a".to_string(),
+                commitment: "0a1b".to_string(),
+            }],
+            outputs: vec![("x".to_string(), true)],
+            started_at: 1,
+            finished_at: 2,
+            key_id: "party0".to_string(),
+            signature: "c0de".to_string(),
+        };
+        validate(SchemaKind::Receipt, &round_trip(&receipt)).unwrap();
+
+        let dump = ProgressDump::new(
+            "everything",
+            EngineProgress {
+                party_id: 1,
+                cursor: 2,
+                acked_round: 1,
+                pending: true,
+                computed: vec![true, false],
+            },
+        );
+        validate(SchemaKind::ProgressDump, &round_trip(&dump)).unwrap();
+
+        // Types embedded in other files or sealed, without a schema of their own
+        for hash in [HashAlgorithm::Sha256, HashAlgorithm::Blake3] {
+            round_trip(&hash);
+        }
+        round_trip(&ReleaseCheckpoint {
+            domain,
+            revealed: vec![true],
+            acks: vec![true, false],
+        });
+        round_trip(&ShareOpening {
+            input: "a".to_string(),
+            party: 1,
+            share: true,
+            nonce: "00".to_string(),
+        });
+        round_trip(&InputOpening {
+            name: "a".to_string(),
+            value: false,
+            nonce: "00".to_string(),
+        });
+
+        // Unknown fields are rejected, so a misspelt one is caught
+        let mut misspelt = source;
+        misspelt["metadata"]["public_input"] = json!(["c"]);
+        assert!(validate(SchemaKind::Circuit, &misspelt).is_err());
+        misspelt["metadata"]["buses"][0]["encoding"] = json!("twos");
+        assert!(validate(SchemaKind::Circuit, &misspelt).is_err());
+    }
+
+    /// The published schemas in `schemas/` match `SchemaKind::schema`
+    /// After an intended change, regenerate them with
+    /// `GMW_UPDATE_SCHEMA=1 cargo test schema`
+    #[test]
+    fn test_schema_files_are_current() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        for kind in SchemaKind::all() {
+            let path = directory.join(format!("{kind}.schema.json"));
+            let schema = format!("{:#}\n", kind.schema());
+            if std::env::var_os("GMW_UPDATE_SCHEMA").is_some() {
+                fs::create_dir_all(&directory).unwrap();
+                fs::write(&path, &schema).unwrap();
+                continue;
+            }
+            let published = fs::read_to_string(&path).unwrap_or_default();
+            assert!(
+                published == schema,
+                "{} is out of date; rerun with GMW_UPDATE_SCHEMA=1",
+                path.display()
+            );
+        }
+        assert_eq!(
+            "receipt".parse::<SchemaKind>().unwrap(),
+            SchemaKind::Receipt
+        );
+        assert!("circuits".parse::<SchemaKind>().is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+
+use crate::receipt::ReceiptHash;
+
+/// Security parameters, so cost versus assurance is an explicit choice
+/// `computational_bits` (κ) sizes commitment nonces at κ bits and requires
+/// commitment hashes with at least 2κ-bit digests for binding.
+/// `statistical_bits` (σ) bounds the failure chance of checks that can pass
+/// by luck at 2^-σ; the semi-honest protocol has no such check yet, so it is
+/// validated and carried for the malicious-mode checks to come
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityConfig {
+    pub computational_bits: usize,
+    pub statistical_bits: usize,
+}
+
+impl SecurityConfig {
+    pub fn new(computational_bits: usize, statistical_bits: usize) -> Result<Self> {
+        if !(80..=256).contains(&computational_bits) || !computational_bits.is_multiple_of(8) {
+            return Err(anyhow::anyhow!(
+                "Computational security must be a multiple of 8 from 80 to 256 bits, got {}",
+                computational_bits
+            ));
+        }
+        if !(20..=128).contains(&statistical_bits) {
+            return Err(anyhow::anyhow!(
+                "Statistical security must be 20 to 128 bits, got {}",
+                statistical_bits
+            ));
+        }
+        Ok(Self {
+            computational_bits,
+            statistical_bits,
+        })
+    }
+
+    /// Fresh random commitment nonce of κ bits
+    pub fn nonce(&self) -> Vec<u8> {
+        (0..self.computational_bits / 8)
+            .map(|_| rand::random::<u8>())
+            .collect()
+    }
+
+    /// Fail unless `hash` has a digest of at least 2κ bits
+    pub fn check_hash(&self, hash: &dyn ReceiptHash) -> Result<()> {
+        let digest_bits = hash.hash(&[]).len() * 8;
+        if digest_bits < 2 * self.computational_bits {
+            return Err(anyhow::anyhow!(
+                "A {}-bit hash cannot bind commitments at {}-bit security",
+                digest_bits,
+                self.computational_bits
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SecurityConfig {
+    /// 128-bit computational, 40-bit statistical security
+    fn default() -> Self {
+        Self {
+            computational_bits: 128,
+            statistical_bits: 40,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256::Sha256;
+
+    #[test]
+    fn test_security_config() {
+        let config = SecurityConfig::default();
+        assert_eq!(config.nonce().len(), 16);
+        assert!(config.check_hash(&Sha256).is_ok());
+
+        let high = SecurityConfig::new(192, 80).unwrap();
+        assert_eq!(high.nonce().len(), 24);
+        assert!(high.check_hash(&Sha256).is_err());
+
+        assert!(SecurityConfig::new(100, 40).is_err());
+        assert!(SecurityConfig::new(64, 40).is_err());
+        assert!(SecurityConfig::new(128, 10).is_err());
+    }
+}
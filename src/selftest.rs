@@ -0,0 +1,193 @@
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+use crate::audit::CircuitAuditor;
+use crate::circuit::{BusEncoding, Circuit, CircuitBuilder, CircuitFormat};
+use crate::gates::and_gate;
+use crate::lut::{lookup_shared, LookupTable};
+use crate::protocol::GmwProtocol;
+use crate::protocols::{equality_circuit, oblivious_map_lookup};
+use crate::simulation::{NetworkModel, NetworkSimulator};
+
+/// Random trials per circuit of the battery
+const TRIALS: usize = 16;
+
+/// One timed stage of a self-test
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SelfTestStage {
+    pub name: &'static str,
+    pub elapsed: Duration,
+    /// Why the stage failed, if it did
+    pub error: Option<String>,
+}
+
+/// Outcome of a self-test run
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SelfTestReport {
+    pub parties: usize,
+    pub seed: u64,
+    pub stages: Vec<SelfTestStage>,
+}
+
+impl SelfTestReport {
+    pub fn passed(&self) -> bool {
+        self.stages.iter().all(|stage| stage.error.is_none())
+    }
+}
+
+/// Health check after installing on a new machine: runs a battery of small,
+/// built-in circuits and sub-protocols under local simulation, validating
+/// every output. Nothing is read from disk, so it works from any directory
+pub struct SelfTest {
+    parties: usize,
+    seed: u64,
+}
+
+impl SelfTest {
+    pub fn new(parties: usize, seed: u64) -> Self {
+        Self { parties, seed }
+    }
+
+    /// Run every stage; a failing stage is recorded and the next one still runs
+    pub fn run(&self) -> Result<SelfTestReport> {
+        let protocol = GmwProtocol::new(self.parties)?;
+        let stages: [(&'static str, &dyn Fn() -> Result<()>); 7] = [
+            ("oblivious_transfer", &|| self.oblivious_transfer(&protocol)),
+            ("adder_8", &|| self.audit(&protocol, &adder(8)?)),
+            ("equality_2", &|| {
+                self.audit(&protocol, &equality_circuit(2)?)
+            }),
+            ("map_lookup", &map_lookup),
+            ("lookup_table", &|| self.lookup_table()),
+            ("binary_format", &binary_format),
+            ("network_simulation", &|| network_simulation(&protocol)),
+        ];
+
+        let stages = stages
+            .into_iter()
+            .map(|(name, stage)| {
+                let start = Instant::now();
+                let error = stage().err().map(|error| format!("{error:#}"));
+                SelfTestStage {
+                    name,
+                    elapsed: start.elapsed(),
+                    error,
+                }
+            })
+            .collect();
+
+        Ok(SelfTestReport {
+            parties: self.parties,
+            seed: self.seed,
+            stages,
+        })
+    }
+
+    /// Every AND truth-table row, with fresh random shares
+    fn oblivious_transfer(&self, protocol: &GmwProtocol) -> Result<()> {
+        for (x, y) in [(false, false), (false, true), (true, false), (true, true)] {
+            let shares: Vec<(bool, bool)> = protocol
+                .secret_share(x)
+                .into_iter()
+                .zip(protocol.secret_share(y))
+                .collect();
+            let result = protocol.reconstruct_shares(&and_gate(&shares)?);
+            if result != (x & y) {
+                return Err(anyhow::anyhow!("AND({x}, {y}) gave {result}"));
+            }
+        }
+        Ok(())
+    }
+
+    fn audit(&self, protocol: &GmwProtocol, circuit: &Circuit) -> Result<()> {
+        let report = CircuitAuditor::new(TRIALS, self.seed).run(protocol, circuit)?;
+        match report.mismatches.first() {
+            None => Ok(()),
+            Some(mismatch) => Err(anyhow::anyhow!(
+                "{} mismatching outputs, first {} in trial {} (seed {})",
+                report.mismatches.len(),
+                mismatch.output,
+                mismatch.trial,
+                mismatch.seed
+            )),
+        }
+    }
+
+    fn lookup_table(&self) -> Result<()> {
+        let table = LookupTable::from_fn(4, 4, |x| (x * 7 + 3) % 16)?;
+        for input in 0..16 {
+            let mut shares = vec![0; self.parties];
+            shares[0] = input;
+            let output = lookup_shared(table.preprocess(self.parties)?, &shares)?;
+            let value = output.iter().fold(0, |acc, share| acc ^ share);
+            if value != table.lookup(input) {
+                return Err(anyhow::anyhow!("T({input}) gave {value}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn adder(width: usize) -> Result<Circuit> {
+    CircuitBuilder::build(&format!("adder_{width}"), |b| {
+        let x = b.input_bus("x", width);
+        let y = b.input_bus("y", width);
+        let sum = b.add(&x, &y)?;
+        b.output_bus("sum", &sum.value, BusEncoding::Unsigned);
+        b.output("carry", sum.carry);
+        Ok(())
+    })
+}
+
+fn map_lookup() -> Result<()> {
+    let map = [(1, 10), (4, 40), (9, 90)];
+    for (key, expected) in [(4, 40), (5, 0)] {
+        let report = oblivious_map_lookup(&map, key, 4, 8)?.reconstruct();
+        let value = report.decode::<u64>("value")?;
+        if value != expected {
+            return Err(anyhow::anyhow!("Key {key} gave {value}"));
+        }
+    }
+    Ok(())
+}
+
+fn binary_format() -> Result<()> {
+    let circuit = adder(4)?;
+    let bytes = circuit.encode(CircuitFormat::Binary)?.bytes;
+    let decoded = Circuit::decode(&bytes, CircuitFormat::Binary)?;
+    if serde_json::to_value(&decoded)? != serde_json::to_value(&circuit)? {
+        return Err(anyhow::anyhow!("Binary round trip changed the circuit"));
+    }
+    Ok(())
+}
+
+fn network_simulation(protocol: &GmwProtocol) -> Result<()> {
+    // 11 + 6 on a 4-bit adder
+    let inputs = [true, true, false, true, false, true, true, false];
+    let simulation =
+        NetworkSimulator::new(NetworkModel::lan()).run(protocol, &adder(4)?, &inputs)?;
+    let sum = simulation.execution.decode::<u8>("sum")?;
+    if sum != 1 || simulation.execution.output("carry") != Some(true) {
+        return Err(anyhow::anyhow!("11 + 6 gave {sum}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selftest_passes() {
+        for parties in [2, 3] {
+            let report = SelfTest::new(parties, 7).run().unwrap();
+            for stage in &report.stages {
+                assert_eq!(stage.error, None, "{} with {parties} parties", stage.name);
+            }
+            assert!(report.passed());
+        }
+        assert!(SelfTest::new(1, 7).run().is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use hmac::{Hmac, Mac};
+use sha2::Digest;
+
+use crate::receipt::ReceiptHash;
+
+/// SHA-256 (FIPS 180-4), the default hash for receipts and input commitments
+/// A thin wrapper over the `sha2` and `hmac` crates, so `HashAlgorithm` and
+/// receipt code name it like the other built-in hashes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256;
+
+impl Sha256 {
+    pub fn digest(data: &[u8]) -> [u8; 32] {
+        sha2::Sha256::digest(data).into()
+    }
+
+    /// HMAC-SHA256 (RFC 2104)
+    pub fn hmac(key: &[u8], data: &[u8]) -> [u8; 32] {
+        let mut mac =
+            Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().into()
+    }
+}
+
+impl ReceiptHash for Sha256 {
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        Self::digest(data).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn test_sha256_vectors() {
+        assert_eq!(
+            hex(&Sha256::digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&Sha256::digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two blocks after padding
+        assert_eq!(
+            hex(&Sha256::digest(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_hmac_vectors() {
+        // RFC 4231 test cases 2 and 6 (key longer than a block)
+        assert_eq!(
+            hex(&Sha256::hmac(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex(&Sha256::hmac(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+}
@@ -0,0 +1,498 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::bmr::{GARBLING_ROUNDS, LABEL_LEN};
+use crate::circuit::{Circuit, Layering};
+use crate::protocol::{Backend, GmwProtocol};
+use crate::report::ExecutionReport;
+
+/// Characteristics of a single point-to-point link between two parties
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkModel {
+    /// One-way message latency
+    pub latency: Duration,
+    /// Link bandwidth in bytes per second
+    pub bandwidth: u64,
+}
+
+impl NetworkModel {
+    /// Local network: 0.5 ms latency, 1 Gbit/s
+    pub fn lan() -> Self {
+        Self {
+            latency: Duration::from_micros(500),
+            bandwidth: 125_000_000,
+        }
+    }
+
+    /// Wide-area network: 50 ms latency, 100 Mbit/s
+    pub fn wan() -> Self {
+        Self {
+            latency: Duration::from_millis(50),
+            bandwidth: 12_500_000,
+        }
+    }
+
+    /// Time to deliver `flights` sequential messages carrying `bytes` in total
+    fn transfer_time(&self, flights: u32, bytes: u64) -> Duration {
+        let serialization = Duration::from_secs_f64(bytes as f64 / self.bandwidth.max(1) as f64);
+        self.latency * flights + serialization
+    }
+}
+
+/// Message sizes assumed for projecting communication cost
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommunicationCost {
+    /// Bytes exchanged for one 1-out-of-4 OT (RSA-2048 keys, values and masked messages)
+    pub bytes_per_ot: u64,
+    /// Sequential message flights needed by one OT round
+    pub flights_per_ot: u32,
+    /// Bytes used to send one share bit
+    pub bytes_per_share: u64,
+    /// Framing overhead of one wire-format message carrying a batch of OTs
+    pub bytes_per_message: u64,
+}
+
+impl Default for CommunicationCost {
+    fn default() -> Self {
+        Self {
+            bytes_per_ot: 1024,
+            flights_per_ot: 3,
+            bytes_per_share: 1,
+            bytes_per_message: 64,
+        }
+    }
+}
+
+/// How the OTs of one interactive layer are grouped into wire-format messages
+/// Small batches let later flights start before the whole layer is serialized;
+/// large batches amortize the per-message framing overhead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum OtBatching {
+    /// Pick the batch size with the lowest projected time on the modelled links
+    #[default]
+    Auto,
+    /// Group at most this many OTs per message
+    Fixed(usize),
+}
+
+/// How the parties exchange output shares to open the outputs
+/// All-to-all takes one round but every link carries the shares; a star
+/// takes a second round for the result but only uses the collector's links,
+/// which sends fewer bytes once there are more than four parties
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum ReconstructionTopology {
+    /// Every party broadcasts its output shares to every other party
+    #[default]
+    AllToAll,
+    /// Parties send their shares to `collector`, which broadcasts the result
+    Star { collector: usize },
+}
+
+/// Run n in-process parties and project wall-clock time on a modelled network
+#[derive(Debug, Clone)]
+pub struct NetworkSimulator {
+    default_link: NetworkModel,
+    links: HashMap<(usize, usize), NetworkModel>,
+    cost: CommunicationCost,
+    batching: OtBatching,
+    reconstruction: ReconstructionTopology,
+    /// Whether outputs stay unopened, as in `GmwProtocol::dry_run`
+    dry_run: bool,
+}
+
+/// Projected cost of a simulated run
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SimulationReport {
+    pub execution: ExecutionReport,
+    /// Communication rounds: input sharing, one per interactive layer, and
+    /// one for output reconstruction, or two with a star topology
+    pub rounds: usize,
+    /// OTs per message chosen for each interactive layer
+    pub ot_batch_sizes: Vec<usize>,
+    /// Bytes sent over all links
+    pub total_bytes: u64,
+    /// Measured local computation time
+    pub compute_time: Duration,
+    /// Projected time spent waiting on the network
+    pub network_time: Duration,
+}
+
+impl SimulationReport {
+    /// Projected end-to-end wall-clock time
+    pub fn projected_time(&self) -> Duration {
+        self.compute_time + self.network_time
+    }
+}
+
+impl NetworkSimulator {
+    /// Create a simulator where every link follows the same model
+    pub fn new(default_link: NetworkModel) -> Self {
+        Self {
+            default_link,
+            links: HashMap::new(),
+            cost: CommunicationCost::default(),
+            batching: OtBatching::default(),
+            reconstruction: ReconstructionTopology::default(),
+            dry_run: false,
+        }
+    }
+
+    /// Override the model for the link between two parties
+    pub fn with_link(mut self, party_a: usize, party_b: usize, model: NetworkModel) -> Self {
+        self.links
+            .insert((party_a.min(party_b), party_a.max(party_b)), model);
+        self
+    }
+
+    /// Override the assumed message sizes
+    pub fn with_cost(mut self, cost: CommunicationCost) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Set how OTs are grouped into messages
+    pub fn with_ot_batching(mut self, batching: OtBatching) -> Self {
+        self.batching = batching;
+        self
+    }
+
+    /// Set how output shares are exchanged for reconstruction
+    pub fn with_reconstruction(mut self, topology: ReconstructionTopology) -> Self {
+        self.reconstruction = topology;
+        self
+    }
+
+    /// Evaluate without opening outputs, so the execution report holds no
+    /// outputs; the output round is still projected, as the cost of a real run
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Run the circuit and project its cost on the modelled network
+    /// The projected waits are added to the execution report's timings
+    pub fn run(
+        &self,
+        protocol: &GmwProtocol,
+        circuit: &Circuit,
+        inputs: &[bool],
+    ) -> Result<SimulationReport> {
+        let layering = Layering::new(circuit)?;
+        let party_count = protocol.party_count();
+        if let ReconstructionTopology::Star { collector } = self.reconstruction {
+            if collector >= party_count {
+                return Err(anyhow::anyhow!(
+                    "Collector {} is not one of the {} parties",
+                    collector,
+                    party_count
+                ));
+            }
+        }
+
+        let start = Instant::now();
+        let mut execution = if self.dry_run {
+            protocol.dry_run(circuit, inputs)?
+        } else {
+            protocol.run_with_report(circuit, inputs)?
+        };
+        let compute_time = start.elapsed();
+
+        let mut rounds = 0;
+        let mut ot_batch_sizes = Vec::new();
+        let mut total_bytes = 0;
+        let mut network_time = Duration::ZERO;
+
+        // Input sharing: every party receives one share per input
+        let input_bytes = inputs.len() as u64 * self.cost.bytes_per_share;
+        let (time, bytes) = self.round(party_count, 1, input_bytes);
+        rounds += 1;
+        total_bytes += bytes;
+        network_time += time;
+        execution.timings.input_sharing += time;
+
+        let per_layer = layering.interactive_gates_per_layer(circuit);
+        if protocol.backend() == Backend::Bmr {
+            // Garbling: one OT per AND/OR gate for the mask products, three
+            // label OTs per gate from every party, then every party's share
+            // of the four rows and its labels of the inputs
+            let gates: usize = per_layer.iter().sum();
+            let batch = self.batch_size(party_count, gates);
+            ot_batch_sizes.push(batch);
+            let label_bytes = LABEL_LEN as u64;
+            let label_ots = 3 * gates as u64 * (self.cost.bytes_per_ot + 2 * label_bytes);
+            let row_bytes = (party_count as u64 * label_bytes + 1) * 4 * gates as u64;
+            let garbling = [
+                self.ot_round(party_count, gates, batch),
+                self.round(
+                    party_count,
+                    self.cost.flights_per_ot,
+                    label_ots + self.cost.bytes_per_message,
+                ),
+                self.round(
+                    party_count,
+                    1,
+                    row_bytes + inputs.len() as u64 * label_bytes,
+                ),
+            ];
+            debug_assert_eq!(garbling.len(), GARBLING_ROUNDS);
+            for (time, bytes) in garbling {
+                rounds += 1;
+                total_bytes += bytes;
+                network_time += time;
+                if let Some(timing) = execution.timings.layers.first_mut() {
+                    timing.communication += time;
+                }
+            }
+        } else {
+            // One round per interactive layer: every pair runs one OT per gate
+            for (layer, gates) in per_layer.into_iter().enumerate() {
+                let batch = self.batch_size(party_count, gates);
+                let (time, bytes) = self.ot_round(party_count, gates, batch);
+                rounds += 1;
+                ot_batch_sizes.push(batch);
+                total_bytes += bytes;
+                network_time += time;
+                if let Some(timing) = execution.timings.layers.get_mut(layer + 1) {
+                    timing.communication += time;
+                }
+            }
+        }
+
+        // Output reconstruction
+        let output_bytes = circuit.metadata.outputs.len() as u64 * self.cost.bytes_per_share;
+        let (time, bytes) = match self.reconstruction {
+            // All parties broadcast their output shares
+            ReconstructionTopology::AllToAll => {
+                rounds += 1;
+                self.round(party_count, 1, output_bytes)
+            }
+            // Shares in to the collector, then the opened outputs back out
+            ReconstructionTopology::Star { collector } => {
+                rounds += 2;
+                self.star_round(party_count, collector, 2, 2 * output_bytes)
+            }
+        };
+        total_bytes += bytes;
+        network_time += time;
+        execution.timings.reconstruction += time;
+
+        Ok(SimulationReport {
+            execution,
+            rounds,
+            ot_batch_sizes,
+            total_bytes,
+            compute_time,
+            network_time,
+        })
+    }
+
+    /// Batch size for a layer of `gates` OTs under the configured batching
+    fn batch_size(&self, party_count: usize, gates: usize) -> usize {
+        let gates = gates.max(1);
+        match self.batching {
+            OtBatching::Fixed(size) => size.clamp(1, gates),
+            OtBatching::Auto => {
+                // Powers of two up to the whole layer; ties keep the smaller batch
+                let mut candidates: Vec<usize> = (0..usize::BITS)
+                    .map(|shift| 1 << shift)
+                    .take_while(|&size| size < gates)
+                    .collect();
+                candidates.push(gates);
+                candidates
+                    .into_iter()
+                    .min_by_key(|&size| self.ot_round(party_count, gates, size).0)
+                    .unwrap_or(gates)
+            }
+        }
+    }
+
+    /// Project one interactive round of `gates` OTs sent in batches of `batch`
+    /// The first batch crosses every flight before the rest stream behind it
+    fn ot_round(&self, party_count: usize, gates: usize, batch: usize) -> (Duration, u64) {
+        let messages = gates.div_ceil(batch) as u64;
+        let batch_bytes = batch as u64 * self.cost.bytes_per_ot + self.cost.bytes_per_message;
+        let bytes_per_link =
+            gates as u64 * self.cost.bytes_per_ot + messages * self.cost.bytes_per_message;
+        let flights = self.cost.flights_per_ot;
+        let pipeline_fill = u64::from(flights.saturating_sub(1)) * batch_bytes;
+
+        let mut slowest = Duration::ZERO;
+        let mut total_bytes = 0;
+
+        for i in 0..party_count {
+            for j in (i + 1)..party_count {
+                let link = self.links.get(&(i, j)).unwrap_or(&self.default_link);
+                slowest = slowest.max(link.transfer_time(flights, bytes_per_link + pipeline_fill));
+                total_bytes += bytes_per_link;
+            }
+        }
+
+        (slowest, total_bytes)
+    }
+
+    /// Project a round where only the links of `center` carry `bytes_per_link`
+    fn star_round(
+        &self,
+        party_count: usize,
+        center: usize,
+        flights: u32,
+        bytes_per_link: u64,
+    ) -> (Duration, u64) {
+        let mut slowest = Duration::ZERO;
+        let mut total_bytes = 0;
+
+        for party in (0..party_count).filter(|&party| party != center) {
+            let key = (party.min(center), party.max(center));
+            let link = self.links.get(&key).unwrap_or(&self.default_link);
+            slowest = slowest.max(link.transfer_time(flights, bytes_per_link));
+            total_bytes += bytes_per_link;
+        }
+
+        (slowest, total_bytes)
+    }
+
+    /// Project one round where every link carries `bytes_per_link`
+    /// Links run in parallel, so the slowest link determines the round time
+    fn round(&self, party_count: usize, flights: u32, bytes_per_link: u64) -> (Duration, u64) {
+        let mut slowest = Duration::ZERO;
+        let mut total_bytes = 0;
+
+        for i in 0..party_count {
+            for j in (i + 1)..party_count {
+                let link = self.links.get(&(i, j)).unwrap_or(&self.default_link);
+                slowest = slowest.max(link.transfer_time(flights, bytes_per_link));
+                total_bytes += bytes_per_link;
+            }
+        }
+
+        (slowest, total_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+
+    #[test]
+    fn test_simulation_rounds_and_bytes() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(3).unwrap();
+
+        let report = NetworkSimulator::new(NetworkModel::wan())
+            .run(&protocol, &circuit, &[true, true, false])
+            .unwrap();
+
+        // Input sharing, two interactive layers, output reconstruction
+        assert_eq!(report.rounds, 4);
+        assert_eq!(report.execution.decode::<u8>("total").unwrap(), 2);
+
+        // 3 links: 3 input bytes, 2 + 1 OTs sent one per message, 2 output bytes each
+        assert_eq!(report.ot_batch_sizes, vec![1, 1]);
+        assert_eq!(report.total_bytes, 3 * (3 + 3 * (1024 + 64) + 2));
+
+        // At least one latency per message flight
+        assert!(report.network_time >= Duration::from_millis(50) * (1 + 3 + 3 + 1));
+
+        // Projected waits land in the latency breakdown, per layer
+        let timings = &report.execution.timings;
+        assert_eq!(timings.layers.len(), 3);
+        assert_eq!(timings.layers[0].communication, Duration::ZERO);
+        assert!(timings.layers[1].communication >= Duration::from_millis(150));
+        assert!(timings.input_sharing >= Duration::from_millis(50));
+        assert!(timings.is_network_bound());
+        assert!(timings.total() >= report.network_time);
+    }
+
+    #[test]
+    fn test_bmr_rounds_do_not_grow_with_depth() {
+        let simulator = NetworkSimulator::new(NetworkModel::wan());
+        let gmw = GmwProtocol::new(3).unwrap();
+        let bmr = GmwProtocol::new(3).unwrap().with_backend(Backend::Bmr);
+        for width in [8, 16] {
+            let circuit = reference::adder_comparator(width);
+            let inputs = vec![true; circuit.metadata.inputs.len()];
+            let layered = simulator.run(&gmw, &circuit, &inputs).unwrap();
+            let garbled = simulator.run(&bmr, &circuit, &inputs).unwrap();
+
+            // Input sharing, garbling, output reconstruction
+            assert_eq!(garbled.rounds, 1 + GARBLING_ROUNDS + 1);
+            assert!(layered.rounds > garbled.rounds);
+            assert!(garbled.total_bytes > layered.total_bytes);
+            assert_eq!(garbled.execution.outputs, layered.execution.outputs);
+        }
+    }
+
+    #[test]
+    fn test_star_reconstruction() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(6).unwrap();
+        let inputs = [true, true, false];
+        let simulator = NetworkSimulator::new(NetworkModel::lan());
+
+        let all = simulator.run(&protocol, &circuit, &inputs).unwrap();
+        let star = simulator
+            .clone()
+            .with_reconstruction(ReconstructionTopology::Star { collector: 0 })
+            .run(&protocol, &circuit, &inputs)
+            .unwrap();
+
+        // 15 links carry 2 output bytes, or 5 links carry them both ways
+        assert_eq!(star.rounds, all.rounds + 1);
+        assert_eq!(all.total_bytes - star.total_bytes, 15 * 2 - 5 * 4);
+        assert_eq!(star.execution.outputs, all.execution.outputs);
+
+        let outside = simulator
+            .with_reconstruction(ReconstructionTopology::Star { collector: 6 })
+            .run(&protocol, &circuit, &inputs);
+        assert!(outside.is_err());
+    }
+
+    #[test]
+    fn test_slow_link_dominates() {
+        let circuit = Circuit::from_file("circuits/and.json").unwrap();
+        let protocol = GmwProtocol::new(3).unwrap();
+
+        let lan = NetworkSimulator::new(NetworkModel::lan())
+            .run(&protocol, &circuit, &[true, true])
+            .unwrap();
+        let mixed = NetworkSimulator::new(NetworkModel::lan())
+            .with_link(2, 1, NetworkModel::wan())
+            .run(&protocol, &circuit, &[true, true])
+            .unwrap();
+
+        assert!(mixed.network_time > lan.network_time);
+        assert!(mixed.network_time >= NetworkModel::wan().latency * 5);
+    }
+
+    #[test]
+    fn test_ot_batching() {
+        let circuit = reference::full_adder();
+        let protocol = GmwProtocol::new(2).unwrap();
+        let inputs = [true, false, true];
+
+        let single = NetworkSimulator::new(NetworkModel::lan())
+            .with_ot_batching(OtBatching::Fixed(1))
+            .run(&protocol, &circuit, &inputs)
+            .unwrap();
+        assert_eq!(single.ot_batch_sizes, vec![1, 1]);
+
+        // One extra message header for the split layer
+        let whole = NetworkSimulator::new(NetworkModel::lan())
+            .with_ot_batching(OtBatching::Fixed(usize::MAX))
+            .run(&protocol, &circuit, &inputs)
+            .unwrap();
+        assert_eq!(whole.ot_batch_sizes, vec![2, 1]);
+        assert_eq!(single.total_bytes, whole.total_bytes + 64);
+
+        // Auto-tuning never projects worse than either fixed choice
+        let auto = NetworkSimulator::new(NetworkModel::lan())
+            .run(&protocol, &circuit, &inputs)
+            .unwrap();
+        assert!(auto.network_time <= single.network_time.min(whole.network_time));
+    }
+}
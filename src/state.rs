@@ -0,0 +1,344 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::circuit::PreparedCircuit;
+use crate::domain::ShareDomain;
+use crate::errors::ErrorCode;
+use crate::protocol::{GmwProtocol, OutputShares};
+use crate::store::SecretStore;
+
+/// Version written into every stored share; bumped on incompatible changes
+pub const STATE_FORMAT_VERSION: u32 = 1;
+
+/// One party's share of a stored value, sealed as `{name}.party{N}.sealed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredShare {
+    format: u32,
+    name: String,
+    /// Starts at 1 and grows by one with every save
+    version: u64,
+    /// Evaluation that produced the value; the same in every party's file
+    domain: ShareDomain,
+    party: usize,
+    party_count: usize,
+    /// This party's share of each bit, least significant first
+    bits: Vec<bool>,
+}
+
+/// Named value loaded from a `SharedStateStore`, still secret-shared
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SharedValue {
+    pub name: String,
+    pub version: u64,
+    /// Shares of each bit, indexed as `shares[bit][party]`
+    pub shares: Vec<Vec<bool>>,
+}
+
+/// Secret-shared values kept between evaluations, e.g. running balances
+/// A value is saved from one run's `OutputShares` without being opened, one
+/// file per party sealed under that party's key, and bound by name as an
+/// input of later runs with `GmwProtocol::evaluate_with_state`. Every save
+/// names the version it replaces, so two runs updating the same value cannot
+/// silently overwrite each other
+pub struct SharedStateStore {
+    dir: PathBuf,
+    store: SecretStore,
+}
+
+impl SharedStateStore {
+    pub fn new(dir: impl Into<PathBuf>, store: SecretStore) -> Self {
+        Self {
+            dir: dir.into(),
+            store,
+        }
+    }
+
+    /// Store output bus or output `source` of `shares` as `name`
+    /// `previous` is the version being replaced, `None` for a new value;
+    /// returns the new version
+    pub fn save(
+        &self,
+        name: &str,
+        shares: &OutputShares,
+        source: &str,
+        previous: Option<u64>,
+    ) -> Result<u64> {
+        let current = self.version(name)?;
+        if current != previous {
+            return Err(anyhow::anyhow!(
+                "State {} is at version {}, not {}",
+                name,
+                describe(current),
+                describe(previous)
+            ));
+        }
+
+        let bits = output_bits(shares, source)?;
+        let party_count = bits.first().map_or(0, Vec::len);
+        let version = current.map_or(1, |version| version + 1);
+        fs::create_dir_all(&self.dir)?;
+        for party in 0..party_count {
+            let stored = StoredShare {
+                format: STATE_FORMAT_VERSION,
+                name: name.to_string(),
+                version,
+                domain: shares.domain.clone(),
+                party,
+                party_count,
+                bits: bits.iter().map(|bit| bit[party]).collect(),
+            };
+            self.store.save(self.path(name, party), party, &stored)?;
+        }
+        Ok(version)
+    }
+
+    /// Current version of `name`, or `None` if it was never saved
+    pub fn version(&self, name: &str) -> Result<Option<u64>> {
+        validate_name(name)?;
+        let path = self.path(name, 0);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(self.read(name, &path, 0)?.version))
+    }
+
+    /// Load every party's share of `name`, checking that the files belong
+    /// to the same save
+    pub fn load(&self, name: &str) -> Result<SharedValue> {
+        validate_name(name)?;
+        let first = self.read(name, &self.path(name, 0), 0)?;
+        let mut shares = vec![Vec::with_capacity(first.party_count); first.bits.len()];
+        for party in 0..first.party_count {
+            let stored = self.read(name, &self.path(name, party), party)?;
+            if stored.version != first.version {
+                return Err(anyhow::anyhow!(
+                    "State {} has version {} for party 0 but {} for party {}",
+                    name,
+                    first.version,
+                    stored.version,
+                    party
+                ));
+            }
+            first.domain.check(&stored.domain)?;
+            if stored.party_count != first.party_count || stored.bits.len() != first.bits.len() {
+                return Err(anyhow::anyhow!(
+                    "State {} of party {} does not match party 0",
+                    name,
+                    party
+                ));
+            }
+            for (bit, share) in shares.iter_mut().zip(stored.bits) {
+                bit.push(share);
+            }
+        }
+        Ok(SharedValue {
+            name: name.to_string(),
+            version: first.version,
+            shares,
+        })
+    }
+
+    fn read(&self, name: &str, path: &Path, party: usize) -> Result<StoredShare> {
+        let stored: StoredShare = self
+            .store
+            .load(path)
+            .map_err(|error| anyhow::anyhow!("Cannot read state {}: {}", name, error))?;
+        if stored.format != STATE_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported state format version {}",
+                stored.format
+            ));
+        }
+        if stored.name != name || stored.party != party {
+            return Err(anyhow::anyhow!(
+                "{} holds state {} of party {}",
+                path.display(),
+                stored.name,
+                stored.party
+            ));
+        }
+        Ok(stored)
+    }
+
+    fn path(&self, name: &str, party: usize) -> PathBuf {
+        self.dir.join(format!("{name}.party{party}.sealed"))
+    }
+}
+
+impl GmwProtocol {
+    /// Evaluate with some inputs taken from stored state instead of plaintext
+    /// Each binding names an input bus (or single input) and the stored value
+    /// feeding it; `inputs` gives the remaining inputs in metadata order.
+    /// Stored shares go in as they are, so bound values are never opened
+    pub fn evaluate_with_state(
+        &self,
+        prepared: &PreparedCircuit,
+        inputs: &[bool],
+        state: &SharedStateStore,
+        bindings: &[(&str, &str)],
+    ) -> Result<OutputShares> {
+        let metadata = &prepared.metadata;
+        let mut bound: Vec<Option<Vec<bool>>> = vec![None; metadata.inputs.len()];
+
+        for &(target, name) in bindings {
+            let value = state.load(name)?;
+            let bits: Vec<&str> = match metadata.input_buses.iter().find(|bus| bus.name == target) {
+                Some(bus) => bus.bits.iter().map(String::as_str).collect(),
+                None => vec![target],
+            };
+            if bits.len() != value.shares.len() {
+                return Err(anyhow::anyhow!(
+                    "Input {} takes {} bits but state {} has {}",
+                    target,
+                    bits.len(),
+                    name,
+                    value.shares.len()
+                ));
+            }
+            for (bit, shares) in bits.into_iter().zip(value.shares) {
+                if shares.len() != self.party_count() {
+                    return Err(anyhow::anyhow!(
+                        "State {} is shared among {} parties, not {}",
+                        name,
+                        shares.len(),
+                        self.party_count()
+                    ));
+                }
+                let position = metadata
+                    .inputs
+                    .iter()
+                    .position(|input| input.name == bit)
+                    .ok_or_else(|| anyhow::anyhow!("Input {} not found", bit))?;
+                if bound[position].replace(shares).is_some() {
+                    return Err(anyhow::anyhow!("Input {} is bound twice", bit));
+                }
+            }
+        }
+
+        let unbound = bound.iter().filter(|shares| shares.is_none()).count();
+        if inputs.len() != unbound {
+            return Err(ErrorCode::InputMismatch.tag(anyhow::anyhow!(
+                "Circuit expects {} unbound inputs but got {}",
+                unbound,
+                inputs.len()
+            )));
+        }
+        let mut plain = self.share_inputs(inputs).into_iter();
+        let input_shares: Vec<Vec<bool>> = bound
+            .into_iter()
+            .map(|shares| {
+                shares
+                    .or_else(|| plain.next())
+                    .expect("one input per unbound slot")
+            })
+            .collect();
+        self.evaluate_shared(prepared, &input_shares)
+    }
+}
+
+/// Shares of output bus or output `source`, as `shares[bit][party]`
+fn output_bits(shares: &OutputShares, source: &str) -> Result<Vec<Vec<bool>>> {
+    let names: Vec<&str> = match shares.buses.iter().find(|bus| bus.name == source) {
+        Some(bus) => bus.bits.iter().map(String::as_str).collect(),
+        None => vec![source],
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            shares
+                .names
+                .iter()
+                .position(|output| output == name)
+                .map(|position| shares.shares[position].clone())
+                .ok_or_else(|| anyhow::anyhow!("Output {} not found", name))
+        })
+        .collect()
+}
+
+/// State names become file names, so keep them to a safe alphabet
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(anyhow::anyhow!(
+            "Invalid state name {:?}: use letters, digits, _ and -",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn describe(version: Option<u64>) -> String {
+    version.map_or_else(|| "none".to_string(), |version| version.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{BusEncoding, Circuit, CircuitBuilder};
+    use crate::store::KeyFile;
+    use std::env;
+
+    fn deposit_circuit() -> Circuit {
+        CircuitBuilder::build("deposit", |b| {
+            let balance = b.input_encoded("balance", 8, BusEncoding::Unsigned);
+            let deposit = b.input_encoded("deposit", 8, BusEncoding::Unsigned);
+            let sum = b.add(&balance, &deposit)?;
+            b.output_bus("balance", &sum.value, BusEncoding::Unsigned);
+            Ok(())
+        })
+        .unwrap()
+    }
+
+    fn byte(value: u8) -> Vec<bool> {
+        (0..8).map(|i| value >> i & 1 == 1).collect()
+    }
+
+    #[test]
+    fn test_running_balance() {
+        let dir = env::temp_dir().join(format!("gmw-state-{}", std::process::id()));
+        let state = SharedStateStore::new(&dir, SecretStore::new(KeyFile::new(dir.join("keys"))));
+        let prepared = PreparedCircuit::new(&deposit_circuit());
+        let protocol = GmwProtocol::new(3).unwrap();
+
+        // Opening balance 10 plus a deposit of 5
+        let inputs = [byte(10), byte(5)].concat();
+        let first = protocol.evaluate_prepared(&prepared, &inputs).unwrap();
+        assert_eq!(state.version("balance").unwrap(), None);
+        assert_eq!(state.save("balance", &first, "balance", None).unwrap(), 1);
+
+        // Deposit 7 onto the stored balance without opening it
+        let bindings = [("balance", "balance")];
+        let second = protocol
+            .evaluate_with_state(&prepared, &byte(7), &state, &bindings)
+            .unwrap();
+        assert_eq!(second.reconstruct().decode::<u8>("balance").unwrap(), 22);
+        assert_eq!(
+            state.save("balance", &second, "balance", Some(1)).unwrap(),
+            2
+        );
+
+        // A stale writer is refused, as are mixed-up party files
+        assert!(state.save("balance", &first, "balance", Some(1)).is_err());
+        let stale = dir.join("stale.sealed");
+        fs::copy(dir.join("balance.party1.sealed"), &stale).unwrap();
+        state.save("balance", &first, "balance", Some(2)).unwrap();
+        fs::copy(&stale, dir.join("balance.party1.sealed")).unwrap();
+        assert!(state.load("balance").is_err());
+
+        assert!(protocol
+            .evaluate_with_state(&prepared, &byte(7), &state, &[("balance", "../x")])
+            .is_err());
+        assert!(GmwProtocol::new(2)
+            .unwrap()
+            .evaluate_with_state(&prepared, &byte(7), &state, &[("deposit", "balance")])
+            .is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::circuit::{EvaluationStep, PreparedCircuit};
+use crate::hooks::{GateEvent, GateHook};
+use crate::simulation::CommunicationCost;
+
+/// Work, traffic and time of one interactive layer
+/// Layer 0 is the local work before the first interactive gate; layer `k`
+/// holds the gates of round `k` and the local gates that follow them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LayerStats {
+    pub layer: usize,
+    /// Interactive (AND/OR) gates, one OT per party pair each
+    pub and_gates: usize,
+    /// All gates, counting every gate of a collapsed linear block
+    pub gates: usize,
+    /// Bytes sent over all links, projected with `CommunicationCost::bytes_per_ot`
+    pub bytes: u64,
+    /// Measured evaluation time in milliseconds
+    pub ms: f64,
+}
+
+/// Hook recording per-layer time series for runs of one prepared circuit
+/// Each run starts a fresh series, so `stats` describes the latest run
+pub struct LayerStatsHook {
+    circuit: String,
+    /// Layer of each plan step
+    step_layers: Vec<usize>,
+    layers: Mutex<Vec<LayerStats>>,
+}
+
+impl LayerStatsHook {
+    pub fn new(prepared: &PreparedCircuit, party_count: usize) -> Self {
+        Self::with_cost(prepared, party_count, CommunicationCost::default())
+    }
+
+    /// Project bytes with custom message sizes
+    pub fn with_cost(
+        prepared: &PreparedCircuit,
+        party_count: usize,
+        cost: CommunicationCost,
+    ) -> Self {
+        let pairs = (party_count * party_count.saturating_sub(1) / 2) as u64;
+        let step_layers = prepared.step_layers().to_vec();
+        let mut layers: Vec<LayerStats> = (0..=prepared.rounds)
+            .map(|layer| LayerStats {
+                layer,
+                and_gates: 0,
+                gates: 0,
+                bytes: 0,
+                ms: 0.0,
+            })
+            .collect();
+
+        for (step, &layer) in prepared.plan.steps.iter().zip(&step_layers) {
+            let stats = &mut layers[layer];
+            match step {
+                EvaluationStep::Linear(block) => stats.gates += block.gate_count(),
+                EvaluationStep::Gate(gate) => {
+                    stats.gates += 1;
+                    if gate.gate_type.is_interactive() {
+                        stats.and_gates += 1;
+                        stats.bytes += pairs * cost.bytes_per_ot;
+                    }
+                }
+            }
+        }
+
+        Self {
+            circuit: prepared.name.clone(),
+            step_layers,
+            layers: Mutex::new(layers),
+        }
+    }
+
+    /// Stats of the latest run, one entry per layer
+    pub fn stats(&self) -> Vec<LayerStats> {
+        self.layers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// `layer,and_gates,gates,bytes,ms` rows with a header line
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("layer,and_gates,gates,bytes,ms\n");
+        for stats in self.stats() {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{:.3}",
+                stats.layer, stats.and_gates, stats.gates, stats.bytes, stats.ms
+            );
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.stats())?)
+    }
+}
+
+impl GateHook for LayerStatsHook {
+    fn after_gate(&self, event: &GateEvent<'_>, elapsed: Duration) -> Result<()> {
+        let (GateEvent::Gate { circuit, step, .. } | GateEvent::Linear { circuit, step, .. }) =
+            *event;
+        let Some(&layer) = self.step_layers.get(step) else {
+            return Ok(());
+        };
+        if circuit != self.circuit {
+            return Ok(());
+        }
+
+        let mut layers = self.layers.lock().unwrap_or_else(PoisonError::into_inner);
+        if step == 0 {
+            for stats in layers.iter_mut() {
+                stats.ms = 0.0;
+            }
+        }
+        layers[layer].ms += elapsed.as_secs_f64() * 1000.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::reference;
+    use crate::circuit::Layering;
+    use crate::protocol::GmwProtocol;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_layer_stats() {
+        let circuit = reference::adder_comparator(2);
+        let prepared = PreparedCircuit::new(&circuit);
+        let hook = Arc::new(LayerStatsHook::new(&prepared, 3));
+        let protocol = GmwProtocol::new(3).unwrap().with_hook(Arc::clone(&hook));
+        protocol.run_prepared(&prepared, &[true; 4]).unwrap();
+
+        let stats = hook.stats();
+        assert_eq!(stats.len(), prepared.rounds + 1);
+        let layering = Layering::new(&circuit).unwrap();
+        let and_gates: Vec<usize> = stats[1..].iter().map(|layer| layer.and_gates).collect();
+        assert_eq!(and_gates, layering.interactive_gates_per_layer(&circuit));
+        assert_eq!(
+            stats.iter().map(|layer| layer.gates).sum::<usize>(),
+            circuit.gates.len()
+        );
+        // Three party pairs, one 1024-byte OT each per AND gate
+        assert!(stats
+            .iter()
+            .all(|layer| layer.bytes == layer.and_gates as u64 * 3 * 1024));
+
+        let csv = hook.to_csv();
+        assert!(csv.starts_with("layer,and_gates,gates,bytes,ms\n0,0,"));
+        assert_eq!(csv.lines().count(), stats.len() + 1);
+        let json: serde_json::Value = serde_json::from_str(&hook.to_json().unwrap()).unwrap();
+        assert_eq!(json[1]["and_gates"], stats[1].and_gates);
+    }
+}
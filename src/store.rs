@@ -0,0 +1,385 @@
+use anyhow::Result;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::hash::HashAlgorithm;
+use crate::receipt::{decode_hex, encode_hex};
+
+/// Leading bytes of every sealed file
+const SEALED_MAGIC: &[u8; 4] = b"GMWS";
+/// ChaCha20-Poly1305 under a key derived with the file's hash algorithm
+const SEALED_VERSION: u8 = 3;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+/// Magic, version, hash algorithm and little-endian party number
+const PARTY_END: usize = 14;
+
+/// Source of the per-party keys that encrypt persisted artifacts
+pub trait KeyBackend: Send + Sync {
+    /// The party's 32-byte storage key, created on first use
+    fn key(&self, party: usize) -> Result<[u8; 32]>;
+}
+
+/// Keys kept as `party{N}.key` files in a directory
+/// Missing keys are generated and written readable by the owner only
+#[derive(Debug, Clone)]
+pub struct KeyFile {
+    dir: PathBuf,
+}
+
+impl KeyFile {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, party: usize) -> PathBuf {
+        self.dir.join(format!("party{party}.key"))
+    }
+}
+
+impl KeyBackend for KeyFile {
+    fn key(&self, party: usize) -> Result<[u8; 32]> {
+        let path = self.path(party);
+        if !path.exists() {
+            fs::create_dir_all(&self.dir)?;
+            let key = rand::random::<[u8; 32]>();
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            match options.open(&path) {
+                Ok(mut file) => {
+                    file.write_all(encode_hex(&key).as_bytes())?;
+                    return Ok(key);
+                }
+                // Another process created the key first; use theirs
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        parse_key(&fs::read_to_string(&path)?)
+            .ok_or_else(|| anyhow::anyhow!("Invalid key file {}", path.display()))
+    }
+}
+
+/// Keys kept in the operating system's keyring
+/// Uses `secret-tool` (libsecret) on Linux and `security` (Keychain) on
+/// macOS, with one entry per party under `service`
+#[derive(Debug, Clone)]
+pub struct OsKeyring {
+    service: String,
+}
+
+impl OsKeyring {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+
+    fn lookup(&self, account: &str) -> Result<Option<String>> {
+        let output = if cfg!(target_os = "macos") {
+            Command::new("security")
+                .args(["find-generic-password", "-s", &self.service])
+                .args(["-a", account, "-w"])
+                .output()?
+        } else {
+            Command::new("secret-tool")
+                .args(["lookup", "service", &self.service, "account", account])
+                .output()?
+        };
+        let secret = String::from_utf8(output.stdout)?;
+        Ok((output.status.success() && !secret.trim().is_empty()).then_some(secret))
+    }
+
+    /// The secret goes to the tool's stdin, never its arguments, which
+    /// every local user can read from the process list
+    fn store(&self, account: &str, secret: &str) -> Result<()> {
+        let (mut command, input) = if cfg!(target_os = "macos") {
+            // `security -i` reads commands from stdin
+            let mut command = Command::new("security");
+            command.arg("-i");
+            let input = format!(
+                "add-generic-password -s {} -a {} -w {}\n",
+                quote(&self.service),
+                quote(account),
+                quote(secret)
+            );
+            (command, input)
+        } else {
+            let mut command = Command::new("secret-tool");
+            command
+                .arg("store")
+                .arg(format!("--label={} {}", self.service, account))
+                .args(["service", &self.service, "account", account]);
+            (command, secret.to_string())
+        };
+        let mut child = command.stdin(Stdio::piped()).spawn()?;
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Keyring tool has no stdin"))?
+            .write_all(input.as_bytes())?;
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("Keyring refused to store {}", account));
+        }
+        Ok(())
+    }
+}
+
+impl KeyBackend for OsKeyring {
+    fn key(&self, party: usize) -> Result<[u8; 32]> {
+        let account = format!("party{party}");
+        if let Some(secret) = self.lookup(&account)? {
+            return parse_key(&secret)
+                .ok_or_else(|| anyhow::anyhow!("Invalid keyring entry {}", account));
+        }
+        let key = rand::random::<[u8; 32]>();
+        self.store(&account, &encode_hex(&key))?;
+        Ok(key)
+    }
+}
+
+/// Double-quoted argument for a `security -i` command line
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn parse_key(text: &str) -> Option<[u8; 32]> {
+    decode_hex(text.trim())?.try_into().ok()
+}
+
+/// Encrypted storage for persisted shares, openings and checkpoints
+/// Each file is sealed with ChaCha20-Poly1305 under a key derived from its
+/// party's key with the keyed hash (HMAC-SHA256 by default, or keyed
+/// BLAKE3). The algorithm and party number are kept in the clear and
+/// authenticated as associated data, so a reader knows how to check the
+/// file and which key to ask the backend for
+pub struct SecretStore {
+    backend: Box<dyn KeyBackend>,
+    hash: HashAlgorithm,
+}
+
+impl SecretStore {
+    pub fn new(backend: impl KeyBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+            hash: HashAlgorithm::default(),
+        }
+    }
+
+    /// Seal new files with `hash`; files sealed with another algorithm
+    /// can still be opened
+    pub fn with_hash(mut self, hash: HashAlgorithm) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Hash algorithm new files are sealed with
+    pub fn hash(&self) -> HashAlgorithm {
+        self.hash
+    }
+
+    /// Key for `label`'s use, derived from `party`'s key with the keyed hash
+    pub(crate) fn derive_key(&self, party: usize, label: &[u8]) -> Result<[u8; 32]> {
+        Ok(self.hash.keyed_hash(&self.backend.key(party)?, label))
+    }
+
+    /// Encrypt and authenticate `plaintext` under `party`'s key
+    pub fn seal(&self, party: usize, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let hash = self.hash;
+        let cipher = aead_cipher(hash, &self.backend.key(party)?);
+        let nonce = rand::random::<[u8; NONCE_LEN]>();
+
+        let mut sealed = SEALED_MAGIC.to_vec();
+        sealed.push(SEALED_VERSION);
+        sealed.push(algorithm_tag(hash));
+        sealed.extend_from_slice(&(party as u64).to_le_bytes());
+        sealed.extend_from_slice(&nonce);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce.into(),
+                Payload {
+                    msg: plaintext,
+                    aad: &sealed,
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("Could not seal a file for party {}", party))?;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Whether bytes look like a sealed file rather than plain JSON
+    pub fn is_sealed(bytes: &[u8]) -> bool {
+        bytes.starts_with(SEALED_MAGIC)
+    }
+
+    /// Party that sealed a file, read from its header
+    pub fn party(sealed: &[u8]) -> Result<usize> {
+        if sealed.len() < PARTY_END || &sealed[..4] != SEALED_MAGIC {
+            return Err(anyhow::anyhow!("Not a sealed file"));
+        }
+        if sealed[4] != SEALED_VERSION {
+            return Err(anyhow::anyhow!("Unsupported sealed version {}", sealed[4]));
+        }
+        if sealed.len() < PARTY_END + NONCE_LEN + TAG_LEN {
+            return Err(anyhow::anyhow!("Sealed file is truncated"));
+        }
+        let party = u64::from_le_bytes(sealed[6..PARTY_END].try_into()?);
+        Ok(usize::try_from(party)?)
+    }
+
+    /// Hash algorithm a file was sealed with, read from its header
+    pub fn algorithm(sealed: &[u8]) -> Result<HashAlgorithm> {
+        Self::party(sealed)?;
+        match sealed[5] {
+            0 => Ok(HashAlgorithm::Sha256),
+            1 => Ok(HashAlgorithm::Blake3),
+            tag => Err(anyhow::anyhow!("Unknown sealed hash algorithm {}", tag)),
+        }
+    }
+
+    /// Check and decrypt a sealed file with the key of the party that sealed it
+    pub fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        let party = Self::party(sealed)?;
+        let hash = Self::algorithm(sealed)?;
+        let key = self.backend.key(party)?;
+
+        let (header, ciphertext) = sealed.split_at(PARTY_END + NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = header[PARTY_END..].try_into()?;
+        aead_cipher(hash, &key)
+            .decrypt(
+                &nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad: header,
+                },
+            )
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Sealed file of party {} was tampered with or uses another key",
+                    party
+                )
+            })
+    }
+
+    /// Serialize `value` as JSON and write it sealed under `party`'s key
+    pub fn save<T: Serialize>(
+        &self,
+        path: impl AsRef<Path>,
+        party: usize,
+        value: &T,
+    ) -> Result<()> {
+        Ok(fs::write(
+            path,
+            self.seal(party, &serde_json::to_vec(value)?)?,
+        )?)
+    }
+
+    /// Read a file written by `save`
+    pub fn load<T: DeserializeOwned>(&self, path: impl AsRef<Path>) -> Result<T> {
+        Ok(serde_json::from_slice(&self.unseal(&fs::read(path)?)?)?)
+    }
+}
+
+fn algorithm_tag(hash: HashAlgorithm) -> u8 {
+    match hash {
+        HashAlgorithm::Sha256 => 0,
+        HashAlgorithm::Blake3 => 1,
+    }
+}
+
+/// ChaCha20-Poly1305 keyed for sealing
+fn aead_cipher(hash: HashAlgorithm, key: &[u8; 32]) -> ChaCha20Poly1305 {
+    ChaCha20Poly1305::new(&hash.keyed_hash(key, b"gmw-rs store aead").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ShareDomain;
+    use crate::release::ReleaseCheckpoint;
+    use std::env;
+
+    #[test]
+    fn test_sealed_round_trip_and_tamper() {
+        let dir = env::temp_dir().join(format!("gmw-store-{}", std::process::id()));
+        let store = SecretStore::new(KeyFile::new(&dir));
+        let checkpoint = ReleaseCheckpoint {
+            domain: ShareDomain::new(3, "full_adder"),
+            revealed: vec![true, false, true],
+            acks: vec![true, true],
+        };
+
+        let path = dir.join("checkpoint.sealed");
+        store.save(&path, 1, &checkpoint).unwrap();
+        let sealed = fs::read(&path).unwrap();
+        assert_eq!(SecretStore::party(&sealed).unwrap(), 1);
+        assert!(!String::from_utf8_lossy(&sealed).contains("revealed"));
+        assert_eq!(store.load::<ReleaseCheckpoint>(&path).unwrap(), checkpoint);
+        // Keys persist across stores
+        let reopened = SecretStore::new(KeyFile::new(&dir));
+        assert_eq!(
+            reopened.load::<ReleaseCheckpoint>(&path).unwrap(),
+            checkpoint
+        );
+
+        let mut tampered = sealed.clone();
+        tampered[20] ^= 1;
+        assert!(store.unseal(&tampered).is_err());
+        // Another party's key does not open the file
+        let mut relabeled = sealed;
+        relabeled[6] = 0;
+        assert!(store.unseal(&relabeled).is_err());
+
+        // BLAKE3-sealed files record their algorithm and open in any store
+        let blake3 = SecretStore::new(KeyFile::new(&dir)).with_hash(HashAlgorithm::Blake3);
+        blake3.save(&path, 0, &checkpoint).unwrap();
+        let sealed = fs::read(&path).unwrap();
+        assert_eq!(
+            SecretStore::algorithm(&sealed).unwrap(),
+            HashAlgorithm::Blake3
+        );
+        assert_eq!(store.load::<ReleaseCheckpoint>(&path).unwrap(), checkpoint);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sealed_versions() {
+        let dir = env::temp_dir().join(format!("gmw-store-versions-{}", std::process::id()));
+        let store = SecretStore::new(KeyFile::new(&dir));
+
+        let sealed = store.seal(0, b"share").unwrap();
+        assert_eq!(sealed[4], SEALED_VERSION);
+        assert_eq!(sealed.len(), PARTY_END + NONCE_LEN + 5 + TAG_LEN);
+        // The header is associated data: changing the algorithm byte fails
+        let mut relabeled = sealed.clone();
+        relabeled[5] = 1;
+        assert!(store.unseal(&relabeled).is_err());
+
+        // Only the current version opens, older and newer ones are refused
+        for version in [1, 2, 4] {
+            let mut other = sealed.clone();
+            other[4] = version;
+            assert!(SecretStore::party(&other).is_err());
+            assert!(store.unseal(&other).is_err());
+        }
+        assert!(SecretStore::party(&SEALED_MAGIC[..]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_keyring_command_quoting() {
+        assert_eq!(quote("gmw-rs"), "\"gmw-rs\"");
+        assert_eq!(quote("a \"b\" \\c"), "\"a \\\"b\\\" \\\\c\"");
+    }
+}
@@ -0,0 +1,13 @@
+use gmw_rs::GateType;
+
+// `GateType` is non-exhaustive, so downstream matches need a wildcard arm
+fn rounds(gate: &GateType) -> usize {
+    match gate {
+        GateType::XOR | GateType::NOT => 0,
+        GateType::AND | GateType::OR => 1,
+    }
+}
+
+fn main() {
+    println!("{}", rounds(&GateType::AND));
+}
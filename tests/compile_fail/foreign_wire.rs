@@ -0,0 +1,13 @@
+use gmw_rs::circuit::CircuitBuilder;
+
+fn main() {
+    let _ = CircuitBuilder::build("outer", |outer| {
+        let a = outer.input("a");
+        CircuitBuilder::build("inner", |inner| {
+            let b = inner.input("b");
+            inner.and(a, b); // `a` belongs to `outer`
+            Ok(())
+        })?;
+        Ok(())
+    });
+}
@@ -0,0 +1,11 @@
+use gmw_rs::{BusEncoding, FromBits};
+
+struct Celsius(i16);
+
+impl FromBits for Celsius {
+    fn from_bits(bits: &[bool], encoding: BusEncoding) -> anyhow::Result<Self> {
+        i16::from_bits(bits, encoding).map(Celsius)
+    }
+}
+
+fn main() {}
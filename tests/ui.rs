@@ -0,0 +1,9 @@
+//! Compile-fail tests pinning the API's deliberate restrictions: sealed
+//! traits, builder-branded wires and non-exhaustive types
+#![cfg(feature = "std")]
+
+#[test]
+fn compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/compile_fail/*.rs");
+}